@@ -127,7 +127,7 @@ async fn graphiql() -> impl IntoResponse {
 #[tokio::main]
 async fn main() {
     let conn_pool = build_default_connection_pool().await;
-    let schema = build_schema(PoolWrapper::Pool(conn_pool));
+    let schema = build_schema(PoolWrapper::from(conn_pool));
     let app = Route::new().at(GRAPHQL_ENDPOINT, post(GraphQL::new(schema)).get(graphiql));
     let listener = TcpListener::bind("localhost:3000");
     Server::new(listener).run(app).await.unwrap();
@@ -155,7 +155,9 @@ mod tests {
 
     use crate::{build_schema, Book, PoolWrapper};
 
-    async fn get_connection_pool() -> PoolWrapper<DieselAsyncPostgresBackend<DieselBb8>> {
+    async fn get_connection_pool(
+        mutable: bool,
+    ) -> PoolWrapper<DieselAsyncPostgresBackend<DieselBb8>> {
         static MIGRATIONS: EmbeddedMigrations =
             embed_migrations!("examples/async-graphql/migrations");
 
@@ -200,11 +202,16 @@ mod tests {
             })
             .await;
 
-        let conn_pool = db_pool.pull_immutable().await;
-        PoolWrapper::ReusablePool(conn_pool)
+        if mutable {
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            PoolWrapper::from(conn_pool)
+        } else {
+            let conn_pool = db_pool.pull_immutable().await;
+            PoolWrapper::from(conn_pool)
+        }
     }
 
-    async fn test() {
+    async fn test(mutable: bool) {
         #[derive(Deserialize)]
         struct Data {
             books: Vec<Book>,
@@ -224,7 +231,7 @@ mod tests {
         const QUERY: &str = "{ books { id title } }";
         const MUTATION: &str = "mutation AddBook($title: String!) { addBook(title: $title) }";
 
-        let conn_pool = get_connection_pool().await;
+        let conn_pool = get_connection_pool(mutable).await;
 
         let schema = Arc::new(build_schema(conn_pool));
 
@@ -276,11 +283,16 @@ mod tests {
 
     #[test(shared)]
     async fn test1() {
-        test().await;
+        test(false).await;
     }
 
     #[test(shared)]
     async fn test2() {
-        test().await;
+        test(false).await;
+    }
+
+    #[test(shared)]
+    async fn test3() {
+        test(true).await;
     }
 }
@@ -27,7 +27,7 @@ mod tests {
                 let backend = SqlxMySQLBackend::new(
                     config.into(),
                     || MySqlPoolOptions::new().max_connections(10),
-                    || MySqlPoolOptions::new().max_connections(2),
+                    |_| MySqlPoolOptions::new().max_connections(2),
                     move |mut conn| {
                         Box::pin(async move {
                             conn.execute(
@@ -27,7 +27,7 @@ mod tests {
                 let backend = SqlxPostgresBackend::new(
                     config.into(),
                     || PgPoolOptions::new().max_connections(10),
-                    || PgPoolOptions::new().max_connections(2),
+                    |_| PgPoolOptions::new().max_connections(2),
                     move |mut conn| {
                         Box::pin(async {
                             conn.execute(
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+#[cfg(feature = "sqlx-mysql")]
+use sqlx::{mysql::MySqlConnectOptions, Connection, Executor, MySqlConnection, Row};
+
+#[cfg(feature = "sqlx-mysql")]
+use crate::common::{config::PrivilegedMySQLConfig, statement::mysql};
+#[cfg(feature = "tokio-postgres")]
+use crate::common::{config::PrivilegedPostgresConfig, statement::postgres};
+
+#[cfg(feature = "tokio-postgres")]
+use super::common::tls::tokio_postgres::make_tls;
+
+/// Databases and roles dropped by [`cleanup_all`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CleanupSummary {
+    /// Names of dropped databases
+    pub databases: Vec<String>,
+    /// Names of dropped roles (users for MySQL)
+    pub roles: Vec<String>,
+    /// Labels of dropped roles that were last pulled with a label, by role name
+    pub labels: HashMap<String, String>,
+}
+
+/// Privileged configuration able to drop databases and roles out-of-band
+#[async_trait]
+pub trait Cleanup {
+    /// Error returned while connecting or dropping
+    type Error;
+
+    /// Drops all databases and roles whose names start with a prefix
+    async fn cleanup_all(&self, prefix: &str) -> Result<CleanupSummary, Self::Error>;
+}
+
+/// Drops all databases and roles whose names start with a prefix without constructing a backend
+///
+/// Intended for manual cleanup after interrupted runs, such as from an `xtask`.
+/// Databases created with the default name generator start with `db_pool_`.
+/// Connections to dropped Postgres databases are terminated first.
+/// No labels are reported for MySQL servers without user attributes, such as `MariaDB`.
+///
+/// Postgres requires the `tokio-postgres` feature and MySQL requires the `sqlx-mysql` feature.
+/// # Example
+/// ```no_run
+/// use db_pool::{r#async::cleanup_all, PrivilegedPostgresConfig};
+/// use dotenvy::dotenv;
+///
+/// async fn f() {
+///     dotenv().ok();
+///
+///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+///
+///     let summary = cleanup_all(&config, "db_pool_").await.unwrap();
+///     println!("dropped databases: {:?}", summary.databases);
+///     println!("dropped roles: {:?}", summary.roles);
+/// }
+///
+/// tokio_test::block_on(f());
+/// ```
+pub async fn cleanup_all<C: Cleanup + Sync>(
+    config: &C,
+    prefix: &str,
+) -> Result<CleanupSummary, C::Error> {
+    config.cleanup_all(prefix).await
+}
+
+#[cfg(feature = "tokio-postgres")]
+#[async_trait]
+impl Cleanup for PrivilegedPostgresConfig {
+    type Error = tokio_postgres::Error;
+
+    async fn cleanup_all(&self, prefix: &str) -> Result<CleanupSummary, Self::Error> {
+        let config = tokio_postgres::Config::from(self.clone());
+        let (client, connection) = config.connect(make_tls()).await?;
+        tokio::spawn(connection);
+
+        // Drop databases after terminating their connections
+        let databases = client
+            .query(
+                postgres::get_database_names_with_prefix(prefix).as_str(),
+                &[],
+            )
+            .await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect::<Vec<String>>();
+        for db_name in &databases {
+            client
+                .batch_execute(postgres::terminate_database_connections(db_name).as_str())
+                .await?;
+            client
+                .batch_execute(postgres::drop_database(db_name).as_str())
+                .await?;
+        }
+
+        // Drop roles once their databases are gone, remembering their labels
+        let labels = client
+            .query(postgres::get_role_labels_with_prefix(prefix).as_str(), &[])
+            .await?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect::<HashMap<String, String>>();
+        let roles = client
+            .query(postgres::get_role_names_with_prefix(prefix).as_str(), &[])
+            .await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect::<Vec<String>>();
+        for role_name in &roles {
+            client
+                .batch_execute(postgres::drop_role(role_name).as_str())
+                .await?;
+        }
+
+        Ok(CleanupSummary {
+            databases,
+            roles,
+            labels,
+        })
+    }
+}
+
+#[cfg(feature = "sqlx-mysql")]
+#[async_trait]
+impl Cleanup for PrivilegedMySQLConfig {
+    type Error = sqlx::Error;
+
+    async fn cleanup_all(&self, prefix: &str) -> Result<CleanupSummary, Self::Error> {
+        let opts = MySqlConnectOptions::from(self.clone());
+        let conn = &mut MySqlConnection::connect_with(&opts).await?;
+
+        // Drop databases
+        let databases = conn
+            .fetch_all(mysql::get_database_names_with_prefix(prefix).as_str())
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<String>, _>>()?;
+        for db_name in &databases {
+            conn.execute(mysql::drop_database(db_name).as_str()).await?;
+        }
+
+        // Drop users on every host they were created for, remembering their labels
+        // unless the server keeps no user attributes, such as MariaDB and MySQL before 8.0.21
+        let labels = match conn
+            .fetch_all(mysql::get_user_labels_with_prefix(prefix).as_str())
+            .await
+        {
+            Ok(rows) => rows
+                .iter()
+                .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+                .collect::<Result<HashMap<String, String>, Self::Error>>()?,
+            Err(sqlx::Error::Database(_)) => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+        let users = conn
+            .fetch_all(mysql::get_user_names_and_hosts_with_prefix(prefix).as_str())
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+            .collect::<Result<Vec<(String, String)>, Self::Error>>()?;
+        for (user_name, host) in &users {
+            conn.execute(mysql::drop_user(user_name, host).as_str())
+                .await?;
+        }
+
+        Ok(CleanupSummary {
+            databases,
+            roles: users.into_iter().map(|(user_name, _)| user_name).collect(),
+            labels,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tokio-postgres"))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::collections::HashMap;
+
+    use tokio_postgres::{Client, NoTls};
+    use tokio_shared_rt::test;
+    use uuid::Uuid;
+
+    use crate::{
+        common::statement::postgres,
+        tests::{get_privileged_postgres_config, PG_DROP_LOCK},
+    };
+
+    use super::{cleanup_all, CleanupSummary};
+
+    async fn exists(query: &str, name: &str, client: &Client) -> bool {
+        client
+            .query(query, &[])
+            .await
+            .unwrap()
+            .iter()
+            .any(|row| row.get::<_, &str>(0) == name)
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn cleanup_drops_databases_and_roles_with_prefix() {
+        let prefix = format!("db_pool_cleanup_{}_", Uuid::new_v4().simple());
+        let name = format!("{prefix}leftover");
+        let name = name.as_str();
+
+        let config = get_privileged_postgres_config();
+
+        let (client, connection) = tokio_postgres::Config::from(config.clone())
+            .connect(NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+
+        let _guard = PG_DROP_LOCK.read().await;
+
+        // leave database and role behind as if from an interrupted run
+        client
+            .batch_execute(format!("CREATE DATABASE {name}").as_str())
+            .await
+            .unwrap();
+        client
+            .batch_execute(format!("CREATE ROLE {name}").as_str())
+            .await
+            .unwrap();
+        client
+            .batch_execute(postgres::comment_role(name, "tests::leftover").as_str())
+            .await
+            .unwrap();
+
+        let summary = cleanup_all(config, prefix.as_str()).await.unwrap();
+        assert_eq!(
+            summary,
+            CleanupSummary {
+                databases: vec![name.to_owned()],
+                roles: vec![name.to_owned()],
+                labels: HashMap::from([(name.to_owned(), "tests::leftover".to_owned())]),
+            }
+        );
+
+        let databases_query = postgres::get_database_names_with_prefix(prefix.as_str());
+        let roles_query = postgres::get_role_names_with_prefix(prefix.as_str());
+        assert!(!exists(databases_query.as_str(), name, &client).await);
+        assert!(!exists(roles_query.as_str(), name, &client).await);
+    }
+}
+
+#[cfg(all(test, feature = "sqlx-mysql"))]
+mod mysql_tests {
+    #![allow(clippy::unwrap_used)]
+
+    use sqlx::{mysql::MySqlConnectOptions, Connection, Executor, MySqlConnection, Row};
+    use tokio_shared_rt::test;
+    use uuid::Uuid;
+
+    use crate::{
+        common::statement::mysql,
+        tests::{get_privileged_mysql_config, MYSQL_DROP_LOCK},
+    };
+
+    use super::{cleanup_all, CleanupSummary};
+
+    async fn exists(query: &str, name: &str, conn: &mut MySqlConnection) -> bool {
+        conn.fetch_all(query)
+            .await
+            .unwrap()
+            .iter()
+            .any(|row| row.get::<String, _>(0) == name)
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn cleanup_drops_databases_and_users_with_prefix() {
+        // Keep user name within the length limit of MySQL
+        let prefix = format!("db_pool_{}_", &Uuid::new_v4().simple().to_string()[..12]);
+        let name = format!("{prefix}leftover");
+        let name = name.as_str();
+
+        let config = get_privileged_mysql_config();
+
+        let conn = &mut MySqlConnection::connect_with(&MySqlConnectOptions::from(config.clone()))
+            .await
+            .unwrap();
+
+        let _guard = MYSQL_DROP_LOCK.read().await;
+
+        // leave database and user behind as if from an interrupted run
+        conn.execute(mysql::create_database(name, None, None).as_str())
+            .await
+            .unwrap();
+        conn.execute(mysql::create_user(name, "%").as_str())
+            .await
+            .unwrap();
+
+        // users must be dropped even on servers keeping no labels
+        let summary = cleanup_all(config, prefix.as_str()).await.unwrap();
+        assert_eq!(
+            summary,
+            CleanupSummary {
+                databases: vec![name.to_owned()],
+                roles: vec![name.to_owned()],
+                ..CleanupSummary::default()
+            }
+        );
+
+        let databases_query = mysql::get_database_names_with_prefix(prefix.as_str());
+        let users_query = mysql::get_user_names_and_hosts_with_prefix(prefix.as_str());
+        assert!(!exists(databases_query.as_str(), name, conn).await);
+        assert!(!exists(users_query.as_str(), name, conn).await);
+    }
+}
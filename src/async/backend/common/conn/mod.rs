@@ -1,2 +1,4 @@
+#[cfg(feature = "mysql-async")]
+pub(in crate::r#async::backend) mod mysql_async;
 #[cfg(feature = "_sea-orm")]
 pub(in crate::r#async::backend) mod sea_orm;
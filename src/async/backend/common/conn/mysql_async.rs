@@ -0,0 +1,25 @@
+use std::ops::{Deref, DerefMut};
+
+use mysql_async::Conn;
+
+pub struct PooledConnection(Conn);
+
+impl From<Conn> for PooledConnection {
+    fn from(value: Conn) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Conn;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -1,5 +1,7 @@
 #[cfg(feature = "_diesel-async")]
 mod diesel;
+#[cfg(feature = "mysql-async")]
+pub(in crate::r#async::backend) mod mysql_async;
 #[cfg(feature = "_sea-orm")]
 pub(in crate::r#async::backend) mod sea_orm;
 #[cfg(feature = "_sqlx")]
@@ -1,3 +1,4 @@
 pub(in crate::r#async::backend) mod conn;
 pub(in crate::r#async::backend) mod error;
 pub(in crate::r#async::backend) mod pool;
+pub(in crate::r#async::backend) mod tls;
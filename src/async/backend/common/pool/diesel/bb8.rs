@@ -1,4 +1,8 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+};
 
 use async_trait::async_trait;
 use bb8::{Builder, ManageConnection, Pool, PooledConnection, RunError};
@@ -8,9 +12,9 @@ use diesel_async::{
     AsyncConnection,
 };
 
-use crate::r#async::backend::error::Error as BackendError;
+use crate::r#async::{backend::error::Error as BackendError, rollback::RollbackPool};
 
-use super::r#trait::DieselPoolAssociation;
+use super::r#trait::{run_with_rollback, DieselPoolAssociation};
 
 /// [`Diesel bb8`](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/bb8/index.html) association
 /// # Example
@@ -62,6 +66,7 @@ where
     RunError<<Manager<Connection> as ManageConnection>::Error>: Into<RunError<DieselPoolError>>,
 {
     type PooledConnection<'pool> = PooledConnection<'pool, Manager<Connection>>;
+    type OwnedPooledConnection = PooledConnection<'static, Manager<Connection>>;
 
     type Builder = Builder<Manager<Connection>>;
     type Pool = Pool<Manager<Connection>>;
@@ -84,6 +89,38 @@ where
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError> {
         pool.get().await.map_err(|err| err.into().into())
     }
+
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError> {
+        pool.get_owned().await.map_err(|err| err.into().into())
+    }
+}
+
+#[async_trait]
+impl<Connection> RollbackPool for Pool<Manager<Connection>>
+where
+    Connection: AsyncConnection + 'static,
+    Manager<Connection>: ManageConnection,
+    for<'pool> PooledConnection<'pool, Manager<Connection>>: DerefMut<Target = Connection>,
+    <Manager<Connection> as ManageConnection>::Error: Into<RunError<DieselPoolError>>,
+    RunError<<Manager<Connection> as ManageConnection>::Error>: Into<RunError<DieselPoolError>>,
+{
+    type Connection = Connection;
+    type Error = BackendError<BuildError, PoolError, ConnectionError, Error>;
+
+    async fn with_rollback<T, F>(&self, f: F) -> Result<T, Self::Error>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send,
+    {
+        let mut conn =
+            <DieselBb8 as DieselPoolAssociation<Connection>>::get_connection(self).await?;
+        run_with_rollback(&mut *conn, f)
+            .await
+            .map_err(BackendError::Query)
+    }
 }
 
 #[derive(Debug)]
@@ -1,36 +1,90 @@
+use std::{future::Future, ops::Deref, pin::Pin};
+
 use async_trait::async_trait;
-use deadpool::managed::{BuildError, Object, Pool, PoolBuilder, PoolError as DeadpoolPoolError};
+use deadpool::{
+    managed::{
+        BuildError as DeadpoolBuildError, Manager as DeadpoolManager, Object, Pool, PoolConfig,
+        PoolError as DeadpoolPoolError,
+    },
+    Runtime,
+};
 use diesel::{result::Error as DieselError, ConnectionError};
 use diesel_async::{
-    pooled_connection::{AsyncDieselConnectionManager, PoolError},
-    AsyncPgConnection,
+    pooled_connection::{AsyncDieselConnectionManager, PoolError as DieselPoolError},
+    AsyncConnection,
 };
 
-use crate::r#async::backend::error::Error as BackendError;
+use crate::r#async::{backend::error::Error as BackendError, rollback::RollbackPool};
 
-use super::r#trait::DieselPoolAssociation;
+use super::r#trait::{run_with_rollback, DieselPoolAssociation};
 
 type DieselManager<Connection> = AsyncDieselConnectionManager<Connection>;
 
 /// [`Diesel deadpool`](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/deadpool/index.html) association
+///
+/// Since a deadpool pool builder requires its connection manager upfront, pools are configured using a [`PoolConfig`](https://docs.rs/deadpool/0.12.1/deadpool/managed/struct.PoolConfig.html) instead.
+/// # Example
+/// ```
+/// use db_pool::{
+///     r#async::{DieselAsyncMySQLBackend, DieselDeadpool},
+///     PrivilegedMySQLConfig,
+/// };
+/// use deadpool::managed::PoolConfig;
+/// use diesel::sql_query;
+/// use diesel_async::RunQueryDsl;
+/// use dotenvy::dotenv;
+///
+/// async fn f() {
+///     dotenv().ok();
+///
+///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+///
+///     let backend = DieselAsyncMySQLBackend::<DieselDeadpool>::new(
+///         config,
+///         || PoolConfig::new(10),
+///         || PoolConfig::new(2),
+///         None,
+///         move |mut conn| {
+///             Box::pin(async move {
+///                 sql_query("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+///                     .execute(&mut conn)
+///                     .await
+///                     .unwrap();
+///             })
+///         },
+///     )
+///     .await
+///     .unwrap();
+/// }
+///
+/// tokio_test::block_on(f());
+/// ```
 pub struct DieselDeadpool;
 
 #[async_trait]
-impl DieselPoolAssociation<AsyncPgConnection> for DieselDeadpool {
-    type PooledConnection<'pool> = Object<DieselManager<AsyncPgConnection>>;
+impl<Connection> DieselPoolAssociation<Connection> for DieselDeadpool
+where
+    Connection: AsyncConnection + 'static,
+    DieselManager<Connection>: DeadpoolManager<Type = Connection, Error = DieselPoolError>,
+{
+    type PooledConnection<'pool> = Object<DieselManager<Connection>>;
+    type OwnedPooledConnection = Object<DieselManager<Connection>>;
 
-    type Builder = PoolBuilder<DieselManager<AsyncPgConnection>>;
-    type Pool = Pool<DieselManager<AsyncPgConnection>>;
+    type Builder = PoolConfig;
+    type Pool = Pool<DieselManager<Connection>>;
 
-    type BuildError = BuildError<PoolError>;
-    type PoolError = DeadpoolPoolError<PoolError>;
+    type BuildError = BuildError;
+    type PoolError = PoolError;
 
     async fn build_pool(
-        builder: Self::Builder,
-        // TODO: add builder wrapper
-        _manager: DieselManager<AsyncPgConnection>,
+        builder: PoolConfig,
+        manager: DieselManager<Connection>,
     ) -> Result<Self::Pool, Self::BuildError> {
-        builder.build().map_err(Into::into)
+        Pool::builder(manager)
+            .config(builder)
+            .runtime(Runtime::Tokio1)
+            .build()
+            .map_err(Into::into)
     }
 
     async fn get_connection<'pool>(
@@ -38,30 +92,79 @@ impl DieselPoolAssociation<AsyncPgConnection> for DieselDeadpool {
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError> {
         pool.get().await.map_err(Into::into)
     }
+
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError> {
+        pool.get().await.map_err(Into::into)
+    }
 }
 
-impl From<BuildError<PoolError>>
-    for BackendError<
-        BuildError<PoolError>,
-        DeadpoolPoolError<PoolError>,
-        ConnectionError,
-        DieselError,
-    >
+#[async_trait]
+impl<Connection> RollbackPool for Pool<DieselManager<Connection>>
+where
+    Connection: AsyncConnection + 'static,
+    DieselManager<Connection>: DeadpoolManager<Type = Connection, Error = DieselPoolError>,
 {
-    fn from(value: BuildError<PoolError>) -> Self {
+    type Connection = Connection;
+    type Error = BackendError<BuildError, PoolError, ConnectionError, DieselError>;
+
+    async fn with_rollback<T, F>(&self, f: F) -> Result<T, Self::Error>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send,
+    {
+        let mut conn =
+            <DieselDeadpool as DieselPoolAssociation<Connection>>::get_connection(self).await?;
+        run_with_rollback(&mut *conn, f)
+            .await
+            .map_err(BackendError::Query)
+    }
+}
+
+#[derive(Debug)]
+pub struct BuildError(DeadpoolBuildError);
+
+impl Deref for BuildError {
+    type Target = DeadpoolBuildError;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<DeadpoolBuildError> for BuildError {
+    fn from(value: DeadpoolBuildError) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct PoolError(DeadpoolPoolError<DieselPoolError>);
+
+impl Deref for PoolError {
+    type Target = DeadpoolPoolError<DieselPoolError>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<DeadpoolPoolError<DieselPoolError>> for PoolError {
+    fn from(value: DeadpoolPoolError<DieselPoolError>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BuildError> for BackendError<BuildError, PoolError, ConnectionError, DieselError> {
+    fn from(value: BuildError) -> Self {
         Self::Build(value)
     }
 }
 
-impl From<DeadpoolPoolError<PoolError>>
-    for BackendError<
-        BuildError<PoolError>,
-        DeadpoolPoolError<PoolError>,
-        ConnectionError,
-        DieselError,
-    >
-{
-    fn from(value: DeadpoolPoolError<PoolError>) -> Self {
+impl From<PoolError> for BackendError<BuildError, PoolError, ConnectionError, DieselError> {
+    fn from(value: PoolError) -> Self {
         Self::Pool(value)
     }
 }
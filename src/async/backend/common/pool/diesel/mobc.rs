@@ -1,4 +1,8 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+};
 
 use async_trait::async_trait;
 use diesel::{result::Error as DieselError, ConnectionError};
@@ -10,9 +14,9 @@ use mobc::{
     Builder, Connection as MobcConnection, Error as MobcError, Manager as MobcManager, Pool,
 };
 
-use crate::r#async::backend::error::Error as BackendError;
+use crate::r#async::{backend::error::Error as BackendError, rollback::RollbackPool};
 
-use super::r#trait::DieselPoolAssociation;
+use super::r#trait::{run_with_rollback, DieselPoolAssociation};
 
 type DieselManager<Connection> = AsyncDieselConnectionManager<Connection>;
 
@@ -65,6 +69,7 @@ where
     MobcError<<DieselManager<Connection> as MobcManager>::Error>: Into<MobcError<DieselPoolError>>,
 {
     type PooledConnection<'pool> = MobcConnection<DieselManager<Connection>>;
+    type OwnedPooledConnection = MobcConnection<DieselManager<Connection>>;
 
     type Builder = Builder<DieselManager<Connection>>;
     type Pool = Pool<DieselManager<Connection>>;
@@ -84,6 +89,37 @@ where
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError> {
         pool.get().await.map_err(|err| err.into().into())
     }
+
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError> {
+        pool.get().await.map_err(|err| err.into().into())
+    }
+}
+
+#[async_trait]
+impl<Connection> RollbackPool for Pool<DieselManager<Connection>>
+where
+    Connection: AsyncConnection + 'static,
+    DieselManager<Connection>: MobcManager,
+    for<'pool> MobcConnection<DieselManager<Connection>>: DerefMut<Target = Connection>,
+    MobcError<<DieselManager<Connection> as MobcManager>::Error>: Into<MobcError<DieselPoolError>>,
+{
+    type Connection = Connection;
+    type Error = BackendError<BuildError, PoolError, ConnectionError, DieselError>;
+
+    async fn with_rollback<T, F>(&self, f: F) -> Result<T, Self::Error>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send,
+    {
+        let mut conn =
+            <DieselMobc as DieselPoolAssociation<Connection>>::get_connection(self).await?;
+        run_with_rollback(&mut *conn, f)
+            .await
+            .map_err(BackendError::Query)
+    }
 }
 
 #[derive(Debug)]
@@ -1,7 +1,7 @@
 #[cfg(any(all(test, feature = "_diesel-async"), feature = "diesel-async-bb8"))]
 pub mod bb8;
-// #[cfg(feature = "diesel-async-deadpool")]
-// pub mod deadpool;
+#[cfg(feature = "diesel-async-deadpool")]
+pub mod deadpool;
 #[cfg(feature = "diesel-async-mobc")]
 pub mod mobc;
 pub(in crate::r#async::backend) mod r#trait;
@@ -1,8 +1,10 @@
-use std::{fmt::Debug, ops::DerefMut};
+use std::{fmt::Debug, future::Future, ops::DerefMut, pin::Pin};
 
 use async_trait::async_trait;
 use diesel::{result::Error, ConnectionError};
-use diesel_async::{pooled_connection::AsyncDieselConnectionManager, AsyncConnection};
+use diesel_async::{
+    pooled_connection::AsyncDieselConnectionManager, AsyncConnection, TransactionManager,
+};
 
 use crate::r#async::backend::error::Error as BackendError;
 
@@ -12,6 +14,7 @@ where
     Connection: AsyncConnection + 'static,
 {
     type PooledConnection<'pool>: DerefMut<Target = Connection> + Send;
+    type OwnedPooledConnection: DerefMut<Target = Connection> + Send + 'static;
 
     type Builder;
     type Pool: Send + Sync + 'static;
@@ -30,4 +33,21 @@ where
     async fn get_connection<'pool>(
         pool: &'pool Self::Pool,
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError>;
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError>;
+}
+
+pub(super) async fn run_with_rollback<Connection, T, F>(
+    conn: &mut Connection,
+    f: F,
+) -> Result<T, Error>
+where
+    Connection: AsyncConnection,
+    F: for<'c> FnOnce(&'c mut Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>,
+{
+    Connection::TransactionManager::begin_transaction(conn).await?;
+    let value = f(conn).await;
+    Connection::TransactionManager::rollback_transaction(conn).await?;
+    Ok(value)
 }
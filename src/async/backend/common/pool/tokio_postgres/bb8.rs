@@ -1,18 +1,50 @@
-use std::ops::Deref;
+use std::{
+    fmt::{self, Debug},
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
-use bb8::{Builder, Pool, PooledConnection, RunError};
+use bb8::{Builder, CustomizeConnection, Pool, PooledConnection, RunError};
 use bb8_postgres::PostgresConnectionManager;
-use tokio_postgres::{Config, Error, NoTls};
+use tokio_postgres::{Client, Config, Error};
 
 use crate::r#async::backend::{
-    common::error::tokio_postgres::{ConnectionError, QueryError},
+    common::{
+        error::tokio_postgres::{ConnectionError, QueryError},
+        tls::tokio_postgres::{make_tls, MakeTls},
+    },
     error::Error as BackendError,
 };
 
 use super::r#trait::TokioPostgresPoolAssociation;
 
-type Manager = PostgresConnectionManager<NoTls>;
+type Manager = PostgresConnectionManager<MakeTls>;
+
+pub(in crate::r#async::backend) type AfterConnect = dyn for<'c> Fn(&'c mut Client) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
+    + Send
+    + Sync
+    + 'static;
+
+pub(in crate::r#async::backend) struct AfterConnectCustomizer(
+    pub(in crate::r#async::backend) Arc<AfterConnect>,
+);
+
+impl Debug for AfterConnectCustomizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AfterConnectCustomizer")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CustomizeConnection<Client, Error> for AfterConnectCustomizer {
+    async fn on_acquire(&self, conn: &mut Client) -> Result<(), Error> {
+        (self.0)(conn).await
+    }
+}
 
 /// [`tokio-postgres bb8`](https://docs.rs/bb8-postgres/0.8.1/bb8_postgres/) association
 /// # Example
@@ -51,6 +83,7 @@ pub struct TokioPostgresBb8;
 #[async_trait]
 impl TokioPostgresPoolAssociation for TokioPostgresBb8 {
     type PooledConnection<'pool> = PooledConnection<'pool, Manager>;
+    type OwnedPooledConnection = PooledConnection<'static, Manager>;
 
     type Builder = Builder<Manager>;
     type Pool = Pool<Manager>;
@@ -62,7 +95,7 @@ impl TokioPostgresPoolAssociation for TokioPostgresBb8 {
         builder: Builder<Manager>,
         config: Config,
     ) -> Result<Pool<Manager>, BuildError> {
-        let manager = Manager::new(config, NoTls);
+        let manager = Manager::new(config, make_tls());
         builder.build(manager).await.map_err(Into::into)
     }
 
@@ -71,6 +104,12 @@ impl TokioPostgresPoolAssociation for TokioPostgresBb8 {
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError> {
         pool.get().await.map_err(Into::into)
     }
+
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError> {
+        pool.get_owned().await.map_err(Into::into)
+    }
 }
 
 #[derive(Debug)]
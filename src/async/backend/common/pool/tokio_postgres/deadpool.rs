@@ -18,6 +18,7 @@ pub struct TokioPostgresDeadpool;
 #[async_trait]
 impl TokioPostgresPoolAssociation for TokioPostgresDeadpool {
     type PooledConnection<'pool> = PooledConnection;
+    type OwnedPooledConnection = PooledConnection;
 
     type Builder = PoolBuilder<Manager>;
     type Pool = Pool<Manager>;
@@ -38,6 +39,12 @@ impl TokioPostgresPoolAssociation for TokioPostgresDeadpool {
     ) -> Result<PooledConnection, PoolError<Error>> {
         pool.get().await.map(Into::into)
     }
+
+    async fn get_owned_connection(
+        pool: &Pool<Manager>,
+    ) -> Result<PooledConnection, PoolError<Error>> {
+        pool.get().await.map(Into::into)
+    }
 }
 
 pub struct PooledConnection(Object<Manager>);
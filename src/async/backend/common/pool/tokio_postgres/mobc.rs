@@ -3,16 +3,19 @@ use std::ops::Deref;
 use async_trait::async_trait;
 use mobc::{Builder, Connection, Error as MobcError, Pool};
 use mobc_postgres::PgConnectionManager;
-use tokio_postgres::{Config, Error, NoTls};
+use tokio_postgres::{Config, Error};
 
 use crate::r#async::backend::{
-    common::error::tokio_postgres::{ConnectionError, QueryError},
+    common::{
+        error::tokio_postgres::{ConnectionError, QueryError},
+        tls::tokio_postgres::{make_tls, MakeTls},
+    },
     error::Error as BackendError,
 };
 
 use super::r#trait::TokioPostgresPoolAssociation;
 
-type Manager = PgConnectionManager<NoTls>;
+type Manager = PgConnectionManager<MakeTls>;
 
 /// [`tokio-postgres mobc`](https://docs.rs/mobc-postgres/0.8.0/mobc_postgres/) association
 /// # Example
@@ -51,6 +54,7 @@ pub struct TokioPostgresMobc;
 #[async_trait]
 impl TokioPostgresPoolAssociation for TokioPostgresMobc {
     type PooledConnection<'pool> = Connection<Manager>;
+    type OwnedPooledConnection = Connection<Manager>;
 
     type Builder = Builder<Manager>;
     type Pool = Pool<Manager>;
@@ -62,7 +66,7 @@ impl TokioPostgresPoolAssociation for TokioPostgresMobc {
         builder: Builder<Manager>,
         config: Config,
     ) -> Result<Self::Pool, Self::BuildError> {
-        let manager = Manager::new(config, NoTls);
+        let manager = Manager::new(config, make_tls());
         Ok(builder.build(manager))
     }
 
@@ -71,6 +75,10 @@ impl TokioPostgresPoolAssociation for TokioPostgresMobc {
     ) -> Result<Connection<Manager>, PoolError> {
         pool.get().await.map_err(Into::into)
     }
+
+    async fn get_owned_connection(pool: &Self::Pool) -> Result<Connection<Manager>, PoolError> {
+        pool.get().await.map_err(Into::into)
+    }
 }
 
 #[derive(Debug)]
@@ -11,6 +11,7 @@ use crate::r#async::backend::{
 #[async_trait]
 pub trait TokioPostgresPoolAssociation: 'static {
     type PooledConnection<'pool>: DerefMut<Target = Client> + Send;
+    type OwnedPooledConnection: DerefMut<Target = Client> + Send + 'static;
 
     type Builder;
     type Pool: Send + Sync + 'static;
@@ -29,4 +30,7 @@ pub trait TokioPostgresPoolAssociation: 'static {
     async fn get_connection<'pool>(
         pool: &'pool Self::Pool,
     ) -> Result<Self::PooledConnection<'pool>, Self::PoolError>;
+    async fn get_owned_connection(
+        pool: &Self::Pool,
+    ) -> Result<Self::OwnedPooledConnection, Self::PoolError>;
 }
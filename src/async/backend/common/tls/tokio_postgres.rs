@@ -0,0 +1,51 @@
+#[cfg(all(
+    feature = "tokio-postgres-rustls",
+    not(feature = "tokio-postgres-native-tls")
+))]
+use std::sync::Arc;
+
+/// TLS connector selected through the `tokio-postgres-native-tls` and `tokio-postgres-rustls` features,
+/// preferring `tokio-postgres-native-tls` if both are enabled
+#[cfg(feature = "tokio-postgres-native-tls")]
+pub type MakeTls = postgres_native_tls::MakeTlsConnector;
+#[cfg(all(
+    feature = "tokio-postgres-rustls",
+    not(feature = "tokio-postgres-native-tls")
+))]
+pub type MakeTls = tokio_postgres_rustls::MakeRustlsConnect;
+#[cfg(not(any(
+    feature = "tokio-postgres-native-tls",
+    feature = "tokio-postgres-rustls"
+)))]
+pub type MakeTls = tokio_postgres::NoTls;
+
+#[cfg(feature = "tokio-postgres-native-tls")]
+pub fn make_tls() -> MakeTls {
+    let connector = native_tls::TlsConnector::new().expect("TLS connector creation must succeed");
+    MakeTls::new(connector)
+}
+
+#[cfg(all(
+    feature = "tokio-postgres-rustls",
+    not(feature = "tokio-postgres-native-tls")
+))]
+pub fn make_tls() -> MakeTls {
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .expect("default protocol versions must be supported")
+    .with_root_certificates(root_store)
+    .with_no_client_auth();
+    MakeTls::new(config)
+}
+
+#[cfg(not(any(
+    feature = "tokio-postgres-native-tls",
+    feature = "tokio-postgres-rustls"
+)))]
+pub fn make_tls() -> MakeTls {
+    tokio_postgres::NoTls
+}
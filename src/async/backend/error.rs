@@ -1,4 +1,6 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
+
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub enum Error<B: Debug, P: Debug, C: Debug, Q: Debug> {
@@ -6,4 +8,13 @@ pub enum Error<B: Debug, P: Debug, C: Debug, Q: Debug> {
     Pool(P),
     Connection(C),
     Query(Q),
+    MissingDatabaseConnection(Uuid),
+    InvalidDatabaseName(String),
+    RoleExists(String),
+    FailedEntityCreation(Uuid),
+    UncleanDatabase(Uuid, Vec<String>),
+    LeasedDatabases(Vec<Uuid>),
+    UnrestrictedDatabase(Uuid),
+    ConnectTimeout(Duration),
+    MaxDatabasesExceeded(usize, usize),
 }
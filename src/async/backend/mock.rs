@@ -0,0 +1,359 @@
+use std::{convert::Infallible, sync::Arc};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use crate::util::get_db_name;
+
+use super::{error::Error as BackendError, r#trait::Backend};
+
+/// Lifecycle operation recorded by [`MockBackend`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    /// Backend initialized
+    Init,
+    /// Database server pinged
+    Ping,
+    /// Database created
+    Create {
+        /// Database ID
+        db_id: Uuid,
+        /// Whether privileges are restricted
+        restrict_privileges: bool,
+    },
+    /// Database created without entities
+    CreateEmpty {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Reader pool created
+    CreateReaderPool {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Database cleaned
+    Clean {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Database refreshed
+    Refresh {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Restrictions verified
+    VerifyRestricted {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Attached role labeled
+    Label {
+        /// Database ID
+        db_id: Uuid,
+        /// Label
+        label: String,
+    },
+    /// Database dropped
+    Drop {
+        /// Database ID
+        db_id: Uuid,
+        /// Whether privileges were restricted
+        is_restricted: bool,
+    },
+}
+
+/// Dummy connection pool handed out by [`MockBackend`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MockPool {
+    database_id: Option<Uuid>,
+}
+
+impl MockPool {
+    /// Returns the ID of the database the pool was handed out for, or [`None`] for the privileged pool
+    #[must_use]
+    pub fn database_id(&self) -> Option<Uuid> {
+        self.database_id
+    }
+}
+
+/// In-process backend that records lifecycle operations instead of running them against a database server
+///
+/// Intended for unit-testing fixture wiring and [`PoolWrapper`](super::super::PoolWrapper) plumbing offline.
+/// Clones share their recorded operations, so a clone kept before creating a database pool can be inspected afterwards.
+/// # Example
+/// ```
+/// use db_pool::r#async::{DatabasePoolBuilderTrait, MockBackend, MockCall};
+///
+/// async fn f() {
+///     let backend = MockBackend::new();
+///
+///     let db_pool = backend.clone().create_database_pool().await.unwrap();
+///     let db_id = db_pool.pull_immutable().await.database_id();
+///
+///     assert_eq!(
+///         backend.calls()[..2],
+///         [
+///             MockCall::Init,
+///             MockCall::Create {
+///                 db_id,
+///                 restrict_privileges: true
+///             }
+///         ]
+///     );
+/// }
+///
+/// tokio_test::block_on(f());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    calls: Arc<Mutex<Vec<MockCall>>>,
+    privileged_pool: MockPool,
+}
+
+impl MockBackend {
+    /// Creates a new mock backend
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the lifecycle operations recorded so far, in order
+    #[must_use]
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().clone()
+    }
+
+    fn record(&self, call: MockCall) {
+        self.calls.lock().push(call);
+    }
+}
+
+type BError = BackendError<Infallible, Infallible, Infallible, Infallible>;
+
+#[async_trait]
+impl Backend for MockBackend {
+    type Pool = MockPool;
+
+    type BuildError = Infallible;
+    type PoolError = Infallible;
+    type ConnectionError = Infallible;
+    type QueryError = Infallible;
+
+    async fn init(&self) -> Result<(), BError> {
+        self.record(MockCall::Init);
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), BError> {
+        self.record(MockCall::Ping);
+        Ok(())
+    }
+
+    async fn create(&self, db_id: Uuid, restrict_privileges: bool) -> Result<MockPool, BError> {
+        self.record(MockCall::Create {
+            db_id,
+            restrict_privileges,
+        });
+        Ok(MockPool {
+            database_id: Some(db_id),
+        })
+    }
+
+    async fn create_empty(&self, db_id: Uuid) -> Result<MockPool, BError> {
+        self.record(MockCall::CreateEmpty { db_id });
+        Ok(MockPool {
+            database_id: Some(db_id),
+        })
+    }
+
+    async fn create_reader_pool(&self, db_id: Uuid) -> Result<MockPool, BError> {
+        self.record(MockCall::CreateReaderPool { db_id });
+        Ok(MockPool {
+            database_id: Some(db_id),
+        })
+    }
+
+    async fn clean(&self, db_id: Uuid) -> Result<(), BError> {
+        self.record(MockCall::Clean { db_id });
+        Ok(())
+    }
+
+    async fn refresh(&self, db_id: Uuid) -> Result<(), BError> {
+        self.record(MockCall::Refresh { db_id });
+        Ok(())
+    }
+
+    async fn verify_restricted(&self, db_id: Uuid) -> Result<(), BError> {
+        self.record(MockCall::VerifyRestricted { db_id });
+        Ok(())
+    }
+
+    async fn label(&self, db_id: Uuid, label: &str) -> Result<(), BError> {
+        self.record(MockCall::Label {
+            db_id,
+            label: label.to_owned(),
+        });
+        Ok(())
+    }
+
+    async fn drop(&self, db_id: Uuid, is_restricted: bool) -> Result<(), BError> {
+        self.record(MockCall::Drop {
+            db_id,
+            is_restricted,
+        });
+        Ok(())
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        Some(format!("mock:///{}", get_db_name(db_id)))
+    }
+
+    fn privileged_pool(&self) -> &MockPool {
+        &self.privileged_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use futures::FutureExt;
+    use tokio_shared_rt::test;
+
+    use crate::r#async::{
+        backend::{r#trait::Backend, Error},
+        db_pool::DatabasePoolBuilder,
+        PoolWrapper,
+    };
+
+    use super::{MockBackend, MockCall};
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_records_lifecycle_operations() {
+        let backend = MockBackend::new();
+        let db_pool = backend.clone().create_database_pool().await.unwrap();
+
+        // pulling twice must reuse and clean the database
+        let db_id = db_pool.pull_immutable().await.database_id();
+        assert_eq!(db_pool.pull_immutable().await.database_id(), db_id);
+        db_pool.teardown().await.unwrap();
+
+        assert_eq!(
+            backend.calls(),
+            [
+                MockCall::Init,
+                MockCall::Create {
+                    db_id,
+                    restrict_privileges: true
+                },
+                MockCall::Clean { db_id },
+                MockCall::Drop {
+                    db_id,
+                    is_restricted: true
+                },
+            ]
+        );
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_pulls_many_databases() {
+        let backend = MockBackend::new();
+        let db_pool = backend.clone().create_database_pool().await.unwrap();
+
+        // pulling must create only the databases missing from the pool
+        let db_id = db_pool.pull_immutable().await.database_id();
+        let conn_pools = db_pool.pull_many(3).await.unwrap();
+        let mut db_ids = conn_pools
+            .iter()
+            .map(|conn_pool| conn_pool.database_id())
+            .collect::<Vec<_>>();
+        db_ids.sort();
+        db_ids.dedup();
+        assert_eq!(db_ids.len(), 3);
+        assert!(db_ids.contains(&db_id));
+
+        let creates = backend
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, MockCall::Create { .. }))
+            .count();
+        assert_eq!(creates, 3);
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_pulls_many_databases_within_max_databases() {
+        const MAX_DATABASES: usize = 2;
+
+        let backend = MockBackend::new();
+        let db_pool = backend
+            .clone()
+            .create_database_pool()
+            .await
+            .unwrap()
+            .max_databases(MAX_DATABASES);
+
+        // pulling more databases than the cap must fail instead of waiting forever
+        assert!(matches!(
+            db_pool.pull_many(MAX_DATABASES + 1).await,
+            Err(Error::MaxDatabasesExceeded(n, max)) if n == MAX_DATABASES + 1 && max == MAX_DATABASES
+        ));
+
+        // pulling must wait until capacity for all databases is available
+        let conn_pool = db_pool.pull_immutable().await;
+        assert!(db_pool.pull_many(MAX_DATABASES).now_or_never().is_none());
+        drop(conn_pool);
+        let conn_pools = db_pool.pull_many(MAX_DATABASES).await.unwrap();
+        assert!(db_pool.pull_immutable().now_or_never().is_none());
+
+        // databases must not be created beyond the cap
+        let creates = backend
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, MockCall::Create { .. }))
+            .count();
+        assert_eq!(creates, MAX_DATABASES);
+
+        drop(conn_pools);
+        assert!(db_pool.pull_immutable().now_or_never().is_some());
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_prewarms_databases_within_max_databases() {
+        const MAX_DATABASES: usize = 3;
+
+        let backend = MockBackend::new();
+        let db_pool = backend
+            .clone()
+            .create_database_pool()
+            .await
+            .unwrap()
+            .max_databases(MAX_DATABASES);
+
+        // prewarming must leave room for leased databases and stop at the cap
+        let conn_pool = db_pool.pull_immutable().await;
+        db_pool.prewarm(MAX_DATABASES).await.unwrap();
+        drop(conn_pool);
+        db_pool.prewarm(1).await.unwrap();
+
+        let creates = backend
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, MockCall::Create { .. }))
+            .count();
+        assert_eq!(creates, MAX_DATABASES);
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn wrapper_derefs_to_mock_pool() {
+        let backend = MockBackend::new();
+
+        // wrapped privileged pool must not belong to any database
+        let pool = PoolWrapper::from(*backend.privileged_pool());
+        assert_eq!(pool.database_id(), None);
+    }
+}
@@ -1,5 +1,8 @@
+mod cleanup;
 mod common;
 mod error;
+#[cfg(feature = "mock")]
+mod mock;
 #[cfg(feature = "_async-mysql")]
 mod mysql;
 #[cfg(feature = "_async-postgres")]
@@ -8,20 +11,26 @@ pub(crate) mod r#trait;
 
 pub(crate) use error::Error;
 
+pub use cleanup::{cleanup_all, Cleanup, CleanupSummary};
+
 #[cfg(feature = "diesel-async-bb8")]
 pub use common::pool::diesel::bb8::DieselBb8;
-// #[cfg(feature = "diesel-async-deadpool")]
-// pub use common::pool::diesel::deadpool::DieselDeadpool;
+#[cfg(feature = "diesel-async-deadpool")]
+pub use common::pool::diesel::deadpool::DieselDeadpool;
 #[cfg(feature = "diesel-async-mobc")]
 pub use common::pool::diesel::mobc::DieselMobc;
 #[cfg(feature = "tokio-postgres-bb8")]
 pub use common::pool::tokio_postgres::bb8::TokioPostgresBb8;
+#[cfg(feature = "mock")]
+pub use mock::{MockBackend, MockCall, MockPool};
 // #[cfg(feature = "tokio-postgres-deadpool")]
 // pub use common::pool::tokio_postgres::deadpool::TokioPostgresDeadpool;
 #[cfg(feature = "tokio-postgres-mobc")]
 pub use common::pool::tokio_postgres::mobc::TokioPostgresMobc;
 #[cfg(feature = "diesel-async-mysql")]
 pub use mysql::DieselAsyncMySQLBackend;
+#[cfg(feature = "mysql-async")]
+pub use mysql::MySQLAsyncBackend;
 #[cfg(feature = "sea-orm-mysql")]
 pub use mysql::SeaORMMySQLBackend;
 #[cfg(feature = "sqlx-mysql")]
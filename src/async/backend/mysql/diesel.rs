@@ -1,7 +1,11 @@
-use std::{borrow::Cow, pin::Pin};
+use std::{borrow::Cow, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use diesel::{prelude::*, result::Error, sql_query, table};
+use diesel::{
+    prelude::*,
+    result::{ConnectionError, DatabaseErrorKind, Error},
+    sql_query, table,
+};
 use diesel_async::{
     pooled_connection::{AsyncDieselConnectionManager, ManagerConfig, SetupCallback},
     AsyncConnection, AsyncMysqlConnection, RunQueryDsl, SimpleAsyncConnection,
@@ -10,8 +14,16 @@ use futures::{future::FutureExt, Future};
 use uuid::Uuid;
 
 use crate::{
-    common::{config::mysql::PrivilegedMySQLConfig, statement::mysql},
-    util::get_db_name,
+    common::{
+        config::{mysql::PrivilegedMySQLConfig, MySqlFlavor},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    r#async::{
+        schema_provider::{create_entities_with, SchemaProvider},
+        PoolWrapper,
+    },
+    util::{get_credentials, get_db_name, PreviousDatabaseQuery, ReusedLogin, TableNamesCache},
 };
 
 use super::{
@@ -22,19 +34,46 @@ use super::{
     r#trait::{MySQLBackend, MySQLBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(AsyncMysqlConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+type CreateEntities = dyn Fn(AsyncMysqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut AsyncMysqlConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`Diesel async MySQL`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncMysqlConnection.html) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct DieselAsyncMySQLBackend<P: DieselPoolAssociation<AsyncMysqlConnection>> {
     privileged_config: PrivilegedMySQLConfig,
     default_pool: P::Pool,
+    table_names_cache: TableNamesCache,
     create_restricted_pool: Box<dyn Fn() -> P::Builder + Send + Sync + 'static>,
     create_connection: Box<dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
 }
 
 impl<P: DieselPoolAssociation<AsyncMysqlConnection>> DieselAsyncMySQLBackend<P> {
@@ -87,11 +126,68 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> DieselAsyncMySQLBackend<P>
             + Sync
             + 'static,
     ) -> Result<Self, P::BuildError> {
-        let create_connection = custom_create_connection.unwrap_or_else(|| {
-            Box::new(|| {
-                Box::new(|connection_url| AsyncMysqlConnection::establish(connection_url).boxed())
-            })
-        });
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            custom_create_connection,
+            move |conn, _| create_entities(conn),
+        )
+        .await
+    }
+
+    /// Creates a new [`Diesel async MySQL`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncMysqlConnection.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncMySQLBackend, DieselBb8},
+    ///     PrivilegedMySQLConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncMySQLBackend::<DieselBb8>::new_with_context(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 sql_query(query.as_str())
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_context(
+        privileged_config: PrivilegedMySQLConfig,
+        create_privileged_pool: impl Fn() -> P::Builder,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static>,
+        >,
+        create_entities: impl Fn(AsyncMysqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<Self, P::BuildError> {
+        let create_connection =
+            custom_create_connection.unwrap_or_else(Self::default_create_connection);
 
         let manager_config = {
             let mut config = ManagerConfig::default();
@@ -105,14 +201,198 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> DieselAsyncMySQLBackend<P>
         let builder = create_privileged_pool();
         let default_pool = P::build_pool(builder, manager).await?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
             privileged_config,
             default_pool,
+            create_restricted_pool,
+            create_connection,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`Diesel async MySQL`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncMysqlConnection.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncMySQLBackend, DieselBb8},
+    ///     PrivilegedMySQLConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::{
+    ///     pooled_connection::AsyncDieselConnectionManager, AsyncMysqlConnection, RunQueryDsl,
+    /// };
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///     let manager =
+    ///         AsyncDieselConnectionManager::<AsyncMysqlConnection>::new(config.default_connection_url());
+    ///     let privileged_pool = Pool::builder().max_size(10).build(manager).await.unwrap();
+    ///
+    ///     let backend = DieselAsyncMySQLBackend::<DieselBb8>::new_with_privileged_pool(
+    ///         config,
+    ///         privileged_pool,
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 sql_query("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        privileged_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static>,
+        >,
+        create_entities: impl Fn(AsyncMysqlConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            custom_create_connection.unwrap_or_else(Self::default_create_connection),
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`Diesel async MySQL`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncMysqlConnection.html) backend whose entities are created by a schema provider
+    ///
+    /// The same provider can apply the schema to the application database at startup.
+    /// # Example
+    /// ```
+    /// use async_trait::async_trait;
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncMySQLBackend, DieselBb8, SchemaProvider},
+    ///     PrivilegedMySQLConfig,
+    /// };
+    /// use diesel::{result::Error, sql_query};
+    /// use diesel_async::{AsyncMysqlConnection, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    ///
+    /// struct Migrations;
+    ///
+    /// #[async_trait]
+    /// impl SchemaProvider<AsyncMysqlConnection> for Migrations {
+    ///     type Error = Error;
+    ///
+    ///     async fn apply(&self, conn: &mut AsyncMysqlConnection) -> Result<(), Error> {
+    ///         sql_query("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .await?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncMySQLBackend::<DieselBb8>::new_with_schema_provider(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         Migrations,
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_schema_provider(
+        privileged_config: PrivilegedMySQLConfig,
+        create_privileged_pool: impl Fn() -> P::Builder,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static>,
+        >,
+        schema_provider: impl SchemaProvider<AsyncMysqlConnection>,
+    ) -> Result<Self, P::BuildError> {
+        let create_entities = create_entities_with(schema_provider);
+        Self::new(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            custom_create_connection,
+            move |conn| {
+                let create_entities = create_entities(conn);
+                Box::pin(async move {
+                    create_entities.await;
+                })
+            },
+        )
+        .await
+    }
+
+    fn default_create_connection(
+    ) -> Box<dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static> {
+        Box::new(|| {
+            Box::new(|connection_url| AsyncMysqlConnection::establish(connection_url).boxed())
+        })
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        default_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        create_connection: Box<
+            dyn Fn() -> SetupCallback<AsyncMysqlConnection> + Send + Sync + 'static,
+        >,
+        create_entities: impl Fn(AsyncMysqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            privileged_config,
+            default_pool,
+            table_names_cache: TableNamesCache::default(),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_connection: Box::new(create_connection),
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -123,6 +403,192 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> DieselAsyncMySQLBackend<P>
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut AsyncMysqlConnection,
+            ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
 }
 
 #[async_trait]
@@ -142,6 +608,13 @@ impl<'pool, P: DieselPoolAssociation<AsyncMysqlConnection>> MySQLBackend<'pool>
         P::get_connection(&self.default_pool).await
     }
 
+    async fn establish_privileged_connection(
+        &self,
+    ) -> Result<AsyncMysqlConnection, ConnectionError> {
+        let database_url = self.privileged_config.default_connection_url();
+        (self.create_connection)()(database_url.as_str()).await
+    }
+
     async fn execute_query(&self, query: &str, conn: &mut AsyncMysqlConnection) -> QueryResult<()> {
         sql_query(query).execute(conn).await?;
         Ok(())
@@ -161,7 +634,47 @@ impl<'pool, P: DieselPoolAssociation<AsyncMysqlConnection>> MySQLBackend<'pool>
     }
 
     fn get_host(&self) -> &str {
-        self.privileged_config.host.as_str()
+        self.user_host
+            .as_deref()
+            .unwrap_or(self.privileged_config.user_host())
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &Error) -> bool {
+        // Diesel does not expose MySQL error numbers, so the error message is matched instead
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("Operation CREATE USER failed")
+        )
+    }
+
+    fn is_transient_lock_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("Deadlock found")
+                    || info.message().starts_with("Lock wait timeout exceeded")
+        )
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
+    }
+
+    fn get_server_flavor(&self) -> MySqlFlavor {
+        self.server_flavor
     }
 
     async fn get_previous_database_names(
@@ -181,26 +694,113 @@ impl<'pool, P: DieselPoolAssociation<AsyncMysqlConnection>> MySQLBackend<'pool>
             .await
     }
 
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut AsyncMysqlConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            schema_name: String,
+        }
+
+        sql_query(query)
+            .load::<DatabaseName>(conn)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.schema_name).collect())
+    }
+
+    async fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut AsyncMysqlConnection,
+    ) -> QueryResult<Vec<String>> {
+        table! {
+            mysql.user (user_name, host) {
+                #[sql_name = "user"]
+                user_name -> Text,
+                host -> Text
+            }
+        }
+
+        user::table
+            .select(user::user_name)
+            .filter(user::user_name.like("db_pool_%"))
+            .filter(user::host.eq(host))
+            .load::<String>(conn)
+            .await
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<AsyncMysqlConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        let database_url = self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        );
+        (self.create_connection)()(database_url.as_str()).await
+    }
+
     async fn create_entities(&self, db_name: &str) -> Result<(), ConnectionError> {
         let database_url = self
             .privileged_config
             .privileged_database_connection_url(db_name);
         let conn = (self.create_connection)()(database_url.as_str()).await?;
-        (self.create_entities)(conn).await;
+        (self.create_entities)(conn, db_name).await;
         Ok(())
     }
 
+    async fn warm_up_connection_pool(&self, pool: &P::Pool) -> Result<(), P::PoolError> {
+        P::get_connection(pool).await.map(drop)
+    }
+
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<P::Pool, P::BuildError> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
         let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
+            username,
+            Some(password),
             db_name,
         );
         let manager_config = {
             let mut config = ManagerConfig::default();
-            config.custom_setup = (self.create_connection)();
+            let setup = (self.create_connection)();
+            config.custom_setup = if self.session_sql_mode.is_some() || self.after_connect.is_some()
+            {
+                let stmt = self
+                    .session_sql_mode
+                    .as_deref()
+                    .map(mysql::set_session_sql_mode);
+                let after_connect = self.after_connect.clone();
+                Box::new(move |url| {
+                    let conn = setup(url);
+                    let stmt = stmt.clone();
+                    let after_connect = after_connect.clone();
+                    Box::pin(async move {
+                        let mut conn = conn.await?;
+                        if let Some(stmt) = stmt {
+                            conn.batch_execute(stmt.as_str())
+                                .await
+                                .map_err(ConnectionError::CouldntSetupConfiguration)?;
+                        }
+                        if let Some(after_connect) = after_connect {
+                            after_connect(&mut conn)
+                                .await
+                                .map_err(ConnectionError::CouldntSetupConfiguration)?;
+                        }
+                        Ok(conn)
+                    })
+                })
+            } else {
+                setup
+            };
             config
         };
         let manager = AsyncDieselConnectionManager::<AsyncMysqlConnection>::new_with_config(
@@ -219,7 +819,8 @@ impl<'pool, P: DieselPoolAssociation<AsyncMysqlConnection>> MySQLBackend<'pool>
         table! {
             tables (table_name) {
                 table_name -> Text,
-                table_schema -> Text
+                table_schema -> Text,
+                table_type -> Text
             }
         }
 
@@ -227,14 +828,74 @@ impl<'pool, P: DieselPoolAssociation<AsyncMysqlConnection>> MySQLBackend<'pool>
 
         tables::table
             .filter(tables::table_schema.eq(db_name))
+            .filter(
+                tables::table_type
+                    .ne_all(self.server_flavor.excluded_table_types().iter().copied()),
+            )
             .select(tables::table_name)
             .load::<String>(conn)
             .await
     }
 
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut AsyncMysqlConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct TableName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            table_name: String,
+        }
+
+        sql_query(mysql::get_nonempty_table_names(table_names, db_name))
+            .load::<TableName>(conn)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.table_name).collect())
+    }
+
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
 }
 
 type BError<BuildError, PoolError> = BackendError<BuildError, PoolError, ConnectionError, Error>;
@@ -252,13 +913,35 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> Backend for DieselAsyncMySQ
         MySQLBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self).ping().await
+    }
+
     async fn create(
         &self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
         MySQLBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -266,6 +949,27 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> Backend for DieselAsyncMySQ
         MySQLBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(
+        &self,
+        db_id: uuid::Uuid,
+        label: &str,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(
         &self,
         db_id: uuid::Uuid,
@@ -273,23 +977,75 @@ impl<P: DieselPoolAssociation<AsyncMysqlConnection>> Backend for DieselAsyncMySQ
     ) -> Result<(), BError<P::BuildError, P::PoolError>> {
         MySQLBackendWrapper::new(self).drop(db_id).await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        _is_restricted: bool,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        MySQLBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        Some(self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        ))
+    }
+
+    fn privileged_pool(&self) -> &P::Pool {
+        &self.default_pool
+    }
+}
+
+impl<P: DieselPoolAssociation<AsyncMysqlConnection>> PoolWrapper<DieselAsyncMySQLBackend<P>> {
+    /// Gets a connection from the wrapped connection pool
+    ///
+    /// The returned connection dereferences to the underlying connection regardless of the pool association.
+    pub async fn get(&self) -> Result<P::PooledConnection<'_>, P::PoolError> {
+        P::get_connection(self).await
+    }
+
+    /// Gets a connection from the wrapped connection pool that does not borrow the pool
+    ///
+    /// Useful for handlers and spawned tasks that require a `'static` connection.
+    pub async fn get_owned(&self) -> Result<P::OwnedPooledConnection, P::PoolError> {
+        P::get_owned_connection(self).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used, clippy::needless_return)]
 
-    use std::borrow::Cow;
+    use std::{borrow::Cow, env};
 
-    use bb8::Pool;
-    use diesel::{insert_into, sql_query, table, Insertable, QueryDsl};
-    use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+    use bb8::{Builder, Pool};
+    use diesel::{
+        dsl::sql, insert_into, select, sql_query, sql_types::Text, table, Insertable, QueryDsl,
+    };
+    use diesel_async::{
+        pooled_connection::AsyncDieselConnectionManager, AsyncMysqlConnection, RunQueryDsl,
+        SimpleAsyncConnection,
+    };
+    use dotenvy::dotenv;
     use futures::future::join_all;
     use tokio_shared_rt::test;
 
     use crate::{
-        common::statement::mysql::tests::{
-            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
+        common::{
+            config::PrivilegedMySQLConfig,
+            statement::mysql::tests::{CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS},
         },
         r#async::{
             backend::{
@@ -302,6 +1058,7 @@ mod tests {
             db_pool::DatabasePoolBuilder,
         },
         tests::get_privileged_mysql_config,
+        MySqlFlavor,
     };
 
     use super::{
@@ -309,7 +1066,7 @@ mod tests {
             test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
             test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
             test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
-            test_pool_drops_previous_databases, MySQLDropLock,
+            test_pool_drops_previous_databases, test_pool_drops_prewarmed_databases, MySQLDropLock,
         },
         DieselAsyncMySQLBackend,
     };
@@ -328,8 +1085,15 @@ mod tests {
     }
 
     async fn create_backend(with_table: bool) -> DieselAsyncMySQLBackend<DieselBb8> {
+        create_backend_with_privileged_pool(with_table, Pool::builder).await
+    }
+
+    async fn create_backend_with_privileged_pool(
+        with_table: bool,
+        create_privileged_pool: impl Fn() -> Builder<AsyncDieselConnectionManager<AsyncMysqlConnection>>,
+    ) -> DieselAsyncMySQLBackend<DieselBb8> {
         let config = get_privileged_mysql_config().clone();
-        DieselAsyncMySQLBackend::new(config, Pool::builder, Pool::builder, None, {
+        DieselAsyncMySQLBackend::new(config, create_privileged_pool, Pool::builder, None, {
             move |mut conn| {
                 if with_table {
                     Box::pin(async move {
@@ -355,12 +1119,36 @@ mod tests {
         .await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_previous_databases_with_single_privileged_connection() {
+        let create_privileged_pool = || Pool::builder().max_size(1);
+        test_backend_drops_previous_databases(
+            create_backend_with_privileged_pool(false, create_privileged_pool).await,
+            create_backend_with_privileged_pool(false, create_privileged_pool)
+                .await
+                .drop_previous_databases(true),
+            create_backend_with_privileged_pool(false, create_privileged_pool)
+                .await
+                .drop_previous_databases(false),
+        )
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_creates_database_with_restricted_privileges() {
         let backend = create_backend(true).await.drop_previous_databases(false);
         test_backend_creates_database_with_restricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_creates_database_with_unrestricted_privileges() {
         let backend = create_backend(true).await.drop_previous_databases(false);
@@ -373,6 +1161,15 @@ mod tests {
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).await.drop_previous_databases(false);
@@ -475,6 +1272,56 @@ mod tests {
         .await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_connects_through_unix_socket() {
+        dotenv().ok();
+
+        // socket connections are only tested where a socket is configured
+        let Ok(socket) = env::var("MYSQL_SOCKET") else {
+            return;
+        };
+        let config = PrivilegedMySQLConfig::from_env().unwrap().host(socket);
+
+        let backend = DieselAsyncMySQLBackend::<DieselBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            None,
+            move |mut conn| {
+                Box::pin(async move {
+                    let query = CREATE_ENTITIES_STATEMENTS.join(";");
+                    conn.batch_execute(query.as_str()).await.unwrap();
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // restricted connections must be made through socket, which connects from localhost
+            assert_eq!(
+                select(sql::<Text>("SUBSTRING_INDEX(USER(), '@', -1)"))
+                    .get_result::<String>(conn)
+                    .await
+                    .unwrap(),
+                "localhost"
+            );
+
+            // entities created through socket must be usable
+            sql_query("INSERT INTO book (title) VALUES ('Title')")
+                .execute(conn)
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_provides_unrestricted_databases() {
         let backend = create_backend(true).await.drop_previous_databases(false);
@@ -564,9 +1411,185 @@ mod tests {
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_database() {
         let backend = create_backend(false).await;
         test_pool_drops_created_unrestricted_database(backend).await;
     }
+
+    #[cfg(feature = "diesel-async-deadpool")]
+    mod deadpool {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use ::deadpool::managed::PoolConfig;
+        use diesel_async::{AsyncConnection, AsyncMysqlConnection, SimpleAsyncConnection};
+        use futures::FutureExt;
+        use tokio_shared_rt::test;
+
+        use crate::{
+            common::statement::mysql::tests::CREATE_ENTITIES_STATEMENTS,
+            r#async::{
+                backend::{
+                    common::pool::diesel::deadpool::DieselDeadpool,
+                    mysql::r#trait::tests::{
+                        test_backend_creates_database_with_unrestricted_privileges,
+                        test_pool_drops_created_unrestricted_database,
+                    },
+                },
+                db_pool::DatabasePoolBuilder,
+            },
+            tests::get_privileged_mysql_config,
+        };
+
+        use super::super::{
+            super::r#trait::tests::{
+                test_backend_cleans_database_with_tables,
+                test_backend_cleans_database_without_tables,
+                test_backend_creates_database_with_restricted_privileges,
+                test_backend_drops_database, test_backend_drops_previous_databases,
+                test_pool_drops_created_restricted_databases, test_pool_drops_previous_databases,
+                test_pool_drops_prewarmed_databases, MySQLDropLock,
+            },
+            DieselAsyncMySQLBackend,
+        };
+
+        async fn create_backend(with_table: bool) -> DieselAsyncMySQLBackend<DieselDeadpool> {
+            let config = get_privileged_mysql_config().clone();
+            DieselAsyncMySQLBackend::new(config, PoolConfig::default, PoolConfig::default, None, {
+                move |mut conn| {
+                    if with_table {
+                        Box::pin(async move {
+                            let query = CREATE_ENTITIES_STATEMENTS.join(";");
+                            conn.batch_execute(query.as_str()).await.unwrap();
+                        })
+                    } else {
+                        Box::pin(async {})
+                    }
+                }
+            })
+            .await
+            .unwrap()
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_drops_previous_databases() {
+            test_backend_drops_previous_databases(
+                create_backend(false).await,
+                create_backend(false).await.drop_previous_databases(true),
+                create_backend(false).await.drop_previous_databases(false),
+            )
+            .await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_creates_database_with_restricted_privileges() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_creates_database_with_restricted_privileges(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_creates_database_with_unrestricted_privileges() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_creates_database_with_unrestricted_privileges(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_cleans_database_with_tables() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_cleans_database_with_tables(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_cleans_database_without_tables() {
+            let backend = create_backend(false).await.drop_previous_databases(false);
+            test_backend_cleans_database_without_tables(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_drops_restricted_database() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_drops_database(backend, true).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_drops_unrestricted_database() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_drops_database(backend, false).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_drops_previous_databases() {
+            test_pool_drops_previous_databases(
+                create_backend(false).await,
+                create_backend(false).await.drop_previous_databases(true),
+                create_backend(false).await.drop_previous_databases(false),
+            )
+            .await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_establishes_connections_with_custom_setup() {
+            let num_connections = Arc::new(AtomicUsize::new(0));
+
+            let config = get_privileged_mysql_config().clone();
+            let backend = DieselAsyncMySQLBackend::<DieselDeadpool>::new(
+                config,
+                PoolConfig::default,
+                PoolConfig::default,
+                Some({
+                    let num_connections = num_connections.clone();
+                    Box::new(move || {
+                        let num_connections = num_connections.clone();
+                        Box::new(move |connection_url| {
+                            num_connections.fetch_add(1, Ordering::Relaxed);
+                            AsyncMysqlConnection::establish(connection_url).boxed()
+                        })
+                    })
+                }),
+                |_| Box::pin(async {}),
+            )
+            .await
+            .unwrap()
+            .drop_previous_databases(false);
+
+            async {
+                let db_pool = backend.create_database_pool().await.unwrap();
+                let conn_pool = db_pool.pull_immutable().await;
+
+                // restricted connections must be established through custom setup
+                let num_established = num_connections.load(Ordering::Relaxed);
+                let _conn = conn_pool.get().await.unwrap();
+                assert!(num_connections.load(Ordering::Relaxed) > num_established);
+            }
+            .lock_read()
+            .await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_drops_created_restricted_databases() {
+            let backend = create_backend(false).await;
+            test_pool_drops_created_restricted_databases(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_drops_created_unrestricted_database() {
+            let backend = create_backend(false).await;
+            test_pool_drops_created_unrestricted_database(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_drops_prewarmed_databases() {
+            let backend = create_backend(false).await;
+            test_pool_drops_prewarmed_databases(backend).await;
+        }
+    }
 }
@@ -1,5 +1,7 @@
 #[cfg(feature = "diesel-async-mysql")]
 mod diesel;
+#[cfg(feature = "mysql-async")]
+mod mysql_async;
 #[cfg(feature = "sea-orm-mysql")]
 mod sea_orm;
 #[cfg(feature = "sqlx-mysql")]
@@ -8,6 +10,8 @@ mod r#trait;
 
 #[cfg(feature = "diesel-async-mysql")]
 pub use diesel::DieselAsyncMySQLBackend;
+#[cfg(feature = "mysql-async")]
+pub use mysql_async::MySQLAsyncBackend;
 #[cfg(feature = "sea-orm-mysql")]
 pub use sea_orm::SeaORMMySQLBackend;
 #[cfg(feature = "sqlx-mysql")]
@@ -0,0 +1,1049 @@
+use std::{borrow::Cow, pin::Pin, time::Duration};
+
+use async_trait::async_trait;
+use futures::Future;
+use mysql_async::{prelude::Queryable, Conn, Opts, OptsBuilder, Pool, PoolOpts};
+use uuid::Uuid;
+
+use crate::{
+    common::{
+        config::MySqlFlavor,
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    util::{
+        get_credentials, get_db_name, url_host, url_userinfo, PreviousDatabaseQuery, ReusedLogin,
+        TableNamesCache,
+    },
+};
+
+use super::{
+    super::{
+        common::{
+            conn::mysql_async::PooledConnection,
+            error::mysql_async::{BuildError, ConnectionError, PoolError, QueryError},
+        },
+        error::Error as BackendError,
+        r#trait::Backend,
+    },
+    r#trait::{MySQLBackend, MySQLBackendWrapper},
+};
+
+type CreateEntities = dyn Fn(Conn, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+/// [`mysql_async`](https://docs.rs/mysql_async/0.34.2/mysql_async/) backend
+#[allow(clippy::struct_excessive_bools)]
+pub struct MySQLAsyncBackend {
+    privileged_opts: Opts,
+    default_pool: Pool,
+    table_names_cache: TableNamesCache,
+    create_restricted_pool: Box<dyn Fn() -> PoolOpts + Send + Sync + 'static>,
+    create_entities: Box<CreateEntities>,
+    drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
+}
+
+impl MySQLAsyncBackend {
+    /// Creates a new [`mysql_async`](https://docs.rs/mysql_async/0.34.2/mysql_async/) backend
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::MySQLAsyncBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use mysql_async::{prelude::Queryable, PoolConstraints, PoolOpts};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = MySQLAsyncBackend::new(
+    ///         config.into(),
+    ///         || PoolOpts::default().with_constraints(PoolConstraints::new(0, 10).unwrap()),
+    ///         || PoolOpts::default().with_constraints(PoolConstraints::new(0, 2).unwrap()),
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 conn.query_drop("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new(
+        privileged_options: Opts,
+        create_privileged_pool: impl Fn() -> PoolOpts,
+        create_restricted_pool: impl Fn() -> PoolOpts + Send + Sync + 'static,
+        create_entities: impl Fn(Conn) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::new_with_context(
+            privileged_options,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`mysql_async`](https://docs.rs/mysql_async/0.34.2/mysql_async/) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::MySQLAsyncBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use mysql_async::{prelude::Queryable, PoolOpts};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = MySQLAsyncBackend::new_with_context(
+    ///         config.into(),
+    ///         PoolOpts::default,
+    ///         PoolOpts::default,
+    ///         move |mut conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 conn.query_drop(query).await.unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new_with_context(
+        privileged_options: Opts,
+        create_privileged_pool: impl Fn() -> PoolOpts,
+        create_restricted_pool: impl Fn() -> PoolOpts + Send + Sync + 'static,
+        create_entities: impl Fn(Conn, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let opts =
+            OptsBuilder::from_opts(privileged_options.clone()).pool_opts(create_privileged_pool());
+        let default_pool = Pool::new(opts);
+
+        Self::from_privileged_pool(
+            privileged_options,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        )
+    }
+
+    /// Creates a new [`mysql_async`](https://docs.rs/mysql_async/0.34.2/mysql_async/) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::MySQLAsyncBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use mysql_async::{prelude::Queryable, Opts, Pool, PoolOpts};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let opts: Opts = PrivilegedMySQLConfig::from_env().unwrap().into();
+    ///     let privileged_pool = Pool::new(opts.clone());
+    ///
+    ///     let backend = MySQLAsyncBackend::new_with_privileged_pool(
+    ///         opts,
+    ///         privileged_pool,
+    ///         PoolOpts::default,
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 conn.query_drop("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_options: Opts,
+        privileged_pool: Pool,
+        create_restricted_pool: impl Fn() -> PoolOpts + Send + Sync + 'static,
+        create_entities: impl Fn(Conn) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_options,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_options: Opts,
+        default_pool: Pool,
+        create_restricted_pool: impl Fn() -> PoolOpts + Send + Sync + 'static,
+        create_entities: impl Fn(Conn, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            privileged_opts: privileged_options,
+            default_pool,
+            table_names_cache: TableNamesCache::default(),
+            create_restricted_pool: Box::new(create_restricted_pool),
+            create_entities: Box::new(create_entities),
+            drop_previous_databases_flag: true,
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+        }
+    }
+
+    /// Drop databases created in previous runs upon initialization
+    #[must_use]
+    pub fn drop_previous_databases(self, value: bool) -> Self {
+        Self {
+            drop_previous_databases_flag: value,
+            ..self
+        }
+    }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    /// Runs after any init statements of the privileged connection options.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool options.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
+
+    fn get_restricted_opts(&self, db_name: &str) -> OptsBuilder {
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        OptsBuilder::from_opts(self.privileged_opts.clone())
+            .db_name(Some(db_name))
+            .user(Some(username))
+            .pass(Some(password))
+    }
+}
+
+#[async_trait]
+impl<'pool> MySQLBackend<'pool> for MySQLAsyncBackend {
+    type Connection = Conn;
+    type PooledConnection = PooledConnection;
+    type Pool = Pool;
+
+    type BuildError = BuildError;
+    type PoolError = PoolError;
+    type ConnectionError = ConnectionError;
+    type QueryError = QueryError;
+
+    async fn get_connection(&'pool self) -> Result<PooledConnection, PoolError> {
+        self.default_pool
+            .get_conn()
+            .await
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    async fn establish_privileged_connection(&self) -> Result<Conn, ConnectionError> {
+        Conn::new(self.privileged_opts.clone())
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn execute_query(&self, query: &str, conn: &mut Conn) -> Result<(), QueryError> {
+        conn.query_drop(query).await.map_err(Into::into)
+    }
+
+    async fn batch_execute_query<'a>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut Conn,
+    ) -> Result<(), QueryError> {
+        let chunks = query.into_iter().collect::<Vec<_>>();
+        if chunks.is_empty() {
+            Ok(())
+        } else {
+            let query = chunks.join(";");
+            self.execute_query(query.as_str(), conn).await
+        }
+    }
+
+    fn get_host(&self) -> &str {
+        self.user_host
+            .as_deref()
+            .unwrap_or_else(|| self.privileged_opts.ip_or_hostname())
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &QueryError) -> bool {
+        matches!(&**err, mysql_async::Error::Server(err) if err.code == mysql::DUPLICATE_USER_ERROR_NUMBER)
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        matches!(
+            &**err,
+            mysql_async::Error::Server(err)
+                if err.code == mysql::DEADLOCK_ERROR_NUMBER
+                    || err.code == mysql::LOCK_WAIT_TIMEOUT_ERROR_NUMBER
+        )
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
+    }
+
+    fn get_server_flavor(&self) -> MySqlFlavor {
+        self.server_flavor
+    }
+
+    async fn get_previous_database_names(
+        &self,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(mysql::GET_DATABASE_NAMES)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(query).await.map_err(Into::into)
+    }
+
+    async fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(mysql::get_user_names(host))
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Conn, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let opts = self.get_restricted_opts(db_name.as_str());
+        Conn::new(opts).await.map_err(Into::into)
+    }
+
+    async fn create_entities(&self, db_name: &str) -> Result<(), ConnectionError> {
+        let opts = OptsBuilder::from_opts(self.privileged_opts.clone()).db_name(Some(db_name));
+        let conn = Conn::new(opts).await?;
+        (self.create_entities)(conn, db_name).await;
+        Ok(())
+    }
+
+    async fn warm_up_connection_pool(&self, pool: &Pool) -> Result<(), PoolError> {
+        pool.get_conn().await.map(drop).map_err(Into::into)
+    }
+
+    async fn create_connection_pool(&self, db_id: Uuid) -> Result<Pool, BuildError> {
+        let db_name = self.get_db_name(db_id);
+        let mut init = self.privileged_opts.init().to_vec();
+        if let Some(sql_mode) = &self.session_sql_mode {
+            init.push(mysql::set_session_sql_mode(sql_mode));
+        }
+        let opts = self
+            .get_restricted_opts(db_name.as_str())
+            .init(init)
+            .pool_opts((self.create_restricted_pool)());
+        Ok(Pool::new(opts))
+    }
+
+    async fn get_table_names(
+        &self,
+        db_name: &str,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(mysql::get_table_names(
+            db_name,
+            self.server_flavor.excluded_table_types(),
+        ))
+        .await
+        .map_err(Into::into)
+    }
+
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(mysql::get_nonempty_table_names(table_names, db_name))
+            .await
+            .map_err(Into::into)
+    }
+
+    fn get_drop_previous_databases(&self) -> bool {
+        self.drop_previous_databases_flag
+    }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
+}
+
+type BError = BackendError<BuildError, PoolError, ConnectionError, QueryError>;
+
+#[async_trait]
+impl Backend for MySQLAsyncBackend {
+    type Pool = Pool;
+
+    type BuildError = BuildError;
+    type PoolError = PoolError;
+    type ConnectionError = ConnectionError;
+    type QueryError = QueryError;
+
+    async fn init(&self) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).init().await
+    }
+
+    async fn ping(&self) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).ping().await
+    }
+
+    async fn create(&self, db_id: uuid::Uuid, restrict_privileges: bool) -> Result<Pool, BError> {
+        MySQLBackendWrapper::new(self)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(&self, db_id: uuid::Uuid) -> Result<Pool, BError> {
+        MySQLBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(&self, db_id: uuid::Uuid) -> Result<Pool, BError> {
+        MySQLBackendWrapper::new(self)
+            .create_reader_pool(db_id)
+            .await
+    }
+
+    async fn clean(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).clean(db_id).await
+    }
+
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(&self, db_id: uuid::Uuid, label: &str) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).label(db_id, label).await
+    }
+
+    async fn drop(&self, db_id: uuid::Uuid, _is_restricted: bool) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).drop(db_id).await
+    }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        _is_restricted: bool,
+    ) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name.as_str());
+        let userinfo = url_userinfo(username, Some(password));
+        let host = url_host(self.privileged_opts.ip_or_hostname());
+        let port = self.privileged_opts.tcp_port();
+        Some(format!("mysql://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &Pool {
+        &self.default_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::needless_return)]
+
+    use futures::future::join_all;
+    use mysql_async::{prelude::Queryable, PoolOpts};
+    use tokio_shared_rt::test;
+
+    use crate::{
+        common::statement::mysql::tests::{
+            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
+        },
+        r#async::{
+            backend::mysql::r#trait::tests::test_backend_creates_database_with_unrestricted_privileges,
+            db_pool::DatabasePoolBuilder,
+        },
+        tests::get_privileged_mysql_config,
+        MySqlFlavor,
+    };
+
+    use super::{
+        super::r#trait::tests::{
+            test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
+            test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
+            test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
+            test_pool_drops_created_unrestricted_database, test_pool_drops_previous_databases,
+            test_pool_drops_prewarmed_databases, MySQLDropLock,
+        },
+        MySQLAsyncBackend,
+    };
+
+    fn create_backend(with_table: bool) -> MySQLAsyncBackend {
+        let config = get_privileged_mysql_config().clone();
+        MySQLAsyncBackend::new(config.into(), PoolOpts::default, PoolOpts::default, {
+            move |mut conn| {
+                Box::pin(async move {
+                    if with_table {
+                        conn.query_drop(CREATE_ENTITIES_STATEMENTS.join(";"))
+                            .await
+                            .unwrap();
+                    }
+                })
+            }
+        })
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_previous_databases() {
+        test_backend_drops_previous_databases(
+            create_backend(false),
+            create_backend(false).drop_previous_databases(true),
+            create_backend(false).drop_previous_databases(false),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_restricted_privileges() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_unrestricted_privileges() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_creates_database_with_unrestricted_privileges(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_tables() {
+        let backend = create_backend(false).drop_previous_databases(false);
+        test_backend_cleans_database_without_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_restricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_drops_database(backend, true).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_unrestricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_drops_database(backend, false).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_previous_databases() {
+        test_pool_drops_previous_databases(
+            create_backend(false),
+            create_backend(false).drop_previous_databases(true),
+            create_backend(false).drop_previous_databases(false),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_isolated_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+            // insert single row into each database
+            join_all(
+                conn_pools
+                    .iter()
+                    .enumerate()
+                    .map(|(i, conn_pool)| async move {
+                        let conn = &mut conn_pool.get_conn().await.unwrap();
+                        conn.exec_drop(
+                            "INSERT INTO book (title) VALUES (?)",
+                            (format!("Title {i}"),),
+                        )
+                        .await
+                        .unwrap();
+                    }),
+            )
+            .await;
+
+            // rows fetched must be as inserted
+            join_all(
+                conn_pools
+                    .iter()
+                    .enumerate()
+                    .map(|(i, conn_pool)| async move {
+                        let conn = &mut conn_pool.get_conn().await.unwrap();
+                        assert_eq!(
+                            conn.query::<String, _>("SELECT title FROM book")
+                                .await
+                                .unwrap(),
+                            vec![format!("Title {i}")]
+                        );
+                    }),
+            )
+            .await;
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_restricted_databases() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get_conn().await.unwrap();
+
+            // DDL statements must fail
+            for stmt in DDL_STATEMENTS {
+                assert!(conn.query_drop(stmt).await.is_err());
+            }
+
+            // DML statements must succeed
+            for stmt in DML_STATEMENTS {
+                assert!(conn.query_drop(stmt).await.is_ok());
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_unrestricted_databases() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // DML statements must succeed
+            {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get_conn().await.unwrap();
+                for stmt in DML_STATEMENTS {
+                    assert!(conn.query_drop(stmt).await.is_ok());
+                }
+            }
+
+            // DDL statements must succeed
+            for stmt in DDL_STATEMENTS {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get_conn().await.unwrap();
+                assert!(conn.query_drop(stmt).await.is_ok());
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_clean_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // fetch connection pools the first time
+            {
+                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+                // databases must be empty
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get_conn().await.unwrap();
+                    assert_eq!(
+                        conn.query_first::<i64, _>("SELECT COUNT(*) FROM book")
+                            .await
+                            .unwrap(),
+                        Some(0)
+                    );
+                }))
+                .await;
+
+                // insert data into each database
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get_conn().await.unwrap();
+                    conn.exec_drop("INSERT INTO book (title) VALUES (?)", ("Title",))
+                        .await
+                        .unwrap();
+                }))
+                .await;
+            }
+
+            // fetch same connection pools a second time
+            {
+                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+                // databases must be empty
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get_conn().await.unwrap();
+                    assert_eq!(
+                        conn.query_first::<i64, _>("SELECT COUNT(*) FROM book")
+                            .await
+                            .unwrap(),
+                        Some(0)
+                    );
+                }))
+                .await;
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_applies_session_sql_mode() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .session_sql_mode("STRICT_ALL_TABLES");
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get_conn().await.unwrap();
+
+            // session must use configured SQL mode
+            assert_eq!(
+                conn.query_first::<String, _>("SELECT @@SESSION.sql_mode")
+                    .await
+                    .unwrap(),
+                Some("STRICT_ALL_TABLES".to_owned())
+            );
+
+            // invalid value must be rejected in strict mode
+            assert!(conn
+                .query_drop("INSERT INTO book (id, title) VALUES ('invalid', 'Title')")
+                .await
+                .is_err());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_created_restricted_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_created_restricted_databases(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_created_unrestricted_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_created_unrestricted_database(backend).await;
+    }
+}
@@ -1,18 +1,26 @@
-use std::{borrow::Cow, pin::Pin};
+use std::{borrow::Cow, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use futures::Future;
 use sea_orm::{
+    sqlx::{
+        mysql::{MySqlConnectOptions, MySqlDatabaseError},
+        Executor, MySql, MySqlConnection,
+    },
     ActiveModelBehavior, ColumnTrait, ConnectOptions, ConnectionTrait, Database,
     DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait,
-    EnumIter, FromQueryResult, PrimaryKeyTrait, QueryFilter, QuerySelect, TransactionError,
-    TransactionTrait,
+    EnumIter, FromQueryResult, PrimaryKeyTrait, QueryFilter, QuerySelect, RuntimeErr, SqlxError,
+    SqlxMySqlConnector, Statement, TransactionError, TransactionTrait,
 };
 use uuid::Uuid;
 
 use crate::{
-    common::{config::PrivilegedMySQLConfig, statement::mysql},
-    util::get_db_name,
+    common::{
+        config::{MySqlFlavor, PrivilegedMySQLConfig},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    util::{get_credentials, get_db_name, PreviousDatabaseQuery, ReusedLogin, TableNamesCache},
 };
 
 use super::{
@@ -27,18 +35,45 @@ use super::{
     r#trait::{MySQLBackend, MySQLBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+type CreateEntities = dyn Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut MySqlConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), SqlxError>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`SeaORM MySQL`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.MySql) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct SeaORMMySQLBackend {
     privileged_config: PrivilegedMySQLConfig,
     default_pool: DatabaseConnection,
+    table_names_cache: TableNamesCache,
     create_restricted_pool: Box<dyn for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
 }
 
 impl SeaORMMySQLBackend {
@@ -86,18 +121,163 @@ impl SeaORMMySQLBackend {
             + Send
             + Sync
             + 'static,
+    ) -> Result<Self, DbErr> {
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+        .await
+    }
+
+    /// Creates a new [`SeaORM MySQL`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.MySql) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SeaORMMySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use sea_orm::ConnectionTrait;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = SeaORMMySQLBackend::new_with_context(
+    ///             config,
+    ///             |opts| {
+    ///                 opts.max_connections(10);
+    ///             },
+    ///             |opts| {
+    ///                 opts.max_connections(2);
+    ///             },
+    ///             move |conn, db_name| {
+    ///                 let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///                 Box::pin(async move {
+    ///                     conn.execute_unprepared(query.as_str())
+    ///                     .await
+    ///                     .unwrap();
+    ///                 })
+    ///             },
+    ///         )
+    ///         .await
+    ///         .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_context(
+        privileged_config: PrivilegedMySQLConfig,
+        create_privileged_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions),
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
     ) -> Result<Self, DbErr> {
         let mut opts = ConnectOptions::new(privileged_config.default_connection_url());
         create_privileged_pool(&mut opts);
         let default_pool = Database::connect(opts).await?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            privileged_config,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`SeaORM MySQL`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.MySql) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SeaORMMySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use sea_orm::{ConnectOptions, ConnectionTrait, Database};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///     let mut opts = ConnectOptions::new(config.default_connection_url());
+    ///     opts.max_connections(10);
+    ///     let privileged_pool = Database::connect(opts).await.unwrap();
+    ///
+    ///     let backend = SeaORMMySQLBackend::new_with_privileged_pool(
+    ///         config,
+    ///         privileged_pool,
+    ///         |opts| {
+    ///             opts.max_connections(2);
+    ///         },
+    ///         move |conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute_unprepared(
+    ///                     "CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)",
+    ///                 )
+    ///                 .await
+    ///                 .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        privileged_pool: DatabaseConnection,
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        default_pool: DatabaseConnection,
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
             privileged_config,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -108,6 +288,195 @@ impl SeaORMMySQLBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    ///
+    /// Restricted pools are then connected through sqlx directly, since sea-orm exposes no such hook.
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut MySqlConnection,
+            )
+                -> Pin<Box<dyn Future<Output = Result<(), SqlxError>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
 }
 
 #[async_trait]
@@ -125,6 +494,12 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
         Ok(self.default_pool.clone().into())
     }
 
+    async fn establish_privileged_connection(&self) -> Result<DatabaseConnection, ConnectionError> {
+        Database::connect(self.privileged_config.default_connection_url())
+            .await
+            .map_err(Into::into)
+    }
+
     async fn execute_query(
         &self,
         query: &str,
@@ -149,7 +524,55 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
     }
 
     fn get_host(&self) -> &str {
-        self.privileged_config.host.as_str()
+        self.user_host
+            .as_deref()
+            .unwrap_or(self.privileged_config.user_host())
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &QueryError) -> bool {
+        match &**err {
+            DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) => {
+                err.as_database_error()
+                    .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+                    .map(MySqlDatabaseError::number)
+                    == Some(mysql::DUPLICATE_USER_ERROR_NUMBER)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        match &**err {
+            DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) => {
+                matches!(
+                    err.as_database_error()
+                        .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+                        .map(MySqlDatabaseError::number),
+                    Some(mysql::DEADLOCK_ERROR_NUMBER | mysql::LOCK_WAIT_TIMEOUT_ERROR_NUMBER)
+                )
+            }
+            _ => false,
+        }
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
+    }
+
+    fn get_server_flavor(&self) -> MySqlFlavor {
+        self.server_flavor
     }
 
     async fn get_previous_database_names(
@@ -185,25 +608,135 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
         })
     }
 
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            schema_name: String,
+        }
+
+        QueryModel::find_by_statement(Statement::from_string(conn.get_database_backend(), query))
+            .all(conn)
+            .await
+            .map(|models| models.into_iter().map(|model| model.schema_name).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(Clone, Debug, DeriveEntityModel)]
+        #[sea_orm(schema_name = "mysql", table_name = "user")]
+        pub struct Model {
+            #[sea_orm(primary_key)]
+            user: String,
+            #[sea_orm(primary_key)]
+            host: String,
+        }
+
+        #[derive(Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+
+        Entity::find()
+            .filter(Column::User.like("db_pool_%"))
+            .filter(Column::Host.eq(host))
+            .all(conn)
+            .await
+            .map(|mut models| models.drain(..).map(|model| model.user).collect())
+            .map_err(Into::into)
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<DatabaseConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        let database_url = self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        );
+        Database::connect(database_url).await.map_err(Into::into)
+    }
+
     async fn create_entities(&self, db_name: &str) -> Result<(), ConnectionError> {
         let database_url = self
             .privileged_config
             .privileged_database_connection_url(db_name);
         let conn = Database::connect(database_url).await?;
-        (self.create_entities)(conn).await;
+        (self.create_entities)(conn, db_name).await;
         Ok(())
     }
 
+    async fn warm_up_connection_pool(&self, pool: &DatabaseConnection) -> Result<(), PoolError> {
+        pool.ping().await.map_err(Into::into)
+    }
+
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<DatabaseConnection, BuildError> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
         let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
+            username,
+            Some(password),
             db_name,
         );
         let mut opts = ConnectOptions::new(database_url);
         (self.create_restricted_pool)(&mut opts);
+
+        // Connect lazily in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            opts.connect_lazy(true);
+        }
+
+        // Connect through sqlx directly since sea-orm exposes no hook for running statements on new connections
+        if self.session_sql_mode.is_some() || self.after_connect.is_some() {
+            let connect_opts = opts
+                .get_url()
+                .parse::<MySqlConnectOptions>()
+                .map_err(|err| DbErr::Conn(RuntimeErr::SqlxError(err)))?;
+            let connect_lazy = opts.get_connect_lazy();
+            let stmt = self
+                .session_sql_mode
+                .as_deref()
+                .map(mysql::set_session_sql_mode);
+            let after_connect = self.after_connect.clone();
+            let pool_opts = opts
+                .sqlx_pool_options::<MySql>()
+                .after_connect(move |conn, _| {
+                    let stmt = stmt.clone();
+                    let after_connect = after_connect.clone();
+                    Box::pin(async move {
+                        if let Some(stmt) = stmt {
+                            conn.execute(stmt.as_str()).await?;
+                        }
+                        if let Some(after_connect) = after_connect {
+                            after_connect(conn).await?;
+                        }
+                        Ok(())
+                    })
+                });
+            let pool = if connect_lazy {
+                pool_opts.connect_lazy_with(connect_opts)
+            } else {
+                pool_opts
+                    .connect_with(connect_opts)
+                    .await
+                    .map_err(|err| DbErr::Conn(RuntimeErr::SqlxError(err)))?
+            };
+            return Ok(SqlxMySqlConnector::from_sqlx_mysql_pool(pool));
+        }
+
         Database::connect(opts).await.map_err(Into::into)
     }
 
@@ -215,10 +748,12 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
     ) -> Result<Vec<String>, QueryError> {
         #[derive(Clone, Debug, DeriveEntityModel)]
         #[sea_orm(table_name = "tables")]
+        #[allow(clippy::struct_field_names)]
         pub struct Model {
             #[sea_orm(primary_key)]
             table_name: String,
             table_schema: String,
+            table_type: String,
         }
 
         #[derive(Debug, EnumIter, DeriveRelation)]
@@ -231,6 +766,8 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
             table_name: String,
         }
 
+        let excluded_table_types = self.server_flavor.excluded_table_types();
+
         conn.transaction(move |txn| {
             let db_name = db_name.to_owned();
             Box::pin(async move {
@@ -240,6 +777,7 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
                     .select_only()
                     .column(Column::TableName)
                     .filter(Column::TableSchema.eq(db_name))
+                    .filter(Column::TableType.is_not_in(excluded_table_types.iter().copied()))
                     .into_model::<QueryModel>()
                     .all(txn)
                     .await
@@ -252,9 +790,68 @@ impl<'pool> MySQLBackend<'pool> for SeaORMMySQLBackend {
         })
     }
 
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            table_name: String,
+        }
+
+        QueryModel::find_by_statement(Statement::from_string(
+            conn.get_database_backend(),
+            mysql::get_nonempty_table_names(table_names, db_name),
+        ))
+        .all(conn)
+        .await
+        .map(|models| models.into_iter().map(|model| model.table_name).collect())
+        .map_err(Into::into)
+    }
+
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
 }
 
 type BError = BackendError<BuildError, PoolError, ConnectionError, QueryError>;
@@ -272,13 +869,29 @@ impl Backend for SeaORMMySQLBackend {
         MySQLBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).ping().await
+    }
+
     async fn create(
         &self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<DatabaseConnection, BError> {
         MySQLBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(&self, db_id: uuid::Uuid) -> Result<DatabaseConnection, BError> {
+        MySQLBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(&self, db_id: uuid::Uuid) -> Result<DatabaseConnection, BError> {
+        MySQLBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -286,9 +899,52 @@ impl Backend for SeaORMMySQLBackend {
         MySQLBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(&self, db_id: uuid::Uuid, label: &str) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(&self, db_id: uuid::Uuid, _is_restricted: bool) -> Result<(), BError> {
         MySQLBackendWrapper::new(self).drop(db_id).await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        _is_restricted: bool,
+    ) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        Some(self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        ))
+    }
+
+    fn privileged_pool(&self) -> &DatabaseConnection {
+        &self.default_pool
+    }
 }
 
 #[cfg(test)]
@@ -311,11 +967,12 @@ mod tests {
             backend::mysql::r#trait::tests::{
                 test_backend_creates_database_with_unrestricted_privileges,
                 test_pool_drops_created_restricted_databases,
-                test_pool_drops_created_unrestricted_database,
+                test_pool_drops_created_unrestricted_database, test_pool_drops_prewarmed_databases,
             },
             db_pool::DatabasePoolBuilder,
         },
         tests::get_privileged_mysql_config,
+        MySqlFlavor,
     };
 
     use super::{
@@ -376,6 +1033,15 @@ mod tests {
         test_backend_creates_database_with_restricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_creates_database_with_unrestricted_privileges() {
         let backend = create_backend(true).await.drop_previous_databases(false);
@@ -388,6 +1054,15 @@ mod tests {
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).await.drop_previous_databases(false);
@@ -560,6 +1235,12 @@ mod tests {
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_database() {
         let backend = create_backend(false).await;
@@ -1,15 +1,29 @@
-use std::{borrow::Cow, pin::Pin};
+use std::{borrow::Cow, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use futures::Future;
 use sqlx::{
-    mysql::{MySqlConnectOptions, MySqlPoolOptions},
+    mysql::{MySqlConnectOptions, MySqlDatabaseError, MySqlPoolOptions},
     pool::PoolConnection,
     Connection, Executor, MySql, MySqlConnection, MySqlPool, Row,
 };
 use uuid::Uuid;
 
-use crate::{common::statement::mysql, util::get_db_name};
+use crate::{
+    common::{
+        config::MySqlFlavor,
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    r#async::{
+        schema_provider::{create_entities_with, SchemaProvider},
+        PoolWrapper,
+    },
+    util::{
+        get_credentials, get_db_name, url_host, url_userinfo, PreviousDatabaseQuery, ReusedLogin,
+        TableNamesCache,
+    },
+};
 
 use super::{
     super::{
@@ -20,22 +34,53 @@ use super::{
     r#trait::{MySQLBackend, MySQLBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(MySqlConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+type CreateEntities = dyn Fn(MySqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut MySqlConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`sqlx MySQL`](https://docs.rs/sqlx/0.8.2/sqlx/struct.MySql.html) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct SqlxMySQLBackend {
     privileged_opts: MySqlConnectOptions,
     default_pool: MySqlPool,
-    create_restricted_pool: Box<dyn Fn() -> MySqlPoolOptions + Send + Sync + 'static>,
+    table_names_cache: TableNamesCache,
+    create_restricted_pool:
+        Box<dyn Fn(&MySqlConnectOptions) -> MySqlPoolOptions + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
 }
 
 impl SqlxMySQLBackend {
     /// Creates a new [`sqlx MySQL`](https://docs.rs/sqlx/0.8.2/sqlx/struct.MySql.html) backend
+    ///
+    /// Restricted pool options are created per database from its restricted connection options,
+    /// allowing settings such as connection lifetimes to be tuned per database
     /// # Example
     /// ```
     /// use db_pool::{r#async::SqlxMySQLBackend, PrivilegedMySQLConfig};
@@ -50,7 +95,7 @@ impl SqlxMySQLBackend {
     ///     let backend = SqlxMySQLBackend::new(
     ///         config.into(),
     ///         || MySqlPoolOptions::new().max_connections(10),
-    ///         || MySqlPoolOptions::new().max_connections(2),
+    ///         |_| MySqlPoolOptions::new().max_connections(2),
     ///         move |mut conn| {
     ///             Box::pin(async move {
     ///                 conn.execute("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
@@ -66,21 +111,234 @@ impl SqlxMySQLBackend {
     pub fn new(
         privileged_options: MySqlConnectOptions,
         create_privileged_pool: impl Fn() -> MySqlPoolOptions,
-        create_restricted_pool: impl Fn() -> MySqlPoolOptions + Send + Sync + 'static,
+        create_restricted_pool: impl Fn(&MySqlConnectOptions) -> MySqlPoolOptions
+            + Send
+            + Sync
+            + 'static,
         create_entities: impl Fn(MySqlConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
             + Send
             + Sync
             + 'static,
+    ) -> Self {
+        Self::new_with_context(
+            privileged_options,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`sqlx MySQL`](https://docs.rs/sqlx/0.8.2/sqlx/struct.MySql.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SqlxMySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use sqlx::{mysql::MySqlPoolOptions, Executor};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = SqlxMySQLBackend::new_with_context(
+    ///         config.into(),
+    ///         || MySqlPoolOptions::new().max_connections(10),
+    ///         |_| MySqlPoolOptions::new().max_connections(2),
+    ///         move |mut conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 conn.execute(query.as_str())
+    ///                      .await
+    ///                      .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new_with_context(
+        privileged_options: MySqlConnectOptions,
+        create_privileged_pool: impl Fn() -> MySqlPoolOptions,
+        create_restricted_pool: impl Fn(&MySqlConnectOptions) -> MySqlPoolOptions
+            + Send
+            + Sync
+            + 'static,
+        create_entities: impl Fn(MySqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
     ) -> Self {
         let pool_opts = create_privileged_pool();
         let default_pool = pool_opts.connect_lazy_with(privileged_options.clone());
 
+        Self::from_privileged_pool(
+            privileged_options,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        )
+    }
+
+    /// Creates a new [`sqlx MySQL`](https://docs.rs/sqlx/0.8.2/sqlx/struct.MySql.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SqlxMySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use sqlx::{
+    ///     mysql::{MySqlConnectOptions, MySqlPoolOptions},
+    ///     Executor,
+    /// };
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let opts: MySqlConnectOptions = PrivilegedMySQLConfig::from_env().unwrap().into();
+    ///     let privileged_pool = MySqlPoolOptions::new()
+    ///         .max_connections(10)
+    ///         .connect_lazy_with(opts.clone());
+    ///
+    ///     let backend = SqlxMySQLBackend::new_with_privileged_pool(
+    ///         opts,
+    ///         privileged_pool,
+    ///         |_| MySqlPoolOptions::new().max_connections(2),
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_options: MySqlConnectOptions,
+        privileged_pool: MySqlPool,
+        create_restricted_pool: impl Fn(&MySqlConnectOptions) -> MySqlPoolOptions
+            + Send
+            + Sync
+            + 'static,
+        create_entities: impl Fn(MySqlConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_options,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`sqlx MySQL`](https://docs.rs/sqlx/0.8.2/sqlx/struct.MySql.html) backend whose entities are created by a schema provider
+    ///
+    /// The same provider can apply the schema to the application database at startup.
+    /// # Example
+    /// ```
+    /// use async_trait::async_trait;
+    /// use db_pool::{
+    ///     r#async::{SchemaProvider, SqlxMySQLBackend},
+    ///     PrivilegedMySQLConfig,
+    /// };
+    /// use dotenvy::dotenv;
+    /// use sqlx::{mysql::MySqlPoolOptions, Executor, MySqlConnection};
+    ///
+    /// struct Migrations;
+    ///
+    /// #[async_trait]
+    /// impl SchemaProvider<MySqlConnection> for Migrations {
+    ///     type Error = sqlx::Error;
+    ///
+    ///     async fn apply(&self, conn: &mut MySqlConnection) -> Result<(), sqlx::Error> {
+    ///         conn.execute("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///             .await?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    ///     let backend = SqlxMySQLBackend::new_with_schema_provider(
+    ///         config.into(),
+    ///         || MySqlPoolOptions::new().max_connections(10),
+    ///         |_| MySqlPoolOptions::new().max_connections(2),
+    ///         Migrations,
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new_with_schema_provider(
+        privileged_options: MySqlConnectOptions,
+        create_privileged_pool: impl Fn() -> MySqlPoolOptions,
+        create_restricted_pool: impl Fn(&MySqlConnectOptions) -> MySqlPoolOptions
+            + Send
+            + Sync
+            + 'static,
+        schema_provider: impl SchemaProvider<MySqlConnection>,
+    ) -> Self {
+        let create_entities = create_entities_with(schema_provider);
+        Self::new(
+            privileged_options,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn| {
+                let create_entities = create_entities(conn);
+                Box::pin(async move {
+                    create_entities.await;
+                })
+            },
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_options: MySqlConnectOptions,
+        default_pool: MySqlPool,
+        create_restricted_pool: impl Fn(&MySqlConnectOptions) -> MySqlPoolOptions
+            + Send
+            + Sync
+            + 'static,
+        create_entities: impl Fn(MySqlConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
         Self {
             privileged_opts: privileged_options,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
         }
     }
 
@@ -92,6 +350,196 @@ impl SqlxMySQLBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    /// Replaces any `after_connect` callback set on the restricted pool options.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    ///
+    /// Replaces any `after_connect` callback set on the restricted pool options.
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut MySqlConnection,
+            )
+                -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
 }
 
 #[async_trait]
@@ -109,6 +557,12 @@ impl<'pool> MySQLBackend<'pool> for SqlxMySQLBackend {
         self.default_pool.acquire().await.map_err(Into::into)
     }
 
+    async fn establish_privileged_connection(&self) -> Result<MySqlConnection, ConnectionError> {
+        MySqlConnection::connect_with(&self.privileged_opts)
+            .await
+            .map_err(Into::into)
+    }
+
     async fn execute_query(
         &self,
         query: &str,
@@ -133,7 +587,45 @@ impl<'pool> MySQLBackend<'pool> for SqlxMySQLBackend {
     }
 
     fn get_host(&self) -> &str {
-        self.privileged_opts.get_host()
+        self.user_host
+            .as_deref()
+            .unwrap_or_else(|| self.privileged_opts.get_host())
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &QueryError) -> bool {
+        err.as_database_error()
+            .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+            .map(MySqlDatabaseError::number)
+            == Some(mysql::DUPLICATE_USER_ERROR_NUMBER)
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        matches!(
+            err.as_database_error()
+                .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+                .map(MySqlDatabaseError::number),
+            Some(mysql::DEADLOCK_ERROR_NUMBER | mysql::LOCK_WAIT_TIMEOUT_ERROR_NUMBER)
+        )
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
+    }
+
+    fn get_server_flavor(&self) -> MySqlFlavor {
+        self.server_flavor
     }
 
     async fn get_previous_database_names(
@@ -148,23 +640,93 @@ impl<'pool> MySQLBackend<'pool> for SqlxMySQLBackend {
             .map_err(Into::into)
     }
 
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut MySqlConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(query)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut MySqlConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(mysql::get_user_names(host).as_str())
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<MySqlConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        let opts = self
+            .privileged_opts
+            .clone()
+            .database(db_name)
+            .username(username)
+            .password(password);
+        MySqlConnection::connect_with(&opts)
+            .await
+            .map_err(Into::into)
+    }
+
     async fn create_entities(&self, db_name: &str) -> Result<(), ConnectionError> {
         let opts = self.privileged_opts.clone().database(db_name);
         let conn = MySqlConnection::connect_with(&opts).await?;
-        (self.create_entities)(conn).await;
+        (self.create_entities)(conn, db_name).await;
         Ok(())
     }
 
+    async fn warm_up_connection_pool(&self, pool: &MySqlPool) -> Result<(), PoolError> {
+        pool.acquire().await.map(drop).map_err(Into::into)
+    }
+
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<MySqlPool, BuildError> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
         let opts = self
             .privileged_opts
             .clone()
             .database(db_name)
-            .username(db_name)
-            .password(db_name);
-        let pool = (self.create_restricted_pool)().connect_lazy_with(opts);
+            .username(username)
+            .password(password);
+        let mut pool_opts = (self.create_restricted_pool)(&opts);
+        if self.session_sql_mode.is_some() || self.after_connect.is_some() {
+            let stmt = self
+                .session_sql_mode
+                .as_deref()
+                .map(mysql::set_session_sql_mode);
+            let after_connect = self.after_connect.clone();
+            pool_opts = pool_opts.after_connect(move |conn, _| {
+                let stmt = stmt.clone();
+                let after_connect = after_connect.clone();
+                Box::pin(async move {
+                    if let Some(stmt) = stmt {
+                        conn.execute(stmt.as_str()).await?;
+                    }
+                    if let Some(after_connect) = after_connect {
+                        after_connect(conn).await?;
+                    }
+                    Ok(())
+                })
+            });
+        }
+        let pool = pool_opts.connect_lazy_with(opts);
         Ok(pool)
     }
 
@@ -173,7 +735,23 @@ impl<'pool> MySQLBackend<'pool> for SqlxMySQLBackend {
         db_name: &str,
         conn: &mut MySqlConnection,
     ) -> Result<Vec<String>, QueryError> {
-        conn.fetch_all(mysql::get_table_names(db_name).as_str())
+        conn.fetch_all(
+            mysql::get_table_names(db_name, self.server_flavor.excluded_table_types()).as_str(),
+        )
+        .await?
+        .iter()
+        .map(|row| row.try_get(0))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)
+    }
+
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut MySqlConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(mysql::get_nonempty_table_names(table_names, db_name).as_str())
             .await?
             .iter()
             .map(|row| row.try_get(0))
@@ -184,6 +762,44 @@ impl<'pool> MySQLBackend<'pool> for SqlxMySQLBackend {
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
 }
 
 type BError = BackendError<BuildError, PoolError, ConnectionError, QueryError>;
@@ -201,13 +817,29 @@ impl Backend for SqlxMySQLBackend {
         MySQLBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).ping().await
+    }
+
     async fn create(
         &self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<MySqlPool, BError> {
         MySQLBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(&self, db_id: uuid::Uuid) -> Result<MySqlPool, BError> {
+        MySQLBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(&self, db_id: uuid::Uuid) -> Result<MySqlPool, BError> {
+        MySQLBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -215,9 +847,59 @@ impl Backend for SqlxMySQLBackend {
         MySQLBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(&self, db_id: uuid::Uuid, label: &str) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(&self, db_id: uuid::Uuid, _is_restricted: bool) -> Result<(), BError> {
         MySQLBackendWrapper::new(self).drop(db_id).await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        _is_restricted: bool,
+    ) -> Result<(), BError> {
+        MySQLBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name.as_str());
+        let userinfo = url_userinfo(username, Some(password));
+        let host = url_host(self.privileged_opts.get_host());
+        let port = self.privileged_opts.get_port();
+        Some(format!("mysql://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &MySqlPool {
+        &self.default_pool
+    }
+}
+
+impl PoolWrapper<SqlxMySQLBackend> {
+    /// Gets a connection from the wrapped connection pool that does not borrow the pool
+    ///
+    /// Useful for handlers and spawned tasks that require a `'static` connection.
+    pub async fn get_owned(&self) -> Result<PoolConnection<MySql>, sqlx::Error> {
+        self.acquire().await
+    }
 }
 
 #[cfg(test)]
@@ -240,6 +922,7 @@ mod tests {
             db_pool::DatabasePoolBuilder,
         },
         tests::get_privileged_mysql_config,
+        MySqlFlavor,
     };
 
     use super::{
@@ -248,7 +931,7 @@ mod tests {
             test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
             test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
             test_pool_drops_created_unrestricted_database, test_pool_drops_previous_databases,
-            MySQLDropLock,
+            test_pool_drops_prewarmed_databases, MySQLDropLock,
         },
         SqlxMySQLBackend,
     };
@@ -261,7 +944,7 @@ mod tests {
         } else {
             opts
         };
-        SqlxMySQLBackend::new(opts, MySqlPoolOptions::new, MySqlPoolOptions::new, {
+        SqlxMySQLBackend::new(opts, MySqlPoolOptions::new, |_| MySqlPoolOptions::new(), {
             move |mut conn| {
                 if with_table {
                     Box::pin(async move {
@@ -295,6 +978,14 @@ mod tests {
         test_backend_creates_database_with_restricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_creates_database_with_unrestricted_privileges() {
         let backend = create_backend(true).drop_previous_databases(false);
@@ -307,6 +998,14 @@ mod tests {
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).drop_previous_databases(false);
@@ -498,12 +1197,50 @@ mod tests {
         .await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_applies_session_sql_mode() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .session_sql_mode("STRICT_ALL_TABLES");
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.acquire().await.unwrap();
+
+            // session must use configured SQL mode
+            assert_eq!(
+                query("SELECT @@SESSION.sql_mode")
+                    .fetch_one(&mut **conn)
+                    .await
+                    .unwrap()
+                    .get::<String, _>(0),
+                "STRICT_ALL_TABLES"
+            );
+
+            // invalid value must be rejected in strict mode
+            assert!(conn
+                .execute("INSERT INTO book (id, title) VALUES ('invalid', 'Title')")
+                .await
+                .is_err());
+        }
+        .lock_read()
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_restricted_databases() {
         let backend = create_backend(false);
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_databases() {
         let backend = create_backend(false);
@@ -1,14 +1,28 @@
 use std::{
     borrow::Cow,
     fmt::Debug,
+    future::Future,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    time::Duration,
+    time::Instant,
 };
 
 use async_trait::async_trait;
+use tokio::time::{sleep, timeout};
 use uuid::Uuid;
 
-use crate::{common::statement::mysql, util::get_db_name};
+use crate::{
+    common::{
+        config::MySqlFlavor,
+        metrics::{MetricEvent, MetricOperation, MetricsCallback},
+        statement::mysql,
+    },
+    util::{
+        get_clean_retry_backoff, is_valid_db_name, PreviousDatabaseQuery, TableNamesCache,
+        DB_NAME_PREFIX,
+    },
+};
 
 use super::super::error::Error as BackendError;
 
@@ -52,6 +66,9 @@ pub(super) trait MySQLBackend<'pool>: Send + Sync + 'static {
         > + Debug;
 
     async fn get_connection(&'pool self) -> Result<Self::PooledConnection, Self::PoolError>;
+    async fn establish_privileged_connection(
+        &self,
+    ) -> Result<Self::Connection, Self::ConnectionError>;
 
     async fn execute_query(
         &self,
@@ -65,21 +82,60 @@ pub(super) trait MySQLBackend<'pool>: Send + Sync + 'static {
     ) -> Result<(), Self::QueryError>;
 
     fn get_host(&self) -> &str;
+    fn get_reused_user(&self) -> Option<&str>;
+    fn is_duplicate_user_error(&self, err: &Self::QueryError) -> bool;
+    fn is_transient_lock_error(&self, err: &Self::QueryError) -> bool;
+    fn get_db_name(&self, db_id: Uuid) -> String;
+    fn get_charset(&self) -> Option<&str>;
+    fn get_collation(&self) -> Option<&str>;
+    fn get_server_flavor(&self) -> MySqlFlavor;
 
     async fn get_previous_database_names(
         &self,
         conn: &mut Self::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Self::Connection, Self::ConnectionError>;
     async fn create_entities(&self, db_name: &str) -> Result<(), Self::ConnectionError>;
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<Self::Pool, Self::BuildError>;
+    async fn warm_up_connection_pool(&self, pool: &Self::Pool) -> Result<(), Self::PoolError>;
 
     async fn get_table_names(
         &self,
         db_name: &str,
         conn: &mut Self::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
 
     fn get_drop_previous_databases(&self) -> bool;
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery>;
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool;
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool;
+    fn get_verify_clean(&self) -> bool;
+    fn get_clean_retries(&self) -> usize;
+    fn get_table_names_cache(&self) -> &TableNamesCache;
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback>;
+    fn get_connect_timeout(&self) -> Option<Duration>;
+    fn get_eager_connect(&self) -> bool;
 }
 
 pub(super) struct MySQLBackendWrapper<'backend, 'pool, B: MySQLBackend<'pool>> {
@@ -104,49 +160,174 @@ impl<'backend, 'pool, B: MySQLBackend<'pool>> Deref for MySQLBackendWrapper<'bac
     }
 }
 
+impl<'backend, 'pool, B: MySQLBackend<'pool>> MySQLBackendWrapper<'backend, 'pool, B> {
+    #[cfg_attr(not(feature = "logging"), allow(clippy::unused_self))]
+    fn is_dry_run(&self) -> bool {
+        #[cfg(feature = "logging")]
+        return self.get_dry_run();
+        #[cfg(not(feature = "logging"))]
+        false
+    }
+
+    async fn execute_query(
+        &self,
+        query: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        if self.get_log_statements() || self.get_dry_run() {
+            log::info!("{query}");
+        }
+
+        // Skip executing statement in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.inner.execute_query(query, conn).await
+    }
+
+    async fn batch_execute_query<'a>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut B::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        let query = {
+            let query = query.into_iter().collect::<Vec<_>>();
+            if self.get_log_statements() || self.get_dry_run() {
+                for stmt in &query {
+                    log::info!("{stmt}");
+                }
+            }
+            query
+        };
+
+        // Skip executing statements in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.inner.batch_execute_query(query, conn).await
+    }
+
+    async fn get_cached_table_names(
+        &self,
+        db_id: Uuid,
+        db_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<Vec<String>, B::QueryError> {
+        if let Some(table_names) = self.get_table_names_cache().lock().get(&db_id) {
+            return Ok(table_names.clone());
+        }
+
+        let table_names = self.get_table_names(db_name, conn).await?;
+        self.get_table_names_cache()
+            .lock()
+            .insert(db_id, table_names.clone());
+        Ok(table_names)
+    }
+
+    async fn connect<T, E>(
+        &self,
+        connection: impl Future<Output = Result<T, E>> + Send,
+    ) -> Result<T, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    where
+        E: Into<BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>,
+    {
+        // Give up connecting once timeout is reached if set
+        let result = if let Some(connect_timeout) = self.get_connect_timeout() {
+            timeout(connect_timeout, connection)
+                .await
+                .map_err(|_| BackendError::ConnectTimeout(connect_timeout))?
+        } else {
+            connection.await
+        };
+        result.map_err(Into::into)
+    }
+
+    fn report_metric(
+        &self,
+        operation: MetricOperation,
+        db_id: Uuid,
+        start: Instant,
+        cleaned_tables: Option<usize>,
+    ) {
+        if let Some(metrics_callback) = self.get_metrics_callback() {
+            metrics_callback(MetricEvent {
+                operation,
+                db_id,
+                elapsed: start.elapsed(),
+                cleaned_tables,
+            });
+        }
+    }
+}
+
 impl<'backend, 'pool, B> MySQLBackendWrapper<'backend, 'pool, B>
 where
     'backend: 'pool,
     B: MySQLBackend<'pool>,
 {
+    pub(super) async fn ping(
+        &'backend self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let conn = &mut self.get_connection().await.map_err(Into::into)?;
+        self.execute_query(mysql::PING, conn)
+            .await
+            .map_err(Into::into)
+    }
+
     pub(super) async fn init(
         &'backend self,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        // Ensure generated database names are valid identifiers before any database is created
+        let db_name = self.get_db_name(Uuid::new_v4());
+        if !is_valid_db_name(&db_name, mysql::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name));
+        }
+
         // Drop previous databases if needed
         if self.get_drop_previous_databases() {
             // Get privileged connection
             let conn = &mut self.get_connection().await.map_err(Into::into)?;
 
-            // Get previous database names
+            // Get previous database names using custom query if set
             self.execute_query(mysql::USE_DEFAULT_DATABASE, conn)
                 .await
                 .map_err(Into::into)?;
-            let mut db_names = self
-                .get_previous_database_names(conn)
+            let db_names = if let Some(query) = self.get_previous_database_query() {
+                self.get_database_names(query(DB_NAME_PREFIX).as_str(), conn)
+                    .await
+            } else {
+                self.get_previous_database_names(conn).await
+            }
+            .map_err(Into::into)?;
+
+            // Drop databases using the same connection to avoid exhausting the privileged pool
+            for db_name in &db_names {
+                self.execute_query(mysql::drop_database(db_name.as_str()).as_str(), conn)
+                    .await
+                    .map_err(Into::into)?;
+            }
+
+            // Get previous user names
+            let host = self.get_host();
+            let user_names = self
+                .get_previous_user_names(host, conn)
                 .await
                 .map_err(Into::into)?;
 
-            // Drop databases
-            let futures = db_names
-                .drain(..)
-                .map(|db_name| async move {
-                    let conn = &mut self.get_connection().await.map_err(Into::into)?;
-                    self.execute_query(mysql::drop_database(db_name.as_str()).as_str(), conn)
+            // Drop users left behind by previous databases
+            for user_name in &user_names {
+                if Some(user_name.as_str()) != self.get_reused_user() {
+                    self.execute_query(mysql::drop_user(user_name.as_str(), host).as_str(), conn)
                         .await
                         .map_err(Into::into)?;
-                    Ok::<
-                        _,
-                        BackendError<
-                            B::BuildError,
-                            B::PoolError,
-                            B::ConnectionError,
-                            B::QueryError,
-                        >,
-                    >(())
-                })
-                .collect::<Vec<_>>();
-            futures::future::try_join_all(futures).await?;
+                }
+            }
         }
 
         Ok(())
@@ -156,57 +337,116 @@ where
         &'backend self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
+        with_entities: bool,
     ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
+        // Ensure database name is a valid identifier
+        if !is_valid_db_name(db_name, mysql::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name.to_owned()));
+        }
+
         let host = self.get_host();
 
+        // Get name of user attached to database
+        let reused_user = self.get_reused_user();
+        let user_name = reused_user.unwrap_or(db_name);
+
         // Get privileged connection
         let conn = &mut self.get_connection().await.map_err(Into::into)?;
 
         // Create database
-        self.execute_query(mysql::create_database(db_name).as_str(), conn)
-            .await
-            .map_err(Into::into)?;
+        self.execute_query(
+            mysql::create_database(db_name, self.get_charset(), self.get_collation()).as_str(),
+            conn,
+        )
+        .await
+        .map_err(Into::into)?;
 
-        // Create user
-        self.execute_query(mysql::create_user(db_name, host).as_str(), conn)
-            .await
-            .map_err(Into::into)?;
+        // Create user if not reusing an existing one
+        if reused_user.is_none() {
+            self.execute_query(mysql::create_user(user_name, host).as_str(), conn)
+                .await
+                .map_err(|err| {
+                    if self.is_duplicate_user_error(&err) {
+                        BackendError::RoleExists(user_name.to_owned())
+                    } else {
+                        err.into()
+                    }
+                })?;
+        }
 
-        // Create entities
-        self.execute_query(mysql::use_database(db_name).as_str(), conn)
-            .await
-            .map_err(Into::into)?;
-        self.create_entities(db_name).await.map_err(Into::into)?;
-        self.execute_query(mysql::USE_DEFAULT_DATABASE, conn)
-            .await
-            .map_err(Into::into)?;
+        // Create entities if needed, except in dry run as the database was never created
+        if with_entities && !self.is_dry_run() {
+            self.execute_query(mysql::use_database(db_name).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+            self.create_entities(db_name).await.map_err(Into::into)?;
+            self.execute_query(mysql::USE_DEFAULT_DATABASE, conn)
+                .await
+                .map_err(Into::into)?;
+        }
 
         if restrict_privileges {
             // Grant privileges to restricted user
             self.execute_query(
-                mysql::grant_restricted_privileges(db_name, host).as_str(),
+                mysql::grant_restricted_privileges(db_name, user_name, host).as_str(),
                 conn,
             )
             .await
             .map_err(Into::into)?;
         } else {
             // Grant all privileges to database-unrestricted user
-            self.execute_query(mysql::grant_all_privileges(db_name, host).as_str(), conn)
-                .await
-                .map_err(Into::into)?;
+            self.execute_query(
+                mysql::grant_all_privileges(db_name, user_name, host).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
         }
 
         // Create connection pool with attached user
+        let pool = self.create_connection_pool(db_id).await?;
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Create, db_id, start, None);
+
+        Ok(pool)
+    }
+
+    pub(super) async fn create_reader_pool(
+        &'backend self,
+        db_id: uuid::Uuid,
+    ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Create connection pool with attached user
+        self.create_connection_pool(db_id).await
+    }
+
+    async fn create_connection_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
         let pool = self
+            .inner
             .create_connection_pool(db_id)
             .await
             .map_err(Into::into)?;
 
+        // Establish a connection ahead of the first query if needed,
+        // except in dry run as the database was never created
+        if self.get_eager_connect() && !self.is_dry_run() {
+            self.warm_up_connection_pool(&pool)
+                .await
+                .map_err(Into::into)?;
+        }
+
         Ok(pool)
     }
 
@@ -215,16 +455,23 @@ where
         db_id: uuid::Uuid,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        // Skip cleaning in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
         // Get privileged connection
         let conn = &mut self.get_connection().await.map_err(Into::into)?;
 
-        // Get table names
+        // Get table names, cached since the schema cannot change
         let table_names = self
-            .get_table_names(db_name, conn)
+            .get_cached_table_names(db_id, db_name, conn)
             .await
             .map_err(Into::into)?;
 
@@ -238,42 +485,239 @@ where
             .await
             .map_err(Into::into)?;
 
-        // Truncate tables
-        self.batch_execute_query(stmts, conn)
+        // Truncate tables, which cannot be done in a transaction since TRUNCATE implicitly commits
+        // Retry after a backoff on deadlocks and lock wait timeouts if needed
+        let mut attempt = 0;
+        let truncated = loop {
+            match self.batch_execute_query(stmts.clone(), conn).await {
+                Err(err)
+                    if attempt < self.get_clean_retries() && self.is_transient_lock_error(&err) =>
+                {
+                    attempt += 1;
+                    sleep(get_clean_retry_backoff(attempt)).await;
+                }
+                truncated => break truncated.map_err(Into::into),
+            }
+        };
+
+        // Turn on foreign key checks even if truncating failed, since the connection is returned to the pool
+        self.execute_query(mysql::TURN_ON_FOREIGN_KEY_CHECKS, conn)
+            .await
+            .map_err(Into::into)?;
+        truncated?;
+
+        // Verify that no table was left with rows if needed
+        if self.get_verify_clean() && !table_names.is_empty() {
+            let nonempty_table_names = self
+                .get_nonempty_table_names(&table_names, db_name, conn)
+                .await
+                .map_err(Into::into)?;
+            if !nonempty_table_names.is_empty() {
+                return Err(BackendError::UncleanDatabase(db_id, nonempty_table_names));
+            }
+        }
+
+        // Report time taken and number of tables cleaned if needed
+        self.report_metric(
+            MetricOperation::Clean,
+            db_id,
+            start,
+            Some(table_names.len()),
+        );
+
+        Ok(())
+    }
+
+    pub(super) async fn refresh(
+        &'backend self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Skip recreating entities in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        // Get database name based on UUID
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        // Get privileged connection
+        let conn = &mut self.get_connection().await.map_err(Into::into)?;
+
+        // Get table names
+        let table_names = self
+            .get_table_names(db_name, conn)
             .await
             .map_err(Into::into)?;
 
-        // Turn on foreign key checks
+        // Generate drop statements
+        let stmts = table_names
+            .iter()
+            .map(|table_name| mysql::drop_table(table_name.as_str(), db_name).into());
+
+        // Turn off foreign key checks
+        self.execute_query(mysql::TURN_OFF_FOREIGN_KEY_CHECKS, conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Drop tables
+        let dropped = self
+            .batch_execute_query(stmts, conn)
+            .await
+            .map_err(Into::into);
+
+        // Turn on foreign key checks even if dropping failed, since the connection is returned to the pool
         self.execute_query(mysql::TURN_ON_FOREIGN_KEY_CHECKS, conn)
             .await
             .map_err(Into::into)?;
+        dropped?;
+
+        // Create entities again, with restricted privileges granted on the whole database
+        self.execute_query(mysql::use_database(db_name).as_str(), conn)
+            .await
+            .map_err(Into::into)?;
+        self.create_entities(db_name).await.map_err(Into::into)?;
+        self.execute_query(mysql::USE_DEFAULT_DATABASE, conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Forget table names of previous entities
+        self.get_table_names_cache().lock().remove(&db_id);
 
         Ok(())
     }
 
-    pub(super) async fn drop(
+    pub(super) async fn verify_restricted(
         &'backend self,
         db_id: uuid::Uuid,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        // Skip verification in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
         // Get database name based on UUID
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
-        let host = self.get_host();
+        // Connect to database as restricted user
+        let mut conn = self
+            .connect(self.establish_restricted_database_connection(db_id))
+            .await?;
+
+        // Creating a table must be rejected
+        if self
+            .execute_query(mysql::CREATE_PRIVILEGE_CHECK_TABLE, &mut conn)
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        // Drop table created unexpectedly
+        self.execute_query(
+            mysql::drop_table(mysql::PRIVILEGE_CHECK_TABLE_NAME, db_name).as_str(),
+            &mut conn,
+        )
+        .await
+        .map_err(Into::into)?;
+
+        Err(BackendError::UnrestrictedDatabase(db_id))
+    }
+
+    pub(super) async fn label(
+        &'backend self,
+        db_id: uuid::Uuid,
+        label: &str,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Leave reused user unlabeled since it is shared by all databases,
+        // and leave users unlabeled on servers not supporting user comments
+        if self.get_reused_user().is_some() || !self.get_server_flavor().supports_user_comments() {
+            return Ok(());
+        }
+
+        // Get database name based on UUID
+        let db_name = self.get_db_name(db_id);
 
         // Get privileged connection
         let conn = &mut self.get_connection().await.map_err(Into::into)?;
 
+        // Label attached user
+        self.execute_query(
+            mysql::comment_user(db_name.as_str(), self.get_host(), label).as_str(),
+            conn,
+        )
+        .await
+        .map_err(Into::into)
+    }
+
+    pub(super) async fn drop(
+        &'backend self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Get privileged connection
+        let conn = &mut self.get_connection().await.map_err(Into::into)?;
+
+        self.drop_over_connection(db_id, conn).await
+    }
+
+    pub(super) async fn drop_with_dedicated_connection(
+        &'backend self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Establish dedicated privileged connection
+        let conn = &mut self
+            .establish_privileged_connection()
+            .await
+            .map_err(Into::into)?;
+
+        self.drop_over_connection(db_id, conn).await
+    }
+
+    async fn drop_over_connection(
+        &'backend self,
+        db_id: uuid::Uuid,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let start = Instant::now();
+
+        // Forget table names
+        self.get_table_names_cache().lock().remove(&db_id);
+
+        // Get database name based on UUID
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let host = self.get_host();
+
         // Drop database
         self.execute_query(mysql::drop_database(db_name).as_str(), conn)
             .await
             .map_err(Into::into)?;
 
-        // Drop attached user
-        self.execute_query(mysql::drop_user(db_name, host).as_str(), conn)
+        if let Some(user_name) = self.get_reused_user() {
+            // Revoke privileges from reused user
+            self.execute_query(
+                mysql::revoke_all_privileges(db_name, user_name, host).as_str(),
+                conn,
+            )
             .await
             .map_err(Into::into)?;
+        } else {
+            // Drop attached user
+            self.execute_query(mysql::drop_user(db_name, host).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Drop, db_id, start, None);
 
         Ok(())
     }
@@ -670,6 +1114,46 @@ pub(super) mod tests {
         .await;
     }
 
+    pub async fn test_pool_drops_prewarmed_databases(backend: impl Backend) {
+        const NUM_DBS: i64 = 3;
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // there must be no databases
+            assert_eq!(count_all_databases(conn).await, 0);
+
+            // create databases ahead of demand
+            db_pool
+                .prewarm(usize::try_from(NUM_DBS).unwrap())
+                .await
+                .unwrap();
+
+            // there must be databases
+            assert_eq!(count_all_databases(conn).await, NUM_DBS);
+
+            // fetch connection pools
+            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+            // prewarmed databases must be reused
+            assert_eq!(count_all_databases(conn).await, NUM_DBS);
+
+            // must release databases back to pool
+            drop(conn_pools);
+
+            // must drop databases
+            drop(db_pool);
+
+            // there must be no databases
+            assert_eq!(count_all_databases(conn).await, 0);
+        }
+        .lock_drop()
+        .await;
+    }
+
     pub async fn test_pool_drops_created_unrestricted_database(backend: impl Backend) {
         let conn_pool = get_privileged_connection_pool().await;
         let conn = &mut conn_pool.get().await.unwrap();
@@ -1,39 +1,108 @@
-use std::{borrow::Cow, collections::HashMap, pin::Pin};
+use std::{borrow::Cow, collections::HashMap, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use diesel::{prelude::*, result::Error, sql_query, table, ConnectionError};
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind, Error},
+    sql_query, table, ConnectionError,
+};
 use diesel_async::{
     pooled_connection::{AsyncDieselConnectionManager, ManagerConfig, SetupCallback},
     AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection,
 };
-use futures::{future::FutureExt, Future};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    Future,
+};
 use parking_lot::Mutex;
 use uuid::Uuid;
 
-use crate::{common::config::postgres::PrivilegedPostgresConfig, util::get_db_name};
+use crate::{
+    common::{
+        config::{
+            postgres::PrivilegedPostgresConfig, CleanStrategy, PgDialect, PrivilegeProfile, Role,
+        },
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    r#async::{
+        schema_provider::{create_entities_with, SchemaProvider},
+        PoolWrapper,
+    },
+    util::{
+        append_pg_connection_params, get_credentials, get_db_name, PreviousDatabaseQuery,
+        ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{
         common::pool::diesel::r#trait::DieselPoolAssociation, error::Error as BackendError,
         r#trait::Backend,
     },
-    r#trait::{PostgresBackend, PostgresBackendWrapper},
+    r#trait::{CustomClean, PostgresBackend, PostgresBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(AsyncPgConnection) -> Pin<Box<dyn Future<Output = AsyncPgConnection> + Send + 'static>>
+type CreateEntities = dyn Fn(AsyncPgConnection, &str) -> Pin<Box<dyn Future<Output = AsyncPgConnection> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut AsyncPgConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`Diesel async Postgres`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncPgConnection.html) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct DieselAsyncPostgresBackend<P: DieselPoolAssociation<AsyncPgConnection>> {
     privileged_config: PrivilegedPostgresConfig,
     default_pool: P::Pool,
+    table_names_cache: TableNamesCache,
     db_conns: Mutex<HashMap<Uuid, AsyncPgConnection>>,
     create_restricted_pool: Box<dyn Fn() -> P::Builder + Send + Sync + 'static>,
     create_connection: Box<dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    clean_strategy: CleanStrategy,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    store_clean_connections_flag: bool,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
+    custom_clean: Option<Box<CustomClean<AsyncPgConnection, Error>>>,
 }
 
 impl<P: DieselPoolAssociation<AsyncPgConnection>> DieselAsyncPostgresBackend<P> {
@@ -89,11 +158,72 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> DieselAsyncPostgresBackend<P>
             + Sync
             + 'static,
     ) -> Result<Self, P::BuildError> {
-        let create_connection = custom_create_connection.unwrap_or_else(|| {
-            Box::new(|| {
-                Box::new(|connection_url| AsyncPgConnection::establish(connection_url).boxed())
-            })
-        });
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            custom_create_connection,
+            move |conn, _| create_entities(conn),
+        )
+        .await
+    }
+
+    /// Creates a new [`Diesel async Postgres`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncPgConnection.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new_with_context(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 sql_query(query.as_str())
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_context(
+        privileged_config: PrivilegedPostgresConfig,
+        create_privileged_pool: impl Fn() -> P::Builder,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static>,
+        >,
+        create_entities: impl Fn(
+                AsyncPgConnection,
+                &str,
+            ) -> Pin<Box<dyn Future<Output = AsyncPgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<Self, P::BuildError> {
+        let create_connection =
+            custom_create_connection.unwrap_or_else(Self::default_create_connection);
 
         let manager_config = {
             let mut config = ManagerConfig::default();
@@ -107,15 +237,216 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> DieselAsyncPostgresBackend<P>
         let builder = create_privileged_pool();
         let default_pool = P::build_pool(builder, manager).await?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            privileged_config,
+            default_pool,
+            create_restricted_pool,
+            create_connection,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`Diesel async Postgres`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncPgConnection.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::{
+    ///     pooled_connection::AsyncDieselConnectionManager, AsyncPgConnection, RunQueryDsl,
+    /// };
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///     let manager =
+    ///         AsyncDieselConnectionManager::<AsyncPgConnection>::new(config.default_connection_url());
+    ///     let privileged_pool = Pool::builder().max_size(10).build(manager).await.unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new_with_privileged_pool(
+    ///         config,
+    ///         privileged_pool,
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        privileged_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static>,
+        >,
+        create_entities: impl Fn(
+                AsyncPgConnection,
+            ) -> Pin<Box<dyn Future<Output = AsyncPgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            custom_create_connection.unwrap_or_else(Self::default_create_connection),
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`Diesel async Postgres`](https://docs.rs/diesel-async/0.5.0/diesel_async/struct.AsyncPgConnection.html) backend whose entities are created by a schema provider
+    ///
+    /// The same provider can apply the schema to the application database at startup.
+    /// # Example
+    /// ```
+    /// use async_trait::async_trait;
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DieselAsyncPostgresBackend, DieselBb8, SchemaProvider},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::{result::Error, sql_query};
+    /// use diesel_async::{AsyncPgConnection, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    ///
+    /// struct Migrations;
+    ///
+    /// #[async_trait]
+    /// impl SchemaProvider<AsyncPgConnection> for Migrations {
+    ///     type Error = Error;
+    ///
+    ///     async fn apply(&self, conn: &mut AsyncPgConnection) -> Result<(), Error> {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .await?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new_with_schema_provider(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         Migrations,
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_schema_provider(
+        privileged_config: PrivilegedPostgresConfig,
+        create_privileged_pool: impl Fn() -> P::Builder,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        custom_create_connection: Option<
+            Box<dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static>,
+        >,
+        schema_provider: impl SchemaProvider<AsyncPgConnection>,
+    ) -> Result<Self, P::BuildError> {
+        Self::new(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            custom_create_connection,
+            create_entities_with(schema_provider),
+        )
+        .await
+    }
+
+    fn default_create_connection(
+    ) -> Box<dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static> {
+        Box::new(|| Box::new(|connection_url| AsyncPgConnection::establish(connection_url).boxed()))
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        default_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        create_connection: Box<
+            dyn Fn() -> SetupCallback<AsyncPgConnection> + Send + Sync + 'static,
+        >,
+        create_entities: impl Fn(
+                AsyncPgConnection,
+                &str,
+            ) -> Pin<Box<dyn Future<Output = AsyncPgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
             privileged_config,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             db_conns: Mutex::new(HashMap::new()),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_connection,
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            clean_strategy: CleanStrategy::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            store_clean_connections_flag: true,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+            custom_clean: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -126,79 +457,524 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> DieselAsyncPostgresBackend<P>
             ..self
         }
     }
-}
-
-#[async_trait]
-impl<'pool, P: DieselPoolAssociation<AsyncPgConnection>> PostgresBackend<'pool>
-    for DieselAsyncPostgresBackend<P>
-{
-    type Connection = AsyncPgConnection;
-    type PooledConnection = P::PooledConnection<'pool>;
-    type Pool = P::Pool;
 
-    type BuildError = P::BuildError;
-    type PoolError = P::PoolError;
-    type ConnectionError = ConnectionError;
-    type QueryError = Error;
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
 
-    async fn execute_query(&self, query: &str, conn: &mut AsyncPgConnection) -> QueryResult<()> {
-        sql_query(query).execute(conn).await?;
-        Ok(())
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
     }
 
-    async fn batch_execute_query<'a>(
-        &self,
-        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
-        conn: &mut AsyncPgConnection,
-    ) -> QueryResult<()> {
-        let query = query.into_iter().collect::<Vec<_>>();
-        if query.is_empty() {
-            Ok(())
-        } else {
-            conn.batch_execute(query.join(";").as_str()).await
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
         }
     }
 
-    async fn get_default_connection(
-        &'pool self,
-    ) -> Result<P::PooledConnection<'pool>, P::PoolError> {
-        P::get_connection(&self.default_pool).await
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
     }
 
-    async fn establish_privileged_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> ConnectionResult<AsyncPgConnection> {
-        let db_name = get_db_name(db_id);
-        let database_url = self
-            .privileged_config
-            .privileged_database_connection_url(db_name.as_str());
-        (self.create_connection)()(database_url.as_str()).await
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
     }
 
-    async fn establish_restricted_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> ConnectionResult<AsyncPgConnection> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
-        );
-        (self.create_connection)()(database_url.as_str()).await
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
     }
 
-    fn put_database_connection(&self, db_id: Uuid, conn: AsyncPgConnection) {
-        self.db_conns.lock().insert(db_id, conn);
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> AsyncPgConnection {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
+    }
+
+    /// How databases are cleaned once their reusable connection pools are returned
+    ///
+    /// Defaults to truncating tables. Ignored when cleaning with a custom routine.
+    #[must_use]
+    pub fn clean_strategy(self, value: CleanStrategy) -> Self {
+        Self {
+            clean_strategy: value,
+            ..self
+        }
+    }
+
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
+    }
+
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
+
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
+    }
+
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Keep a privileged connection open per restricted database for cleaning it
+    ///
+    /// Enabled by default. When disabled, a privileged connection is established on demand
+    /// to clean a database and closed afterwards, trading cleaning latency for fewer idle
+    /// connections to the server.
+    #[must_use]
+    pub fn store_clean_connections(self, value: bool) -> Self {
+        Self {
+            store_clean_connections_flag: value,
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut AsyncPgConnection,
+            ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
+
+    /// Routine cleaning databases in place of truncating all of their tables
+    ///
+    /// Runs on the stored privileged connection to the database, within a transaction if cleaning in transactions.
+    /// Meant for schemas needing bespoke cleaning, such as disabling triggers or deleting rows in foreign key order.
+    #[must_use]
+    pub fn custom_clean(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut AsyncPgConnection,
+            ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            custom_clean: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Establishes privileged and restricted connections to databases from their connection URLs instead of connecting directly
+    ///
+    /// Meant for reaching the database server through a tunnel, such as by wrapping with
+    /// [`AsyncPgConnection::try_from`](diesel_async::AsyncPgConnection::try_from)
+    /// a client connected over a stream forwarded through a bastion host.
+    /// Replaces the connection creation passed when creating the backend, including for connections of restricted pools,
+    /// while connections of the already built privileged pool are left as is.
+    #[must_use]
+    pub fn connector(
+        self,
+        value: impl Fn(&str) -> BoxFuture<'_, ConnectionResult<AsyncPgConnection>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let connector = Arc::new(value);
+        Self {
+            create_connection: Box::new(move || {
+                let connector = connector.clone();
+                Box::new(move |connection_url| connector(connection_url))
+            }),
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl<'pool, P: DieselPoolAssociation<AsyncPgConnection>> PostgresBackend<'pool>
+    for DieselAsyncPostgresBackend<P>
+{
+    type Connection = AsyncPgConnection;
+    type PooledConnection = P::PooledConnection<'pool>;
+    type Pool = P::Pool;
+
+    type BuildError = P::BuildError;
+    type PoolError = P::PoolError;
+    type ConnectionError = ConnectionError;
+    type QueryError = Error;
+
+    async fn execute_query(&self, query: &str, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        sql_query(query).execute(conn).await?;
+        Ok(())
+    }
+
+    async fn batch_execute_query<'a>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<()> {
+        let query = query.into_iter().collect::<Vec<_>>();
+        if query.is_empty() {
+            Ok(())
+        } else {
+            conn.batch_execute(query.join(";").as_str()).await
+        }
+    }
+
+    async fn get_default_connection(
+        &'pool self,
+    ) -> Result<P::PooledConnection<'pool>, P::PoolError> {
+        P::get_connection(&self.default_pool).await
+    }
+
+    async fn establish_privileged_connection(&self) -> ConnectionResult<AsyncPgConnection> {
+        let database_url = self.privileged_config.default_connection_url();
+        (self.create_connection)()(database_url.as_str()).await
+    }
+
+    async fn establish_privileged_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> ConnectionResult<AsyncPgConnection> {
+        let db_name = self.get_db_name(db_id);
+        let database_url = self
+            .privileged_config
+            .privileged_database_connection_url(db_name.as_str());
+        (self.create_connection)()(database_url.as_str()).await
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> ConnectionResult<AsyncPgConnection> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let database_url = append_pg_connection_params(
+            self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ),
+            &self.restricted_connection_params,
+        );
+        (self.create_connection)()(database_url.as_str()).await
+    }
+
+    fn put_database_connection(&self, db_id: Uuid, conn: AsyncPgConnection) {
+        self.db_conns.lock().insert(db_id, conn);
+    }
+
+    fn get_database_connection(&self, db_id: Uuid) -> Option<AsyncPgConnection> {
+        self.db_conns.lock().remove(&db_id)
     }
 
     async fn get_previous_database_names(
@@ -219,21 +995,113 @@ impl<'pool, P: DieselPoolAssociation<AsyncPgConnection>> PostgresBackend<'pool>
             .await
     }
 
-    async fn create_entities(&self, conn: AsyncPgConnection) -> AsyncPgConnection {
-        (self.create_entities)(conn).await
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(query)
+            .load::<DatabaseName>(conn)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    async fn get_previous_owned_database_names(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(postgres::GET_OWNED_DATABASE_NAMES)
+            .load::<DatabaseName>(conn)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    async fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(postgres::get_database_names_with_schema_version(
+            schema_version,
+        ))
+        .load::<DatabaseName>(conn)
+        .await
+        .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    async fn get_previous_role_names(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        table! {
+            pg_roles (rolname) {
+                rolname -> Text
+            }
+        }
+
+        pg_roles::table
+            .select(pg_roles::rolname)
+            .filter(pg_roles::rolname.like("db_pool_%"))
+            .load::<String>(conn)
+            .await
+    }
+
+    async fn create_entities(&self, conn: AsyncPgConnection, db_name: &str) -> AsyncPgConnection {
+        (self.create_entities)(conn, db_name).await
+    }
+
+    async fn warm_up_connection_pool(&self, pool: &P::Pool) -> Result<(), P::PoolError> {
+        P::get_connection(pool).await.map(drop)
     }
 
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<P::Pool, P::BuildError> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let database_url = append_pg_connection_params(
+            self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ),
+            &self.restricted_connection_params,
         );
         let manager_config = {
             let mut config = ManagerConfig::default();
-            config.custom_setup = Box::new((self.create_connection)());
+            let setup = (self.create_connection)();
+            config.custom_setup = if let Some(after_connect) = self.after_connect.clone() {
+                Box::new(move |url| {
+                    let conn = setup(url);
+                    let after_connect = after_connect.clone();
+                    Box::pin(async move {
+                        let mut conn = conn.await?;
+                        after_connect(&mut conn)
+                            .await
+                            .map_err(ConnectionError::CouldntSetupConfiguration)?;
+                        Ok(conn)
+                    })
+                })
+            } else {
+                setup
+            };
             config
         };
         let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
@@ -244,27 +1112,215 @@ impl<'pool, P: DieselPoolAssociation<AsyncPgConnection>> PostgresBackend<'pool>
         P::build_pool(builder, manager).await
     }
 
-    async fn get_table_names(
-        &self,
-        privileged_conn: &mut AsyncPgConnection,
-    ) -> QueryResult<Vec<String>> {
-        table! {
-            pg_tables (tablename) {
-                #[sql_name = "schemaname"]
-                schema_name -> Text,
-                tablename -> Text
-            }
-        }
+    async fn get_table_names(
+        &self,
+        privileged_conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        table! {
+            pg_tables (tablename) {
+                #[sql_name = "schemaname"]
+                schema_name -> Text,
+                tablename -> Text
+            }
+        }
+
+        pg_tables::table
+            .filter(pg_tables::schema_name.ne_all(self.dialect.system_schemas().iter().copied()))
+            .select((pg_tables::schema_name, pg_tables::tablename))
+            .load::<(String, String)>(privileged_conn)
+            .await
+            .map(|table_names| {
+                table_names
+                    .iter()
+                    .map(|(schema_name, table_name)| {
+                        postgres::qualify_table_name(schema_name, table_name)
+                    })
+                    .collect()
+            })
+    }
+
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        privileged_conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct TableName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            table_name: String,
+        }
+
+        sql_query(postgres::get_nonempty_table_names(table_names))
+            .load::<TableName>(privileged_conn)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.table_name).collect())
+    }
+
+    fn is_duplicate_database_error(&self, err: &Error) -> bool {
+        // Diesel does not expose SQLSTATE codes, so the error message is matched instead
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().ends_with("already exists")
+        )
+    }
+
+    fn is_duplicate_role_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("role") && info.message().ends_with("already exists")
+        )
+    }
+
+    fn is_transient_lock_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("deadlock detected")
+        )
+    }
+
+    fn get_drop_previous_databases(&self) -> bool {
+        self.drop_previous_databases_flag
+    }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_clean_strategy(&self) -> CleanStrategy {
+        self.clean_strategy
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
+
+    fn get_custom_clean(&self) -> Option<&CustomClean<AsyncPgConnection, Error>> {
+        self.custom_clean.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
+    }
+
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
+    }
+
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
+    }
+
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
+    }
+
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
+    }
 
-        pg_tables::table
-            .filter(pg_tables::schema_name.ne_all(["pg_catalog", "information_schema"]))
-            .select(pg_tables::tablename)
-            .load(privileged_conn)
-            .await
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
     }
 
-    fn get_drop_previous_databases(&self) -> bool {
-        self.drop_previous_databases_flag
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
+    }
+
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
+    }
+
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
+    }
+
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
+    }
+
+    fn get_store_clean_connections(&self) -> bool {
+        self.store_clean_connections_flag
     }
 }
 
@@ -283,13 +1339,35 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> Backend for DieselAsyncPostgre
         PostgresBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).ping().await
+    }
+
     async fn create(
         &self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
         PostgresBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -297,6 +1375,27 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> Backend for DieselAsyncPostgre
         PostgresBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(
+        &self,
+        db_id: uuid::Uuid,
+        label: &str,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(
         &self,
         db_id: uuid::Uuid,
@@ -306,45 +1405,111 @@ impl<P: DieselPoolAssociation<AsyncPgConnection>> Backend for DieselAsyncPostgre
             .drop(db_id, is_restricted)
             .await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id, is_restricted)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        Some(self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        ))
+    }
+
+    fn privileged_pool(&self) -> &P::Pool {
+        &self.default_pool
+    }
+}
+
+impl<P: DieselPoolAssociation<AsyncPgConnection>> PoolWrapper<DieselAsyncPostgresBackend<P>> {
+    /// Gets a connection from the wrapped connection pool
+    ///
+    /// The returned connection dereferences to the underlying connection regardless of the pool association.
+    pub async fn get(&self) -> Result<P::PooledConnection<'_>, P::PoolError> {
+        P::get_connection(self).await
+    }
+
+    /// Gets a connection from the wrapped connection pool that does not borrow the pool
+    ///
+    /// Useful for handlers and spawned tasks that require a `'static` connection.
+    pub async fn get_owned(&self) -> Result<P::OwnedPooledConnection, P::PoolError> {
+        P::get_owned_connection(self).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used, clippy::needless_return)]
 
-    use std::borrow::Cow;
+    use std::{
+        borrow::Cow,
+        collections::HashMap,
+        env,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
     use bb8::Pool;
-    use diesel::{insert_into, sql_query, table, Insertable, QueryDsl};
-    use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+    use diesel::{
+        dsl::sql,
+        insert_into, select, sql_query,
+        sql_types::{Nullable, Text},
+        table, Insertable, QueryDsl,
+    };
+    use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
     use dotenvy::dotenv;
-    use futures::future::join_all;
+    use futures::{future::join_all, FutureExt};
     use tokio_shared_rt::test;
 
     use crate::{
         common::{
-            config::PrivilegedPostgresConfig,
+            config::{PgDialect, PrivilegedPostgresConfig, Role},
             statement::postgres::tests::{
                 CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
             },
         },
         r#async::{
             backend::{
-                common::pool::diesel::bb8::DieselBb8,
+                common::pool::diesel::{bb8::DieselBb8, r#trait::DieselPoolAssociation},
                 postgres::r#trait::tests::test_pool_drops_created_unrestricted_database,
             },
             db_pool::DatabasePoolBuilder,
+            PoolWrapper, RollbackPoolTrait,
         },
     };
 
     use super::{
         super::r#trait::tests::{
-            test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
+            test_backend_cleans_database_with_tables,
+            test_backend_cleans_database_without_stored_connection,
+            test_backend_cleans_database_without_tables,
+            test_backend_creates_database_owned_by_role,
             test_backend_creates_database_with_restricted_privileges,
             test_backend_creates_database_with_unrestricted_privileges,
-            test_backend_drops_database, test_backend_drops_previous_databases,
+            test_backend_creates_entities_as_restricted_role, test_backend_drops_database,
+            test_backend_drops_previous_databases,
+            test_backend_fails_to_clean_unrestricted_database,
+            test_backend_force_drops_database_with_lingering_connection,
+            test_backend_recreates_database_on_conflict, test_backend_recreates_role_on_conflict,
             test_pool_drops_created_restricted_databases, test_pool_drops_previous_databases,
-            PgDropLock,
+            test_pool_drops_prewarmed_databases, PgDropLock,
         },
         DieselAsyncPostgresBackend,
     };
@@ -362,6 +1527,61 @@ mod tests {
         title: Cow<'a, str>,
     }
 
+    async fn test_pool_wrapper_gets_connection<P: DieselPoolAssociation<AsyncPgConnection>>(
+        backend: DieselAsyncPostgresBackend<P>,
+    ) {
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = PoolWrapper::from(db_pool.create_mutable().await.unwrap());
+
+            // connection must dereference to the underlying connection
+            let conn = &mut *conn_pool.get().await.unwrap();
+            insert_into(book::table)
+                .values(NewBook {
+                    title: "Title".into(),
+                })
+                .execute(conn)
+                .await
+                .unwrap();
+            assert_eq!(
+                book::table.count().get_result::<i64>(conn).await.unwrap(),
+                1
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    async fn test_pool_wrapper_gets_owned_connection<
+        P: DieselPoolAssociation<AsyncPgConnection>,
+    >(
+        backend: DieselAsyncPostgresBackend<P>,
+    ) {
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = PoolWrapper::from(db_pool.create_mutable().await.unwrap());
+
+            // connection must be usable after being moved into a spawned task
+            let mut conn = conn_pool.get_owned().await.unwrap();
+            let count = tokio::spawn(async move {
+                let conn = &mut *conn;
+                insert_into(book::table)
+                    .values(NewBook {
+                        title: "Title".into(),
+                    })
+                    .execute(conn)
+                    .await
+                    .unwrap();
+                book::table.count().get_result::<i64>(conn).await.unwrap()
+            })
+            .await
+            .unwrap();
+            assert_eq!(count, 1);
+        }
+        .lock_read()
+        .await;
+    }
+
     async fn create_backend(with_table: bool) -> DieselAsyncPostgresBackend<DieselBb8> {
         dotenv().ok();
 
@@ -406,12 +1626,77 @@ mod tests {
         test_backend_creates_database_with_unrestricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_database_on_conflict() {
+        test_backend_recreates_database_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_entities_as_restricted_role() {
+        test_backend_creates_entities_as_restricted_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .create_entities_as(Role::Restricted),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_owned_by_role() {
+        test_backend_creates_database_owned_by_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .owner_at_creation(true)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_role_on_conflict() {
+        test_backend_recreates_role_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_with_tables() {
         let backend = create_backend(true).await.drop_previous_databases(false);
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_stored_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .store_clean_connections(false);
+        test_backend_cleans_database_without_stored_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).await.drop_previous_databases(false);
@@ -430,6 +1715,21 @@ mod tests {
         test_backend_drops_database(backend, false).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_previous_databases() {
         test_pool_drops_previous_databases(
@@ -491,6 +1791,43 @@ mod tests {
         .await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_rolls_back_transactions() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // changes must be visible within transaction
+            let count = conn_pool
+                .with_rollback(|conn| {
+                    Box::pin(async move {
+                        insert_into(book::table)
+                            .values(NewBook {
+                                title: "Title".into(),
+                            })
+                            .execute(conn)
+                            .await
+                            .unwrap();
+                        book::table.count().get_result::<i64>(conn).await.unwrap()
+                    })
+                })
+                .await
+                .unwrap();
+            assert_eq!(count, 1);
+
+            // changes must be rolled back
+            let conn = &mut conn_pool.get().await.unwrap();
+            assert_eq!(
+                book::table.count().get_result::<i64>(conn).await.unwrap(),
+                0
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_provides_restricted_databases() {
         let backend = create_backend(true).await.drop_previous_databases(false);
@@ -603,9 +1940,256 @@ mod tests {
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_database() {
         let backend = create_backend(false).await;
         test_pool_drops_created_unrestricted_database(backend).await;
     }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_wrapper_gets_connection() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_pool_wrapper_gets_connection(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_wrapper_gets_owned_connection() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_pool_wrapper_gets_owned_connection(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_applies_restricted_connection_params() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .restricted_connection_params(HashMap::from([
+                ("application_name".to_owned(), "db_pool_test".to_owned()),
+                ("search_path".to_owned(), "public, pg_catalog".to_owned()),
+            ]));
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // restricted connections must carry connection parameters
+            assert_eq!(
+                select(sql::<(Text, Text)>(
+                    "current_setting('application_name'), current_setting('search_path')"
+                ))
+                .get_result::<(String, String)>(conn)
+                .await
+                .unwrap(),
+                ("db_pool_test".to_owned(), "public, pg_catalog".to_owned())
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_after_connect_hook() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .after_connect(|conn| Box::pin(conn.batch_execute("SET statement_timeout = 1234")));
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // restricted connections must carry session settings applied by hook
+            assert_eq!(
+                select(sql::<Text>("current_setting('statement_timeout')"))
+                    .get_result::<String>(conn)
+                    .await
+                    .unwrap(),
+                "1234ms"
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_connects_through_connector() {
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .connector({
+                let connections = connections.clone();
+                move |connection_url| {
+                    connections.fetch_add(1, Ordering::Relaxed);
+                    AsyncPgConnection::establish(connection_url).boxed()
+                }
+            });
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // restricted pool connections must be established through connector
+            assert!(select(sql::<Text>("current_user"))
+                .get_result::<String>(conn)
+                .await
+                .is_ok());
+
+            // privileged database connection and restricted pool connection must be established through connector
+            assert!(AtomicUsize::load(&connections, Ordering::Relaxed) >= 2);
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_connects_through_unix_socket() {
+        dotenv().ok();
+
+        // socket connections are only tested where a socket is configured
+        let Ok(socket) = env::var("POSTGRES_SOCKET") else {
+            return;
+        };
+        let config = PrivilegedPostgresConfig::from_env().unwrap().host(socket);
+
+        let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            None,
+            move |mut conn| {
+                Box::pin(async move {
+                    let query = CREATE_ENTITIES_STATEMENTS.join(";");
+                    conn.batch_execute(query.as_str()).await.unwrap();
+                    conn
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // restricted connections must be made through socket, which reports no client address
+            assert!(select(sql::<Nullable<Text>>("inet_client_addr()::TEXT"))
+                .get_result::<Option<String>>(conn)
+                .await
+                .unwrap()
+                .is_none());
+
+            // entities created through socket must be usable
+            sql_query("INSERT INTO book (title) VALUES ('Title')")
+                .execute(conn)
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[cfg(feature = "diesel-async-deadpool")]
+    mod deadpool {
+        use ::deadpool::managed::PoolConfig;
+        use diesel_async::SimpleAsyncConnection;
+        use dotenvy::dotenv;
+        use tokio_shared_rt::test;
+
+        use crate::{
+            common::{
+                config::PrivilegedPostgresConfig,
+                statement::postgres::tests::CREATE_ENTITIES_STATEMENTS,
+            },
+            r#async::backend::common::pool::diesel::deadpool::DieselDeadpool,
+        };
+
+        use super::{
+            super::{
+                super::r#trait::tests::{
+                    test_backend_cleans_database_with_tables,
+                    test_backend_creates_database_with_restricted_privileges,
+                    test_backend_drops_database, test_pool_drops_created_restricted_databases,
+                },
+                DieselAsyncPostgresBackend,
+            },
+            test_pool_wrapper_gets_connection, test_pool_wrapper_gets_owned_connection,
+        };
+
+        async fn create_backend(with_table: bool) -> DieselAsyncPostgresBackend<DieselDeadpool> {
+            dotenv().ok();
+
+            let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+            DieselAsyncPostgresBackend::new(
+                config,
+                PoolConfig::default,
+                PoolConfig::default,
+                None,
+                {
+                    move |mut conn| {
+                        if with_table {
+                            Box::pin(async move {
+                                let query = CREATE_ENTITIES_STATEMENTS.join(";");
+                                conn.batch_execute(query.as_str()).await.unwrap();
+                                conn
+                            })
+                        } else {
+                            Box::pin(async { conn })
+                        }
+                    }
+                },
+            )
+            .await
+            .unwrap()
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_creates_database_with_restricted_privileges() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_creates_database_with_restricted_privileges(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_cleans_database_with_tables() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_cleans_database_with_tables(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn backend_drops_restricted_database() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_backend_drops_database(backend, true).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_drops_created_restricted_databases() {
+            let backend = create_backend(false).await;
+            test_pool_drops_created_restricted_databases(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_wrapper_gets_connection() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_pool_wrapper_gets_connection(backend).await;
+        }
+
+        #[test(flavor = "multi_thread", shared)]
+        async fn pool_wrapper_gets_owned_connection() {
+            let backend = create_backend(true).await.drop_previous_databases(false);
+            test_pool_wrapper_gets_owned_connection(backend).await;
+        }
+    }
 }
@@ -1,16 +1,28 @@
-use std::{borrow::Cow, collections::HashMap, pin::Pin};
+use std::{borrow::Cow, collections::HashMap, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use futures::Future;
 use parking_lot::Mutex;
 use sea_orm::{
+    sqlx::{error::DatabaseError, postgres::PgConnectOptions, PgConnection, Postgres},
     ActiveModelBehavior, ColumnTrait, ConnectOptions, ConnectionTrait, Database,
     DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait,
-    EnumIter, FromQueryResult, PrimaryKeyTrait, QueryFilter, QuerySelect,
+    EnumIter, FromQueryResult, PrimaryKeyTrait, QueryFilter, QuerySelect, RuntimeErr, SqlxError,
+    SqlxPostgresConnector, Statement,
 };
 use uuid::Uuid;
 
-use crate::{common::config::PrivilegedPostgresConfig, util::get_db_name};
+use crate::{
+    common::{
+        config::{CleanStrategy, PgDialect, PrivilegeProfile, PrivilegedPostgresConfig, Role},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    util::{
+        append_pg_connection_params, get_credentials, get_db_name, PreviousDatabaseQuery,
+        ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{
@@ -21,22 +33,70 @@ use super::{
         error::Error as BackendError,
         r#trait::Backend,
     },
-    r#trait::{PostgresBackend, PostgresBackendWrapper},
+    r#trait::{CustomClean, PostgresBackend, PostgresBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+type CreateEntities = dyn Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut PgConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), SqlxError>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`SeaORM Postgres`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.Postgres) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct SeaORMPostgresBackend {
     privileged_config: PrivilegedPostgresConfig,
     default_pool: DatabaseConnection,
+    table_names_cache: TableNamesCache,
     db_conns: Mutex<HashMap<Uuid, DatabaseConnection>>,
     create_restricted_pool: Box<dyn for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    clean_strategy: CleanStrategy,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    store_clean_connections_flag: bool,
+    pgbouncer_compatible_flag: bool,
+    restricted_endpoint: Option<(String, u16)>,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
+    custom_clean: Option<Box<CustomClean<DatabaseConnection, QueryError>>>,
 }
 
 impl SeaORMPostgresBackend {
@@ -87,19 +147,188 @@ impl SeaORMPostgresBackend {
             + Send
             + Sync
             + 'static,
+    ) -> Result<Self, DbErr> {
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+        .await
+    }
+
+    /// Creates a new [`SeaORM Postgres`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.Postgres) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{r#async::SeaORMPostgresBackend, PrivilegedPostgresConfig};
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    /// use sea_orm::ConnectionTrait;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = SeaORMPostgresBackend::new_with_context(
+    ///         config,
+    ///         |opts| {
+    ///             opts.max_connections(10);
+    ///         },
+    ///         |opts| {
+    ///             opts.max_connections(2);
+    ///         },
+    ///         move |conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 conn.execute_unprepared(query.as_str())
+    ///                 .await
+    ///                 .unwrap();
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_context(
+        privileged_config: PrivilegedPostgresConfig,
+        create_privileged_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions),
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
     ) -> Result<Self, DbErr> {
         let mut opts = ConnectOptions::new(privileged_config.default_connection_url());
         create_privileged_pool(&mut opts);
         let default_pool = Database::connect(opts).await?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            privileged_config,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`SeaORM Postgres`](https://docs.rs/sea-orm/1.0.1/sea_orm/type.DbBackend.html#variant.Postgres) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SeaORMPostgresBackend, PrivilegedPostgresConfig};
+    /// use dotenvy::dotenv;
+    /// use sea_orm::{ConnectOptions, ConnectionTrait, Database};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///     let mut opts = ConnectOptions::new(config.default_connection_url());
+    ///     opts.max_connections(10);
+    ///     let privileged_pool = Database::connect(opts).await.unwrap();
+    ///
+    ///     let backend = SeaORMPostgresBackend::new_with_privileged_pool(
+    ///         config,
+    ///         privileged_pool,
+    ///         |opts| {
+    ///             opts.max_connections(2);
+    ///         },
+    ///         move |conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute_unprepared(
+    ///                     "CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)",
+    ///                 )
+    ///                 .await
+    ///                 .unwrap();
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        privileged_pool: DatabaseConnection,
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        default_pool: DatabaseConnection,
+        create_restricted_pool: impl for<'tmp> Fn(&'tmp mut ConnectOptions) + Send + Sync + 'static,
+        create_entities: impl Fn(DatabaseConnection, &str) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
             privileged_config,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             db_conns: Mutex::new(HashMap::new()),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            clean_strategy: CleanStrategy::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            store_clean_connections_flag: true,
+            pgbouncer_compatible_flag: false,
+            restricted_endpoint: None,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+            custom_clean: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -110,6 +339,480 @@ impl SeaORMPostgresBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
+    }
+
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
+    }
+
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
+    }
+
+    /// How databases are cleaned once their reusable connection pools are returned
+    ///
+    /// Defaults to truncating tables. Ignored when cleaning with a custom routine.
+    #[must_use]
+    pub fn clean_strategy(self, value: CleanStrategy) -> Self {
+        Self {
+            clean_strategy: value,
+            ..self
+        }
+    }
+
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
+    }
+
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
+
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
+    }
+
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Keep a privileged connection open per restricted database for cleaning it
+    ///
+    /// Enabled by default. When disabled, a privileged connection is established on demand
+    /// to clean a database and closed afterwards, trading cleaning latency for fewer idle
+    /// connections to the server.
+    #[must_use]
+    pub fn store_clean_connections(self, value: bool) -> Self {
+        Self {
+            store_clean_connections_flag: value,
+            ..self
+        }
+    }
+
+    /// Make connections made as the restricted role compatible with ``PgBouncer`` in transaction pooling mode
+    ///
+    /// Server-side prepared statements are not cached, since consecutive transactions may run on different server connections.
+    /// Meant to be combined with [`restricted_endpoint`](Self::restricted_endpoint) pointing at ``PgBouncer``,
+    /// while the privileged configuration keep pointing at the server directly so that ``CREATE DATABASE`` and other DDL bypass it.
+    /// Session settings such as those applied by [`after_connect`](Self::after_connect) do not persist across transactions behind ``PgBouncer``.
+    #[must_use]
+    pub fn pgbouncer_compatible(self, value: bool) -> Self {
+        Self {
+            pgbouncer_compatible_flag: value,
+            ..self
+        }
+    }
+
+    /// Host and port that connections made as the restricted role go through instead of those of the privileged configuration,
+    /// such as those of a connection pooler
+    #[must_use]
+    pub fn restricted_endpoint(self, host: impl Into<String>, port: u16) -> Self {
+        Self {
+            restricted_endpoint: Some((host.into(), port)),
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    ///
+    /// Restricted pools are then connected through sqlx directly, since sea-orm exposes no such hook.
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut PgConnection,
+            )
+                -> Pin<Box<dyn Future<Output = Result<(), SqlxError>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
+
+    /// Routine cleaning databases in place of truncating all of their tables
+    ///
+    /// Runs on the stored privileged connection to the database, within a transaction if cleaning in transactions.
+    /// Meant for schemas needing bespoke cleaning, such as disabling triggers or deleting rows in foreign key order.
+    #[must_use]
+    pub fn custom_clean(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut DatabaseConnection,
+            ) -> Pin<Box<dyn Future<Output = Result<(), DbErr>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            custom_clean: Some(Box::new(move |conn| {
+                let clean = value(conn);
+                Box::pin(async move { clean.await.map_err(Into::into) })
+            })),
+            ..self
+        }
+    }
+
+    fn get_restricted_config(&self) -> Cow<'_, PrivilegedPostgresConfig> {
+        // Connect through a separate endpoint if needed
+        match &self.restricted_endpoint {
+            Some((host, port)) => Cow::Owned(
+                self.privileged_config
+                    .clone()
+                    .host(host.clone())
+                    .port(*port),
+            ),
+            None => Cow::Borrowed(&self.privileged_config),
+        }
+    }
+
+    fn get_restricted_database_url(&self, db_name: &str) -> String {
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let mut database_url = append_pg_connection_params(
+            self.get_restricted_config()
+                .restricted_database_connection_url(username, Some(password), db_name),
+            &self.restricted_connection_params,
+        );
+
+        // Stop caching prepared statements, which may not exist on the server connection assigned to a later transaction
+        if self.pgbouncer_compatible_flag {
+            database_url.push(if database_url.contains('?') { '&' } else { '?' });
+            database_url.push_str("statement-cache-capacity=0");
+        }
+
+        database_url
+    }
 }
 
 #[async_trait]
@@ -145,54 +848,142 @@ impl<'pool> PostgresBackend<'pool> for SeaORMPostgresBackend {
         Ok(self.default_pool.clone().into())
     }
 
+    async fn establish_privileged_connection(&self) -> Result<DatabaseConnection, ConnectionError> {
+        let mut opts = ConnectOptions::new(self.privileged_config.default_connection_url());
+        opts.max_connections(1);
+        Database::connect(opts).await.map_err(Into::into)
+    }
+
     async fn establish_privileged_database_connection(
         &self,
         db_id: Uuid,
     ) -> Result<DatabaseConnection, ConnectionError> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let database_url = self
             .privileged_config
             .privileged_database_connection_url(db_name.as_str());
-        let opts = ConnectOptions::new(database_url);
+        let mut opts = ConnectOptions::new(database_url);
+        opts.max_connections(1);
+        Database::connect(opts).await.map_err(Into::into)
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<DatabaseConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let database_url = self.get_restricted_database_url(db_name.as_str());
+        let mut opts = ConnectOptions::new(database_url);
+        opts.max_connections(1);
         Database::connect(opts).await.map_err(Into::into)
     }
 
-    async fn establish_restricted_database_connection(
+    fn put_database_connection(&self, db_id: Uuid, conn: DatabaseConnection) {
+        self.db_conns.lock().insert(db_id, conn);
+    }
+
+    fn get_database_connection(&self, db_id: Uuid) -> Option<DatabaseConnection> {
+        self.db_conns.lock().remove(&db_id)
+    }
+
+    async fn get_previous_database_names(
+        &self,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(Clone, Debug, DeriveEntityModel)]
+        #[sea_orm(table_name = "pg_database")]
+        pub struct Model {
+            #[sea_orm(primary_key)]
+            oid: i32,
+            datname: String,
+        }
+
+        #[derive(Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            datname: String,
+        }
+
+        Entity::find()
+            .select_only()
+            .column(Column::Datname)
+            .filter(Column::Datname.like("db_pool_%"))
+            .into_model::<QueryModel>()
+            .all(conn)
+            .await
+            .map(|mut models| models.drain(..).map(|model| model.datname).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            datname: String,
+        }
+
+        QueryModel::find_by_statement(Statement::from_string(conn.get_database_backend(), query))
+            .all(conn)
+            .await
+            .map(|models| models.into_iter().map(|model| model.datname).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_owned_database_names(
         &self,
-        db_id: Uuid,
-    ) -> Result<DatabaseConnection, ConnectionError> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
-        );
-        let opts = ConnectOptions::new(database_url);
-        Database::connect(opts).await.map_err(Into::into)
-    }
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            datname: String,
+        }
 
-    fn put_database_connection(&self, db_id: Uuid, conn: DatabaseConnection) {
-        self.db_conns.lock().insert(db_id, conn);
+        QueryModel::find_by_statement(Statement::from_string(
+            conn.get_database_backend(),
+            postgres::GET_OWNED_DATABASE_NAMES,
+        ))
+        .all(conn)
+        .await
+        .map(|models| models.into_iter().map(|model| model.datname).collect())
+        .map_err(Into::into)
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> DatabaseConnection {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    async fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            datname: String,
+        }
+
+        QueryModel::find_by_statement(Statement::from_string(
+            conn.get_database_backend(),
+            postgres::get_database_names_with_schema_version(schema_version),
+        ))
+        .all(conn)
+        .await
+        .map(|models| models.into_iter().map(|model| model.datname).collect())
+        .map_err(Into::into)
     }
 
-    async fn get_previous_database_names(
+    async fn get_previous_role_names(
         &self,
         conn: &mut DatabaseConnection,
     ) -> Result<Vec<String>, QueryError> {
         #[derive(Clone, Debug, DeriveEntityModel)]
-        #[sea_orm(table_name = "pg_database")]
+        #[sea_orm(table_name = "pg_roles")]
         pub struct Model {
             #[sea_orm(primary_key)]
-            oid: i32,
-            datname: String,
+            rolname: String,
         }
 
         #[derive(Debug, EnumIter, DeriveRelation)]
@@ -200,37 +991,56 @@ impl<'pool> PostgresBackend<'pool> for SeaORMPostgresBackend {
 
         impl ActiveModelBehavior for ActiveModel {}
 
-        #[derive(FromQueryResult)]
-        struct QueryModel {
-            datname: String,
-        }
-
         Entity::find()
-            .select_only()
-            .column(Column::Datname)
-            .filter(Column::Datname.like("db_pool_%"))
-            .into_model::<QueryModel>()
+            .filter(Column::Rolname.like("db_pool_%"))
             .all(conn)
             .await
-            .map(|mut models| models.drain(..).map(|model| model.datname).collect())
+            .map(|mut models| models.drain(..).map(|model| model.rolname).collect())
             .map_err(Into::into)
     }
 
-    async fn create_entities(&self, conn: DatabaseConnection) -> DatabaseConnection {
-        (self.create_entities)(conn.clone()).await;
+    async fn create_entities(&self, conn: DatabaseConnection, db_name: &str) -> DatabaseConnection {
+        (self.create_entities)(conn.clone(), db_name).await;
         conn
     }
 
+    async fn warm_up_connection_pool(&self, pool: &DatabaseConnection) -> Result<(), PoolError> {
+        pool.ping().await.map_err(Into::into)
+    }
+
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<DatabaseConnection, BuildError> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
-        );
+        let db_name = self.get_db_name(db_id);
+        let database_url = self.get_restricted_database_url(db_name.as_str());
         let mut opts = ConnectOptions::new(database_url);
         (self.create_restricted_pool)(&mut opts);
+
+        // Connect lazily in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            opts.connect_lazy(true);
+        }
+
+        // Connect through sqlx directly since sea-orm exposes no hook for running statements on new connections
+        if let Some(after_connect) = self.after_connect.clone() {
+            let connect_opts = opts
+                .get_url()
+                .parse::<PgConnectOptions>()
+                .map_err(|err| DbErr::Conn(RuntimeErr::SqlxError(err)))?;
+            let connect_lazy = opts.get_connect_lazy();
+            let pool_opts = opts
+                .sqlx_pool_options::<Postgres>()
+                .after_connect(move |conn, _| after_connect(conn));
+            let pool = if connect_lazy {
+                pool_opts.connect_lazy_with(connect_opts)
+            } else {
+                pool_opts
+                    .connect_with(connect_opts)
+                    .await
+                    .map_err(|err| DbErr::Conn(RuntimeErr::SqlxError(err)))?
+            };
+            return Ok(SqlxPostgresConnector::from_sqlx_postgres_pool(pool));
+        }
+
         Database::connect(opts).await.map_err(Into::into)
     }
 
@@ -253,23 +1063,224 @@ impl<'pool> PostgresBackend<'pool> for SeaORMPostgresBackend {
 
         #[derive(FromQueryResult)]
         struct QueryModel {
+            schemaname: String,
             tablename: String,
         }
 
         Entity::find()
             .select_only()
+            .column(Column::Schemaname)
             .column(Column::Tablename)
-            .filter(Column::Schemaname.is_not_in(["pg_catalog", "information_schema"]))
+            .filter(Column::Schemaname.is_not_in(self.dialect.system_schemas().iter().copied()))
             .into_model::<QueryModel>()
             .all(conn)
             .await
-            .map(|mut models| models.drain(..).map(|model| model.tablename).collect())
+            .map(|mut models| {
+                models
+                    .drain(..)
+                    .map(|model| postgres::qualify_table_name(&model.schemaname, &model.tablename))
+                    .collect()
+            })
             .map_err(Into::into)
     }
 
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        conn: &mut DatabaseConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            table_name: String,
+        }
+
+        QueryModel::find_by_statement(Statement::from_string(
+            conn.get_database_backend(),
+            postgres::get_nonempty_table_names(table_names),
+        ))
+        .all(conn)
+        .await
+        .map(|models| models.into_iter().map(|model| model.table_name).collect())
+        .map_err(Into::into)
+    }
+
+    fn is_duplicate_database_error(&self, err: &QueryError) -> bool {
+        match &**err {
+            DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) => {
+                err.as_database_error()
+                    .and_then(DatabaseError::code)
+                    .as_deref()
+                    == Some(postgres::DUPLICATE_DATABASE_CODE)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_duplicate_role_error(&self, err: &QueryError) -> bool {
+        match &**err {
+            DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) => {
+                err.as_database_error()
+                    .and_then(DatabaseError::code)
+                    .as_deref()
+                    == Some(postgres::DUPLICATE_ROLE_CODE)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        match &**err {
+            DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) => {
+                err.as_database_error()
+                    .and_then(DatabaseError::code)
+                    .as_deref()
+                    == Some(postgres::DEADLOCK_DETECTED_CODE)
+            }
+            _ => false,
+        }
+    }
+
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_clean_strategy(&self) -> CleanStrategy {
+        self.clean_strategy
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
+
+    fn get_custom_clean(&self) -> Option<&CustomClean<DatabaseConnection, QueryError>> {
+        self.custom_clean.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
+    }
+
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
+    }
+
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
+    }
+
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
+    }
+
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
+    }
+
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
+    }
+
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
+    }
+
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
+    }
+
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
+    }
+
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
+    }
+
+    fn get_store_clean_connections(&self) -> bool {
+        self.store_clean_connections_flag
+    }
 }
 
 type BError = BackendError<BuildError, PoolError, ConnectionError, QueryError>;
@@ -287,13 +1298,29 @@ impl Backend for SeaORMPostgresBackend {
         PostgresBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).ping().await
+    }
+
     async fn create(
         &self,
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<DatabaseConnection, BError> {
         PostgresBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(&self, db_id: uuid::Uuid) -> Result<DatabaseConnection, BError> {
+        PostgresBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(&self, db_id: uuid::Uuid) -> Result<DatabaseConnection, BError> {
+        PostgresBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -301,11 +1328,53 @@ impl Backend for SeaORMPostgresBackend {
         PostgresBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(&self, db_id: uuid::Uuid, label: &str) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(&self, db_id: uuid::Uuid, is_restricted: bool) -> Result<(), BError> {
         PostgresBackendWrapper::new(self)
             .drop(db_id, is_restricted)
             .await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id, is_restricted)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        Some(
+            self.get_restricted_config()
+                .restricted_database_connection_url(username, Some(password), db_name),
+        )
+    }
+
+    fn privileged_pool(&self) -> &DatabaseConnection {
+        &self.default_pool
+    }
 }
 
 #[cfg(test)]
@@ -315,22 +1384,24 @@ mod tests {
     use dotenvy::dotenv;
     use futures::future::join_all;
     use sea_orm::{
-        ActiveModelBehavior, ActiveModelTrait, ConnectionTrait, DeriveEntityModel,
-        DerivePrimaryKey, DeriveRelation, EntityTrait, EnumIter, FromQueryResult, PaginatorTrait,
-        PrimaryKeyTrait, QuerySelect, Set,
+        sqlx::Executor, ActiveModelBehavior, ActiveModelTrait, ConnectionTrait, Database,
+        DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, EnumIter,
+        FromQueryResult, PaginatorTrait, PrimaryKeyTrait, QuerySelect, Set, Statement,
     };
     use tokio_shared_rt::test;
 
     use crate::{
         common::{
-            config::PrivilegedPostgresConfig,
+            config::{PgDialect, PrivilegedPostgresConfig, Role},
             statement::postgres::tests::{
                 CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
             },
         },
         r#async::{
             backend::postgres::r#trait::tests::{
-                test_backend_drops_database, test_pool_drops_created_unrestricted_database,
+                test_backend_drops_database, test_backend_fails_to_clean_unrestricted_database,
+                test_backend_force_drops_database_with_lingering_connection,
+                test_pool_drops_created_unrestricted_database,
             },
             db_pool::DatabasePoolBuilder,
         },
@@ -338,11 +1409,16 @@ mod tests {
 
     use super::{
         super::r#trait::tests::{
-            test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
+            test_backend_cleans_database_with_tables,
+            test_backend_cleans_database_without_stored_connection,
+            test_backend_cleans_database_without_tables,
+            test_backend_creates_database_owned_by_role,
             test_backend_creates_database_with_restricted_privileges,
             test_backend_creates_database_with_unrestricted_privileges,
-            test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
-            test_pool_drops_previous_databases, PgDropLock,
+            test_backend_creates_entities_as_restricted_role,
+            test_backend_drops_previous_databases, test_backend_recreates_database_on_conflict,
+            test_backend_recreates_role_on_conflict, test_pool_drops_created_restricted_databases,
+            test_pool_drops_previous_databases, test_pool_drops_prewarmed_databases, PgDropLock,
         },
         SeaORMPostgresBackend,
     };
@@ -404,12 +1480,77 @@ mod tests {
         test_backend_creates_database_with_unrestricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_database_on_conflict() {
+        test_backend_recreates_database_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_entities_as_restricted_role() {
+        test_backend_creates_entities_as_restricted_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .create_entities_as(Role::Restricted),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_owned_by_role() {
+        test_backend_creates_database_owned_by_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .owner_at_creation(true)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_role_on_conflict() {
+        test_backend_recreates_role_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_with_tables() {
         let backend = create_backend(true).await.drop_previous_databases(false);
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_stored_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .store_clean_connections(false);
+        test_backend_cleans_database_without_stored_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).await.drop_previous_databases(false);
@@ -428,6 +1569,21 @@ mod tests {
         test_backend_drops_database(backend, false).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_previous_databases() {
         test_pool_drops_previous_databases(
@@ -582,9 +1738,112 @@ mod tests {
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_database() {
         let backend = create_backend(false).await;
         test_pool_drops_created_unrestricted_database(backend).await;
     }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_after_connect_hook() {
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            statement_timeout: String,
+        }
+
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .after_connect(|conn| {
+                Box::pin(async move {
+                    conn.execute("SET statement_timeout = 1234").await?;
+                    Ok(())
+                })
+            });
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // restricted connections must carry session settings applied by hook
+            let query_model = QueryModel::find_by_statement(Statement::from_string(
+                conn_pool.get_database_backend(),
+                "SHOW statement_timeout",
+            ))
+            .one(&**conn_pool)
+            .await
+            .unwrap()
+            .unwrap();
+            assert_eq!(query_model.statement_timeout, "1234ms");
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_uses_provided_privileged_connection() {
+        const APPLICATION_NAME: &str = "shared_privileged_connection";
+
+        #[derive(FromQueryResult)]
+        struct QueryModel {
+            application_name: String,
+        }
+
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+        let privileged_conn = Database::connect(
+            config
+                .clone()
+                .application_name(APPLICATION_NAME)
+                .default_connection_url(),
+        )
+        .await
+        .unwrap();
+
+        let backend = SeaORMPostgresBackend::new_with_privileged_pool(
+            config,
+            privileged_conn,
+            |_| {},
+            |conn| {
+                Box::pin(async move {
+                    conn.execute_unprepared(CREATE_ENTITIES_STATEMENTS.join(";").as_str())
+                        .await
+                        .unwrap();
+                })
+            },
+        )
+        .drop_previous_databases(false);
+
+        // backend must use provided privileged connection
+        let query_model = QueryModel::find_by_statement(Statement::from_string(
+            backend.default_pool.get_database_backend(),
+            "SHOW application_name",
+        ))
+        .one(&backend.default_pool)
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(query_model.application_name, APPLICATION_NAME);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // created database must be usable
+            let book = ActiveModel {
+                title: Set("Title".to_owned()),
+                ..Default::default()
+            };
+            assert!(book.insert(&**conn_pool).await.is_ok());
+        }
+        .lock_read()
+        .await;
+    }
 }
@@ -1,16 +1,31 @@
-use std::{borrow::Cow, collections::HashMap, pin::Pin};
+use std::{borrow::Cow, collections::HashMap, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use futures::Future;
 use parking_lot::Mutex;
 use sqlx::{
+    error::DatabaseError,
     pool::PoolConnection,
     postgres::{PgConnectOptions, PgPoolOptions},
     Connection, Executor, PgConnection, PgPool, Postgres, Row,
 };
 use uuid::Uuid;
 
-use crate::{common::statement::postgres, util::get_db_name};
+use crate::{
+    common::{
+        config::{CleanStrategy, PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    r#async::{
+        schema_provider::{create_entities_with, SchemaProvider},
+        PoolWrapper,
+    },
+    util::{
+        get_credentials, get_db_name, get_pg_application_name, get_pg_runtime_params, url_host,
+        url_userinfo, PreviousDatabaseQuery, ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{
@@ -18,26 +33,77 @@ use super::{
         error::Error as BackendError,
         r#trait::Backend,
     },
-    r#trait::{PostgresBackend, PostgresBackendWrapper},
+    r#trait::{CustomClean, PostgresBackend, PostgresBackendWrapper},
 };
 
-type CreateEntities = dyn Fn(PgConnection) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
+type CreateEntities = dyn Fn(PgConnection, &str) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type AfterConnect = dyn for<'c> Fn(
+        &'c mut PgConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>
     + Send
     + Sync
     + 'static;
 
 /// [`sqlx Postgres`](https://docs.rs/sqlx/0.8.2/sqlx/struct.Postgres.html) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct SqlxPostgresBackend {
     privileged_opts: PgConnectOptions,
     default_pool: PgPool,
+    table_names_cache: TableNamesCache,
     db_conns: Mutex<HashMap<Uuid, PgConnection>>,
-    create_restricted_pool: Box<dyn Fn() -> PgPoolOptions + Send + Sync + 'static>,
+    create_restricted_pool: Box<dyn Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    clean_strategy: CleanStrategy,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    store_clean_connections_flag: bool,
+    pgbouncer_compatible_flag: bool,
+    restricted_endpoint: Option<(String, u16)>,
+    after_connect: Option<Arc<AfterConnect>>,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
+    custom_clean: Option<Box<CustomClean<PgConnection, QueryError>>>,
 }
 
 impl SqlxPostgresBackend {
     /// Creates a new [`sqlx Postgres`](https://docs.rs/sqlx/0.8.2/sqlx/struct.Postgres.html) backend
+    ///
+    /// Restricted pool options are created per database from its restricted connection options,
+    /// allowing settings such as connection lifetimes to be tuned per database
     /// # Example
     /// ```
     /// use db_pool::{r#async::SqlxPostgresBackend, PrivilegedPostgresConfig};
@@ -52,7 +118,7 @@ impl SqlxPostgresBackend {
     ///     let backend = SqlxPostgresBackend::new(
     ///         config.into(),
     ///         || PgPoolOptions::new().max_connections(10),
-    ///         || PgPoolOptions::new().max_connections(2),
+    ///         |_| PgPoolOptions::new().max_connections(2),
     ///         move |mut conn| {
     ///             Box::pin(async move {
     ///                 conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
@@ -69,22 +135,237 @@ impl SqlxPostgresBackend {
     pub fn new(
         privileged_options: PgConnectOptions,
         create_privileged_pool: impl Fn() -> PgPoolOptions,
-        create_restricted_pool: impl Fn() -> PgPoolOptions + Send + Sync + 'static,
+        create_restricted_pool: impl Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static,
         create_entities: impl Fn(PgConnection) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
             + Send
             + Sync
             + 'static,
+    ) -> Self {
+        Self::new_with_context(
+            privileged_options,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`sqlx Postgres`](https://docs.rs/sqlx/0.8.2/sqlx/struct.Postgres.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SqlxPostgresBackend, PrivilegedPostgresConfig};
+    /// use dotenvy::dotenv;
+    /// use sqlx::{postgres::PgPoolOptions, Executor};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = SqlxPostgresBackend::new_with_context(
+    ///         config.into(),
+    ///         || PgPoolOptions::new().max_connections(10),
+    ///         |_| PgPoolOptions::new().max_connections(2),
+    ///         move |mut conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 conn.execute(query.as_str())
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new_with_context(
+        privileged_options: PgConnectOptions,
+        create_privileged_pool: impl Fn() -> PgPoolOptions,
+        create_restricted_pool: impl Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static,
+        create_entities: impl Fn(PgConnection, &str) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
     ) -> Self {
         let pool_opts = create_privileged_pool();
         let default_pool = pool_opts.connect_lazy_with(privileged_options.clone());
 
+        Self::from_privileged_pool(
+            privileged_options,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        )
+    }
+
+    /// Creates a new [`sqlx Postgres`](https://docs.rs/sqlx/0.8.2/sqlx/struct.Postgres.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{r#async::SqlxPostgresBackend, PrivilegedPostgresConfig};
+    /// use dotenvy::dotenv;
+    /// use sqlx::{
+    ///     postgres::{PgConnectOptions, PgPoolOptions},
+    ///     Executor,
+    /// };
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let opts: PgConnectOptions = PrivilegedPostgresConfig::from_env().unwrap().into();
+    ///     let privileged_pool = PgPoolOptions::new()
+    ///         .max_connections(10)
+    ///         .connect_lazy_with(opts.clone());
+    ///
+    ///     let backend = SqlxPostgresBackend::new_with_privileged_pool(
+    ///         opts,
+    ///         privileged_pool,
+    ///         |_| PgPoolOptions::new().max_connections(2),
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_options: PgConnectOptions,
+        privileged_pool: PgPool,
+        create_restricted_pool: impl Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static,
+        create_entities: impl Fn(PgConnection) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_options,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`sqlx Postgres`](https://docs.rs/sqlx/0.8.2/sqlx/struct.Postgres.html) backend whose entities are created by a schema provider
+    ///
+    /// The same provider can apply the schema to the application database at startup.
+    /// # Example
+    /// ```
+    /// use async_trait::async_trait;
+    /// use db_pool::{
+    ///     r#async::{SchemaProvider, SqlxPostgresBackend},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use dotenvy::dotenv;
+    /// use sqlx::{postgres::PgPoolOptions, Executor, PgConnection};
+    ///
+    /// struct Migrations;
+    ///
+    /// #[async_trait]
+    /// impl SchemaProvider<PgConnection> for Migrations {
+    ///     type Error = sqlx::Error;
+    ///
+    ///     async fn apply(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    ///         conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .await?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = SqlxPostgresBackend::new_with_schema_provider(
+    ///         config.into(),
+    ///         || PgPoolOptions::new().max_connections(10),
+    ///         |_| PgPoolOptions::new().max_connections(2),
+    ///         Migrations,
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub fn new_with_schema_provider(
+        privileged_options: PgConnectOptions,
+        create_privileged_pool: impl Fn() -> PgPoolOptions,
+        create_restricted_pool: impl Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static,
+        schema_provider: impl SchemaProvider<PgConnection>,
+    ) -> Self {
+        Self::new(
+            privileged_options,
+            create_privileged_pool,
+            create_restricted_pool,
+            create_entities_with(schema_provider),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_options: PgConnectOptions,
+        default_pool: PgPool,
+        create_restricted_pool: impl Fn(&PgConnectOptions) -> PgPoolOptions + Send + Sync + 'static,
+        create_entities: impl Fn(PgConnection, &str) -> Pin<Box<dyn Future<Output = PgConnection> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
         Self {
             privileged_opts: privileged_options,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             db_conns: Mutex::new(HashMap::new()),
             create_restricted_pool: Box::new(create_restricted_pool),
             create_entities: Box::new(create_entities),
             drop_previous_databases_flag: true,
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            clean_strategy: CleanStrategy::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            store_clean_connections_flag: true,
+            pgbouncer_compatible_flag: false,
+            restricted_endpoint: None,
+            after_connect: None,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+            custom_clean: None,
         }
     }
 
@@ -96,112 +377,818 @@ impl SqlxPostgresBackend {
             ..self
         }
     }
-}
 
-#[async_trait]
-impl<'pool> PostgresBackend<'pool> for SqlxPostgresBackend {
-    type Connection = PgConnection;
-    type PooledConnection = PoolConnection<Postgres>;
-    type Pool = PgPool;
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
 
-    type BuildError = BuildError;
-    type PoolError = PoolError;
-    type ConnectionError = ConnectionError;
-    type QueryError = QueryError;
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
+    }
 
-    async fn execute_query(&self, query: &str, conn: &mut PgConnection) -> Result<(), QueryError> {
-        conn.execute(query).await?;
-        Ok(())
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
+    }
+
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
+    }
+
+    /// How databases are cleaned once their reusable connection pools are returned
+    ///
+    /// Defaults to truncating tables. Ignored when cleaning with a custom routine.
+    #[must_use]
+    pub fn clean_strategy(self, value: CleanStrategy) -> Self {
+        Self {
+            clean_strategy: value,
+            ..self
+        }
+    }
+
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
+    }
+
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
+
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
+    }
+
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Keep a privileged connection open per restricted database for cleaning it
+    ///
+    /// Enabled by default. When disabled, a privileged connection is established on demand
+    /// to clean a database and closed afterwards, trading cleaning latency for fewer idle
+    /// connections to the server.
+    #[must_use]
+    pub fn store_clean_connections(self, value: bool) -> Self {
+        Self {
+            store_clean_connections_flag: value,
+            ..self
+        }
+    }
+
+    /// Make connections made as the restricted role compatible with ``PgBouncer`` in transaction pooling mode
+    ///
+    /// Server-side prepared statements are not cached, since consecutive transactions may run on different server connections.
+    /// Meant to be combined with [`restricted_endpoint`](Self::restricted_endpoint) pointing at ``PgBouncer``,
+    /// while the privileged options keep pointing at the server directly so that ``CREATE DATABASE`` and other DDL bypass it.
+    /// Session settings such as those applied by [`after_connect`](Self::after_connect) do not persist across transactions behind ``PgBouncer``.
+    #[must_use]
+    pub fn pgbouncer_compatible(self, value: bool) -> Self {
+        Self {
+            pgbouncer_compatible_flag: value,
+            ..self
+        }
+    }
+
+    /// Host and port that connections made as the restricted role go through instead of those of the privileged options,
+    /// such as those of a connection pooler
+    #[must_use]
+    pub fn restricted_endpoint(self, host: impl Into<String>, port: u16) -> Self {
+        Self {
+            restricted_endpoint: Some((host.into(), port)),
+            ..self
+        }
+    }
+
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    ///
+    /// Replaces any `after_connect` callback set on the restricted pool options.
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut PgConnection,
+            )
+                -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after_connect: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
+
+    /// Routine cleaning databases in place of truncating all of their tables
+    ///
+    /// Runs on the stored privileged connection to the database, within a transaction if cleaning in transactions.
+    /// Meant for schemas needing bespoke cleaning, such as disabling triggers or deleting rows in foreign key order.
+    #[must_use]
+    pub fn custom_clean(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut PgConnection,
+            )
+                -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            custom_clean: Some(Box::new(move |conn| {
+                let clean = value(conn);
+                Box::pin(async move { clean.await.map_err(Into::into) })
+            })),
+            ..self
+        }
+    }
+
+    fn get_restricted_options(&self, db_name: &str) -> PgConnectOptions {
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let mut opts = self
+            .privileged_opts
+            .clone()
+            .username(username)
+            .password(password)
+            .database(db_name);
+
+        // Connect through a separate endpoint if needed
+        if let Some((host, port)) = &self.restricted_endpoint {
+            opts = opts.host(host).port(*port);
+        }
+
+        // Stop caching prepared statements, which may not exist on the server connection assigned to a later transaction
+        if self.pgbouncer_compatible_flag {
+            opts = opts.statement_cache_capacity(0);
+        }
+
+        self.apply_restricted_connection_params(opts)
+    }
+
+    fn apply_restricted_connection_params(&self, opts: PgConnectOptions) -> PgConnectOptions {
+        let opts = match get_pg_application_name(&self.restricted_connection_params) {
+            Some(application_name) => opts.application_name(application_name),
+            None => opts,
+        };
+        let runtime_params = get_pg_runtime_params(&self.restricted_connection_params);
+        if runtime_params.is_empty() {
+            opts
+        } else {
+            opts.options(runtime_params)
+        }
+    }
+}
+
+#[async_trait]
+impl<'pool> PostgresBackend<'pool> for SqlxPostgresBackend {
+    type Connection = PgConnection;
+    type PooledConnection = PoolConnection<Postgres>;
+    type Pool = PgPool;
+
+    type BuildError = BuildError;
+    type PoolError = PoolError;
+    type ConnectionError = ConnectionError;
+    type QueryError = QueryError;
+
+    async fn execute_query(&self, query: &str, conn: &mut PgConnection) -> Result<(), QueryError> {
+        conn.execute(query).await?;
+        Ok(())
+    }
+
+    async fn batch_execute_query<'a>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut PgConnection,
+    ) -> Result<(), QueryError> {
+        let query = query.into_iter().collect::<Vec<_>>().join(";");
+        self.execute_query(query.as_str(), conn).await
+    }
+
+    async fn get_default_connection(&'pool self) -> Result<PoolConnection<Postgres>, PoolError> {
+        self.default_pool.acquire().await.map_err(Into::into)
+    }
+
+    async fn establish_privileged_connection(&self) -> Result<PgConnection, ConnectionError> {
+        PgConnection::connect_with(&self.privileged_opts)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn establish_privileged_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<PgConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let opts = self.privileged_opts.clone().database(db_name.as_str());
+        PgConnection::connect_with(&opts).await.map_err(Into::into)
+    }
+
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<PgConnection, ConnectionError> {
+        let db_name = self.get_db_name(db_id);
+        let opts = self.get_restricted_options(db_name.as_str());
+        PgConnection::connect_with(&opts).await.map_err(Into::into)
+    }
+
+    fn put_database_connection(&self, db_id: Uuid, conn: PgConnection) {
+        self.db_conns.lock().insert(db_id, conn);
+    }
+
+    fn get_database_connection(&self, db_id: Uuid) -> Option<PgConnection> {
+        self.db_conns.lock().remove(&db_id)
+    }
+
+    async fn get_previous_database_names(
+        &self,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::GET_DATABASE_NAMES)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(query)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_owned_database_names(
+        &self,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::GET_OWNED_DATABASE_NAMES)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::get_database_names_with_schema_version(schema_version).as_str())
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_role_names(
+        &self,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::GET_ROLE_NAMES)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn create_entities(&self, conn: PgConnection, db_name: &str) -> PgConnection {
+        (self.create_entities)(conn, db_name).await
+    }
+
+    async fn warm_up_connection_pool(&self, pool: &PgPool) -> Result<(), PoolError> {
+        pool.acquire().await.map(drop).map_err(Into::into)
+    }
+
+    async fn create_connection_pool(&self, db_id: Uuid) -> Result<PgPool, BuildError> {
+        let db_name = self.get_db_name(db_id);
+        let opts = self.get_restricted_options(db_name.as_str());
+        let mut pool_opts = (self.create_restricted_pool)(&opts);
+        if let Some(after_connect) = self.after_connect.clone() {
+            pool_opts = pool_opts.after_connect(move |conn, _| after_connect(conn));
+        }
+        let pool = pool_opts.connect_lazy_with(opts);
+        Ok(pool)
+    }
+
+    async fn get_table_names(&self, conn: &mut PgConnection) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::get_table_names(self.dialect.system_schemas()).as_str())
+            .await?
+            .iter()
+            .map(|row| {
+                Ok(postgres::qualify_table_name(
+                    row.try_get(0)?,
+                    row.try_get(1)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(Into::into)
+    }
+
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        conn: &mut PgConnection,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.fetch_all(postgres::get_nonempty_table_names(table_names).as_str())
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    fn is_duplicate_database_error(&self, err: &QueryError) -> bool {
+        err.as_database_error()
+            .and_then(DatabaseError::code)
+            .as_deref()
+            == Some(postgres::DUPLICATE_DATABASE_CODE)
+    }
+
+    fn is_duplicate_role_error(&self, err: &QueryError) -> bool {
+        err.as_database_error()
+            .and_then(DatabaseError::code)
+            .as_deref()
+            == Some(postgres::DUPLICATE_ROLE_CODE)
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        err.as_database_error()
+            .and_then(DatabaseError::code)
+            .as_deref()
+            == Some(postgres::DEADLOCK_DETECTED_CODE)
+    }
+
+    fn get_drop_previous_databases(&self) -> bool {
+        self.drop_previous_databases_flag
+    }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_clean_strategy(&self) -> CleanStrategy {
+        self.clean_strategy
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
+
+    fn get_custom_clean(&self) -> Option<&CustomClean<PgConnection, QueryError>> {
+        self.custom_clean.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
     }
 
-    async fn batch_execute_query<'a>(
-        &self,
-        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
-        conn: &mut PgConnection,
-    ) -> Result<(), QueryError> {
-        let query = query.into_iter().collect::<Vec<_>>().join(";");
-        self.execute_query(query.as_str(), conn).await
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
     }
 
-    async fn get_default_connection(&'pool self) -> Result<PoolConnection<Postgres>, PoolError> {
-        self.default_pool.acquire().await.map_err(Into::into)
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
     }
 
-    async fn establish_privileged_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<PgConnection, ConnectionError> {
-        let db_name = get_db_name(db_id);
-        let opts = self.privileged_opts.clone().database(db_name.as_str());
-        PgConnection::connect_with(&opts).await.map_err(Into::into)
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
     }
 
-    async fn establish_restricted_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<PgConnection, ConnectionError> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let opts = self
-            .privileged_opts
-            .clone()
-            .username(db_name)
-            .password(db_name)
-            .database(db_name);
-        PgConnection::connect_with(&opts).await.map_err(Into::into)
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
     }
 
-    fn put_database_connection(&self, db_id: Uuid, conn: PgConnection) {
-        self.db_conns.lock().insert(db_id, conn);
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> PgConnection {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
     }
 
-    async fn get_previous_database_names(
-        &self,
-        conn: &mut PgConnection,
-    ) -> Result<Vec<String>, QueryError> {
-        conn.fetch_all(postgres::GET_DATABASE_NAMES)
-            .await?
-            .iter()
-            .map(|row| row.try_get(0))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
     }
 
-    async fn create_entities(&self, conn: PgConnection) -> PgConnection {
-        (self.create_entities)(conn).await
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
     }
 
-    async fn create_connection_pool(&self, db_id: Uuid) -> Result<PgPool, BuildError> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let opts = self
-            .privileged_opts
-            .clone()
-            .database(db_name)
-            .username(db_name)
-            .password(db_name);
-        let pool = (self.create_restricted_pool)().connect_lazy_with(opts);
-        Ok(pool)
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
     }
 
-    async fn get_table_names(&self, conn: &mut PgConnection) -> Result<Vec<String>, QueryError> {
-        conn.fetch_all(postgres::GET_TABLE_NAMES)
-            .await?
-            .iter()
-            .map(|row| row.try_get(0))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
     }
 
-    fn get_drop_previous_databases(&self) -> bool {
-        self.drop_previous_databases_flag
+    fn get_store_clean_connections(&self) -> bool {
+        self.store_clean_connections_flag
     }
 }
 
@@ -220,9 +1207,25 @@ impl Backend for SqlxPostgresBackend {
         PostgresBackendWrapper::new(self).init().await
     }
 
+    async fn ping(&self) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).ping().await
+    }
+
     async fn create(&self, db_id: uuid::Uuid, restrict_privileges: bool) -> Result<PgPool, BError> {
         PostgresBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(&self, db_id: uuid::Uuid) -> Result<PgPool, BError> {
+        PostgresBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(&self, db_id: uuid::Uuid) -> Result<PgPool, BError> {
+        PostgresBackendWrapper::new(self)
+            .create_reader_pool(db_id)
             .await
     }
 
@@ -230,43 +1233,121 @@ impl Backend for SqlxPostgresBackend {
         PostgresBackendWrapper::new(self).clean(db_id).await
     }
 
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(&self, db_id: uuid::Uuid) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(&self, db_id: uuid::Uuid, label: &str) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self).label(db_id, label).await
+    }
+
     async fn drop(&self, db_id: uuid::Uuid, is_restricted: bool) -> Result<(), BError> {
         PostgresBackendWrapper::new(self)
             .drop(db_id, is_restricted)
             .await
     }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BError> {
+        PostgresBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id, is_restricted)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name.as_str());
+        let (host, port) = self.restricted_endpoint.as_ref().map_or_else(
+            || {
+                (
+                    self.privileged_opts.get_host(),
+                    self.privileged_opts.get_port(),
+                )
+            },
+            |(host, port)| (host.as_str(), *port),
+        );
+        let userinfo = url_userinfo(username, Some(password));
+        let host = url_host(host);
+        Some(format!("postgres://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &PgPool {
+        &self.default_pool
+    }
+}
+
+impl PoolWrapper<SqlxPostgresBackend> {
+    /// Gets a connection from the wrapped connection pool that does not borrow the pool
+    ///
+    /// Useful for handlers and spawned tasks that require a `'static` connection.
+    pub async fn get_owned(&self) -> Result<PoolConnection<Postgres>, sqlx::Error> {
+        self.acquire().await
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    #![allow(clippy::unwrap_used, clippy::needless_return)]
+    #![allow(clippy::unwrap_used, clippy::needless_return, clippy::large_futures)]
+
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
 
+    use async_trait::async_trait;
     use futures::{future::join_all, StreamExt};
     use sqlx::{
         postgres::{PgConnectOptions, PgPoolOptions},
-        query, query_as, Executor, FromRow, Row,
+        query, query_as, Connection, Executor, FromRow, PgConnection, Row,
     };
     use tokio_shared_rt::test;
+    use uuid::Uuid;
 
     use crate::{
-        common::statement::postgres::tests::{
-            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
+        common::{
+            config::{PgDialect, Role},
+            statement::postgres::{
+                self,
+                tests::{CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS},
+            },
         },
         r#async::{
             backend::postgres::r#trait::tests::{
+                test_backend_creates_database_owned_by_role,
                 test_backend_creates_database_with_unrestricted_privileges,
-                test_backend_drops_database, test_pool_drops_created_unrestricted_database,
+                test_backend_creates_entities_as_restricted_role, test_backend_drops_database,
+                test_backend_fails_to_clean_unrestricted_database,
+                test_backend_force_drops_database_with_lingering_connection,
+                test_backend_recreates_database_on_conflict,
+                test_backend_recreates_role_on_conflict,
+                test_pool_drops_created_unrestricted_database,
             },
             db_pool::DatabasePoolBuilder,
+            RollbackPoolTrait, SchemaProvider,
         },
     };
 
     use super::{
         super::r#trait::tests::{
-            test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
+            test_backend_cleans_database_with_tables,
+            test_backend_cleans_database_without_stored_connection,
+            test_backend_cleans_database_without_tables,
             test_backend_creates_database_with_restricted_privileges,
             test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
-            test_pool_drops_previous_databases, PgDropLock,
+            test_pool_drops_previous_databases, test_pool_drops_prewarmed_databases, PgDropLock,
         },
         SqlxPostgresBackend,
     };
@@ -277,7 +1358,7 @@ mod tests {
                 .username("postgres")
                 .password("postgres"),
             PgPoolOptions::new,
-            PgPoolOptions::new,
+            |_| PgPoolOptions::new(),
             {
                 move |mut conn| {
                     if with_table {
@@ -320,12 +1401,71 @@ mod tests {
         test_backend_creates_database_with_unrestricted_privileges(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_database_on_conflict() {
+        test_backend_recreates_database_on_conflict(
+            create_backend(true).drop_previous_databases(false),
+            create_backend(true)
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_entities_as_restricted_role() {
+        test_backend_creates_entities_as_restricted_role(
+            create_backend(true)
+                .drop_previous_databases(false)
+                .create_entities_as(Role::Restricted),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_owned_by_role() {
+        test_backend_creates_database_owned_by_role(
+            create_backend(true)
+                .drop_previous_databases(false)
+                .owner_at_creation(true)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_role_on_conflict() {
+        test_backend_recreates_role_on_conflict(
+            create_backend(true).drop_previous_databases(false),
+            create_backend(true)
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_with_tables() {
         let backend = create_backend(true).drop_previous_databases(false);
         test_backend_cleans_database_with_tables(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_stored_connection() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .store_clean_connections(false);
+        test_backend_cleans_database_without_stored_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).drop_previous_databases(false);
@@ -344,6 +1484,20 @@ mod tests {
         test_backend_drops_database(backend, false).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_previous_databases() {
         test_pool_drops_previous_databases(
@@ -407,6 +1561,83 @@ mod tests {
         .await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_rolls_back_transactions() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // changes must be visible within transaction
+            let count = conn_pool
+                .with_rollback(|conn| {
+                    Box::pin(async move {
+                        query("INSERT INTO book (title) VALUES ('Title')")
+                            .execute(&mut *conn)
+                            .await
+                            .unwrap();
+                        let (count,): (i64,) = query_as("SELECT COUNT(*) FROM book")
+                            .fetch_one(&mut *conn)
+                            .await
+                            .unwrap();
+                        count
+                    })
+                })
+                .await
+                .unwrap();
+            assert_eq!(count, 1);
+
+            // changes must be rolled back
+            let (count,): (i64,) = query_as("SELECT COUNT(*) FROM book")
+                .fetch_one(&**conn_pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 0);
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_entities_through_schema_provider() {
+        struct BookSchema;
+
+        #[async_trait]
+        impl SchemaProvider<PgConnection> for BookSchema {
+            type Error = sqlx::Error;
+
+            async fn apply(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+                conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+                    .await?;
+                Ok(())
+            }
+        }
+
+        let backend = SqlxPostgresBackend::new_with_schema_provider(
+            PgConnectOptions::new()
+                .username("postgres")
+                .password("postgres"),
+            PgPoolOptions::new,
+            |_| PgPoolOptions::new(),
+            BookSchema,
+        )
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // entities must be created by schema provider
+            assert!(query("INSERT INTO book (title) VALUES ('Title')")
+                .execute(&**conn_pool)
+                .await
+                .is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_provides_restricted_databases() {
         let backend = create_backend(true).drop_previous_databases(false);
@@ -523,9 +1754,247 @@ mod tests {
         test_pool_drops_created_restricted_databases(backend).await;
     }
 
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
     #[test(flavor = "multi_thread", shared)]
     async fn pool_drops_created_unrestricted_database() {
         let backend = create_backend(false);
         test_pool_drops_created_unrestricted_database(backend).await;
     }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_after_connect_hook() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .after_connect(|conn| {
+                Box::pin(async move {
+                    conn.execute("SET statement_timeout = 1234").await?;
+                    Ok(())
+                })
+            });
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // restricted connections must carry session settings applied by hook
+            let (statement_timeout,): (String,) = query_as("SHOW statement_timeout")
+                .fetch_one(&**conn_pool)
+                .await
+                .unwrap();
+            assert_eq!(statement_timeout, "1234ms");
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_pgbouncer_compatible_databases() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .pgbouncer_compatible(true)
+            .restricted_endpoint("127.0.0.1", 5432);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut *conn_pool.acquire().await.unwrap();
+
+            // restricted connections must go through separate endpoint
+            assert!(conn_pool
+                .database_url()
+                .unwrap()
+                .contains("@127.0.0.1:5432/"));
+            let (client_addr,): (String,) = query_as("SELECT host(inet_client_addr())")
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+            assert_eq!(client_addr, "127.0.0.1");
+
+            // prepared statements must not be cached
+            query("INSERT INTO book (title) VALUES ($1)")
+                .bind("Title")
+                .execute(&mut *conn)
+                .await
+                .unwrap();
+            assert_eq!(conn.cached_statements_size(), 0);
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_url_with_encoded_credentials() {
+        const PASSWORD: &str = "p@ss:w/o'rd?#%";
+
+        let role_name = format!("reused_{}", Uuid::new_v4().simple());
+
+        async {
+            let mut conn = PgConnection::connect_with(
+                &PgConnectOptions::new()
+                    .host("localhost")
+                    .username("postgres")
+                    .password("postgres"),
+            )
+            .await
+            .unwrap();
+
+            // Create role with reserved characters in its password
+            conn.execute(
+                format!(
+                    "CREATE ROLE {role_name} LOGIN PASSWORD {}",
+                    postgres::quote_literal(PASSWORD)
+                )
+                .as_str(),
+            )
+            .await
+            .unwrap();
+
+            let backend = create_backend(true)
+                .drop_previous_databases(false)
+                .reuse_role(role_name.as_str(), PASSWORD);
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+
+                // URL must connect as the reused role despite reserved characters in its password
+                let database_url = conn_pool.database_url().unwrap();
+                let mut db_conn = PgConnection::connect(database_url.as_str()).await.unwrap();
+                let (current_user,): (String,) = query_as("SELECT current_user::TEXT")
+                    .fetch_one(&mut db_conn)
+                    .await
+                    .unwrap();
+                assert_eq!(current_user, role_name);
+            }
+
+            // Drop role along with databases it was granted access to
+            db_pool.teardown().await.unwrap();
+            conn.execute(postgres::drop_role(role_name.as_str()).as_str())
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_url_with_ipv6_host() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .restricted_endpoint("::1", 5432);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // IPv6 address must be bracketed in URL
+            let database_url = conn_pool.database_url().unwrap();
+            assert!(database_url.contains("@[::1]:5432/"));
+            assert!(database_url.parse::<PgConnectOptions>().is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_creates_restricted_pools_from_database_options() {
+        const MAX_LIFETIME: Duration = Duration::from_secs(30);
+
+        let db_names = Arc::new(Mutex::new(Vec::new()));
+        let backend = SqlxPostgresBackend::new(
+            PgConnectOptions::new()
+                .username("postgres")
+                .password("postgres"),
+            PgPoolOptions::new,
+            {
+                let db_names = db_names.clone();
+                move |opts| {
+                    db_names
+                        .lock()
+                        .unwrap()
+                        .push(opts.get_database().unwrap().to_owned());
+                    PgPoolOptions::new().max_lifetime(MAX_LIFETIME)
+                }
+            },
+            |conn| Box::pin(async { conn }),
+        )
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // restricted pool must be created from options of its database
+            let (db_name,): (String,) = query_as("SELECT current_database()")
+                .fetch_one(&**conn_pool)
+                .await
+                .unwrap();
+            assert!(db_names.lock().unwrap().contains(&db_name));
+            assert_eq!(conn_pool.options().get_max_lifetime(), Some(MAX_LIFETIME));
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_connects_eagerly() {
+        async {
+            // restricted pool must not connect until queried by default
+            let db_pool = create_backend(false)
+                .drop_previous_databases(false)
+                .create_database_pool()
+                .await
+                .unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            assert_eq!(conn_pool.size(), 0);
+
+            // restricted pool must hold a connection before being queried
+            let db_pool = create_backend(false)
+                .drop_previous_databases(false)
+                .eager_connect(true)
+                .create_database_pool()
+                .await
+                .unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            assert_eq!(conn_pool.size(), 1);
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_databases_over_tls() {
+        let backend = SqlxPostgresBackend::new(
+            PgConnectOptions::new()
+                .host("localhost")
+                .username("postgres")
+                .password("postgres")
+                .ssl_mode(sqlx::postgres::PgSslMode::Require),
+            PgPoolOptions::new,
+            |_| PgPoolOptions::new(),
+            |conn| Box::pin(async { conn }),
+        )
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // connection must be encrypted
+            let (ssl,): (bool,) =
+                query_as("SELECT ssl FROM pg_catalog.pg_stat_ssl WHERE pid = pg_backend_pid()")
+                    .fetch_one(&**conn_pool)
+                    .await
+                    .unwrap();
+            assert!(ssl);
+        }
+        .lock_read()
+        .await;
+    }
 }
@@ -1,38 +1,104 @@
-use std::{borrow::Cow, collections::HashMap, convert::Into, pin::Pin};
+use std::{borrow::Cow, collections::HashMap, convert::Into, pin::Pin, time::Duration};
 
 use async_trait::async_trait;
 use futures::Future;
 use parking_lot::Mutex;
-use tokio_postgres::{Client, Config, NoTls};
+use tokio_postgres::{config::Host, error::SqlState, Client, Config};
 use uuid::Uuid;
 
-use crate::{common::statement::postgres, util::get_db_name};
+use crate::{
+    common::{
+        config::{CleanStrategy, PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    r#async::PoolWrapper,
+    util::{
+        format_pg_options, get_credentials, get_db_name, get_pg_application_name, url_host,
+        url_userinfo, PreviousDatabaseQuery, ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{
         common::{
             error::tokio_postgres::{ConnectionError, QueryError},
             pool::tokio_postgres::r#trait::TokioPostgresPoolAssociation,
+            tls::tokio_postgres::make_tls,
         },
         error::Error as BackendError,
         r#trait::Backend,
     },
-    r#trait::{PostgresBackend, PostgresBackendWrapper},
+    r#trait::{CustomClean, PostgresBackend, PostgresBackendWrapper},
+};
+
+#[cfg(any(all(test, feature = "tokio-postgres"), feature = "tokio-postgres-bb8"))]
+use std::sync::Arc;
+
+#[cfg(any(all(test, feature = "tokio-postgres"), feature = "tokio-postgres-bb8"))]
+use super::super::common::pool::tokio_postgres::bb8::{
+    AfterConnect, AfterConnectCustomizer, TokioPostgresBb8,
 };
 
-type CreateEntities = dyn Fn(Client) -> Pin<Box<dyn Future<Output = Client> + Send + 'static>>
+type Connector = dyn Fn(
+        Config,
+    ) -> Pin<Box<dyn Future<Output = Result<Client, tokio_postgres::Error>> + Send + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+type CreateEntities = dyn Fn(Client, &str) -> Pin<Box<dyn Future<Output = Client> + Send + 'static>>
     + Send
     + Sync
     + 'static;
 
 /// [`tokio-postgres`](https://docs.rs/tokio-postgres/0.7.10/tokio_postgres/) backend
+#[allow(clippy::struct_excessive_bools)]
 pub struct TokioPostgresBackend<P: TokioPostgresPoolAssociation> {
     privileged_config: Config,
     default_pool: P::Pool,
+    table_names_cache: TableNamesCache,
     db_conns: Mutex<HashMap<Uuid, Client>>,
     create_restricted_pool: Box<dyn Fn() -> P::Builder + Send + Sync + 'static>,
     create_entities: Box<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Box<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    clean_strategy: CleanStrategy,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    store_clean_connections_flag: bool,
+    name_generator: Box<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Box<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
+    eager_connect_flag: bool,
+    custom_clean: Option<Box<CustomClean<Client, QueryError>>>,
+    connector: Option<Box<Connector>>,
 }
 
 impl<P: TokioPostgresPoolAssociation> TokioPostgresBackend<P> {
@@ -81,18 +147,189 @@ impl<P: TokioPostgresPoolAssociation> TokioPostgresBackend<P> {
             + Send
             + Sync
             + 'static,
+    ) -> Result<Self, P::BuildError> {
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+        .await
+    }
+
+    /// Creates a new [`tokio-postgres`](https://docs.rs/tokio-postgres/0.7.10/tokio_postgres/) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{TokioPostgresBackend, TokioPostgresBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///     
+    ///     let backend = TokioPostgresBackend::<TokioPostgresBb8>::new_with_context(
+    ///         config.into(),
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         move |conn, db_name| {
+    ///             let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///             Box::pin(async move {
+    ///                 conn.execute(
+    ///                     query.as_str(),
+    ///                     &[],
+    ///                 )
+    ///                 .await
+    ///                 .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn new_with_context(
+        privileged_config: Config,
+        create_privileged_pool: impl Fn() -> P::Builder,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        create_entities: impl Fn(Client, &str) -> Pin<Box<dyn Future<Output = Client> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
     ) -> Result<Self, P::BuildError> {
         let builder = create_privileged_pool();
         let default_pool = P::build_pool(builder, privileged_config.clone()).await?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            privileged_config,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`tokio-postgres`](https://docs.rs/tokio-postgres/0.7.10/tokio_postgres/) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use bb8_postgres::PostgresConnectionManager;
+    /// use db_pool::{
+    ///     r#async::{TokioPostgresBackend, TokioPostgresBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use dotenvy::dotenv;
+    /// use tokio_postgres::{Config, NoTls};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config: Config = PrivilegedPostgresConfig::from_env().unwrap().into();
+    ///     let manager = PostgresConnectionManager::new(config.clone(), NoTls);
+    ///     let privileged_pool = Pool::builder().max_size(10).build(manager).await.unwrap();
+    ///
+    ///     let backend = TokioPostgresBackend::<TokioPostgresBb8>::new_with_privileged_pool(
+    ///         config,
+    ///         privileged_pool,
+    ///         || Pool::builder().max_size(2),
+    ///         move |conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute(
+    ///                     "CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)",
+    ///                     &[],
+    ///                 )
+    ///                 .await
+    ///                 .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: Config,
+        privileged_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        create_entities: impl Fn(Client) -> Pin<Box<dyn Future<Output = Client> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_config: Config,
+        default_pool: P::Pool,
+        create_restricted_pool: impl Fn() -> P::Builder + Send + Sync + 'static,
+        create_entities: impl Fn(Client, &str) -> Pin<Box<dyn Future<Output = Client> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
             privileged_config,
             default_pool,
+            table_names_cache: TableNamesCache::default(),
             db_conns: Mutex::new(HashMap::new()),
             create_entities: Box::new(create_entities),
             create_restricted_pool: Box::new(create_restricted_pool),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            clean_strategy: CleanStrategy::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            store_clean_connections_flag: true,
+            name_generator: Box::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+            eager_connect_flag: false,
+            custom_clean: None,
+            connector: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -103,442 +340,3280 @@ impl<P: TokioPostgresPoolAssociation> TokioPostgresBackend<P> {
             ..self
         }
     }
-}
 
-#[async_trait]
-impl<'pool, P: TokioPostgresPoolAssociation> PostgresBackend<'pool> for TokioPostgresBackend<P> {
-    type Connection = Client;
-    type PooledConnection = P::PooledConnection<'pool>;
-    type Pool = P::Pool;
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Box::new(value)),
+            ..self
+        }
+    }
 
-    type BuildError = P::BuildError;
-    type PoolError = P::PoolError;
-    type ConnectionError = ConnectionError;
-    type QueryError = QueryError;
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
+    }
 
-    async fn execute_query(&self, query: &str, conn: &mut Client) -> Result<(), QueryError> {
-        conn.execute(query, &[]).await?;
-        Ok(())
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
+        }
     }
 
-    async fn batch_execute_query<'a>(
-        &self,
-        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
-        conn: &mut Client,
-    ) -> Result<(), QueryError> {
-        let query = query.into_iter().collect::<Vec<_>>().join(";");
-        conn.batch_execute(query.as_str()).await?;
-        Ok(())
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
     }
 
-    async fn get_default_connection(
-        &'pool self,
-    ) -> Result<P::PooledConnection<'pool>, P::PoolError> {
-        P::get_connection(&self.default_pool).await
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
     }
 
-    async fn establish_privileged_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<Client, ConnectionError> {
-        let mut config = self.privileged_config.clone();
-        let db_name = get_db_name(db_id);
-        config.dbname(db_name.as_str());
-        let (client, connection) = config.connect(NoTls).await?;
-        tokio::spawn(connection);
-        Ok(client)
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
     }
 
-    async fn establish_restricted_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<Client, ConnectionError> {
-        let mut config = self.privileged_config.clone();
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        config.user(db_name).password(db_name).dbname(db_name);
-        let (client, connection) = config.connect(NoTls).await?;
-        tokio::spawn(connection);
-        Ok(client)
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
     }
 
-    fn put_database_connection(&self, db_id: Uuid, conn: Client) {
-        self.db_conns.lock().insert(db_id, conn);
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> Client {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    /// How databases are cleaned once their reusable connection pools are returned
+    ///
+    /// Defaults to truncating tables. Ignored when cleaning with a custom routine.
+    #[must_use]
+    pub fn clean_strategy(self, value: CleanStrategy) -> Self {
+        Self {
+            clean_strategy: value,
+            ..self
+        }
     }
 
-    async fn get_previous_database_names(
-        &self,
-        conn: &mut Client,
-    ) -> Result<Vec<String>, QueryError> {
-        conn.query(postgres::GET_DATABASE_NAMES, &[])
-            .await
-            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
-            .map_err(Into::into)
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
     }
 
-    async fn create_entities(&self, conn: Client) -> Client {
-        (self.create_entities)(conn).await
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
     }
 
-    async fn create_connection_pool(&self, db_id: Uuid) -> Result<P::Pool, P::BuildError> {
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        let mut config = self.privileged_config.clone();
-        config.dbname(db_name);
-        config.user(db_name);
-        config.password(db_name);
-        let builder = (self.create_restricted_pool)();
-        P::build_pool(builder, config).await
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
     }
 
-    async fn get_table_names(
-        &self,
-        privileged_conn: &mut Client,
-    ) -> Result<Vec<String>, QueryError> {
-        privileged_conn
-            .query(postgres::GET_TABLE_NAMES, &[])
-            .await
-            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
-            .map_err(Into::into)
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
     }
 
-    fn get_drop_previous_databases(&self) -> bool {
-        self.drop_previous_databases_flag
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
     }
-}
 
-type BError<BuildError, PoolError> =
-    BackendError<BuildError, PoolError, ConnectionError, QueryError>;
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
 
-#[async_trait]
-impl<P: TokioPostgresPoolAssociation> Backend for TokioPostgresBackend<P> {
-    type Pool = P::Pool;
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
+
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
+    }
+
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Keep a privileged connection open per restricted database for cleaning it
+    ///
+    /// Enabled by default. When disabled, a privileged connection is established on demand
+    /// to clean a database and closed afterwards, trading cleaning latency for fewer idle
+    /// connections to the server.
+    #[must_use]
+    pub fn store_clean_connections(self, value: bool) -> Self {
+        Self {
+            store_clean_connections_flag: value,
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Box::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities, to clean databases when not storing connections,
+    /// and to verify restrictions. Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+
+    /// Establishes a connection in each restricted pool before handing it out
+    ///
+    /// Moves the latency of connecting from the first query to database creation
+    /// and surfaces connection errors when pulling rather than when querying.
+    #[must_use]
+    pub fn eager_connect(self, value: bool) -> Self {
+        Self {
+            eager_connect_flag: value,
+            ..self
+        }
+    }
+
+    /// Routine cleaning databases in place of truncating all of their tables
+    ///
+    /// Runs on the stored privileged connection to the database, within a transaction if cleaning in transactions.
+    /// Meant for schemas needing bespoke cleaning, such as disabling triggers or deleting rows in foreign key order.
+    #[must_use]
+    pub fn custom_clean(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut Client,
+            ) -> Pin<
+                Box<dyn Future<Output = Result<(), tokio_postgres::Error>> + Send + 'c>,
+            > + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            custom_clean: Some(Box::new(move |conn| {
+                let clean = value(conn);
+                Box::pin(async move { clean.await.map_err(Into::into) })
+            })),
+            ..self
+        }
+    }
+
+    /// Establishes privileged and restricted connections to databases from their configuration instead of connecting directly
+    ///
+    /// Meant for reaching the database server through a tunnel, such as by calling
+    /// [`Config::connect_raw`](tokio_postgres::Config::connect_raw) with a stream forwarded through a bastion host.
+    /// The connector is responsible for spawning the connection task.
+    /// Connections of privileged and restricted pools are still established by their connection managers.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::r#async::{TokioPostgresBackend, TokioPostgresBb8};
+    /// use tokio::net::TcpStream;
+    /// use tokio_postgres::{Config, NoTls};
+    ///
+    /// async fn f() {
+    ///     let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+    ///         "host=localhost user=postgres password=postgres"
+    ///             .parse::<Config>()
+    ///             .unwrap(),
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         |conn| Box::pin(async { conn }),
+    ///     )
+    ///     .await
+    ///     .unwrap()
+    ///     .connector(|config| {
+    ///         Box::pin(async move {
+    ///             // stream forwarded through a tunnel in practice
+    ///             let stream = TcpStream::connect("localhost:5432").await.unwrap();
+    ///             let (client, connection) = config.connect_raw(stream, NoTls).await?;
+    ///             tokio::spawn(connection);
+    ///             Ok(client)
+    ///         })
+    ///     });
+    /// }
+    /// ```
+    #[must_use]
+    pub fn connector(
+        self,
+        value: impl Fn(
+                Config,
+            ) -> Pin<
+                Box<dyn Future<Output = Result<Client, tokio_postgres::Error>> + Send + 'static>,
+            > + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            connector: Some(Box::new(value)),
+            ..self
+        }
+    }
+
+    async fn connect_with_config(&self, config: Config) -> Result<Client, ConnectionError> {
+        // Connect through custom connector if set, directly otherwise
+        if let Some(connector) = &self.connector {
+            return connector(config).await.map_err(Into::into);
+        }
+
+        let (client, connection) = config.connect(make_tls()).await?;
+        tokio::spawn(connection);
+        Ok(client)
+    }
+}
+
+#[cfg(any(all(test, feature = "tokio-postgres"), feature = "tokio-postgres-bb8"))]
+impl TokioPostgresBackend<TokioPostgresBb8> {
+    /// Hook run on every new connection of restricted pools, for applying session settings such as the time zone or search path
+    ///
+    /// Replaces any connection customizer set on the restricted pool builder.
+    #[must_use]
+    pub fn after_connect(
+        self,
+        value: impl for<'c> Fn(
+                &'c mut Client,
+            ) -> Pin<
+                Box<dyn Future<Output = Result<(), tokio_postgres::Error>> + Send + 'c>,
+            > + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let create_restricted_pool = self.create_restricted_pool;
+        let after_connect: Arc<AfterConnect> = Arc::new(value);
+        Self {
+            create_restricted_pool: Box::new(move || {
+                create_restricted_pool()
+                    .connection_customizer(Box::new(AfterConnectCustomizer(after_connect.clone())))
+            }),
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl<'pool, P: TokioPostgresPoolAssociation> PostgresBackend<'pool> for TokioPostgresBackend<P> {
+    type Connection = Client;
+    type PooledConnection = P::PooledConnection<'pool>;
+    type Pool = P::Pool;
 
     type BuildError = P::BuildError;
     type PoolError = P::PoolError;
     type ConnectionError = ConnectionError;
     type QueryError = QueryError;
 
-    async fn init(&self) -> Result<(), BError<P::BuildError, P::PoolError>> {
-        PostgresBackendWrapper::new(self).init().await
+    async fn execute_query(&self, query: &str, conn: &mut Client) -> Result<(), QueryError> {
+        conn.execute(query, &[]).await?;
+        Ok(())
     }
 
-    async fn create(
+    async fn batch_execute_query<'a>(
         &self,
-        db_id: uuid::Uuid,
-        restrict_privileges: bool,
-    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
-        PostgresBackendWrapper::new(self)
-            .create(db_id, restrict_privileges)
-            .await
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut Client,
+    ) -> Result<(), QueryError> {
+        let query = query.into_iter().collect::<Vec<_>>().join(";");
+        conn.batch_execute(query.as_str()).await?;
+        Ok(())
     }
 
-    async fn clean(&self, db_id: uuid::Uuid) -> Result<(), BError<P::BuildError, P::PoolError>> {
-        PostgresBackendWrapper::new(self).clean(db_id).await
+    async fn get_default_connection(
+        &'pool self,
+    ) -> Result<P::PooledConnection<'pool>, P::PoolError> {
+        P::get_connection(&self.default_pool).await
     }
 
-    async fn drop(
-        &self,
-        db_id: uuid::Uuid,
-        is_restricted: bool,
-    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
-        PostgresBackendWrapper::new(self)
-            .drop(db_id, is_restricted)
+    async fn establish_privileged_connection(&self) -> Result<Client, ConnectionError> {
+        self.connect_with_config(self.privileged_config.clone())
             .await
     }
-}
 
-#[cfg(test)]
-mod tests {
-    #![allow(clippy::unwrap_used, clippy::needless_return)]
+    async fn establish_privileged_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Client, ConnectionError> {
+        let mut config = self.privileged_config.clone();
+        let db_name = self.get_db_name(db_id);
+        config.dbname(db_name.as_str());
+        self.connect_with_config(config).await
+    }
 
-    use bb8::Pool;
-    use futures::future::join_all;
-    use tokio_postgres::Config;
-    use tokio_shared_rt::test;
+    async fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Client, ConnectionError> {
+        let mut config = self.privileged_config.clone();
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        config.user(username).password(password).dbname(db_name);
+        if let Some(application_name) = get_pg_application_name(&self.restricted_connection_params)
+        {
+            config.application_name(application_name);
+        }
+        if let Some(options) = format_pg_options(&self.restricted_connection_params) {
+            config.options(options.as_str());
+        }
+        self.connect_with_config(config).await
+    }
 
-    use crate::{
-        common::statement::postgres::tests::{
-            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
-        },
-        r#async::{
-            backend::{
-                common::pool::tokio_postgres::bb8::TokioPostgresBb8,
-                postgres::r#trait::tests::{
-                    test_backend_creates_database_with_unrestricted_privileges,
-                    test_backend_drops_database, test_pool_drops_created_unrestricted_database,
-                },
-            },
-            db_pool::DatabasePoolBuilder,
-        },
-    };
+    fn put_database_connection(&self, db_id: Uuid, conn: Client) {
+        self.db_conns.lock().insert(db_id, conn);
+    }
 
-    use super::{
-        super::r#trait::tests::{
-            test_backend_cleans_database_with_tables, test_backend_cleans_database_without_tables,
-            test_backend_creates_database_with_restricted_privileges,
-            test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
-            test_pool_drops_previous_databases, PgDropLock,
-        },
-        TokioPostgresBackend,
-    };
+    fn get_database_connection(&self, db_id: Uuid) -> Option<Client> {
+        self.db_conns.lock().remove(&db_id)
+    }
 
-    async fn create_backend(with_table: bool) -> TokioPostgresBackend<TokioPostgresBb8> {
+    async fn get_previous_database_names(
+        &self,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_DATABASE_NAMES, &[])
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(query, &[])
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_previous_owned_database_names(
+        &self,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_OWNED_DATABASE_NAMES, &[])
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    async fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(
+            postgres::get_database_names_with_schema_version(schema_version).as_str(),
+            &[],
+        )
+        .await
+        .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+        .map_err(Into::into)
+    }
+
+    async fn get_previous_role_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_ROLE_NAMES, &[])
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    async fn create_entities(&self, conn: Client, db_name: &str) -> Client {
+        (self.create_entities)(conn, db_name).await
+    }
+
+    async fn warm_up_connection_pool(&self, pool: &P::Pool) -> Result<(), P::PoolError> {
+        P::get_connection(pool).await.map(drop)
+    }
+
+    async fn create_connection_pool(&self, db_id: Uuid) -> Result<P::Pool, P::BuildError> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let mut config = self.privileged_config.clone();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        config.dbname(db_name);
+        config.user(username);
+        config.password(password);
+        if let Some(application_name) = get_pg_application_name(&self.restricted_connection_params)
+        {
+            config.application_name(application_name);
+        }
+        if let Some(options) = format_pg_options(&self.restricted_connection_params) {
+            config.options(options.as_str());
+        }
+        let builder = (self.create_restricted_pool)();
+        P::build_pool(builder, config).await
+    }
+
+    async fn get_table_names(
+        &self,
+        privileged_conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        privileged_conn
+            .query(
+                postgres::get_table_names(self.dialect.system_schemas()).as_str(),
+                &[],
+            )
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| postgres::qualify_table_name(row.get(0), row.get(1)))
+                    .collect()
+            })
+            .map_err(Into::into)
+    }
+
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        privileged_conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        privileged_conn
+            .query(
+                postgres::get_nonempty_table_names(table_names).as_str(),
+                &[],
+            )
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    fn is_duplicate_database_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::DUPLICATE_DATABASE)
+    }
+
+    fn is_duplicate_role_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::DUPLICATE_OBJECT)
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::T_R_DEADLOCK_DETECTED)
+    }
+
+    fn get_drop_previous_databases(&self) -> bool {
+        self.drop_previous_databases_flag
+    }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_clean_strategy(&self) -> CleanStrategy {
+        self.clean_strategy
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn get_eager_connect(&self) -> bool {
+        self.eager_connect_flag
+    }
+
+    fn get_custom_clean(&self) -> Option<&CustomClean<Client, QueryError>> {
+        self.custom_clean.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
+    }
+
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
+    }
+
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
+    }
+
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
+    }
+
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
+    }
+
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
+    }
+
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
+    }
+
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
+    }
+
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
+    }
+
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
+    }
+
+    fn get_store_clean_connections(&self) -> bool {
+        self.store_clean_connections_flag
+    }
+}
+
+type BError<BuildError, PoolError> =
+    BackendError<BuildError, PoolError, ConnectionError, QueryError>;
+
+#[async_trait]
+impl<P: TokioPostgresPoolAssociation> Backend for TokioPostgresBackend<P> {
+    type Pool = P::Pool;
+
+    type BuildError = P::BuildError;
+    type PoolError = P::PoolError;
+    type ConnectionError = ConnectionError;
+    type QueryError = QueryError;
+
+    async fn init(&self) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).init().await
+    }
+
+    async fn ping(&self) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).ping().await
+    }
+
+    async fn create(
+        &self,
+        db_id: uuid::Uuid,
+        restrict_privileges: bool,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .create(db_id, restrict_privileges, true)
+            .await
+    }
+
+    async fn create_empty(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .create(db_id, false, false)
+            .await
+    }
+
+    async fn create_reader_pool(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<P::Pool, BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .create_reader_pool(db_id)
+            .await
+    }
+
+    async fn clean(&self, db_id: uuid::Uuid) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).clean(db_id).await
+    }
+
+    async fn refresh(&self, db_id: uuid::Uuid) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).refresh(db_id).await
+    }
+
+    async fn verify_restricted(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .verify_restricted(db_id)
+            .await
+    }
+
+    async fn label(
+        &self,
+        db_id: uuid::Uuid,
+        label: &str,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self).label(db_id, label).await
+    }
+
+    async fn drop(
+        &self,
+        db_id: uuid::Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .drop(db_id, is_restricted)
+            .await
+    }
+
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: uuid::Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BError<P::BuildError, P::PoolError>> {
+        PostgresBackendWrapper::new(self)
+            .drop_with_dedicated_connection(db_id, is_restricted)
+            .await
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name.as_str());
+        let userinfo = url_userinfo(username, Some(password));
+        let host = match self.privileged_config.get_hosts().first() {
+            Some(Host::Tcp(host)) => host.clone(),
+            Some(Host::Unix(path)) => path.to_string_lossy().into_owned(),
+            None => "localhost".to_owned(),
+        };
+        let host = url_host(host.as_str());
+        let port = self
+            .privileged_config
+            .get_ports()
+            .first()
+            .copied()
+            .unwrap_or(5432);
+        Some(format!("postgres://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &P::Pool {
+        &self.default_pool
+    }
+}
+
+impl<P: TokioPostgresPoolAssociation> PoolWrapper<TokioPostgresBackend<P>> {
+    /// Gets a connection from the wrapped connection pool
+    ///
+    /// The returned connection dereferences to the underlying connection regardless of the pool association.
+    pub async fn get(&self) -> Result<P::PooledConnection<'_>, P::PoolError> {
+        P::get_connection(self).await
+    }
+
+    /// Gets a connection from the wrapped connection pool that does not borrow the pool
+    ///
+    /// Useful for handlers and spawned tasks that require a `'static` connection.
+    pub async fn get_owned(&self) -> Result<P::OwnedPooledConnection, P::PoolError> {
+        P::get_owned_connection(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::needless_return, clippy::large_futures)]
+
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use bb8::{Builder, Pool};
+    use bb8_postgres::PostgresConnectionManager;
+    use futures::{
+        future::{join, join_all},
+        FutureExt,
+    };
+    use parking_lot::Mutex;
+    use tokio::{net::TcpListener, runtime::Handle, task::block_in_place};
+    use tokio_postgres::{error::SqlState, Config, NoTls};
+    use tokio_shared_rt::test;
+    use uuid::Uuid;
+
+    use crate::{
+        common::{
+            config::{CleanStrategy, PgDialect, PrivilegeProfile, Role},
+            statement::postgres::{
+                self,
+                tests::{
+                    CREATE_ENTITIES_STATEMENTS, CREATE_ENTITIES_WITH_FOREIGN_KEYS_STATEMENTS,
+                    DDL_STATEMENTS, DML_STATEMENTS,
+                },
+            },
+        },
+        r#async::{
+            backend::{
+                common::{
+                    pool::tokio_postgres::bb8::TokioPostgresBb8, tls::tokio_postgres::MakeTls,
+                },
+                postgres::r#trait::tests::{
+                    test_backend_creates_database_owned_by_role,
+                    test_backend_creates_database_with_unrestricted_privileges,
+                    test_backend_creates_entities_as_restricted_role, test_backend_drops_database,
+                    test_backend_fails_to_clean_unrestricted_database,
+                    test_backend_force_drops_database_with_lingering_connection,
+                    test_backend_recreates_database_on_conflict,
+                    test_backend_recreates_role_on_conflict,
+                    test_pool_drops_created_unrestricted_database,
+                },
+                r#trait::Backend,
+                Error as BackendError,
+            },
+            db_pool::DatabasePoolBuilder,
+            DynDatabasePool, SingleUseConnectionPool,
+        },
+        util::get_db_name,
+        MetricOperation, Unrestricted,
+    };
+
+    use super::{
+        super::r#trait::tests::{
+            test_backend_cleans_database_with_tables,
+            test_backend_cleans_database_without_stored_connection,
+            test_backend_cleans_database_without_tables,
+            test_backend_creates_database_with_restricted_privileges,
+            test_backend_drops_previous_databases, test_backend_keeps_group_role_of_kept_databases,
+            test_pool_drops_created_restricted_databases, test_pool_drops_previous_databases,
+            test_pool_drops_prewarmed_databases,
+            test_pool_drops_returned_databases_in_drop_clean_mode, test_pool_tears_down_databases,
+            PgDropLock,
+        },
+        TokioPostgresBackend,
+    };
+
+    async fn create_backend(with_table: bool) -> TokioPostgresBackend<TokioPostgresBb8> {
+        create_backend_with_privileged_pool(with_table, Pool::builder).await
+    }
+
+    async fn create_backend_with_privileged_pool(
+        with_table: bool,
+        create_privileged_pool: impl Fn() -> Builder<PostgresConnectionManager<MakeTls>>,
+    ) -> TokioPostgresBackend<TokioPostgresBb8> {
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        TokioPostgresBackend::new(config, create_privileged_pool, Pool::builder, {
+            move |conn| {
+                if with_table {
+                    Box::pin(async move {
+                        conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                            .await
+                            .unwrap();
+                        conn
+                    })
+                } else {
+                    Box::pin(async { conn })
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_previous_databases() {
+        test_backend_drops_previous_databases(
+            create_backend(false).await,
+            create_backend(false).await.drop_previous_databases(true),
+            create_backend(false).await.drop_previous_databases(false),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_previous_databases_with_single_privileged_connection() {
+        let create_privileged_pool = || Pool::builder().max_size(1);
+        test_backend_drops_previous_databases(
+            create_backend_with_privileged_pool(false, create_privileged_pool).await,
+            create_backend_with_privileged_pool(false, create_privileged_pool)
+                .await
+                .drop_previous_databases(true),
+            create_backend_with_privileged_pool(false, create_privileged_pool)
+                .await
+                .drop_previous_databases(false),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_releases_initialization_lock_when_init_is_cancelled() {
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            // initialization must block while another initializer holds the lock
+            client
+                .batch_execute(postgres::LOCK_INITIALIZATION)
+                .await
+                .unwrap();
+            let backend = create_backend(false).await.drop_previous_databases(true);
+            assert!(
+                tokio::time::timeout(Duration::from_millis(500), backend.init())
+                    .await
+                    .is_err()
+            );
+            client
+                .batch_execute(postgres::UNLOCK_INITIALIZATION)
+                .await
+                .unwrap();
+
+            // lock must not be left held by the cancelled initialization
+            let backend = create_backend(false).await.drop_previous_databases(true);
+            tokio::time::timeout(Duration::from_secs(10), backend.init())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        .lock_drop()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_keeps_group_role_of_kept_databases() {
+        test_backend_keeps_group_role_of_kept_databases(
+            create_backend(true)
+                .await
+                .schema_version("group")
+                .group_role(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_restricted_privileges() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_creates_database_with_restricted_privileges(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_with_unrestricted_privileges() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_creates_database_with_unrestricted_privileges(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_database_on_conflict() {
+        test_backend_recreates_database_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_entities_as_restricted_role() {
+        test_backend_creates_entities_as_restricted_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .create_entities_as(Role::Restricted),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_creates_database_owned_by_role() {
+        test_backend_creates_database_owned_by_role(
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .owner_at_creation(true)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_recreates_role_on_conflict() {
+        test_backend_recreates_role_on_conflict(
+            create_backend(true).await.drop_previous_databases(false),
+            create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .recreate_on_conflict(true),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_transaction() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .clean_in_transaction(true);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_with_foreign_keys_in_cockroachdb_dialect() {
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            |conn| {
+                Box::pin(async move {
+                    conn.batch_execute(&CREATE_ENTITIES_WITH_FOREIGN_KEYS_STATEMENTS.join(";"))
+                        .await
+                        .unwrap();
+                    conn
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false)
+        .dialect(PgDialect::CockroachDB);
+
+        async {
+            let db_id = Uuid::new_v4();
+            let conn_pool = backend.create(db_id, true).await.unwrap();
+            let conn = conn_pool.get().await.unwrap();
+            conn.batch_execute(
+                "INSERT INTO author DEFAULT VALUES; INSERT INTO book (author_id) VALUES (1)",
+            )
+            .await
+            .unwrap();
+
+            // referenced tables must be cleaned along with referencing tables
+            backend.clean(db_id).await.unwrap();
+            for table_name in ["author", "book"] {
+                assert_eq!(
+                    conn.query_one(format!("SELECT COUNT(*) FROM {table_name}").as_str(), &[])
+                        .await
+                        .unwrap()
+                        .get::<_, i64>(0),
+                    0
+                );
+            }
+
+            drop(conn);
+            drop(conn_pool);
+            backend.drop(db_id, true).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_stored_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .store_clean_connections(false);
+        test_backend_cleans_database_without_stored_connection(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_cleans_database_without_tables() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+        test_backend_cleans_database_without_tables(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_provides_privileged_pool() {
+        let backend = create_backend(false).await;
+
+        // privileged pool must connect as privileged user
+        assert!(backend
+            .privileged_pool()
+            .get()
+            .await
+            .unwrap()
+            .query_one(
+                "SELECT rolsuper FROM pg_catalog.pg_roles WHERE rolname = current_user",
+                &[],
+            )
+            .await
+            .unwrap()
+            .get::<_, bool>(0));
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_restricted_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_drops_database(backend, true).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_drops_unrestricted_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_drops_database(backend, false).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_previous_databases() {
+        test_pool_drops_previous_databases(
+            create_backend(false).await,
+            create_backend(false).await.drop_previous_databases(true),
+            create_backend(false).await.drop_previous_databases(false),
+        )
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_isolated_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+            // insert single row into each database
+            join_all(
+                conn_pools
+                    .iter()
+                    .enumerate()
+                    .map(|(i, conn_pool)| async move {
+                        let conn = &mut conn_pool.get().await.unwrap();
+                        conn.execute(
+                            "INSERT INTO book (title) VALUES ($1)",
+                            &[&format!("Title {i}").as_str()],
+                        )
+                        .await
+                        .unwrap();
+                    }),
+            )
+            .await;
+
+            // rows fetched must be as inserted
+            join_all(
+                conn_pools
+                    .iter()
+                    .enumerate()
+                    .map(|(i, conn_pool)| async move {
+                        let conn = &mut conn_pool.get().await.unwrap();
+                        assert_eq!(
+                            conn.query("SELECT title FROM book", &[])
+                                .await
+                                .unwrap()
+                                .iter()
+                                .map(|row| row.get::<_, String>(0))
+                                .collect::<Vec<_>>(),
+                            vec![format!("Title {i}")]
+                        );
+                    }),
+            )
+            .await;
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_restricted_databases() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // DDL statements must fail
+            for stmt in DDL_STATEMENTS {
+                assert!(conn.execute(stmt, &[]).await.is_err());
+            }
+
+            // DML statements must succeed
+            for stmt in DML_STATEMENTS {
+                assert!(conn.execute(stmt, &[]).await.is_ok());
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_unrestricted_databases() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // DML statements must succeed
+            {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get().await.unwrap();
+                for stmt in DML_STATEMENTS {
+                    assert!(conn.execute(stmt, &[]).await.is_ok());
+                }
+            }
+
+            // DDL statements must succeed
+            for stmt in DDL_STATEMENTS {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get().await.unwrap();
+                assert!(conn.execute(stmt, &[]).await.is_ok());
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_databases_with_createdb_only_profile() {
+        let login_name = format!("createdb_{}", Uuid::new_v4().simple());
+        let admin = create_backend(false).await;
+        let admin_conn = admin.privileged_pool().get().await.unwrap();
+        admin_conn
+            .batch_execute(
+                format!("CREATE ROLE {login_name} WITH LOGIN PASSWORD '{login_name}' CREATEDB CREATEROLE")
+                    .as_str(),
+            )
+            .await
+            .unwrap();
+
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user(login_name.as_str())
+            .password(login_name.as_str())
+            .dbname("postgres");
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            |conn| {
+                Box::pin(async move {
+                    conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                        .await
+                        .unwrap();
+                    conn
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false)
+        .privilege_profile(PrivilegeProfile::CreateDb);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // DML statements must succeed on restricted database created without superuser
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = &mut conn_pool.get().await.unwrap();
+                for stmt in DML_STATEMENTS {
+                    assert!(conn.execute(stmt, &[]).await.is_ok());
+                }
+                for stmt in DDL_STATEMENTS {
+                    assert!(conn.execute(stmt, &[]).await.is_err());
+                }
+            }
+
+            // DDL statements must succeed on unrestricted database created without superuser
+            for stmt in DDL_STATEMENTS {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get().await.unwrap();
+                assert!(conn.execute(stmt, &[]).await.is_ok());
+            }
+
+            db_pool.teardown().await.unwrap();
+        }
+        .lock_read()
+        .await;
+
+        admin_conn
+            .batch_execute(format!("DROP ROLE {login_name}").as_str())
+            .await
+            .unwrap();
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_databases_with_extensions() {
+        let backend = create_backend(false)
+            .await
+            .drop_previous_databases(false)
+            .extensions(&["pgcrypto"]);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // extension must be usable in restricted database
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = &mut conn_pool.get().await.unwrap();
+                assert!(conn.query_one("SELECT gen_salt('bf')", &[]).await.is_ok());
+            }
+
+            // extension must be usable in unrestricted database
+            {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn = &mut conn_pool.get().await.unwrap();
+                assert!(conn.query_one("SELECT gen_salt('bf')", &[]).await.is_ok());
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_clean_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // fetch connection pools the first time
+            {
+                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+                // databases must be empty
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get().await.unwrap();
+                    assert_eq!(
+                        conn.query_one("SELECT COUNT(*) FROM book", &[])
+                            .await
+                            .unwrap()
+                            .get::<_, i64>(0),
+                        0
+                    );
+                }))
+                .await;
+
+                // insert data into each database
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get().await.unwrap();
+                    conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                        .await
+                        .unwrap();
+                }))
+                .await;
+            }
+
+            // fetch same connection pools a second time
+            {
+                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+                // databases must be empty
+                join_all(conn_pools.iter().map(|conn_pool| async move {
+                    let conn = &mut conn_pool.get().await.unwrap();
+                    assert_eq!(
+                        conn.query_one("SELECT COUNT(*) FROM book", &[])
+                            .await
+                            .unwrap()
+                            .get::<_, i64>(0),
+                        0
+                    );
+                }))
+                .await;
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_grants_privileges_on_tables_created_later() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // Create table as privileged user after privileges are granted
+            {
+                let mut config = Config::new();
+                config
+                    .host("localhost")
+                    .user("postgres")
+                    .password("postgres")
+                    .dbname(conn_pool.database_name());
+                let (client, connection) = config.connect(NoTls).await.unwrap();
+                tokio::spawn(connection);
+                client
+                    .batch_execute("CREATE TABLE author(id SERIAL PRIMARY KEY, name TEXT NOT NULL)")
+                    .await
+                    .unwrap();
+            }
+
+            // restricted role must be able to access table
+            let conn = &mut conn_pool.get().await.unwrap();
+            assert!(conn
+                .execute("INSERT INTO author (name) VALUES ($1)", &[&"Name"])
+                .await
+                .is_ok());
+            assert!(conn.query("SELECT * FROM author", &[]).await.is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_id() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let immutable_conn_pool = db_pool.pull_immutable().await;
+            let mutable_conn_pool = db_pool.create_mutable().await.unwrap();
+
+            // database name must be derived from database ID
+            assert_eq!(
+                get_db_name(immutable_conn_pool.database_id()),
+                immutable_conn_pool.database_name()
+            );
+            assert_eq!(
+                get_db_name(mutable_conn_pool.database_id()),
+                mutable_conn_pool.database_name()
+            );
+
+            // databases must have distinct IDs
+            assert_ne!(
+                immutable_conn_pool.database_id(),
+                mutable_conn_pool.database_id()
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_keeps_database_on_panic() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .keep_on_panic(true);
+
+            let db_name = Mutex::new(None);
+            let result = AssertUnwindSafe(async {
+                let conn_pool = db_pool.create_mutable().await.unwrap();
+                *db_name.lock() = Some(conn_pool.database_name().to_owned());
+                panic!("test must fail");
+            })
+            .catch_unwind()
+            .await;
+            assert!(result.is_err());
+            let db_name = db_name.into_inner().unwrap();
+
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            // database must survive panic
+            assert!(client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .await
+                .unwrap()
+                .is_some());
+
+            client
+                .execute(
+                    format!("DROP DATABASE {db_name} WITH (FORCE)").as_str(),
+                    &[],
+                )
+                .await
+                .unwrap();
+            client
+                .execute(format!("DROP ROLE {db_name}").as_str(), &[])
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_records_teardown_errors() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // no errors must be recorded after successful drops
+            drop(db_pool.create_mutable().await.unwrap());
+            assert!(db_pool.take_teardown_errors().is_empty());
+
+            // failed drop of database dropped behind the back of its connection pool must be recorded
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            client
+                .batch_execute(format!("DROP DATABASE {db_name} WITH (FORCE)").as_str())
+                .await
+                .unwrap();
+            drop(conn_pool);
+            let errors = db_pool.take_teardown_errors();
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(errors[0], BackendError::Query(_)));
+            client
+                .batch_execute(format!("DROP ROLE {db_name}").as_str())
+                .await
+                .unwrap();
+
+            // errors must be cleared once taken
+            assert!(db_pool.take_teardown_errors().is_empty());
+
+            // failed drop must panic if needed
+            let db_pool = db_pool.panic_on_drop_error(true);
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            client
+                .batch_execute(format!("DROP DATABASE {db_name} WITH (FORCE)").as_str())
+                .await
+                .unwrap();
+            assert!(catch_unwind(AssertUnwindSafe(|| drop(conn_pool))).is_err());
+            assert!(db_pool.take_teardown_errors().is_empty());
+            client
+                .batch_execute(format!("DROP ROLE {db_name}").as_str())
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_waits_for_returned_database_at_max_databases() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .max_databases(1);
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let db_name = conn_pool.database_name().to_owned();
+
+            // second pull must wait for first connection pool to be returned and reuse its database
+            let (next_db_name, ()) = join(
+                async { db_pool.pull_immutable().await.database_name().to_owned() },
+                async move {
+                    tokio::task::yield_now().await;
+                    drop(conn_pool);
+                },
+            )
+            .await;
+            assert_eq!(next_db_name, db_name);
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_on_acquire_after_cleaning() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .on_acquire(|conn_pool| {
+                    Box::pin(async {
+                        conn_pool
+                            .get()
+                            .await
+                            .unwrap()
+                            .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                            .await
+                            .unwrap();
+                        conn_pool
+                    })
+                });
+
+            // data inserted on acquire must be present once per pull
+            for _ in 0..2 {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = conn_pool.get().await.unwrap();
+                assert_eq!(
+                    conn.query_one("SELECT COUNT(*) FROM book", &[])
+                        .await
+                        .unwrap()
+                        .get::<_, i64>(0),
+                    1
+                );
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_on_first_acquire_once_per_database() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .on_first_acquire(|conn_pool| {
+                    Box::pin(async {
+                        conn_pool
+                            .get()
+                            .await
+                            .unwrap()
+                            .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                            .await
+                            .unwrap();
+                        conn_pool
+                    })
+                });
+
+            // data inserted on first acquire must only be present on the first pull
+            for count in [1, 0] {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = conn_pool.get().await.unwrap();
+                assert_eq!(
+                    conn.query_one("SELECT COUNT(*) FROM book", &[])
+                        .await
+                        .unwrap()
+                        .get::<_, i64>(0),
+                    count
+                );
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_labels_attached_roles() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // attached role must carry label of latest pull
+            for label in ["tests::first", "tests::second"] {
+                let conn_pool = db_pool.pull_immutable_labeled(label).await.unwrap();
+                let conn = conn_pool.get().await.unwrap();
+                assert_eq!(
+                    conn.query_one(
+                        "SELECT shobj_description(oid, 'pg_authid') FROM pg_catalog.pg_roles WHERE rolname = current_user",
+                        &[]
+                    )
+                    .await
+                    .unwrap()
+                    .get::<_, &str>(0),
+                    label
+                );
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_cleans_unlogged_tables() {
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            move |conn| {
+                Box::pin(async move {
+                    conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                        .await
+                        .unwrap();
+                    conn.batch_execute(
+                        &postgres::convert_tables_unlogged(["book", "dummy"]).join(";"),
+                    )
+                    .await
+                    .unwrap();
+                    conn
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = conn_pool.get().await.unwrap();
+
+                // tables must be unlogged
+                assert_eq!(
+                    conn.query_one(
+                        "SELECT COUNT(*) FROM pg_catalog.pg_class WHERE relname IN ('book', 'dummy') AND relpersistence = 'u'",
+                        &[],
+                    )
+                    .await
+                    .unwrap()
+                    .get::<_, i64>(0),
+                    2
+                );
+
+                conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                    .await
+                    .unwrap();
+            }
+
+            // unlogged tables must be cleaned when database is reused
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+            assert_eq!(
+                conn.query_one("SELECT COUNT(*) FROM book", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, i64>(0),
+                0
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_lists_leased_databases() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            {
+                let immutable_conn_pool = db_pool.pull_immutable().await;
+                let mutable_conn_pool = db_pool.create_mutable().await.unwrap();
+
+                // databases held through pulled and created connection pools must be listed
+                let mut db_ids = db_pool.leased_databases();
+                db_ids.sort();
+                let mut expected_db_ids = vec![
+                    immutable_conn_pool.database_id(),
+                    mutable_conn_pool.database_id(),
+                ];
+                expected_db_ids.sort();
+                assert_eq!(db_ids, expected_db_ids);
+            }
+
+            // returned databases must no longer be listed
+            assert!(db_pool.leased_databases().is_empty());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn dyn_pool_hands_out_connection_pools() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = DynDatabasePool::from(backend.create_database_pool().await.unwrap());
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let mutable_conn_pool = db_pool.create_mutable().await.unwrap();
+
+            // leased databases must be tracked through the erased pool
+            let mut db_ids = db_pool.leased_databases();
+            db_ids.sort();
+            let mut expected_db_ids =
+                vec![conn_pool.database_id(), mutable_conn_pool.database_id()];
+            expected_db_ids.sort();
+            assert_eq!(db_ids, expected_db_ids);
+
+            // underlying pool must be recoverable by its type
+            assert!(conn_pool.pool::<String>().is_none());
+            let pool = conn_pool
+                .pool::<Pool<PostgresConnectionManager<MakeTls>>>()
+                .unwrap();
+            let conn = pool.get().await.unwrap();
+            assert_eq!(
+                conn.query_one("SELECT current_database()", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, &str>(0),
+                conn_pool.database_name()
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_verifies_clean_database() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .verify_clean(true);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = conn_pool.get().await.unwrap();
+                conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                    .await
+                    .unwrap();
+            }
+
+            // verified database must be handed out clean
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+            assert_eq!(
+                conn.query_one("SELECT COUNT(*) FROM book", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, i64>(0),
+                0
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_discards_database_on_failed_entity_creation_in_transaction() {
+        // entity creation either panics or swallows the failed statement
+        for swallows_error in [false, true] {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+                config,
+                Pool::builder,
+                Pool::builder,
+                move |conn| {
+                    Box::pin(async move {
+                        conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                            .await
+                            .unwrap();
+                        let inserted = conn
+                            .batch_execute("INSERT INTO missing (id) VALUES (1)")
+                            .await;
+                        if !swallows_error {
+                            inserted.unwrap();
+                        }
+                        conn
+                    })
+                },
+            )
+            .await
+            .unwrap()
+            .drop_previous_databases(false)
+            .create_entities_in_transaction(true);
+
+            async {
+                let db_id = Uuid::new_v4();
+                let db_name = get_db_name(db_id);
+
+                // database creation must fail
+                assert!(matches!(
+                    backend.create(db_id, true).await,
+                    Err(BackendError::FailedEntityCreation(id)) if id == db_id
+                ));
+
+                // partially created database and role must be dropped
+                let conn = &mut backend.default_pool.get().await.unwrap();
+                assert!(conn
+                    .query_opt(
+                        "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                        &[&db_name],
+                    )
+                    .await
+                    .unwrap()
+                    .is_none());
+                assert!(conn
+                    .query_opt(
+                        "SELECT 1 FROM pg_catalog.pg_roles WHERE rolname = $1",
+                        &[&db_name],
+                    )
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+            .lock_read()
+            .await;
+        }
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_url() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // URL must connect to the pulled database as its attached role
+            let (client, connection) =
+                tokio_postgres::connect(conn_pool.database_url().unwrap().as_str(), NoTls)
+                    .await
+                    .unwrap();
+            tokio::spawn(connection);
+            assert_eq!(
+                client
+                    .query_one("SELECT current_database()", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, String>(0),
+                conn_pool.database_name()
+            );
+            assert!(client
+                .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .await
+                .is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_url_with_encoded_credentials() {
+        const PASSWORD: &str = "p@ss:w/o'rd?#%";
+
+        let role_name = format!("reused_{}", Uuid::new_v4().simple());
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            // Create role with reserved characters in its password
+            client
+                .batch_execute(
+                    format!(
+                        "CREATE ROLE {role_name} LOGIN PASSWORD {}",
+                        postgres::quote_literal(PASSWORD)
+                    )
+                    .as_str(),
+                )
+                .await
+                .unwrap();
+
+            let backend = create_backend(true)
+                .await
+                .drop_previous_databases(false)
+                .reuse_role(role_name.as_str(), PASSWORD);
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            {
+                let conn_pool = db_pool.pull_immutable().await;
+
+                // URL must connect as the reused role despite reserved characters in its password
+                let (conn_client, connection) =
+                    tokio_postgres::connect(conn_pool.database_url().unwrap().as_str(), NoTls)
+                        .await
+                        .unwrap();
+                tokio::spawn(connection);
+                assert_eq!(
+                    conn_client
+                        .query_one("SELECT current_user", &[])
+                        .await
+                        .unwrap()
+                        .get::<_, String>(0),
+                    role_name
+                );
+            }
+
+            // Drop role along with databases it was granted access to
+            db_pool.teardown().await.unwrap();
+            client
+                .batch_execute(postgres::drop_role(role_name.as_str()).as_str())
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_database_name_to_entity_creation() {
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new_with_context(
+            config,
+            Pool::builder,
+            Pool::builder,
+            move |conn, db_name| {
+                let query = format!("CREATE TABLE tenant AS SELECT '{db_name}'::TEXT AS name");
+                Box::pin(async move {
+                    conn.batch_execute(query.as_str()).await.unwrap();
+                    conn
+                })
+            },
+        )
+        .await
+        .unwrap()
+        .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+
+            // entities must be created with name of database
+            assert_eq!(
+                conn.query_one("SELECT name FROM tenant", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, String>(0),
+                conn_pool.database_name()
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_created_restricted_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_created_restricted_databases(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_prewarmed_databases() {
+        let backend = create_backend(false).await;
+        test_pool_drops_prewarmed_databases(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_returned_databases_in_drop_clean_mode() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+        test_pool_drops_returned_databases_in_drop_clean_mode(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_tears_down_databases() {
+        let backend = create_backend(false).await;
+        test_pool_tears_down_databases(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_drops_created_unrestricted_database() {
+        let backend = create_backend(false).await;
+        test_pool_drops_created_unrestricted_database(backend).await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_pings_server() {
+        // reachable server must respond
+        assert!(create_backend(false).await.ping().await.is_ok());
+
+        // unreachable server must fail with a pool error
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .port(1)
+            .user("postgres")
+            .password("postgres");
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+            config,
+            || Pool::builder().connection_timeout(Duration::from_secs(1)),
+            Pool::builder,
+            |conn| Box::pin(async { conn }),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(backend.ping().await, Err(BackendError::Pool(_))));
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_after_connect_hook() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .after_connect(|conn| Box::pin(conn.batch_execute("SET statement_timeout = 1234")));
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+
+            // restricted connections must carry session settings applied by hook
+            assert_eq!(
+                conn.query_one("SHOW statement_timeout", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, String>(0),
+                "1234ms"
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_refreshes_schema() {
+        let stmt = Arc::new(Mutex::new(
+            "CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)",
+        ));
+
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        let backend =
+            TokioPostgresBackend::<TokioPostgresBb8>::new(config, Pool::builder, Pool::builder, {
+                let stmt = stmt.clone();
+                move |conn| {
+                    let stmt = *stmt.lock();
+                    Box::pin(async move {
+                        conn.batch_execute(stmt).await.unwrap();
+                        conn
+                    })
+                }
+            })
+            .await
+            .unwrap()
+            .drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let db_id = {
+                let conn_pool = db_pool.pull_immutable().await;
+
+                // leased database must not be refreshed
+                assert!(matches!(
+                    db_pool.refresh_schema().await,
+                    Err(BackendError::LeasedDatabases(db_ids)) if db_ids == vec![conn_pool.database_id()]
+                ));
+
+                conn_pool.database_id()
+            };
+
+            // idle database must be refreshed with new entities
+            *stmt.lock() = "CREATE TABLE author(id SERIAL PRIMARY KEY)";
+            db_pool.refresh_schema().await.unwrap();
+
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+
+            // database must be kept
+            assert_eq!(conn_pool.database_id(), db_id);
+
+            // previous tables must be dropped
+            assert!(conn.execute("SELECT * FROM book", &[]).await.is_err());
+
+            // new tables must be usable by restricted user
+            assert!(conn
+                .execute("INSERT INTO author DEFAULT VALUES", &[])
+                .await
+                .is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_connects_through_connector() {
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .create_entities_as(Role::Restricted)
+            .connector({
+                let connections = connections.clone();
+                move |config| {
+                    connections.fetch_add(1, Ordering::Relaxed);
+                    Box::pin(async move {
+                        let (client, connection) = config.connect(NoTls).await?;
+                        tokio::spawn(connection);
+                        Ok(client)
+                    })
+                }
+            });
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+
+            // privileged and restricted database connections must be established through connector
+            assert_eq!(connections.load(Ordering::Relaxed), 2);
+
+            // entities created through connector must be usable
+            let conn = conn_pool.get().await.unwrap();
+            assert!(conn
+                .execute("INSERT INTO book (title) VALUES ('Title')", &[])
+                .await
+                .is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_recreates_schema_on_clean() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .create_entities_as(Role::Restricted)
+            .clean_strategy(CleanStrategy::Recreate);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let db_id = db_pool.pull_immutable().await.database_id();
+
+            // schema must be restored as often as it is altered
+            for _ in 0..3 {
+                let conn_pool = db_pool.pull_immutable().await;
+                let conn = conn_pool.get().await.unwrap();
+
+                // database must be kept
+                assert_eq!(conn_pool.database_id(), db_id);
+
+                // base schema must be restored with fresh sequences
+                assert_eq!(
+                    conn.query_one(
+                        "INSERT INTO book (title) VALUES ('Title') RETURNING id",
+                        &[]
+                    )
+                    .await
+                    .unwrap()
+                    .get::<_, i32>(0),
+                    1
+                );
+                assert!(conn
+                    .execute("INSERT INTO dummy DEFAULT VALUES", &[])
+                    .await
+                    .is_ok());
+
+                // alter schema as restricted user
+                conn.batch_execute("ALTER TABLE book DROP COLUMN title; DROP TABLE dummy")
+                    .await
+                    .unwrap();
+            }
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_read_only_databases() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .read_only(true);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+
+            // reads must succeed
+            assert!(conn.query("SELECT * FROM book", &[]).await.is_ok());
+
+            // writes must fail
+            assert!(conn
+                .execute("INSERT INTO book (title) VALUES ('Title')", &[])
+                .await
+                .is_err());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_runs_ddl_through_unrestricted_databases() {
+        // helper accepting only unrestricted connection pools
+        async fn create_table(
+            conn_pool: &SingleUseConnectionPool<
+                TokioPostgresBackend<TokioPostgresBb8>,
+                Unrestricted,
+            >,
+        ) {
+            let conn = conn_pool.assert_can_ddl().get().await.unwrap();
+            conn.batch_execute("CREATE TABLE genre (id SERIAL PRIMARY KEY)")
+                .await
+                .unwrap();
+        }
+
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            create_table(&conn_pool).await;
+
+            let conn = conn_pool.get().await.unwrap();
+            assert!(conn
+                .execute("INSERT INTO genre DEFAULT VALUES", &[])
+                .await
+                .is_ok());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_asserts_restricted_databases() {
+        let create_backend = |grants_create: bool| async move {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            TokioPostgresBackend::<TokioPostgresBb8>::new(config, Pool::builder, Pool::builder, {
+                move |conn| {
+                    Box::pin(async move {
+                        conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                            .await
+                            .unwrap();
+                        if grants_create {
+                            conn.batch_execute("GRANT CREATE ON SCHEMA public TO PUBLIC")
+                                .await
+                                .unwrap();
+                        }
+                        conn
+                    })
+                }
+            })
+            .await
+            .unwrap()
+            .drop_previous_databases(false)
+        };
+
+        let restricted_backend = create_backend(false).await;
+        let unrestricted_backend = create_backend(true).await;
+
+        async {
+            // restricted databases must pass
+            let db_pool = restricted_backend.create_database_pool().await.unwrap();
+            db_pool.assert_restricted().await.unwrap();
+
+            // databases granting too much must fail
+            let db_pool = unrestricted_backend.create_database_pool().await.unwrap();
+            let db_id = db_pool.pull_immutable().await.database_id();
+            assert!(matches!(
+                db_pool.assert_restricted().await,
+                Err(BackendError::UnrestrictedDatabase(id)) if id == db_id
+            ));
+
+            // table created while checking must be dropped
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+            assert!(conn
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_tables WHERE tablename = $1",
+                    &[&postgres::PRIVILEGE_CHECK_TABLE_NAME]
+                )
+                .await
+                .unwrap()
+                .is_none());
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_creates_empty_mutable_databases() {
+        let backend = create_backend(true).await;
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.create_mutable_empty().await.unwrap();
+            let conn = conn_pool.get().await.unwrap();
+
+            // no entities must exist
+            assert!(conn
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_tables WHERE schemaname = 'public'",
+                    &[]
+                )
+                .await
+                .unwrap()
+                .is_none());
+
+            // entities must be creatable from scratch
+            conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                .await
+                .unwrap();
+            conn.execute("INSERT INTO book (title) VALUES ('Title')", &[])
+                .await
+                .unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_shares_keyed_databases() {
+        let backend = create_backend(true).await.drop_previous_databases(false);
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            let writer_conn_pool = db_pool.pull_immutable_keyed("a").await;
+            let reader_conn_pool = db_pool.pull_immutable_keyed("a").await;
+            let other_conn_pool = db_pool.pull_immutable_keyed("b").await;
+
+            // same key must share the same database
+            assert_eq!(
+                writer_conn_pool.database_id(),
+                reader_conn_pool.database_id()
+            );
+            assert_ne!(
+                writer_conn_pool.database_id(),
+                other_conn_pool.database_id()
+            );
+
+            // data written through one handle must be visible through the other
+            writer_conn_pool
+                .get()
+                .await
+                .unwrap()
+                .execute("INSERT INTO book (title) VALUES ('Title')", &[])
+                .await
+                .unwrap();
+            assert_eq!(
+                reader_conn_pool
+                    .get()
+                    .await
+                    .unwrap()
+                    .query_one("SELECT COUNT(*) FROM book", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, i64>(0),
+                1
+            );
+
+            // database must be leased until all handles are dropped
+            let db_id = writer_conn_pool.database_id();
+            drop(writer_conn_pool);
+            assert!(db_pool.leased_databases().contains(&db_id));
+            drop(reader_conn_pool);
+            assert!(!db_pool.leased_databases().contains(&db_id));
+
+            // returned database must be cleaned and reused
+            let conn_pool = db_pool.pull_immutable().await;
+            assert_eq!(conn_pool.database_id(), db_id);
+            assert_eq!(
+                conn_pool
+                    .get()
+                    .await
+                    .unwrap()
+                    .query_one("SELECT COUNT(*) FROM book", &[])
+                    .await
+                    .unwrap()
+                    .get::<_, i64>(0),
+                0
+            );
+        }
+        .lock_read()
+        .await;
+    }
+
+    #[cfg(any(
+        feature = "tokio-postgres-native-tls",
+        feature = "tokio-postgres-rustls"
+    ))]
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_provides_databases_over_tls() {
         let mut config = Config::new();
         config
             .host("localhost")
             .user("postgres")
-            .password("postgres");
-        TokioPostgresBackend::new(config, Pool::builder, Pool::builder, {
-            move |conn| {
-                if with_table {
-                    Box::pin(async move {
-                        conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
-                            .await
-                            .unwrap();
-                        conn
-                    })
-                } else {
-                    Box::pin(async { conn })
-                }
-            }
-        })
+            .password("postgres")
+            .ssl_mode(tokio_postgres::config::SslMode::Require);
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new(
+            config,
+            Pool::builder,
+            Pool::builder,
+            |conn| Box::pin(async { conn }),
+        )
         .await
         .unwrap()
-    }
+        .drop_previous_databases(false);
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn backend_drops_previous_databases() {
-        test_backend_drops_previous_databases(
-            create_backend(false).await,
-            create_backend(false).await.drop_previous_databases(true),
-            create_backend(false).await.drop_previous_databases(false),
-        )
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = conn_pool.get().await.unwrap();
+
+            // connection must be encrypted
+            assert!(conn
+                .query_one(
+                    "SELECT ssl FROM pg_catalog.pg_stat_ssl WHERE pid = pg_backend_pid()",
+                    &[]
+                )
+                .await
+                .unwrap()
+                .get::<_, bool>(0));
+        }
+        .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn backend_creates_database_with_restricted_privileges() {
+    async fn backend_caches_table_names_of_restricted_databases() {
         let backend = create_backend(true).await.drop_previous_databases(false);
-        test_backend_creates_database_with_restricted_privileges(backend).await;
-    }
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn backend_creates_database_with_unrestricted_privileges() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
-        test_backend_creates_database_with_unrestricted_privileges(backend).await;
-    }
+        async {
+            let db_id = Uuid::new_v4();
+            backend.create(db_id, true).await.unwrap();
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn backend_cleans_database_with_tables() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
-        test_backend_cleans_database_with_tables(backend).await;
+            // table names must be cached upon cleaning
+            backend.clean(db_id).await.unwrap();
+            assert!(backend.table_names_cache.lock().contains_key(&db_id));
+
+            // cached table names must be reused when cleaning again
+            backend.clean(db_id).await.unwrap();
+
+            // cached table names must be forgotten upon dropping
+            backend.drop(db_id, true).await.unwrap();
+            assert!(!backend.table_names_cache.lock().contains_key(&db_id));
+        }
+        .lock_read()
+        .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn backend_cleans_database_without_tables() {
+    async fn pool_drops_databases_on_provided_runtime() {
         let backend = create_backend(false).await.drop_previous_databases(false);
-        test_backend_cleans_database_without_tables(backend).await;
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .runtime_handle(Handle::current());
+
+            // database must be dropped on provided runtime when dropped outside of any runtime
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            block_in_place(|| thread::spawn(move || drop(conn_pool)).join().unwrap());
+            assert!(client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .await
+                .unwrap()
+                .is_none());
+            assert!(db_pool.take_teardown_errors().is_empty());
+        }
+        .lock_read()
+        .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn backend_drops_restricted_database() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
-        test_backend_drops_database(backend, true).await;
+    async fn pool_drops_databases_on_creating_runtime() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // database must be dropped on the runtime the pool was created on when dropped outside of any runtime
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            block_in_place(|| thread::spawn(move || drop(conn_pool)).join().unwrap());
+            assert!(client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .await
+                .unwrap()
+                .is_none());
+            assert!(db_pool.take_teardown_errors().is_empty());
+        }
+        .lock_read()
+        .await;
     }
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn backend_drops_unrestricted_database() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
-        test_backend_drops_database(backend, false).await;
+    #[tokio::test]
+    async fn pool_drops_databases_inside_current_thread_runtime() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // database must be dropped before drop returns despite the current-thread runtime being blocked
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            drop(conn_pool);
+            assert!(client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .await
+                .unwrap()
+                .is_none());
+            assert!(db_pool.take_teardown_errors().is_empty());
+        }
+        .lock_read()
+        .await;
     }
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn pool_drops_previous_databases() {
-        test_pool_drops_previous_databases(
-            create_backend(false).await,
-            create_backend(false).await.drop_previous_databases(true),
-            create_backend(false).await.drop_previous_databases(false),
-        )
+    #[tokio::test]
+    async fn pool_drops_databases_on_provided_current_thread_runtime() {
+        let backend = create_backend(false).await.drop_previous_databases(false);
+
+        async {
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
+
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .runtime_handle(Handle::current());
+
+            // database must be dropped before drop returns when given a current-thread runtime
+            let conn_pool = db_pool.create_mutable().await.unwrap();
+            let db_name = conn_pool.database_name().to_owned();
+            drop(conn_pool);
+            assert!(client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .await
+                .unwrap()
+                .is_none());
+            assert!(db_pool.take_teardown_errors().is_empty());
+        }
+        .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn pool_provides_isolated_databases() {
-        const NUM_DBS: i64 = 3;
-
-        let backend = create_backend(true).await.drop_previous_databases(false);
+    async fn pool_cleans_all_available_databases() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .metrics_callback({
+                let events = events.clone();
+                move |event| events.lock().push(event)
+            });
+        let count_cleans = || {
+            events
+                .lock()
+                .iter()
+                .filter(|event| event.operation == MetricOperation::Clean)
+                .count()
+        };
 
         async {
             let db_pool = backend.create_database_pool().await.unwrap();
-            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
 
-            // insert single row into each database
-            join_all(
-                conn_pools
-                    .iter()
-                    .enumerate()
-                    .map(|(i, conn_pool)| async move {
-                        let conn = &mut conn_pool.get().await.unwrap();
-                        conn.execute(
-                            "INSERT INTO book (title) VALUES ($1)",
-                            &[&format!("Title {i}").as_str()],
-                        )
-                        .await
-                        .unwrap();
-                    }),
-            )
-            .await;
+            let _leased_conn_pool = db_pool.pull_immutable().await;
+            {
+                let _conn_pool_1 = db_pool.pull_immutable().await;
+                let _conn_pool_2 = db_pool.pull_immutable().await;
+            }
 
-            // rows fetched must be as inserted
-            join_all(
-                conn_pools
-                    .iter()
-                    .enumerate()
-                    .map(|(i, conn_pool)| async move {
-                        let conn = &mut conn_pool.get().await.unwrap();
-                        assert_eq!(
-                            conn.query("SELECT title FROM book", &[])
-                                .await
-                                .unwrap()
-                                .iter()
-                                .map(|row| row.get::<_, String>(0))
-                                .collect::<Vec<_>>(),
-                            vec![format!("Title {i}")]
-                        );
-                    }),
-            )
-            .await;
+            // returned databases must be cleaned while leased databases are skipped
+            db_pool.clean_all_available().await.unwrap();
+            assert_eq!(count_cleans(), 2);
+
+            // cleaned databases must not be cleaned again when pulled
+            let _conn_pool_1 = db_pool.pull_immutable().await;
+            let _conn_pool_2 = db_pool.pull_immutable().await;
+            assert_eq!(count_cleans(), 2);
         }
         .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn pool_provides_restricted_databases() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
+    async fn pool_drops_created_databases_when_pulling_many_fails() {
+        // Give the first database an invalid name so that only its creation fails,
+        // after the name sampled when creating the pool
+        let db_names = Arc::new(Mutex::new(Vec::<(Uuid, String)>::new()));
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .name_generator({
+                let db_names = db_names.clone();
+                move |db_id| {
+                    let mut db_names = db_names.lock();
+                    if let Some((_, db_name)) = db_names.iter().find(|(id, _)| *id == db_id) {
+                        return db_name.clone();
+                    }
+                    let db_name = if db_names.len() == 1 {
+                        "invalid-name".to_owned()
+                    } else {
+                        get_db_name(db_id)
+                    };
+                    db_names.push((db_id, db_name.clone()));
+                    db_name
+                }
+            });
 
         async {
-            let db_pool = backend.create_database_pool().await.unwrap();
+            let mut config = Config::new();
+            config
+                .host("localhost")
+                .user("postgres")
+                .password("postgres");
+            let (client, connection) = config.connect(NoTls).await.unwrap();
+            tokio::spawn(connection);
 
-            let conn_pool = db_pool.pull_immutable().await;
-            let conn = &mut conn_pool.get().await.unwrap();
+            let db_pool = backend.create_database_pool().await.unwrap();
 
-            // DDL statements must fail
-            for stmt in DDL_STATEMENTS {
-                assert!(conn.execute(stmt, &[]).await.is_err());
+            // pulling must fail once all databases are created
+            assert!(matches!(
+                db_pool.pull_many(3).await,
+                Err(BackendError::InvalidDatabaseName(_))
+            ));
+            let created_db_names = db_names
+                .lock()
+                .iter()
+                .skip(2)
+                .map(|(_, db_name)| db_name.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(created_db_names.len(), 2);
+
+            // databases created alongside the failed one must be dropped
+            for db_name in created_db_names {
+                assert!(client
+                    .query_opt(
+                        "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                        &[&db_name],
+                    )
+                    .await
+                    .unwrap()
+                    .is_none());
             }
+            assert!(db_pool.take_teardown_errors().is_empty());
+        }
+        .lock_read()
+        .await;
+    }
 
-            // DML statements must succeed
-            for stmt in DML_STATEMENTS {
-                assert!(conn.execute(stmt, &[]).await.is_ok());
-            }
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_rejects_invalid_generated_database_names() {
+        // names must be checked before any database is created
+        let backend = create_backend(false)
+            .await
+            .drop_previous_databases(false)
+            .name_generator(|db_id| format!("db-pool-{db_id}"));
+        assert!(matches!(
+            backend.create_database_pool().await,
+            Err(BackendError::InvalidDatabaseName(_))
+        ));
+    }
+
+    #[test(flavor = "multi_thread", shared)]
+    async fn pool_skips_cleaning_prewarmed_databases() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .metrics_callback({
+                let events = events.clone();
+                move |event| events.lock().push(event)
+            });
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+            db_pool.prewarm(2).await.unwrap();
+
+            // prewarmed databases must be pulled without being cleaned
+            let conn_pool_1 = db_pool.pull_immutable().await;
+            let conn_pool_2 = db_pool.pull_immutable().await;
+            assert!(conn_pool_1.was_reused() && conn_pool_2.was_reused());
+            assert!(!events
+                .lock()
+                .iter()
+                .any(|event| event.operation == MetricOperation::Clean));
         }
         .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn pool_provides_unrestricted_databases() {
-        let backend = create_backend(true).await.drop_previous_databases(false);
+    async fn pool_cleans_databases_with_custom_routine() {
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .custom_clean(|conn| Box::pin(conn.batch_execute("DELETE FROM book")));
 
         async {
             let db_pool = backend.create_database_pool().await.unwrap();
 
-            // DML statements must succeed
             {
-                let conn_pool = db_pool.create_mutable().await.unwrap();
+                let conn_pool = db_pool.pull_immutable().await;
                 let conn = &mut conn_pool.get().await.unwrap();
-                for stmt in DML_STATEMENTS {
-                    assert!(conn.execute(stmt, &[]).await.is_ok());
-                }
+                conn.batch_execute(
+                    "INSERT INTO book (title) VALUES ('Title'); INSERT INTO dummy DEFAULT VALUES",
+                )
+                .await
+                .unwrap();
             }
 
-            // DDL statements must succeed
-            for stmt in DDL_STATEMENTS {
-                let conn_pool = db_pool.create_mutable().await.unwrap();
-                let conn = &mut conn_pool.get().await.unwrap();
-                assert!(conn.execute(stmt, &[]).await.is_ok());
-            }
+            // only the custom routine must run in place of truncating tables
+            let conn_pool = db_pool.pull_immutable().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+            let row = conn
+                .query_one(
+                    "SELECT (SELECT COUNT(*) FROM book), (SELECT COUNT(*) FROM dummy)",
+                    &[],
+                )
+                .await
+                .unwrap();
+            assert_eq!(row.get::<_, i64>(0), 0);
+            assert_eq!(row.get::<_, i64>(1), 1);
         }
         .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn pool_provides_clean_databases() {
-        const NUM_DBS: i64 = 3;
-
-        let backend = create_backend(true).await.drop_previous_databases(false);
+    async fn backend_retries_clean_on_deadlock() {
+        const RAISE_DEADLOCK: &str =
+            "DO $$ BEGIN RAISE EXCEPTION 'deadlock detected' USING ERRCODE = 'deadlock_detected'; END $$";
 
-        async {
-            let db_pool = backend.create_database_pool().await.unwrap();
+        // number of upcoming cleans to fail on a deadlock
+        let deadlocks = Arc::new(AtomicUsize::new(0));
 
-            // fetch connection pools the first time
-            {
-                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+        let backend = create_backend(true)
+            .await
+            .drop_previous_databases(false)
+            .clean_in_transaction(true)
+            .clean_retries(2)
+            .custom_clean({
+                let deadlocks = deadlocks.clone();
+                move |conn| {
+                    let deadlocked = deadlocks
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                        .is_ok();
+                    Box::pin(conn.batch_execute(if deadlocked {
+                        RAISE_DEADLOCK
+                    } else {
+                        "DELETE FROM book"
+                    }))
+                }
+            });
 
-                // databases must be empty
-                join_all(conn_pools.iter().map(|conn_pool| async move {
-                    let conn = &mut conn_pool.get().await.unwrap();
-                    assert_eq!(
-                        conn.query_one("SELECT COUNT(*) FROM book", &[])
-                            .await
-                            .unwrap()
-                            .get::<_, i64>(0),
-                        0
-                    );
-                }))
-                .await;
+        async {
+            let db_id = Uuid::new_v4();
+            backend.create(db_id, true).await.unwrap();
+
+            // cleaning must succeed within the allowed number of retries
+            deadlocks.store(2, Ordering::Relaxed);
+            backend.clean(db_id).await.unwrap();
+            assert_eq!(deadlocks.load(Ordering::Relaxed), 0);
+
+            // cleaning must fail once retries are exhausted
+            deadlocks.store(3, Ordering::Relaxed);
+            assert!(matches!(
+                backend.clean(db_id).await,
+                Err(BackendError::Query(err)) if err.code() == Some(&SqlState::T_R_DEADLOCK_DETECTED)
+            ));
+            assert_eq!(deadlocks.load(Ordering::Relaxed), 0);
+
+            // stored connection must be kept outside of a transaction after failing to clean
+            backend.clean(db_id).await.unwrap();
+
+            backend.drop(db_id, true).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
 
-                // insert data into each database
-                join_all(conn_pools.iter().map(|conn_pool| async move {
-                    let conn = &mut conn_pool.get().await.unwrap();
-                    conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
-                        .await
-                        .unwrap();
-                }))
-                .await;
+    #[test(flavor = "multi_thread", shared)]
+    async fn backend_times_out_connecting_to_unresponsive_server() {
+        const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+        // server accepting connections without ever responding
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
             }
+        });
 
-            // fetch same connection pools a second time
-            {
-                let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+        let mut config = Config::new();
+        config
+            .host("localhost")
+            .user("postgres")
+            .password("postgres");
+        let privileged_pool = Pool::builder()
+            .build(PostgresConnectionManager::new(config.clone(), NoTls))
+            .await
+            .unwrap();
+        config.host("127.0.0.1").port(port);
+
+        let backend = TokioPostgresBackend::<TokioPostgresBb8>::new_with_privileged_pool(
+            config,
+            privileged_pool,
+            Pool::builder,
+            |conn| Box::pin(async { conn }),
+        )
+        .drop_previous_databases(false)
+        .connect_timeout(CONNECT_TIMEOUT);
 
-                // databases must be empty
-                join_all(conn_pools.iter().map(|conn_pool| async move {
-                    let conn = &mut conn_pool.get().await.unwrap();
-                    assert_eq!(
-                        conn.query_one("SELECT COUNT(*) FROM book", &[])
-                            .await
-                            .unwrap()
-                            .get::<_, i64>(0),
-                        0
-                    );
-                }))
-                .await;
-            }
+        async {
+            // connecting to created database must time out
+            let db_id = Uuid::new_v4();
+            assert!(matches!(
+                backend.create(db_id, true).await,
+                Err(BackendError::ConnectTimeout(timeout)) if timeout == CONNECT_TIMEOUT
+            ));
+            backend.drop(db_id, true).await.unwrap();
         }
         .lock_read()
         .await;
     }
 
     #[test(flavor = "multi_thread", shared)]
-    async fn pool_drops_created_restricted_databases() {
-        let backend = create_backend(false).await;
-        test_pool_drops_created_restricted_databases(backend).await;
-    }
+    async fn pool_provides_separate_reader_pool() {
+        let default = create_backend(true).await.drop_previous_databases(false);
+        let enabled = create_backend(true).await.drop_previous_databases(false);
 
-    #[test(flavor = "multi_thread", shared)]
-    async fn pool_drops_created_unrestricted_database() {
-        let backend = create_backend(false).await;
-        test_pool_drops_created_unrestricted_database(backend).await;
+        async {
+            // reader must be the writer by default
+            {
+                let db_pool = default.create_database_pool().await.unwrap();
+                let conn_pool = db_pool.pull_immutable().await;
+                assert!(std::ptr::eq(conn_pool.reader(), conn_pool.writer()));
+            }
+
+            let db_pool = enabled
+                .create_database_pool()
+                .await
+                .unwrap()
+                .separate_reader_pool(true);
+            let conn_pool = db_pool.pull_immutable().await;
+            assert!(!std::ptr::eq(conn_pool.reader(), conn_pool.writer()));
+
+            let writer = conn_pool.writer().get().await.unwrap();
+            let reader = conn_pool.reader().get().await.unwrap();
+
+            // reader must read what writer wrote over a distinct connection to the same database
+            writer
+                .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .await
+                .unwrap();
+            let title = reader
+                .query_one("SELECT title FROM book", &[])
+                .await
+                .unwrap()
+                .get::<_, String>(0);
+            assert_eq!(title, "Title");
+
+            let get_pid_and_database = "SELECT pg_backend_pid(), current_database()";
+            let writer_row = writer.query_one(get_pid_and_database, &[]).await.unwrap();
+            let reader_row = reader.query_one(get_pid_and_database, &[]).await.unwrap();
+            assert_ne!(writer_row.get::<_, i32>(0), reader_row.get::<_, i32>(0));
+            assert_eq!(writer_row.get::<_, &str>(1), conn_pool.database_name());
+            assert_eq!(reader_row.get::<_, &str>(1), conn_pool.database_name());
+        }
+        .lock_read()
+        .await;
     }
 }
@@ -1,17 +1,39 @@
 use std::{
     borrow::Cow,
     fmt::Debug,
+    future::Future,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    time::Duration,
+    time::Instant,
 };
 
 use async_trait::async_trait;
+use futures::FutureExt;
+use tokio::time::{sleep, timeout};
 use uuid::Uuid;
 
-use crate::{common::statement::postgres, util::get_db_name};
+use crate::{
+    common::{
+        config::{CleanStrategy, PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricOperation, MetricsCallback},
+        statement::postgres,
+    },
+    util::{
+        get_clean_retry_backoff, is_valid_db_name, PreviousDatabaseQuery, TableNamesCache,
+        DB_NAME_PREFIX,
+    },
+};
 
 use super::super::error::Error as BackendError;
 
+pub(super) type CustomClean<C, E> = dyn for<'c> Fn(&'c mut C) -> Pin<Box<dyn Future<Output = Result<(), E>> + Send + 'c>>
+    + Send
+    + Sync
+    + 'static;
+
 #[async_trait]
 pub(super) trait PostgresBackend<'pool>: Send + Sync + 'static {
     type Connection;
@@ -64,6 +86,9 @@ pub(super) trait PostgresBackend<'pool>: Send + Sync + 'static {
 
     async fn get_default_connection(&'pool self)
         -> Result<Self::PooledConnection, Self::PoolError>;
+    async fn establish_privileged_connection(
+        &self,
+    ) -> Result<Self::Connection, Self::ConnectionError>;
     async fn establish_privileged_database_connection(
         &self,
         db_id: Uuid,
@@ -73,21 +98,85 @@ pub(super) trait PostgresBackend<'pool>: Send + Sync + 'static {
         db_id: Uuid,
     ) -> Result<Self::Connection, Self::ConnectionError>;
     fn put_database_connection(&self, db_id: Uuid, conn: Self::Connection);
-    fn get_database_connection(&self, db_id: Uuid) -> Self::Connection;
+    fn get_database_connection(&self, db_id: Uuid) -> Option<Self::Connection>;
 
     async fn get_previous_database_names(
         &self,
         conn: &mut Self::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
-    async fn create_entities(&self, conn: Self::Connection) -> Self::Connection;
+    async fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_previous_owned_database_names(
+        &self,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_previous_role_names(
+        &self,
+        conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    async fn create_entities(&self, conn: Self::Connection, db_name: &str) -> Self::Connection;
     async fn create_connection_pool(&self, db_id: Uuid) -> Result<Self::Pool, Self::BuildError>;
+    async fn warm_up_connection_pool(&self, pool: &Self::Pool) -> Result<(), Self::PoolError>;
 
     async fn get_table_names(
         &self,
         privileged_conn: &mut Self::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
+    async fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        privileged_conn: &mut Self::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+
+    fn is_duplicate_database_error(&self, err: &Self::QueryError) -> bool;
+    fn is_duplicate_role_error(&self, err: &Self::QueryError) -> bool;
+    fn is_transient_lock_error(&self, err: &Self::QueryError) -> bool;
 
     fn get_drop_previous_databases(&self) -> bool;
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery>;
+    fn get_only_drop_owned(&self) -> bool;
+    fn get_schema_version(&self) -> Option<&str>;
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool;
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool;
+    fn get_force_drop(&self) -> bool;
+    fn get_reused_role(&self) -> Option<&str>;
+    fn get_dialect(&self) -> PgDialect;
+    fn get_clean_strategy(&self) -> CleanStrategy;
+    fn get_create_entities_in_transaction(&self) -> bool;
+    fn get_clean_in_transaction(&self) -> bool;
+    fn get_verify_clean(&self) -> bool;
+    fn get_clean_retries(&self) -> usize;
+    fn get_table_names_cache(&self) -> &TableNamesCache;
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback>;
+    fn get_connect_timeout(&self) -> Option<Duration>;
+    fn get_eager_connect(&self) -> bool;
+    fn get_custom_clean(&self) -> Option<&CustomClean<Self::Connection, Self::QueryError>>;
+    fn get_db_name(&self, db_id: Uuid) -> String;
+    fn get_encoding(&self) -> Option<&str>;
+    fn get_lc_collate(&self) -> Option<&str>;
+    fn get_lc_ctype(&self) -> Option<&str>;
+    fn get_template(&self) -> Option<&str>;
+    fn get_extensions(&self) -> &[String];
+    fn get_group_role(&self) -> bool;
+    fn get_search_path(&self) -> &[String];
+    fn get_role_attributes(&self) -> &[String];
+    fn get_schemas(&self) -> &[String];
+    fn get_recreate_on_conflict(&self) -> bool;
+    fn get_owner_at_creation(&self) -> bool;
+    fn get_create_entities_as(&self) -> Role;
+    fn get_privilege_profile(&self) -> PrivilegeProfile;
+    fn get_read_only(&self) -> bool;
+    fn get_store_clean_connections(&self) -> bool;
 }
 
 pub(super) struct PostgresBackendWrapper<'backend, 'pool, B: PostgresBackend<'pool>> {
@@ -102,139 +191,951 @@ impl<'backend, 'pool, B: PostgresBackend<'pool>> PostgresBackendWrapper<'backend
             _marker: &PhantomData,
         }
     }
-}
+}
+
+impl<'backend, 'pool, B: PostgresBackend<'pool>> Deref
+    for PostgresBackendWrapper<'backend, 'pool, B>
+{
+    type Target = B;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner
+    }
+}
+
+/// Privileged connection to a database, released once dropped so that it is stored back
+/// for reuse whether the operation using it succeeds or fails
+struct DatabaseConnection<'wrapper, 'backend, 'pool, B: PostgresBackend<'pool>> {
+    wrapper: &'wrapper PostgresBackendWrapper<'backend, 'pool, B>,
+    db_id: Uuid,
+    conn: Option<B::Connection>,
+}
+
+impl<'pool, B: PostgresBackend<'pool>> DatabaseConnection<'_, '_, 'pool, B> {
+    fn take(&mut self) -> B::Connection {
+        self.conn
+            .take()
+            .expect("conn must always contain a [Some] value")
+    }
+
+    fn put(&mut self, conn: B::Connection) {
+        self.conn = Some(conn);
+    }
+
+    fn discard(mut self) {
+        self.conn = None;
+    }
+}
+
+impl<'pool, B: PostgresBackend<'pool>> Deref for DatabaseConnection<'_, '_, 'pool, B> {
+    type Target = B::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn
+            .as_ref()
+            .expect("conn must always contain a [Some] value")
+    }
+}
+
+impl<'pool, B: PostgresBackend<'pool>> DerefMut for DatabaseConnection<'_, '_, 'pool, B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.conn
+            .as_mut()
+            .expect("conn must always contain a [Some] value")
+    }
+}
+
+impl<'pool, B: PostgresBackend<'pool>> Drop for DatabaseConnection<'_, '_, 'pool, B> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.wrapper.release_database_connection(self.db_id, conn);
+        }
+    }
+}
+
+impl<'backend, 'pool, B: PostgresBackend<'pool>> PostgresBackendWrapper<'backend, 'pool, B> {
+    #[cfg_attr(not(feature = "logging"), allow(clippy::unused_self))]
+    fn is_dry_run(&self) -> bool {
+        #[cfg(feature = "logging")]
+        return self.get_dry_run();
+        #[cfg(not(feature = "logging"))]
+        false
+    }
+
+    async fn execute_query(
+        &self,
+        query: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        if self.get_log_statements() || self.get_dry_run() {
+            log::info!("{query}");
+        }
+
+        // Skip executing statement in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.inner.execute_query(query, conn).await
+    }
+
+    async fn batch_execute_query<'a>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'a, str>> + Send,
+        conn: &mut B::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        let query = {
+            let query = query.into_iter().collect::<Vec<_>>();
+            if self.get_log_statements() || self.get_dry_run() {
+                for stmt in &query {
+                    log::info!("{stmt}");
+                }
+            }
+            query
+        };
+
+        // Skip executing statements in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.inner.batch_execute_query(query, conn).await
+    }
+
+    async fn get_cached_table_names(
+        &self,
+        db_id: Uuid,
+        conn: &mut B::Connection,
+    ) -> Result<Vec<String>, B::QueryError> {
+        if let Some(table_names) = self.get_table_names_cache().lock().get(&db_id) {
+            return Ok(table_names.clone());
+        }
+
+        let table_names = self.get_table_names(conn).await?;
+        self.get_table_names_cache()
+            .lock()
+            .insert(db_id, table_names.clone());
+        Ok(table_names)
+    }
+
+    async fn connect<T, E>(
+        &self,
+        connection: impl Future<Output = Result<T, E>> + Send,
+    ) -> Result<T, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    where
+        E: Into<BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>,
+    {
+        // Give up connecting once timeout is reached if set
+        let result = if let Some(connect_timeout) = self.get_connect_timeout() {
+            timeout(connect_timeout, connection)
+                .await
+                .map_err(|_| BackendError::ConnectTimeout(connect_timeout))?
+        } else {
+            connection.await
+        };
+        result.map_err(Into::into)
+    }
+
+    fn report_metric(
+        &self,
+        operation: MetricOperation,
+        db_id: Uuid,
+        start: Instant,
+        cleaned_tables: Option<usize>,
+    ) {
+        if let Some(metrics_callback) = self.get_metrics_callback() {
+            metrics_callback(MetricEvent {
+                operation,
+                db_id,
+                elapsed: start.elapsed(),
+                cleaned_tables,
+            });
+        }
+    }
+
+    async fn create_entities(
+        &self,
+        mut conn: B::Connection,
+        db_name: &str,
+        db_id: Uuid,
+    ) -> Result<
+        B::Connection,
+        BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        if !self.get_create_entities_in_transaction() {
+            return Ok(self.inner.create_entities(conn, db_name).await);
+        }
+
+        // Start transaction
+        self.execute_query(postgres::START_TRANSACTION, &mut conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Create entities, rolling back by dropping connection if entity creation panics
+        let mut conn = AssertUnwindSafe(self.inner.create_entities(conn, db_name))
+            .catch_unwind()
+            .await
+            .map_err(|_| BackendError::FailedEntityCreation(db_id))?;
+
+        // Roll back if a statement failed, since committing an aborted transaction silently rolls it back
+        if self
+            .execute_query(postgres::CHECK_TRANSACTION, &mut conn)
+            .await
+            .is_err()
+        {
+            self.execute_query(postgres::ROLLBACK_TRANSACTION, &mut conn)
+                .await
+                .map_err(Into::into)?;
+            return Err(BackendError::FailedEntityCreation(db_id));
+        }
+
+        // Commit transaction
+        self.execute_query(postgres::COMMIT_TRANSACTION, &mut conn)
+            .await
+            .map_err(Into::into)?;
+
+        Ok(conn)
+    }
+
+    async fn grant_unrestricted_privileges(
+        &self,
+        db_name: &str,
+        role_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        if self.get_privilege_profile().transfers_ownership() {
+            // Grant database ownership
+            self.execute_query(
+                postgres::grant_database_ownership(db_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)
+        } else {
+            // Grant all privileges on database and public schema in place of ownership
+            self.execute_query(
+                postgres::grant_database_privileges(db_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+            self.execute_query(
+                postgres::grant_schema_privileges(postgres::PUBLIC_SCHEMA_NAME, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)
+        }
+    }
+
+    async fn grant_schema_creation(
+        &self,
+        role_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        for schema_name in self.get_schemas() {
+            self.execute_query(
+                postgres::grant_schema_creation(schema_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_schema_creation(
+        &self,
+        role_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        for schema_name in self.get_schemas() {
+            self.execute_query(
+                postgres::revoke_schema_creation(schema_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+
+    async fn create_extensions(
+        &self,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        for extension in self.get_extensions() {
+            self.execute_query(postgres::create_extension(extension).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+
+    async fn grant_restricted_privileges(
+        &self,
+        role_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let read_only = self.get_read_only();
+        for schema_name in self.get_schemas() {
+            // Grant schema usage to restricted role
+            self.execute_query(
+                postgres::grant_schema_usage(schema_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+
+            // Grant table privileges to restricted role, limited to reading if needed
+            self.execute_query(
+                if read_only {
+                    postgres::grant_read_only_table_privileges(schema_name, role_name)
+                } else {
+                    postgres::grant_restricted_table_privileges(schema_name, role_name)
+                }
+                .as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+
+            // Grant sequence privileges to restricted role
+            self.execute_query(
+                postgres::grant_restricted_sequence_privileges(schema_name, role_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+
+            // Grant table privileges on future tables to restricted role, limited to reading if needed
+            self.execute_query(
+                if read_only {
+                    postgres::grant_read_only_default_table_privileges(schema_name, role_name)
+                } else {
+                    postgres::grant_restricted_default_table_privileges(schema_name, role_name)
+                }
+                .as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+
+            // Grant sequence privileges on future sequences to restricted role
+            self.execute_query(
+                postgres::grant_restricted_default_sequence_privileges(schema_name, role_name)
+                    .as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+
+    async fn acquire_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<
+        DatabaseConnection<'_, 'backend, 'pool, B>,
+        BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        let conn = if self.get_store_clean_connections() {
+            // Take stored privileged connection
+            self.get_database_connection(db_id)
+                .ok_or(BackendError::MissingDatabaseConnection(db_id))?
+        } else {
+            // Connect to database as privileged user
+            self.connect(self.establish_privileged_database_connection(db_id))
+                .await?
+        };
+
+        Ok(DatabaseConnection {
+            wrapper: self,
+            db_id,
+            conn: Some(conn),
+        })
+    }
+
+    fn release_database_connection(&self, db_id: Uuid, conn: B::Connection) {
+        // Store privileged connection if needed, closing it otherwise
+        if self.get_store_clean_connections() {
+            self.put_database_connection(db_id, conn);
+        }
+    }
+
+    async fn create_database(
+        &self,
+        db_name: &str,
+        owner: Option<&str>,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let create_database = postgres::create_database(
+            db_name,
+            owner,
+            self.get_encoding(),
+            self.get_lc_collate(),
+            self.get_lc_ctype(),
+            self.get_template(),
+        );
+        if let Err(err) = self.execute_query(create_database.as_str(), conn).await {
+            if !(self.get_recreate_on_conflict() && self.is_duplicate_database_error(&err)) {
+                return Err(err.into());
+            }
+            // Keep owner role created beforehand
+            self.drop_conflicting_database(db_name, owner.is_none(), conn)
+                .await?;
+            self.execute_query(create_database.as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+        Ok(())
+    }
+
+    async fn create_role(
+        &self,
+        role_name: &str,
+        group_role: Option<&str>,
+        owned_db_name: Option<&str>,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let create_role = postgres::create_role(role_name, group_role, self.get_role_attributes());
+        if let Err(err) = self.execute_query(create_role.as_str(), conn).await {
+            if !self.is_duplicate_role_error(&err) {
+                return Err(err.into());
+            }
+            if !self.get_recreate_on_conflict() {
+                return Err(BackendError::RoleExists(role_name.to_owned()));
+            }
+            // Drop database owned by role left over by a previous run if needed
+            if let Some(db_name) = owned_db_name {
+                self.terminate_database_connections(db_name, conn).await?;
+                self.execute_query(postgres::drop_database_if_exists(db_name).as_str(), conn)
+                    .await
+                    .map_err(Into::into)?;
+            }
+            // Drop role left over by a previous run
+            self.execute_query(postgres::drop_role(role_name).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+            self.execute_query(create_role.as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+        Ok(())
+    }
+
+    async fn restrict_database_connection(
+        &self,
+        db_name: &str,
+        role_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        self.execute_query(
+            postgres::revoke_public_database_connection(db_name).as_str(),
+            conn,
+        )
+        .await
+        .map_err(Into::into)?;
+        self.execute_query(
+            postgres::grant_database_connection(db_name, role_name).as_str(),
+            conn,
+        )
+        .await
+        .map_err(Into::into)
+    }
+
+    async fn terminate_database_connections(
+        &self,
+        db_name: &str,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Terminate connections on Postgres only, as CockroachDB lacks pg_terminate_backend
+        // and drops databases regardless of open connections
+        if self.get_dialect() == PgDialect::Postgres {
+            self.execute_query(
+                postgres::terminate_database_connections(db_name).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+        }
+        Ok(())
+    }
+
+    async fn drop_conflicting_database(
+        &self,
+        db_name: &str,
+        drop_role: bool,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Terminate connections to conflicting database
+        self.terminate_database_connections(db_name, conn).await?;
+
+        // Drop conflicting database
+        self.execute_query(postgres::drop_database(db_name).as_str(), conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Drop attached role if needed and not reusing an existing one
+        if drop_role && self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role_if_exists(db_name).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'backend, 'pool, B> PostgresBackendWrapper<'backend, 'pool, B>
+where
+    'backend: 'pool,
+    B: PostgresBackend<'pool>,
+{
+    async fn drop_previous_databases(
+        &self,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Get names of databases to keep since marked with the current schema version
+        let kept_db_names = if let Some(schema_version) = self.get_schema_version() {
+            self.get_database_names_with_schema_version(schema_version, conn)
+                .await
+                .map_err(Into::into)?
+        } else {
+            Vec::new()
+        };
+
+        // Get previous database names using custom query if set,
+        // restricted to databases tagged as created by a backend otherwise if needed
+        let db_names = if let Some(query) = self.get_previous_database_query() {
+            self.get_database_names(query(DB_NAME_PREFIX).as_str(), conn)
+                .await
+        } else if self.get_only_drop_owned() {
+            self.get_previous_owned_database_names(conn).await
+        } else {
+            self.get_previous_database_names(conn).await
+        }
+        .map_err(Into::into)?
+        .into_iter()
+        .filter(|db_name| !kept_db_names.contains(db_name))
+        .collect::<Vec<_>>();
+
+        // Drop databases using the same connection to avoid exhausting the privileged pool
+        for db_name in &db_names {
+            // Terminate lingering connections if needed
+            if self.get_force_drop() {
+                self.terminate_database_connections(db_name.as_str(), conn)
+                    .await?;
+            }
+
+            self.execute_query(postgres::drop_database(db_name.as_str()).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        // Get previous role names
+        let role_names = self
+            .get_previous_role_names(conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Drop roles left behind by previous databases, restricted to roles of dropped databases if needed,
+        // keeping the group role shared by databases that may still exist
+        for role_name in &role_names {
+            if Some(role_name.as_str()) != self.get_reused_role()
+                && role_name != postgres::GROUP_ROLE_NAME
+                && (!self.get_only_drop_owned() || db_names.contains(role_name))
+                && !kept_db_names.contains(role_name)
+            {
+                self.execute_query(postgres::drop_role(role_name.as_str()).as_str(), conn)
+                    .await
+                    .map_err(Into::into)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) async fn ping(
+        &'backend self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+        self.execute_query(postgres::PING, conn)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn initialize(
+        &self,
+        conn: &mut B::Connection,
+        creates_group_role: bool,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Drop previous databases if needed
+        if self.get_drop_previous_databases() {
+            self.drop_previous_databases(conn).await?;
+        }
+
+        // Create group role if needed
+        if creates_group_role {
+            self.execute_query(
+                postgres::create_group_role(postgres::GROUP_ROLE_NAME).as_str(),
+                conn,
+            )
+            .await
+            .map_err(Into::into)?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) async fn init(
+        &'backend self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Ensure generated database names are valid identifiers before any database is created
+        let db_name = self.get_db_name(Uuid::new_v4());
+        if !is_valid_db_name(&db_name, postgres::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name));
+        }
+
+        let creates_group_role = self.get_reused_role().is_none() && self.get_group_role();
+
+        if self.get_drop_previous_databases() || creates_group_role {
+            if self.get_dialect() == PgDialect::Postgres {
+                // Establish dedicated connection to default database as privileged user,
+                // so that the session-level lock is released once it closes even if initialization is cancelled
+                let conn = &mut self
+                    .establish_privileged_connection()
+                    .await
+                    .map_err(Into::into)?;
+
+                // Lock initialization so that only one initializer sharing the server runs at a time
+                self.execute_query(postgres::LOCK_INITIALIZATION, conn)
+                    .await
+                    .map_err(Into::into)?;
+
+                let initialized = self.initialize(conn, creates_group_role).await;
+
+                // Unlock initialization even if it failed, reporting the initialization error first
+                let unlocked = self
+                    .execute_query(postgres::UNLOCK_INITIALIZATION, conn)
+                    .await;
+                initialized?;
+                unlocked.map_err(Into::into)?;
+            } else {
+                // Get connection to default database as privileged user
+                let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+
+                self.initialize(conn, creates_group_role).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn create_restricted_entities(
+        &'backend self,
+        mut conn: B::Connection,
+        grantee_name: &str,
+        db_name: &str,
+        db_id: Uuid,
+        with_entities: bool,
+    ) -> Result<
+        B::Connection,
+        BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        match self.get_create_entities_as() {
+            Role::Privileged => {
+                // Create entities as privileged user if needed
+                if with_entities {
+                    conn = match self.create_entities(conn, db_name, db_id).await {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            self.discard_database(db_name).await?;
+                            return Err(err);
+                        }
+                    };
+                }
+
+                // Grant privileges to restricted role
+                self.grant_restricted_privileges(grantee_name, &mut conn)
+                    .await?;
+            }
+            Role::Restricted => {
+                // Grant privileges to restricted role
+                self.grant_restricted_privileges(grantee_name, &mut conn)
+                    .await?;
+
+                // Create entities as restricted user if needed
+                if with_entities {
+                    if let Err(err) = self
+                        .create_entities_as_restricted_role(grantee_name, db_name, db_id, &mut conn)
+                        .await
+                    {
+                        self.discard_database(db_name).await?;
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Ok(conn)
+    }
 
-impl<'backend, 'pool, B: PostgresBackend<'pool>> Deref
-    for PostgresBackendWrapper<'backend, 'pool, B>
-{
-    type Target = B;
+    async fn create_entities_as_restricted_role(
+        &'backend self,
+        grantee_name: &str,
+        db_name: &str,
+        db_id: Uuid,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Temporarily grant entity creation to restricted role
+        self.grant_schema_creation(grantee_name, conn).await?;
 
-    fn deref(&self) -> &Self::Target {
-        self.inner
+        // Connect to database as restricted user
+        let restricted_conn = self
+            .connect(self.establish_restricted_database_connection(db_id))
+            .await?;
+
+        // Create entities as restricted user
+        let created = self
+            .create_entities(restricted_conn, db_name, db_id)
+            .await
+            .map(drop);
+
+        // Revoke entity creation from restricted role
+        self.revoke_schema_creation(grantee_name, conn).await?;
+
+        created
     }
-}
 
-impl<'backend, 'pool, B> PostgresBackendWrapper<'backend, 'pool, B>
-where
-    'backend: 'pool,
-    B: PostgresBackend<'pool>,
-{
-    pub(super) async fn init(
+    async fn discard_database(
         &'backend self,
+        db_name: &str,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
-        // Drop previous databases if needed
-        if self.get_drop_previous_databases() {
-            // Get connection to default database as privileged user
-            let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+        // Get connection to default database as privileged user
+        let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+
+        // Terminate connections left open while creating database
+        self.terminate_database_connections(db_name, conn).await?;
+
+        // Drop database
+        self.execute_query(postgres::drop_database(db_name).as_str(), conn)
+            .await
+            .map_err(Into::into)?;
 
-            // Get previous database names
-            let db_names = self
-                .get_previous_database_names(conn)
+        // Drop attached role if not reusing an existing one
+        if self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role(db_name).as_str(), conn)
                 .await
                 .map_err(Into::into)?;
-
-            // Drop databases
-            let futures = db_names
-                .iter()
-                .map(|db_name| async move {
-                    let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
-                    self.execute_query(postgres::drop_database(db_name.as_str()).as_str(), conn)
-                        .await
-                        .map_err(Into::into)?;
-                    Ok::<
-                        _,
-                        BackendError<
-                            B::BuildError,
-                            B::PoolError,
-                            B::ConnectionError,
-                            B::QueryError,
-                        >,
-                    >(())
-                })
-                .collect::<Vec<_>>();
-            futures::future::try_join_all(futures).await?;
         }
 
         Ok(())
     }
-
     pub(super) async fn create(
         &'backend self,
         db_id: Uuid,
         restrict_privileges: bool,
+        with_entities: bool,
     ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
-        // Get connection to default database as privileged user
-        let default_conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+        // Ensure database name is a valid identifier
+        if !is_valid_db_name(db_name, postgres::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name.to_owned()));
+        }
 
-        // Create database
-        self.execute_query(postgres::create_database(db_name).as_str(), default_conn)
-            .await
-            .map_err(Into::into)?;
+        // Get name of role attached to database
+        let reused_role = self.get_reused_role();
+        let role_name = reused_role.unwrap_or(db_name);
 
-        // Create role
-        self.execute_query(postgres::create_role(db_name).as_str(), default_conn)
-            .await
-            .map_err(Into::into)?;
+        // Get name of role holding restricted privileges
+        let group_role =
+            (reused_role.is_none() && self.get_group_role()).then_some(postgres::GROUP_ROLE_NAME);
+        let grantee_name = group_role.unwrap_or(role_name);
 
-        if restrict_privileges {
-            // Connect to database as privileged user
-            let conn = self
-                .establish_privileged_database_connection(db_id)
-                .await
-                .map_err(Into::into)?;
+        // Get whether database is owned by its role since creation
+        let owner_at_creation = !restrict_privileges
+            && self.get_owner_at_creation()
+            && self.get_privilege_profile().transfers_ownership();
 
-            // Create entities as privileged user
-            let mut conn = self.create_entities(conn).await;
+        // Get whether database ownership is granted to database-unrestricted role
+        let transfers_ownership = self.get_privilege_profile().transfers_ownership();
 
-            // Grant table privileges to restricted role
-            self.execute_query(
-                postgres::grant_restricted_table_privileges(db_name).as_str(),
-                &mut conn,
-            )
-            .await
-            .map_err(Into::into)?;
+        {
+            // Get connection to default database as privileged user,
+            // released before entity creation so that concurrent creations do not wait on it
+            let default_conn = &mut self.get_default_connection().await.map_err(Into::into)?;
 
-            // Grant sequence privileges to restricted role
-            self.execute_query(
-                postgres::grant_restricted_sequence_privileges(db_name).as_str(),
-                &mut conn,
+            // Create owner role first if not reusing an existing one
+            if owner_at_creation && reused_role.is_none() {
+                self.create_role(role_name, group_role, Some(db_name), default_conn)
+                    .await?;
+            }
+
+            // Create database, recreating one left over by a previous run if needed
+            self.create_database(
+                db_name,
+                owner_at_creation.then_some(role_name),
+                default_conn,
             )
-            .await
-            .map_err(Into::into)?;
+            .await?;
 
-            // Store database connection for reuse when cleaning
-            self.put_database_connection(db_id, conn);
-        } else {
-            // Grant database ownership to database-unrestricted role
+            // Tag database as created by a backend
             self.execute_query(
-                postgres::grant_database_ownership(db_name, db_name).as_str(),
+                postgres::comment_database(db_name, self.get_schema_version()).as_str(),
                 default_conn,
             )
             .await
             .map_err(Into::into)?;
 
-            // Connect to database as database-unrestricted user
-            let conn = self
-                .establish_restricted_database_connection(db_id)
+            // Create role if not reusing an existing one
+            if !owner_at_creation && reused_role.is_none() {
+                self.create_role(role_name, group_role, None, default_conn)
+                    .await?;
+            }
+
+            // Restrict connecting to database to its role if attached to group role,
+            // so that privileges granted to group role in database cannot be used by other database roles
+            if group_role.is_some() {
+                self.restrict_database_connection(db_name, role_name, default_conn)
+                    .await?;
+            }
+
+            // Set search path of role in database if needed
+            let search_path = self.get_search_path();
+            if !search_path.is_empty() {
+                self.execute_query(
+                    postgres::set_role_search_path(role_name, db_name, search_path).as_str(),
+                    default_conn,
+                )
                 .await
                 .map_err(Into::into)?;
+            }
+
+            // Grant database ownership to database-unrestricted role if not owning it already
+            if !restrict_privileges && !owner_at_creation && transfers_ownership {
+                self.grant_unrestricted_privileges(db_name, role_name, default_conn)
+                    .await?;
+            }
+        }
+
+        // Skip statements run on database in dry run, as it was never created
+        if self.is_dry_run() {
+            return self.create_connection_pool(db_id).await;
+        }
+
+        if restrict_privileges {
+            // Connect to database as privileged user
+            let mut conn = self
+                .connect(self.establish_privileged_database_connection(db_id))
+                .await?;
 
-            // Create entities as database-unrestricted user
-            let _ = self.create_entities(conn).await;
+            // Install extensions as privileged user
+            self.create_extensions(&mut conn).await?;
+
+            // Create entities and grant privileges to restricted role
+            let conn = self
+                .create_restricted_entities(conn, grantee_name, db_name, db_id, with_entities)
+                .await?;
+
+            // Store database connection for reuse when cleaning if needed
+            self.release_database_connection(db_id, conn);
+        } else {
+            if !self.get_extensions().is_empty() || !transfers_ownership {
+                let mut conn = self
+                    .connect(self.establish_privileged_database_connection(db_id))
+                    .await?;
+
+                // Install extensions as privileged user
+                self.create_extensions(&mut conn).await?;
+
+                // Grant equivalent privileges to database-unrestricted role in place of ownership if needed
+                if !transfers_ownership {
+                    self.grant_unrestricted_privileges(db_name, role_name, &mut conn)
+                        .await?;
+                }
+            }
+
+            if with_entities {
+                // Connect to database as database-unrestricted user
+                let conn = self
+                    .connect(self.establish_restricted_database_connection(db_id))
+                    .await?;
+
+                // Create entities as database-unrestricted user
+                if let Err(err) = self.create_entities(conn, db_name, db_id).await {
+                    self.discard_database(db_name).await?;
+                    return Err(err);
+                }
+            }
         };
 
         // Create connection pool with attached role
+        let pool = self.create_connection_pool(db_id).await?;
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Create, db_id, start, None);
+
+        Ok(pool)
+    }
+
+    pub(super) async fn create_reader_pool(
+        &'backend self,
+        db_id: Uuid,
+    ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Create connection pool with attached role
+        self.create_connection_pool(db_id).await
+    }
+
+    async fn create_connection_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<B::Pool, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
         let pool = self
+            .inner
             .create_connection_pool(db_id)
             .await
             .map_err(Into::into)?;
 
+        // Establish a connection ahead of the first query if needed,
+        // except in dry run as the database was never created
+        if self.get_eager_connect() && !self.is_dry_run() {
+            self.warm_up_connection_pool(&pool)
+                .await
+                .map_err(Into::into)?;
+        }
+
         Ok(pool)
     }
 
@@ -243,26 +1144,262 @@ where
         db_id: Uuid,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
+        // Skip cleaning in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
+        let clean_strategy = self.get_clean_strategy();
+
+        // Recreate entities instead of cleaning tables if needed
+        if clean_strategy == CleanStrategy::Recreate && self.get_custom_clean().is_none() {
+            let table_count = self.recreate_entities(db_id).await?;
+
+            // Report time taken and number of tables dropped if needed
+            self.report_metric(MetricOperation::Clean, db_id, start, Some(table_count));
+
+            return Ok(());
+        }
+
         // Get privileged connection to database
-        let mut conn = self.get_database_connection(db_id);
+        let mut conn = self.acquire_database_connection(db_id).await?;
 
-        // Get table names
-        let table_names = self.get_table_names(&mut conn).await.map_err(Into::into)?;
+        // Get table names, cached since the schema cannot change
+        let table_names = self
+            .get_cached_table_names(db_id, &mut conn)
+            .await
+            .map_err(Into::into)?;
+
+        // Generate cleaning statements
+        let dialect = self.get_dialect();
+        let stmts = match (clean_strategy, dialect) {
+            (CleanStrategy::Truncate | CleanStrategy::Recreate, PgDialect::Postgres) => table_names
+                .iter()
+                .map(|table_name| postgres::truncate_table(table_name.as_str()))
+                .collect::<Vec<_>>(),
+            // Delete from all tables in a single statement, so that foreign keys are only checked once all are emptied
+            (CleanStrategy::Truncate | CleanStrategy::Recreate, PgDialect::CockroachDB)
+            | (CleanStrategy::Delete, _) => (!table_names.is_empty())
+                .then(|| postgres::delete_from_tables(&table_names))
+                .into_iter()
+                .collect(),
+        };
+        let stmts = stmts.iter().map(|stmt| stmt.as_str().into());
+
+        let in_transaction = self.get_clean_in_transaction();
+        let mut attempt = 0;
+        loop {
+            // Start transaction if needed
+            if in_transaction {
+                self.execute_query(postgres::START_TRANSACTION, &mut conn)
+                    .await
+                    .map_err(Into::into)?;
+            }
+
+            // Clean tables with custom routine if set, truncating them otherwise
+            let cleaned = if let Some(custom_clean) = self.get_custom_clean() {
+                custom_clean(&mut conn).await
+            } else {
+                self.batch_execute_query(stmts.clone(), &mut conn).await
+            };
+
+            // Retry after a backoff on deadlocks if needed
+            match cleaned {
+                Err(err)
+                    if attempt < self.get_clean_retries() && self.is_transient_lock_error(&err) =>
+                {
+                    if in_transaction {
+                        self.execute_query(postgres::ROLLBACK_TRANSACTION, &mut conn)
+                            .await
+                            .map_err(Into::into)?;
+                    }
+                    attempt += 1;
+                    sleep(get_clean_retry_backoff(attempt)).await;
+                }
+                Err(err) => {
+                    // Roll back so that the connection is stored back outside of a transaction,
+                    // discarding it if rolling back fails
+                    if in_transaction
+                        && self
+                            .execute_query(postgres::ROLLBACK_TRANSACTION, &mut conn)
+                            .await
+                            .is_err()
+                    {
+                        conn.discard();
+                    }
+                    return Err(err.into());
+                }
+                Ok(()) => break,
+            }
+        }
+
+        // Commit transaction if needed
+        if in_transaction {
+            self.execute_query(postgres::COMMIT_TRANSACTION, &mut conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        // Find tables left with rows if verifying cleaning
+        let nonempty_table_names = if self.get_verify_clean() && !table_names.is_empty() {
+            self.get_nonempty_table_names(&table_names, &mut conn)
+                .await
+                .map_err(Into::into)?
+        } else {
+            Vec::new()
+        };
+
+        // Store database connection back for reuse if needed
+        drop(conn);
+
+        if !nonempty_table_names.is_empty() {
+            return Err(BackendError::UncleanDatabase(db_id, nonempty_table_names));
+        }
+
+        // Report time taken and number of tables cleaned if needed
+        self.report_metric(
+            MetricOperation::Clean,
+            db_id,
+            start,
+            self.get_custom_clean()
+                .is_none()
+                .then_some(table_names.len()),
+        );
+
+        Ok(())
+    }
+
+    pub(super) async fn refresh(
+        &'backend self,
+        db_id: Uuid,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        self.recreate_entities(db_id).await.map(drop)
+    }
+
+    async fn recreate_entities(
+        &'backend self,
+        db_id: Uuid,
+    ) -> Result<usize, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Skip recreating entities in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(0);
+        }
+
+        // Get database name based on UUID
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        // Get privileged connection to database
+        let mut conn = self.acquire_database_connection(db_id).await?;
 
-        // Generate truncate statements
+        // Drop tables
+        let table_names = self.get_table_names(&mut conn).await.map_err(Into::into)?;
         let stmts = table_names
             .iter()
-            .map(|table_name| postgres::truncate_table(table_name.as_str()).into());
-
-        // Truncate tables
+            .map(|table_name| postgres::drop_table(table_name.as_str()).into());
         self.batch_execute_query(stmts, &mut conn)
             .await
             .map_err(Into::into)?;
 
-        // Store database connection back for reuse
-        self.put_database_connection(db_id, conn);
+        // Drop sequences left behind by dropped tables
+        self.execute_query(
+            postgres::drop_sequences(self.get_dialect().system_schemas()).as_str(),
+            &mut conn,
+        )
+        .await
+        .map_err(Into::into)?;
+
+        // Create entities again as the same role as upon creation
+        match self.get_create_entities_as() {
+            // Restricted privileges are granted by default on entities created by privileged user
+            Role::Privileged => {
+                // Connection moved into entity creation is only stored back if it succeeds
+                let created_conn = self.create_entities(conn.take(), db_name, db_id).await?;
+                conn.put(created_conn);
+            }
+            Role::Restricted => {
+                // Get name of role holding restricted privileges
+                let reused_role = self.get_reused_role();
+                let grantee_name = if reused_role.is_none() && self.get_group_role() {
+                    postgres::GROUP_ROLE_NAME
+                } else {
+                    reused_role.unwrap_or(db_name)
+                };
+
+                self.create_entities_as_restricted_role(grantee_name, db_name, db_id, &mut conn)
+                    .await?;
+            }
+        }
+
+        // Forget table names of previous entities
+        self.get_table_names_cache().lock().remove(&db_id);
 
-        Ok(())
+        Ok(table_names.len())
+    }
+
+    pub(super) async fn verify_restricted(
+        &'backend self,
+        db_id: Uuid,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Skip verification in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        // Connect to database as restricted user
+        let mut conn = self
+            .connect(self.establish_restricted_database_connection(db_id))
+            .await?;
+
+        // Creating a table must be rejected
+        if self
+            .execute_query(postgres::CREATE_PRIVILEGE_CHECK_TABLE, &mut conn)
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        // Drop table created unexpectedly
+        self.execute_query(
+            postgres::drop_table(postgres::PRIVILEGE_CHECK_TABLE_NAME).as_str(),
+            &mut conn,
+        )
+        .await
+        .map_err(Into::into)?;
+
+        Err(BackendError::UnrestrictedDatabase(db_id))
+    }
+
+    pub(super) async fn label(
+        &'backend self,
+        db_id: Uuid,
+        label: &str,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Leave reused role unlabeled since it is shared by all databases
+        if self.get_reused_role().is_some() {
+            return Ok(());
+        }
+
+        // Get database name based on UUID
+        let db_name = self.get_db_name(db_id);
+
+        // Get connection to default database as privileged user
+        let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+
+        // Label attached role
+        self.execute_query(
+            postgres::comment_role(db_name.as_str(), label).as_str(),
+            conn,
+        )
+        .await
+        .map_err(Into::into)
     }
 
     pub(super) async fn drop(
@@ -271,27 +1408,69 @@ where
         is_restricted: bool,
     ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
-        // Drop privileged connection to database
+        // Get connection to default database as privileged user
+        let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+
+        self.drop_over_connection(db_id, is_restricted, self.get_force_drop(), conn)
+            .await
+    }
+
+    pub(super) async fn drop_with_dedicated_connection(
+        &'backend self,
+        db_id: Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        // Establish dedicated connection to default database as privileged user
+        let conn = &mut self
+            .establish_privileged_connection()
+            .await
+            .map_err(Into::into)?;
+
+        // Terminate lingering connections, as those bound to a blocked runtime cannot close
+        self.drop_over_connection(db_id, is_restricted, true, conn)
+            .await
+    }
+
+    async fn drop_over_connection(
+        &'backend self,
+        db_id: Uuid,
+        is_restricted: bool,
+        force: bool,
+        conn: &mut B::Connection,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        let start = Instant::now();
+
+        // Drop privileged connection to database and forget its table names
         if is_restricted {
             self.get_database_connection(db_id);
+            self.get_table_names_cache().lock().remove(&db_id);
         }
 
         // Get database name based on UUID
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
-        // Get connection to default database as privileged user
-        let conn = &mut self.get_default_connection().await.map_err(Into::into)?;
+        // Terminate lingering connections if needed
+        if force {
+            self.terminate_database_connections(db_name, conn).await?;
+        }
 
         // Drop database
         self.execute_query(postgres::drop_database(db_name).as_str(), conn)
             .await
             .map_err(Into::into)?;
 
-        // Drop attached role
-        self.execute_query(postgres::drop_role(db_name).as_str(), conn)
-            .await
-            .map_err(Into::into)?;
+        // Drop attached role if not reusing an existing one
+        if self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role(db_name).as_str(), conn)
+                .await
+                .map_err(Into::into)?;
+        }
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Drop, db_id, start, None);
 
         Ok(())
     }
@@ -310,14 +1489,24 @@ pub(super) mod tests {
         future::{join_all, try_join_all},
         Future,
     };
-    use tokio::sync::OnceCell;
+    use tokio::{
+        sync::OnceCell,
+        time::{sleep, Duration},
+    };
     use uuid::Uuid;
 
     use crate::{
-        common::statement::postgres::tests::{DDL_STATEMENTS, DML_STATEMENTS},
-        r#async::{backend::r#trait::Backend, db_pool::DatabasePoolBuilder},
+        common::statement::postgres::{
+            self,
+            tests::{DDL_STATEMENTS, DML_STATEMENTS},
+        },
+        r#async::{
+            backend::{r#trait::Backend, Error as BackendError},
+            db_pool::DatabasePoolBuilder,
+        },
         tests::{get_privileged_postgres_config, PG_DROP_LOCK},
         util::get_db_name,
+        CleanMode,
     };
 
     pub type Pool = Bb8Pool<AsyncDieselConnectionManager<AsyncPgConnection>>;
@@ -329,6 +1518,20 @@ pub(super) mod tests {
         }
     }
 
+    table! {
+        pg_roles (rolname) {
+            rolname -> Text
+        }
+    }
+
+    table! {
+        pg_stat_activity (pid) {
+            pid -> Int4,
+            datname -> Nullable<Text>,
+            usename -> Nullable<Text>
+        }
+    }
+
     table! {
         book (id) {
             id -> Int4,
@@ -402,6 +1605,32 @@ pub(super) mod tests {
             .unwrap()
     }
 
+    async fn create_role(conn: &mut AsyncPgConnection) -> String {
+        let role_name = get_db_name(Uuid::new_v4());
+        sql_query(format!("CREATE ROLE {role_name}"))
+            .execute(conn)
+            .await
+            .unwrap();
+        role_name
+    }
+
+    async fn create_roles(count: i64, conn: &mut AsyncPgConnection) -> Vec<String> {
+        let mut role_names = Vec::new();
+        for _ in 0..count {
+            role_names.push(create_role(conn).await);
+        }
+        role_names
+    }
+
+    async fn count_roles(role_names: &Vec<String>, conn: &mut AsyncPgConnection) -> i64 {
+        pg_roles::table
+            .filter(pg_roles::rolname.eq_any(role_names))
+            .count()
+            .get_result(conn)
+            .await
+            .unwrap()
+    }
+
     async fn count_all_databases(conn: &mut AsyncPgConnection) -> i64 {
         pg_database::table
             .filter(pg_database::datname.like("db_pool_%"))
@@ -420,6 +1649,24 @@ pub(super) mod tests {
         .unwrap()
     }
 
+    async fn privileged_connections_closed(db_name: &str, conn: &mut AsyncPgConnection) -> bool {
+        // Closed connections may linger briefly on the server
+        for _ in 0..50 {
+            let count = pg_stat_activity::table
+                .filter(pg_stat_activity::datname.eq(db_name))
+                .filter(pg_stat_activity::usename.ne(db_name))
+                .count()
+                .get_result::<i64>(conn)
+                .await
+                .unwrap();
+            if count == 0 {
+                return true;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
     async fn insert_books(count: i64, conn: &mut AsyncPgConnection) {
         #[derive(Insertable)]
         #[diesel(table_name = book)]
@@ -446,6 +1693,7 @@ pub(super) mod tests {
         disabled: B,
     ) {
         const NUM_DBS: i64 = 3;
+        const NUM_ROLES: i64 = 3;
 
         async {
             let conn_pool = get_privileged_connection_pool().await;
@@ -453,18 +1701,46 @@ pub(super) mod tests {
 
             for (backend, cleans) in [(default, true), (enabled, true), (disabled, false)] {
                 let db_names = create_databases(NUM_DBS, conn_pool).await;
+                let role_names = create_roles(NUM_ROLES, conn).await;
                 assert_eq!(count_databases(&db_names, conn).await, NUM_DBS);
+                assert_eq!(count_roles(&role_names, conn).await, NUM_ROLES);
                 backend.init().await.unwrap();
                 assert_eq!(
                     count_databases(&db_names, conn).await,
                     if cleans { 0 } else { NUM_DBS }
                 );
+                // orphaned roles must be dropped along with databases
+                assert_eq!(
+                    count_roles(&role_names, conn).await,
+                    if cleans { 0 } else { NUM_ROLES }
+                );
             }
         }
         .lock_drop()
         .await;
     }
 
+    pub async fn test_backend_keeps_group_role_of_kept_databases(backend: impl Backend) {
+        async {
+            let conn_pool = get_privileged_connection_pool().await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            // database kept by its schema version, with privileges granted to the group role
+            backend.init().await.unwrap();
+            let db_id = Uuid::new_v4();
+            let db_names = vec![get_db_name(db_id)];
+            drop(backend.create(db_id, true).await.unwrap());
+
+            // group role must be kept while dropping previous databases
+            backend.init().await.unwrap();
+            assert_eq!(count_databases(&db_names, conn).await, 1);
+
+            backend.drop(db_id, true).await.unwrap();
+        }
+        .lock_drop()
+        .await;
+    }
+
     pub async fn test_backend_creates_database_with_restricted_privileges(backend: impl Backend) {
         let db_id = Uuid::new_v4();
         let db_name = get_db_name(db_id);
@@ -559,6 +1835,166 @@ pub(super) mod tests {
         .await;
     }
 
+    pub async fn test_backend_recreates_database_on_conflict<B: Backend>(default: B, enabled: B) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            // leave database and role behind as if from a previous run
+            sql_query(format!("CREATE DATABASE {db_name}"))
+                .execute(conn)
+                .await
+                .unwrap();
+            sql_query(format!("CREATE ROLE {db_name}"))
+                .execute(conn)
+                .await
+                .unwrap();
+
+            // creating conflicting database must fail by default
+            default.init().await.unwrap();
+            assert!(default.create(db_id, true).await.is_err());
+            assert!(database_exists(db_name, conn).await);
+
+            // conflicting database must be recreated if enabled
+            enabled.init().await.unwrap();
+            enabled.create(db_id, true).await.unwrap();
+            assert!(database_exists(db_name, conn).await);
+
+            // recreated database must be usable by recreated role
+            {
+                let conn_pool = &mut create_restricted_connection_pool(db_name).await;
+                let conn = &mut conn_pool.get().await.unwrap();
+
+                for stmt in DML_STATEMENTS {
+                    assert!(sql_query(stmt).execute(conn).await.is_ok());
+                }
+            }
+
+            enabled.drop(db_id, true).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    pub async fn test_backend_recreates_role_on_conflict<B: Backend>(default: B, enabled: B) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            // leave role behind as if from a previous run
+            sql_query(format!("CREATE ROLE {db_name}"))
+                .execute(conn)
+                .await
+                .unwrap();
+
+            // creating conflicting role must fail by default
+            default.init().await.unwrap();
+            assert!(matches!(
+                default.create(db_id, true).await,
+                Err(BackendError::RoleExists(role_name)) if role_name == db_name
+            ));
+            sql_query(postgres::drop_database(db_name))
+                .execute(conn)
+                .await
+                .unwrap();
+
+            // conflicting role must be recreated if enabled
+            enabled.init().await.unwrap();
+            enabled.create(db_id, true).await.unwrap();
+
+            // database must be usable by recreated role
+            {
+                let conn_pool = &mut create_restricted_connection_pool(db_name).await;
+                let conn = &mut conn_pool.get().await.unwrap();
+
+                for stmt in DML_STATEMENTS {
+                    assert!(sql_query(stmt).execute(conn).await.is_ok());
+                }
+            }
+
+            enabled.drop(db_id, true).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    pub async fn test_backend_creates_entities_as_restricted_role(backend: impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        async {
+            backend.init().await.unwrap();
+            backend.create(db_id, true).await.unwrap();
+
+            {
+                let conn_pool = &mut create_restricted_connection_pool(db_name).await;
+                let conn = &mut conn_pool.get().await.unwrap();
+
+                // restricted role must not be able to create further entities
+                assert!(sql_query("CREATE TABLE author(id SERIAL PRIMARY KEY)")
+                    .execute(conn)
+                    .await
+                    .is_err());
+
+                // restricted role must be able to drop entities it owns
+                assert!(sql_query("DROP TABLE book").execute(conn).await.is_ok());
+            }
+
+            backend.drop(db_id, true).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    pub async fn test_backend_creates_database_owned_by_role(backend: impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            // leave database owned by role behind as if from a previous run
+            sql_query(format!("CREATE ROLE {db_name}"))
+                .execute(conn)
+                .await
+                .unwrap();
+            sql_query(format!("CREATE DATABASE {db_name} OWNER {db_name}"))
+                .execute(conn)
+                .await
+                .unwrap();
+
+            // conflicting database and role must be recreated
+            backend.init().await.unwrap();
+            backend.create(db_id, false).await.unwrap();
+
+            // database must be owned by recreated role
+            {
+                let conn_pool = &mut create_restricted_connection_pool(db_name).await;
+                let conn = &mut conn_pool.get().await.unwrap();
+
+                assert!(sql_query(postgres::comment_database(db_name, None))
+                    .execute(conn)
+                    .await
+                    .is_ok());
+            }
+
+            backend.drop(db_id, false).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
     pub async fn test_backend_cleans_database_with_tables(backend: impl Backend) {
         const NUM_BOOKS: i64 = 3;
 
@@ -593,6 +2029,42 @@ pub(super) mod tests {
         .await;
     }
 
+    pub async fn test_backend_cleans_database_without_stored_connection(backend: impl Backend) {
+        const NUM_BOOKS: i64 = 3;
+
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let privileged_conn_pool = get_privileged_connection_pool().await;
+        let privileged_conn = &mut privileged_conn_pool.get().await.unwrap();
+
+        async {
+            backend.init().await.unwrap();
+            backend.create(db_id, true).await.unwrap();
+
+            // privileged connection must not be kept after creating database
+            assert!(privileged_connections_closed(db_name, privileged_conn).await);
+
+            let conn_pool = &mut create_restricted_connection_pool(db_name).await;
+            let conn = &mut conn_pool.get().await.unwrap();
+
+            insert_books(NUM_BOOKS, conn).await;
+            backend.clean(db_id).await.unwrap();
+
+            // there must be no books
+            assert_eq!(
+                book::table.count().get_result::<i64>(conn).await.unwrap(),
+                0
+            );
+
+            // privileged connection must not be kept after cleaning database
+            assert!(privileged_connections_closed(db_name, privileged_conn).await);
+        }
+        .lock_read()
+        .await;
+    }
+
     pub async fn test_backend_cleans_database_without_tables(backend: impl Backend) {
         let db_id = Uuid::new_v4();
 
@@ -627,6 +2099,53 @@ pub(super) mod tests {
         .await;
     }
 
+    pub async fn test_backend_fails_to_clean_unrestricted_database(backend: impl Backend) {
+        let db_id = Uuid::new_v4();
+
+        async {
+            backend.init().await.unwrap();
+            backend.create(db_id, false).await.unwrap();
+
+            // cleaning must fail without a stored privileged connection
+            assert!(matches!(
+                backend.clean(db_id).await,
+                Err(BackendError::MissingDatabaseConnection(id)) if id == db_id
+            ));
+
+            backend.drop(db_id, false).await.unwrap();
+        }
+        .lock_read()
+        .await;
+    }
+
+    pub async fn test_backend_force_drops_database_with_lingering_connection(
+        backend: impl Backend,
+    ) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            // database must exist
+            backend.init().await.unwrap();
+            backend.create(db_id, true).await.unwrap();
+            assert!(database_exists(db_name, conn).await);
+
+            // open connection that is never closed
+            let restricted_conn_pool = create_restricted_connection_pool(db_name).await;
+            let _restricted_conn = restricted_conn_pool.get().await.unwrap();
+
+            // database must not exist
+            backend.drop(db_id, true).await.unwrap();
+            assert!(!database_exists(db_name, conn).await);
+        }
+        .lock_read()
+        .await;
+    }
+
     pub async fn test_pool_drops_previous_databases<B: Backend>(
         default: B,
         enabled: B,
@@ -686,6 +2205,108 @@ pub(super) mod tests {
         .await;
     }
 
+    pub async fn test_pool_drops_prewarmed_databases(backend: impl Backend) {
+        const NUM_DBS: i64 = 3;
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // there must be no databases
+            assert_eq!(count_all_databases(conn).await, 0);
+
+            // create databases ahead of demand
+            db_pool
+                .prewarm(usize::try_from(NUM_DBS).unwrap())
+                .await
+                .unwrap();
+
+            // there must be databases
+            assert_eq!(count_all_databases(conn).await, NUM_DBS);
+
+            // fetch connection pools
+            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+            // prewarmed databases must be reused
+            assert_eq!(count_all_databases(conn).await, NUM_DBS);
+
+            // must release databases back to pool
+            drop(conn_pools);
+
+            // must drop databases
+            drop(db_pool);
+
+            // there must be no databases
+            assert_eq!(count_all_databases(conn).await, 0);
+        }
+        .lock_drop()
+        .await;
+    }
+
+    pub async fn test_pool_drops_returned_databases_in_drop_clean_mode(backend: impl Backend) {
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            let db_pool = backend
+                .create_database_pool()
+                .await
+                .unwrap()
+                .clean_mode(CleanMode::Drop);
+
+            // fetch connection pool
+            let db_name = {
+                let conn_pool = db_pool.pull_immutable().await;
+                let db_name = conn_pool.database_name().to_owned();
+
+                // database must exist
+                assert!(database_exists(db_name.as_str(), conn).await);
+
+                db_name
+            };
+
+            // database must be dropped once connection pool is returned
+            assert!(!database_exists(db_name.as_str(), conn).await);
+
+            // a fresh database must be created upon pulling again
+            let conn_pool = db_pool.pull_immutable().await;
+            assert_ne!(conn_pool.database_name(), db_name);
+            assert!(database_exists(conn_pool.database_name(), conn).await);
+        }
+        .lock_read()
+        .await;
+    }
+
+    pub async fn test_pool_tears_down_databases(backend: impl Backend) {
+        const NUM_DBS: i64 = 3;
+
+        let conn_pool = get_privileged_connection_pool().await;
+        let conn = &mut conn_pool.get().await.unwrap();
+
+        async {
+            let db_pool = backend.create_database_pool().await.unwrap();
+
+            // fetch connection pools
+            let conn_pools = join_all((0..NUM_DBS).map(|_| db_pool.pull_immutable())).await;
+
+            // there must be databases
+            assert_eq!(count_all_databases(conn).await, NUM_DBS);
+
+            // must release databases back to pool
+            drop(conn_pools);
+
+            // must drop databases without errors
+            db_pool.teardown().await.unwrap();
+
+            // there must be no databases
+            assert_eq!(count_all_databases(conn).await, 0);
+        }
+        .lock_drop()
+        .await;
+    }
+
     pub async fn test_pool_drops_created_unrestricted_database(backend: impl Backend) {
         let conn_pool = get_privileged_connection_pool().await;
         let conn = &mut conn_pool.get().await.unwrap();
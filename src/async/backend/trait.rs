@@ -8,24 +8,29 @@ use super::error::Error;
 /// Backend trait
 #[async_trait]
 pub trait Backend: Sized + Send + Sync + 'static {
-    /// Connection pool type that implements [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html)
-    type Pool: Send;
+    /// Connection pool type that implements [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html) and [`Sync`](https://doc.rust-lang.org/std/marker/trait.Sync.html)
+    type Pool: Send + Sync;
 
     /// Connection pool build error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html) and [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html)
     type BuildError: Debug + Send;
     /// Connection pool error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html) and [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html)
     type PoolError: Debug + Send;
-    /// Connection error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html)
-    type ConnectionError: Debug;
-    /// Query error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html)
-    type QueryError: Debug;
+    /// Connection error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html) and [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html)
+    type ConnectionError: Debug + Send;
+    /// Query error type that implements [`Debug`](https://doc.rust-lang.org/std/fmt/trait.Debug.html) and [`Send`](https://doc.rust-lang.org/std/marker/trait.Send.html)
+    type QueryError: Debug + Send;
 
     /// Initializes the backend
     async fn init(
         &self,
     ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
-    #[allow(clippy::complexity)]
 
+    /// Checks that the database server is reachable by running a trivial query as the privileged user
+    async fn ping(
+        &self,
+    ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
+
+    #[allow(clippy::complexity)]
     /// Creates a database
     async fn create(
         &self,
@@ -36,16 +41,83 @@ pub trait Backend: Sized + Send + Sync + 'static {
         Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>,
     >;
 
+    #[allow(clippy::complexity)]
+    /// Creates an unrestricted database without running entity creation on it
+    async fn create_empty(
+        &self,
+        db_id: Uuid,
+    ) -> Result<
+        Self::Pool,
+        Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>,
+    >;
+
+    #[allow(clippy::complexity)]
+    /// Creates an additional connection pool to an existing restricted database for its attached user
+    async fn create_reader_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<
+        Self::Pool,
+        Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>,
+    >;
+
     /// Cleans a database
     async fn clean(
         &self,
         db_id: Uuid,
     ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
 
+    /// Drops the tables of a restricted database and re-runs entity creation on it
+    async fn refresh(
+        &self,
+        db_id: Uuid,
+    ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
+
+    /// Verifies that the attached role of a restricted database is unable to create tables
+    async fn verify_restricted(
+        &self,
+        db_id: Uuid,
+    ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
+
+    /// Labels the attached role of a database, unless reusing an existing role
+    async fn label(
+        &self,
+        db_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
+
     /// Drops a database
     async fn drop(
         &self,
         db_id: Uuid,
         is_restricted: bool,
     ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>;
+
+    /// Drops a database over a dedicated privileged connection
+    ///
+    /// Allows dropping from outside the runtime that drives the connections of the privileged connection pool,
+    /// terminating lingering connections to the database where the server requires it.
+    /// Defaults to dropping over the privileged connection pool.
+    async fn drop_with_dedicated_connection(
+        &self,
+        db_id: Uuid,
+        is_restricted: bool,
+    ) -> Result<(), Error<Self::BuildError, Self::PoolError, Self::ConnectionError, Self::QueryError>>
+    {
+        self.drop(db_id, is_restricted).await
+    }
+
+    /// Returns the name of a database
+    fn get_database_name(&self, db_id: Uuid) -> String;
+
+    /// Returns the connection URL of a database for its attached user
+    ///
+    /// Defaults to [`None`] for backends not exposing connection URLs.
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let _ = db_id;
+        None
+    }
+
+    /// Returns the privileged connection pool, for running maintenance queries as the privileged user
+    fn privileged_pool(&self) -> &Self::Pool;
 }
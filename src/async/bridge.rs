@@ -0,0 +1,122 @@
+use std::{future::Future, panic, sync::OnceLock, thread};
+
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+fn get_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("runtime creation must succeed")
+    })
+}
+
+/// Runs a future to completion from synchronous code
+///
+/// Reuses the ambient runtime if it is multi-threaded, and otherwise falls back to a runtime shared by all
+/// calls, so that async backends can be used from `#[test]` functions without `#[tokio::test]`.
+/// Within a current-thread runtime, which cannot be blocked in place, the future is run on the shared runtime
+/// from a separate thread, so it must not depend on tasks spawned on the current-thread runtime.
+/// # Panics
+/// Panics if the future panics.
+/// # Example
+/// ```
+/// use db_pool::{
+///     r#async::{block_on, DatabasePoolBuilderTrait, SqlxPostgresBackend},
+///     PrivilegedPostgresConfig,
+/// };
+/// use dotenvy::dotenv;
+/// use sqlx::{postgres::PgPoolOptions, Executor};
+///
+/// dotenv().ok();
+///
+/// let config = PrivilegedPostgresConfig::from_env().unwrap();
+///
+/// let backend = block_on(async {
+///     SqlxPostgresBackend::new(
+///         config.into(),
+///         || PgPoolOptions::new().max_connections(10),
+///         |_| PgPoolOptions::new().max_connections(2),
+///         move |mut conn| {
+///             Box::pin(async move {
+///                 conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+///                     .await
+///                     .unwrap();
+///                 conn
+///             })
+///         },
+///     )
+/// });
+///
+/// let db_pool = block_on(backend.create_database_pool()).unwrap();
+/// let conn_pool = block_on(db_pool.pull_immutable());
+///
+/// block_on(conn_pool.execute("INSERT INTO book (title) VALUES ('Title')")).unwrap();
+/// ```
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        // Block ambient multi-threaded runtime in place
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        // Blocking a current-thread runtime in place is not possible, so block on the shared runtime from a separate thread instead
+        Ok(_) => thread::scope(|scope| {
+            scope
+                .spawn(|| get_runtime().block_on(future))
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        }),
+        // No ambient runtime
+        Err(_) => get_runtime().block_on(future),
+    }
+}
+
+/// Runs a future to completion on the runtime of the given handle from synchronous code
+///
+/// The handle must belong to a multi-threaded runtime, which drives its connections on its own worker threads.
+pub(crate) fn block_on_handle<F>(handle: &Handle, future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        // Block ambient multi-threaded runtime in place
+        Ok(current) if current.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        // Blocking a current-thread runtime in place is not possible, so block on a separate thread instead
+        Ok(_) => thread::scope(|scope| {
+            scope
+                .spawn(|| handle.block_on(future))
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        }),
+        // No ambient runtime
+        Err(_) => handle.block_on(future),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::block_on;
+
+    #[test]
+    fn blocks_outside_runtime() {
+        assert_eq!(block_on(async { 1 }), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocks_inside_multi_thread_runtime() {
+        assert_eq!(block_on(async { 1 }), 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn blocks_inside_current_thread_runtime() {
+        assert_eq!(block_on(async { 1 }), 1);
+    }
+}
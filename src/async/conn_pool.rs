@@ -1,14 +1,75 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    marker::PhantomData,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
 
+use parking_lot::Mutex;
+use tokio::runtime::{Handle, RuntimeFlavor};
 use uuid::Uuid;
 
-use super::backend::{r#trait::Backend, Error as BackendError};
+use crate::common::privileges::{Privileges, Restricted, Unrestricted};
+
+use super::{
+    backend::{r#trait::Backend, Error as BackendError},
+    bridge::{block_on, block_on_handle},
+};
+
+/// Errors of databases that failed to drop while dropping their connection pools
+pub(crate) type TeardownErrors<B> = Arc<
+    Mutex<
+        Vec<
+            BackendError<
+                <B as Backend>::BuildError,
+                <B as Backend>::PoolError,
+                <B as Backend>::ConnectionError,
+                <B as Backend>::QueryError,
+            >,
+        >,
+    >,
+>;
+
+/// Handle to the runtime on which databases are dropped while dropping their connection pools
+pub(crate) type RuntimeHandle = Arc<Mutex<Option<Handle>>>;
 
 struct ConnectionPool<B: Backend> {
     backend: Arc<B>,
     db_id: Uuid,
+    db_name: String,
     conn_pool: Option<B::Pool>,
+    reader_pool: Option<B::Pool>,
     is_restricted: bool,
+    keep_on_panic: Arc<AtomicBool>,
+    teardown_errors: TeardownErrors<B>,
+    panic_on_drop_error: Arc<AtomicBool>,
+    runtime_handle: RuntimeHandle,
+}
+
+impl<B: Backend> ConnectionPool<B> {
+    fn database_id(&self) -> Uuid {
+        self.db_id
+    }
+
+    fn database_name(&self) -> &str {
+        self.db_name.as_str()
+    }
+
+    fn database_url(&self) -> Option<String> {
+        self.backend.get_database_url(self.db_id)
+    }
+
+    async fn drop_database(
+        &mut self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        self.conn_pool = None;
+        self.reader_pool = None;
+        (*self.backend).drop(self.db_id, self.is_restricted).await
+    }
 }
 
 impl<B: Backend> Deref for ConnectionPool<B> {
@@ -23,35 +84,116 @@ impl<B: Backend> Deref for ConnectionPool<B> {
 
 impl<B: Backend> Drop for ConnectionPool<B> {
     fn drop(&mut self) {
-        self.conn_pool = None;
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                (*self.backend)
-                    .drop(self.db_id, self.is_restricted)
-                    .await
-                    .ok();
-            });
-        });
+        // Skip if the database was already dropped during teardown
+        if self.conn_pool.take().is_none() {
+            return;
+        }
+        self.reader_pool = None;
+
+        // Keep database for inspection if dropped while unwinding from a panic
+        if thread::panicking() && self.keep_on_panic.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // A current-thread runtime cannot drive the connections bound to it while blocked,
+        // so drop the database over a dedicated connection when within or given one
+        let runtime_handle = self.runtime_handle.lock().clone();
+        let is_current_thread = runtime_handle
+            .iter()
+            .cloned()
+            .chain(Handle::try_current().ok())
+            .any(|handle| handle.runtime_flavor() == RuntimeFlavor::CurrentThread);
+
+        let drop = {
+            let backend = self.backend.clone();
+            let db_id = self.db_id;
+            let db_name = self.db_name.clone();
+            let is_restricted = self.is_restricted;
+            let teardown_errors = self.teardown_errors.clone();
+            let panic_on_drop_error = self.panic_on_drop_error.clone();
+            let is_panicking = thread::panicking();
+            async move {
+                let result = if is_current_thread {
+                    (*backend)
+                        .drop_with_dedicated_connection(db_id, is_restricted)
+                        .await
+                } else {
+                    (*backend).drop(db_id, is_restricted).await
+                };
+                if let Err(err) = result {
+                    // Panic unless already unwinding, as panicking again would abort
+                    assert!(
+                        !panic_on_drop_error.load(Ordering::Relaxed) || is_panicking,
+                        "database {db_name} must be dropped: {err:?}"
+                    );
+                    teardown_errors.lock().push(err);
+                }
+            }
+        };
+
+        match runtime_handle {
+            // Drop database on the provided or captured multi-threaded runtime
+            Some(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                block_on_handle(&handle, drop);
+            }
+            // Drop database on the ambient multi-threaded runtime or on the shared runtime otherwise
+            _ => block_on(drop),
+        }
     }
 }
 
 /// Reusable connection pool wrapper
-pub struct ReusableConnectionPool<B: Backend>(ConnectionPool<B>);
+///
+/// Its type parameter tracks the privileges granted on the database.
+pub struct ReusableConnectionPool<B: Backend, P: Privileges = Restricted>(
+    ConnectionPool<B>,
+    PhantomData<P>,
+);
 
-impl<B: Backend> ReusableConnectionPool<B> {
+impl<B: Backend, P: Privileges> ReusableConnectionPool<B, P> {
     pub(crate) async fn new(
         backend: Arc<B>,
+        keep_on_panic: Arc<AtomicBool>,
+        teardown_errors: TeardownErrors<B>,
+        panic_on_drop_error: Arc<AtomicBool>,
+        runtime_handle: RuntimeHandle,
+        separate_reader_pool: Arc<AtomicBool>,
     ) -> Result<Self, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
         let db_id = Uuid::new_v4();
-        let conn_pool = backend.create(db_id, true).await?;
+        let conn_pool = backend.create(db_id, P::IS_RESTRICTED).await?;
+        let reader_pool = if separate_reader_pool.load(Ordering::Relaxed) {
+            match backend.create_reader_pool(db_id).await {
+                Ok(reader_pool) => Some(reader_pool),
+                Err(err) => {
+                    // Record failure to drop the database, as only the reader pool error is returned
+                    drop(conn_pool);
+                    if let Err(drop_err) = (*backend).drop(db_id, P::IS_RESTRICTED).await {
+                        teardown_errors.lock().push(drop_err);
+                    }
+                    return Err(err);
+                }
+            }
+        } else {
+            None
+        };
+        let db_name = backend.get_database_name(db_id);
 
-        Ok(Self(ConnectionPool {
-            backend,
-            db_id,
-            conn_pool: Some(conn_pool),
-            is_restricted: true,
-        }))
+        Ok(Self(
+            ConnectionPool {
+                backend,
+                db_id,
+                db_name,
+                conn_pool: Some(conn_pool),
+                reader_pool,
+                is_restricted: P::IS_RESTRICTED,
+                keep_on_panic,
+                teardown_errors,
+                panic_on_drop_error,
+                runtime_handle,
+            },
+            PhantomData,
+        ))
     }
 
     pub(crate) async fn clean(
@@ -60,9 +202,58 @@ impl<B: Backend> ReusableConnectionPool<B> {
     {
         self.0.backend.clean(self.0.db_id).await
     }
+
+    pub(crate) async fn refresh(
+        &mut self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        self.0.backend.refresh(self.0.db_id).await
+    }
+
+    pub(crate) async fn drop_database(
+        mut self,
+    ) -> Result<(), BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
+    {
+        self.0.drop_database().await
+    }
+
+    /// Returns the ID of the database, from which its name is derived
+    #[must_use]
+    pub fn database_id(&self) -> Uuid {
+        self.0.database_id()
+    }
+
+    /// Returns the name of the database
+    #[must_use]
+    pub fn database_name(&self) -> &str {
+        self.0.database_name()
+    }
+
+    /// Returns the connection URL of the database for its attached user
+    ///
+    /// [`None`] if the backend does not expose connection URLs.
+    #[must_use]
+    pub fn database_url(&self) -> Option<String> {
+        self.0.database_url()
+    }
+
+    /// Returns the connection pool to write to the database through
+    #[must_use]
+    pub fn writer(&self) -> &B::Pool {
+        &self.0
+    }
+
+    /// Returns the connection pool to read from the database through
+    ///
+    /// This is a distinct pool to the same database if separate reader pools are enabled on the database pool,
+    /// and the writer pool otherwise.
+    #[must_use]
+    pub fn reader(&self) -> &B::Pool {
+        self.0.reader_pool.as_ref().unwrap_or_else(|| self.writer())
+    }
 }
 
-impl<B: Backend> Deref for ReusableConnectionPool<B> {
+impl<B: Backend, P: Privileges> Deref for ReusableConnectionPool<B, P> {
     type Target = B::Pool;
 
     fn deref(&self) -> &Self::Target {
@@ -71,29 +262,96 @@ impl<B: Backend> Deref for ReusableConnectionPool<B> {
 }
 
 /// Single-use connection pool wrapper
-pub struct SingleUseConnectionPool<B: Backend>(ConnectionPool<B>);
+///
+/// Its type parameter tracks the privileges granted on the database.
+pub struct SingleUseConnectionPool<B: Backend, P: Privileges = Unrestricted>(
+    ConnectionPool<B>,
+    Arc<Mutex<Vec<Uuid>>>,
+    PhantomData<P>,
+);
 
-impl<B: Backend> SingleUseConnectionPool<B> {
+impl<B: Backend, P: Privileges> SingleUseConnectionPool<B, P> {
     pub(crate) async fn new(
         backend: Arc<B>,
+        keep_on_panic: Arc<AtomicBool>,
+        teardown_errors: TeardownErrors<B>,
+        panic_on_drop_error: Arc<AtomicBool>,
+        runtime_handle: RuntimeHandle,
+        leased: Arc<Mutex<Vec<Uuid>>>,
+        with_entities: bool,
     ) -> Result<Self, BackendError<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>
     {
         let db_id = Uuid::new_v4();
-        let conn_pool = backend.create(db_id, false).await?;
+        let conn_pool = if with_entities {
+            backend.create(db_id, P::IS_RESTRICTED).await?
+        } else {
+            backend.create_empty(db_id).await?
+        };
+        let db_name = backend.get_database_name(db_id);
+
+        leased.lock().push(db_id);
 
-        Ok(Self(ConnectionPool {
-            backend,
-            db_id,
-            conn_pool: Some(conn_pool),
-            is_restricted: false,
-        }))
+        Ok(Self(
+            ConnectionPool {
+                backend,
+                db_id,
+                db_name,
+                conn_pool: Some(conn_pool),
+                reader_pool: None,
+                is_restricted: P::IS_RESTRICTED,
+                keep_on_panic,
+                teardown_errors,
+                panic_on_drop_error,
+                runtime_handle,
+            },
+            leased,
+            PhantomData,
+        ))
+    }
+
+    /// Returns the ID of the database, from which its name is derived
+    #[must_use]
+    pub fn database_id(&self) -> Uuid {
+        self.0.database_id()
+    }
+
+    /// Returns the name of the database
+    #[must_use]
+    pub fn database_name(&self) -> &str {
+        self.0.database_name()
+    }
+
+    /// Returns the connection URL of the database for its attached user
+    ///
+    /// [`None`] if the backend does not expose connection URLs.
+    #[must_use]
+    pub fn database_url(&self) -> Option<String> {
+        self.0.database_url()
+    }
+}
+
+impl<B: Backend> SingleUseConnectionPool<B, Unrestricted> {
+    /// Returns the connection pool, asserting at compile time that DDL statements can be run through it
+    ///
+    /// Only available on unrestricted connection pools, so that helpers running DDL statements
+    /// can require one by calling it.
+    #[must_use]
+    pub fn assert_can_ddl(&self) -> &B::Pool {
+        &self.0
     }
 }
 
-impl<B: Backend> Deref for SingleUseConnectionPool<B> {
+impl<B: Backend, P: Privileges> Deref for SingleUseConnectionPool<B, P> {
     type Target = B::Pool;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
+
+impl<B: Backend, P: Privileges> Drop for SingleUseConnectionPool<B, P> {
+    fn drop(&mut self) {
+        let db_id = self.0.db_id;
+        self.1.lock().retain(|leased_db_id| *leased_db_id != db_id);
+    }
+}
@@ -1,26 +1,1154 @@
-use std::sync::Arc;
+use std::{
+    future::Future,
+    mem::take,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use parking_lot::Mutex;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use uuid::Uuid;
+
+use crate::common::{
+    config::CleanMode,
+    privileges::{Restricted, Unrestricted},
+};
+
+use super::{
+    backend::{r#trait::Backend, Error},
+    conn_pool::{
+        ReusableConnectionPool as ReusableConnectionPoolInner, RuntimeHandle,
+        SingleUseConnectionPool, TeardownErrors,
+    },
+    object_pool::{ObjectPool, Reusable, SharedReusable},
+};
+
+/// Wrapper for a reusable connection pool wrapped in a reusable object wrapper
+pub type ReusableConnectionPool<'a, B> = Reusable<'a, ReusableConnectionPoolInner<B, Restricted>>;
+
+/// Wrapper for a reusable connection pool shared by all handles pulled under the same key
+pub type SharedConnectionPool<'a, B> =
+    SharedReusable<'a, ReusableConnectionPoolInner<B, Restricted>>;
+
+/// Database pool
+pub struct DatabasePool<B: Backend> {
+    backend: Arc<B>,
+    object_pool: ObjectPool<ReusableConnectionPoolInner<B>>,
+    keep_on_panic: Arc<AtomicBool>,
+    teardown_errors: TeardownErrors<B>,
+    panic_on_drop_error: Arc<AtomicBool>,
+    runtime_handle: RuntimeHandle,
+    separate_reader_pool: Arc<AtomicBool>,
+    leased: Arc<Mutex<Vec<Uuid>>>,
+}
+
+impl<B: Backend> DatabasePool<B> {
+    /// Pulls a reusable connection pool
+    ///
+    /// Privileges are granted only for ``SELECT``, ``INSERT``, ``UPDATE``, and ``DELETE`` operations.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub async fn pull_immutable(&self) -> ReusableConnectionPool<B> {
+        self.object_pool.pull().await
+    }
+
+    /// Pulls a reusable connection pool shared by all handles pulled under the same key
+    ///
+    /// Pulling again under a key whose handles have not all been dropped returns the same database,
+    /// so that multiple handles can work on one isolated database.
+    /// The database is returned to the pool once all handles pulled under its key are dropped.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let writer_conn_pool = db_pool.pull_immutable_keyed("fixture").await;
+    ///     let reader_conn_pool = db_pool.pull_immutable_keyed("fixture").await;
+    ///     assert_eq!(
+    ///         writer_conn_pool.database_id(),
+    ///         reader_conn_pool.database_id()
+    ///     );
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn pull_immutable_keyed(&self, key: &str) -> SharedConnectionPool<B> {
+        self.object_pool.pull_keyed(key).await
+    }
+
+    /// Pulls a reusable connection pool and labels the attached role of its database, such as with the name of the pulling test
+    ///
+    /// The label is stored as a comment on the role (user for MySQL) and replaces any label of a previous pull,
+    /// so that leaked databases can be traced back to the test that last pulled them. Labels are reported by
+    /// [`cleanup_all`](super::cleanup_all). Reused roles are left unlabeled since they are shared by all databases.
+    /// Labeling MySQL users requires MySQL 8.0.21 or later, and users are left unlabeled in the `MariaDB` flavor.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool
+    ///         .pull_immutable_labeled("tests::creates_book")
+    ///         .await
+    ///         .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn pull_immutable_labeled(
+        &self,
+        label: impl Into<String>,
+    ) -> Result<
+        ReusableConnectionPool<B>,
+        Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        let conn_pool = self.object_pool.pull().await;
+        self.backend
+            .label(conn_pool.database_id(), label.into().as_str())
+            .await?;
+        Ok(conn_pool)
+    }
+
+    /// Pulls multiple reusable connection pools at once
+    ///
+    /// Databases missing from the pool are created concurrently, after which all connection pools are pulled concurrently.
+    /// If creating any database fails, the databases created so far are dropped.
+    /// Pulling waits until capacity for all connection pools is available at once,
+    /// and pulling more connection pools than the cap set via [`max_databases`](Self::max_databases) returns an error.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pools = db_pool.pull_many(3).await.unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn pull_many(
+        &self,
+        n: usize,
+    ) -> Result<
+        Vec<ReusableConnectionPool<B>>,
+        Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        // Reject pulling more databases than can ever be leased at once
+        if let Some(max) = self.object_pool.max_capacity() {
+            if n > max {
+                return Err(Error::MaxDatabasesExceeded(n, max));
+            }
+        }
+
+        // Create missing databases ahead of pulling so that a failure drops all of them
+        self.object_pool
+            .pull_many(n, |missing| self.create_conn_pools(missing))
+            .await
+    }
+
+    /// Caps the number of reusable connection pools
+    ///
+    /// Once the cap is reached, pulling waits until a connection pool is returned instead of creating another database.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap().max_databases(4);
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn max_databases(self, value: usize) -> Self {
+        Self {
+            object_pool: self.object_pool.capacity(value),
+            ..self
+        }
+    }
+
+    /// Runs a closure on a reusable connection pool every time it is pulled
+    ///
+    /// Unlike entity creation, which runs once per created database, the closure runs on every pull after the database is cleaned,
+    /// so that data it inserts is removed once the connection pool is returned and pulled again.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend
+    ///         .create_database_pool()
+    ///         .await
+    ///         .unwrap()
+    ///         .on_acquire(|conn_pool| {
+    ///             Box::pin(async {
+    ///                 {
+    ///                     let mut conn = conn_pool.get().await.unwrap();
+    ///                     sql_query("INSERT INTO book (title) VALUES ('Title')")
+    ///                         .execute(&mut *conn)
+    ///                         .await
+    ///                         .unwrap();
+    ///                 }
+    ///                 conn_pool
+    ///             })
+    ///         });
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn on_acquire(
+        self,
+        on_acquire: impl Fn(
+                ReusableConnectionPoolInner<B>,
+            )
+                -> Pin<Box<dyn Future<Output = ReusableConnectionPoolInner<B>> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            object_pool: self.object_pool.on_acquire(on_acquire),
+            ..self
+        }
+    }
+
+    /// Runs a closure on a reusable connection pool the first time its database is pulled, such as to apply migrations registered at runtime
+    ///
+    /// Unlike entity creation, which is fixed upon constructing the backend, the closure can differ across database pools.
+    /// It is skipped whenever a database is reused, and runs before any closure set with [`on_acquire`](Self::on_acquire).
+    /// The connection pool carries restricted privileges, so statements requiring more privileges should be run
+    /// through a privileged connection to the database named by `database_name`.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config.clone(),
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend
+    ///         .create_database_pool()
+    ///         .await
+    ///         .unwrap()
+    ///         .on_first_acquire(move |conn_pool| {
+    ///             let url = config
+    ///                 .clone()
+    ///                 .maintenance_database(conn_pool.database_name())
+    ///                 .default_connection_url();
+    ///             Box::pin(async move {
+    ///                 let mut conn = AsyncPgConnection::establish(url.as_str()).await.unwrap();
+    ///                 sql_query("CREATE INDEX book_title ON book (title)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn_pool
+    ///             })
+    ///         });
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn on_first_acquire(
+        self,
+        on_first_acquire: impl Fn(
+                ReusableConnectionPoolInner<B>,
+            )
+                -> Pin<Box<dyn Future<Output = ReusableConnectionPoolInner<B>> + Send + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            object_pool: self
+                .object_pool
+                .on_first_acquire(ReusableConnectionPoolInner::database_id, on_first_acquire),
+            ..self
+        }
+    }
+
+    /// Sets what happens to the database of a reusable connection pool once the connection pool is returned
+    ///
+    /// Databases are cleaned and reused by default.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     CleanMode, PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend
+    ///         .create_database_pool()
+    ///         .await
+    ///         .unwrap()
+    ///         .clean_mode(CleanMode::Drop);
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn clean_mode(self, value: CleanMode) -> Self {
+        Self {
+            object_pool: self.object_pool.detach_on_return(value == CleanMode::Drop),
+            ..self
+        }
+    }
+
+    /// Keeps databases of connection pools dropped while panicking, such as in failed tests
+    ///
+    /// A kept database is not dropped, cleaned, or reused, and can be found for inspection by its name.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap().keep_on_panic(true);
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn keep_on_panic(self, value: bool) -> Self {
+        self.keep_on_panic.store(value, Ordering::Relaxed);
+        Self {
+            object_pool: self.object_pool.detach_on_panic(value),
+            ..self
+        }
+    }
+
+    /// Panics when a database fails to drop while dropping its connection pool instead of recording the error
+    ///
+    /// No panic occurs while already unwinding from a panic, in which case the error is recorded.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap().panic_on_drop_error(true);
+    ///     let conn_pool = db_pool.pull_immutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn panic_on_drop_error(self, value: bool) -> Self {
+        self.panic_on_drop_error.store(value, Ordering::Relaxed);
+        self
+    }
+
+    /// Drops databases on the runtime of the given handle when dropping their connection pools
+    ///
+    /// By default, databases are dropped on the runtime the database pool was created on if it is multi-threaded.
+    /// Otherwise, they are dropped on the ambient runtime if it is multi-threaded and on a shared runtime
+    /// if there is none.
+    /// Within a current-thread runtime, or given a handle to one, databases are dropped on a shared runtime
+    /// over a dedicated connection, since a blocked current-thread runtime cannot drive its connections.
+    /// Providing a handle allows databases to be dropped on the runtime their backend's connections belong to,
+    /// such as when a database pool is shared across tests each running on their own runtime.
+    /// The runtime must outlive the database pool.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    /// use tokio::runtime::Builder;
+    ///
+    /// let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+    ///
+    /// let db_pool = runtime.block_on(async {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     backend
+    ///         .create_database_pool()
+    ///         .await
+    ///         .unwrap()
+    ///         .runtime_handle(runtime.handle().clone())
+    /// });
+    ///
+    /// // database is dropped on the provided runtime
+    /// let conn_pool = runtime.block_on(db_pool.create_mutable()).unwrap();
+    /// drop(conn_pool);
+    /// ```
+    #[must_use]
+    pub fn runtime_handle(self, handle: Handle) -> Self {
+        *self.runtime_handle.lock() = Some(handle);
+        self
+    }
+
+    /// Gives each reusable connection pool a distinct reader pool to the same database
+    ///
+    /// Allows tests to read through `reader` and write through `writer`
+    /// as they would against a read replica, with both pools connecting to the same database.
+    /// Without this, both accessors return the same pool.
+    /// Only applies to connection pools created afterwards.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend
+    ///         .create_database_pool()
+    ///         .await
+    ///         .unwrap()
+    ///         .separate_reader_pool(true);
+    ///     let conn_pool = db_pool.pull_immutable().await;
+    ///
+    ///     sql_query("INSERT INTO book (title) VALUES ('Title')")
+    ///         .execute(&mut conn_pool.writer().get().await.unwrap())
+    ///         .await
+    ///         .unwrap();
+    ///     sql_query("SELECT title FROM book")
+    ///         .execute(&mut conn_pool.reader().get().await.unwrap())
+    ///         .await
+    ///         .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn separate_reader_pool(self, value: bool) -> Self {
+        self.separate_reader_pool.store(value, Ordering::Relaxed);
+        self
+    }
+
+    /// Creates reusable connection pools ahead of demand
+    ///
+    /// The databases are created concurrently and parked in the pool to be pulled later
+    /// without being cleaned first.
+    /// If a cap is set via [`max_databases`](Self::max_databases), no more databases are created
+    /// than the cap leaves room for, counting the databases already in the pool and those leased.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     db_pool.prewarm(5).await.unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn prewarm(
+        &self,
+        n: usize,
+    ) -> Result<(), Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>> {
+        // Create no more databases than the remaining capacity allows
+        let n = self
+            .object_pool
+            .remaining_capacity()
+            .map_or(n, |remaining| n.min(remaining));
+        let conn_pools = self.create_conn_pools(n).await?;
+        // Park newly created databases as ready since they have nothing to clean
+        self.object_pool.extend_ready(conn_pools);
+        Ok(())
+    }
+
+    /// Creates a single-use connection pool
+    ///
+    /// All privileges are granted.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool.create_mutable();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn create_mutable(
+        &self,
+    ) -> Result<
+        SingleUseConnectionPool<B, Unrestricted>,
+        Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        SingleUseConnectionPool::new(
+            self.backend.clone(),
+            self.keep_on_panic.clone(),
+            self.teardown_errors.clone(),
+            self.panic_on_drop_error.clone(),
+            self.runtime_handle.clone(),
+            self.leased.clone(),
+            true,
+        )
+        .await
+    }
 
-use async_trait::async_trait;
+    /// Creates a single-use connection pool to an empty database
+    ///
+    /// All privileges are granted and entity creation is skipped, which suits tests running migrations from scratch.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool.create_mutable_empty();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn create_mutable_empty(
+        &self,
+    ) -> Result<
+        SingleUseConnectionPool<B, Unrestricted>,
+        Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
+    > {
+        SingleUseConnectionPool::new(
+            self.backend.clone(),
+            self.keep_on_panic.clone(),
+            self.teardown_errors.clone(),
+            self.panic_on_drop_error.clone(),
+            self.runtime_handle.clone(),
+            self.leased.clone(),
+            false,
+        )
+        .await
+    }
 
-use super::{
-    backend::{r#trait::Backend, Error},
-    conn_pool::{ReusableConnectionPool as ReusableConnectionPoolInner, SingleUseConnectionPool},
-    object_pool::{ObjectPool, Reusable},
-};
+    /// Returns the IDs of databases currently leased through pulled and created connection pools
+    ///
+    /// The IDs are a snapshot taken at the time of the call, meant for diagnosing tests stuck on held databases.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool.pull_immutable().await;
+    ///     let db_ids = db_pool.leased_databases();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    #[must_use]
+    pub fn leased_databases(&self) -> Vec<Uuid> {
+        let mut db_ids = self.object_pool.leased();
+        db_ids.extend(self.leased.lock().iter().copied());
+        db_ids
+    }
 
-/// Wrapper for a reusable connection pool wrapped in a reusable object wrapper
-pub type ReusableConnectionPool<'a, B> = Reusable<'a, ReusableConnectionPoolInner<B>>;
+    /// Takes the errors of databases that failed to drop while dropping their connection pools
+    ///
+    /// Errors are recorded as connection pools are dropped and cleared once taken, so that a test can assert that cleanup succeeded.
+    /// Dropping blocks on the ambient runtime, which must be multi-threaded for privileged connections to be acquired while blocking.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     {
+    ///         let conn_pool = db_pool.create_mutable().await.unwrap();
+    ///     }
+    ///     assert!(db_pool.take_teardown_errors().is_empty());
+    /// }
+    ///
+    /// tokio::runtime::Runtime::new().unwrap().block_on(f());
+    /// ```
+    #[allow(clippy::complexity)]
+    pub fn take_teardown_errors(
+        &self,
+    ) -> Vec<Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>> {
+        take(&mut *self.teardown_errors.lock())
+    }
 
-/// Database pool
-pub struct DatabasePool<B: Backend> {
-    backend: Arc<B>,
-    object_pool: ObjectPool<ReusableConnectionPoolInner<B>>,
-}
+    /// Drops the tables of idle reusable connection pools' databases and re-runs entity creation on them
+    ///
+    /// Databases and their attached roles are kept, which avoids a full teardown when entities change between iterations.
+    /// Fails with ``LeasedDatabases`` without refreshing anything while pulled connection pools are still held.
+    /// Databases that fail to refresh are dropped and the first error is returned.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     {
+    ///         let conn_pool = db_pool.pull_immutable().await;
+    ///     }
+    ///     db_pool.refresh_schema().await.unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn refresh_schema(
+        &self,
+    ) -> Result<(), Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>> {
+        // Refuse to refresh while pulled databases are still in use
+        let db_ids = self.object_pool.leased();
+        if !db_ids.is_empty() {
+            return Err(Error::LeasedDatabases(db_ids));
+        }
 
-impl<B: Backend> DatabasePool<B> {
-    /// Pulls a reusable connection pool
+        // Take idle databases out of the pool while refreshing them
+        let results = join_all(self.object_pool.drain().into_iter().map(
+            |mut conn_pool| async move {
+                let refreshed = conn_pool.refresh().await;
+                (conn_pool, refreshed)
+            },
+        ))
+        .await;
+
+        // Return refreshed databases to the pool, dropping the others
+        let mut error = None;
+        let conn_pools = results
+            .into_iter()
+            .filter_map(|(conn_pool, refreshed)| match refreshed {
+                Ok(()) => Some(conn_pool),
+                Err(err) => {
+                    error.get_or_insert(err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        self.object_pool.extend(conn_pools);
+
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Cleans the databases of all returned reusable connection pools concurrently
     ///
-    /// Privileges are granted only for ``SELECT``, ``INSERT``, ``UPDATE``, and ``DELETE`` operations.
+    /// Databases are otherwise cleaned one at a time as they are pulled, so cleaning them ahead of a burst of pulls
+    /// makes those pulls faster. Databases of pulled connection pools are skipped.
+    /// Databases that fail to clean are cleaned again when pulled and all errors are returned.
     /// # Example
     /// ```
     /// use bb8::Pool;
@@ -56,19 +1184,56 @@ impl<B: Backend> DatabasePool<B> {
     ///     .unwrap();
     ///
     ///     let db_pool = backend.create_database_pool().await.unwrap();
-    ///     let conn_pool = db_pool.pull_immutable();
+    ///     {
+    ///         let conn_pool_1 = db_pool.pull_immutable().await;
+    ///         let conn_pool_2 = db_pool.pull_immutable().await;
+    ///     }
+    ///     db_pool.clean_all_available().await.unwrap();
     /// }
     ///
     /// tokio_test::block_on(f());
     /// ```
-    #[must_use]
-    pub async fn pull_immutable(&self) -> ReusableConnectionPool<B> {
-        self.object_pool.pull().await
+    pub async fn clean_all_available(
+        &self,
+    ) -> Result<(), Vec<Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>>
+    {
+        // Take returned databases out of the pool while cleaning them
+        let results = join_all(self.object_pool.drain_unready().into_iter().map(
+            |mut conn_pool| async move {
+                let cleaned = conn_pool.clean().await;
+                (conn_pool, cleaned)
+            },
+        ))
+        .await;
+
+        // Return cleaned databases to the pool as ready to use, and the others to be cleaned when pulled
+        let mut errors = Vec::new();
+        let mut unclean_conn_pools = Vec::new();
+        let conn_pools = results
+            .into_iter()
+            .filter_map(|(conn_pool, cleaned)| match cleaned {
+                Ok(()) => Some(conn_pool),
+                Err(err) => {
+                    errors.push(err);
+                    unclean_conn_pools.push(conn_pool);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        self.object_pool.extend_ready(conn_pools);
+        self.object_pool.extend(unclean_conn_pools);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
-    /// Creates a single-use connection pool
+    /// Pulls a reusable connection pool and verifies that its attached role is unable to create tables
     ///
-    /// All privileges are granted.
+    /// Fails with ``UnrestrictedDatabase`` if creating a table unexpectedly succeeds, in which case the table is dropped again.
+    /// Meant as a one-time sanity check when setting up a test suite that customizes privileges.
     /// # Example
     /// ```
     /// use bb8::Pool;
@@ -104,18 +1269,137 @@ impl<B: Backend> DatabasePool<B> {
     ///     .unwrap();
     ///
     ///     let db_pool = backend.create_database_pool().await.unwrap();
-    ///     let conn_pool = db_pool.create_mutable();
+    ///     db_pool.assert_restricted().await.unwrap();
     /// }
     ///
     /// tokio_test::block_on(f());
     /// ```
-    pub async fn create_mutable(
+    pub async fn assert_restricted(
+        &self,
+    ) -> Result<(), Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>> {
+        let conn_pool = self.pull_immutable().await;
+        self.backend
+            .verify_restricted(conn_pool.database_id())
+            .await
+    }
+
+    /// Drops all databases of reusable connection pools and waits for the drops to complete
+    ///
+    /// Unlike dropping the database pool, which drops databases one by one while blocking, the databases are dropped concurrently
+    /// and all errors are returned instead of being ignored. Databases of single-use connection pools are dropped along with their pools.
+    /// Errors recorded while dropping connection pools and not yet taken are returned first.
+    /// # Example
+    /// ```
+    /// use bb8::Pool;
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::sql_query;
+    /// use diesel_async::RunQueryDsl;
+    /// use dotenvy::dotenv;
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+    ///         config,
+    ///         || Pool::builder().max_size(10),
+    ///         || Pool::builder().max_size(2),
+    ///         None,
+    ///         move |mut conn| {
+    ///             Box::pin(async {
+    ///                 sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .execute(&mut conn)
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     {
+    ///         let conn_pool = db_pool.pull_immutable().await;
+    ///     }
+    ///     db_pool.teardown().await.unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    pub async fn teardown(
+        self,
+    ) -> Result<(), Vec<Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>>>
+    {
+        let mut errors = self.take_teardown_errors();
+        errors.extend(
+            join_all(
+                self.object_pool
+                    .drain()
+                    .into_iter()
+                    .map(ReusableConnectionPoolInner::drop_database),
+            )
+            .await
+            .into_iter()
+            .filter_map(Result::err),
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    async fn create_conn_pools(
         &self,
+        n: usize,
     ) -> Result<
-        SingleUseConnectionPool<B>,
+        Vec<ReusableConnectionPoolInner<B>>,
         Error<B::BuildError, B::PoolError, B::ConnectionError, B::QueryError>,
     > {
-        SingleUseConnectionPool::new(self.backend.clone()).await
+        // Wait for all databases to be created, as cancelling a creation midway would leave its database behind
+        let mut error = None;
+        let conn_pools = join_all((0..n).map(|_| {
+            ReusableConnectionPoolInner::new(
+                self.backend.clone(),
+                self.keep_on_panic.clone(),
+                self.teardown_errors.clone(),
+                self.panic_on_drop_error.clone(),
+                self.runtime_handle.clone(),
+                self.separate_reader_pool.clone(),
+            )
+        }))
+        .await
+        .into_iter()
+        .filter_map(|result| match result {
+            Ok(conn_pool) => Some(conn_pool),
+            Err(err) => {
+                error.get_or_insert(err);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+        let Some(error) = error else {
+            return Ok(conn_pools);
+        };
+
+        // Drop the databases created alongside a failed one
+        let errors = join_all(
+            conn_pools
+                .into_iter()
+                .map(ReusableConnectionPoolInner::drop_database),
+        )
+        .await;
+        self.teardown_errors
+            .lock()
+            .extend(errors.into_iter().filter_map(Result::err));
+        Err(error)
     }
 }
 
@@ -170,15 +1454,41 @@ pub trait DatabasePoolBuilder: Backend {
     > {
         self.init().await?;
         let backend = Arc::new(self);
+        let keep_on_panic = Arc::new(AtomicBool::new(false));
+        let teardown_errors = Arc::new(Mutex::new(Vec::new()));
+        let panic_on_drop_error = Arc::new(AtomicBool::new(false));
+        // Capture the runtime the backend's connections are spawned on, unless it cannot be blocked on
+        let runtime_handle =
+            Arc::new(Mutex::new(Handle::try_current().ok().filter(|handle| {
+                handle.runtime_flavor() == RuntimeFlavor::MultiThread
+            })));
+        let separate_reader_pool = Arc::new(AtomicBool::new(false));
         let object_pool = {
             let backend = backend.clone();
+            let keep_on_panic = keep_on_panic.clone();
+            let teardown_errors = teardown_errors.clone();
+            let panic_on_drop_error = panic_on_drop_error.clone();
+            let runtime_handle = runtime_handle.clone();
+            let separate_reader_pool = separate_reader_pool.clone();
             ObjectPool::new(
                 move || {
                     let backend = backend.clone();
+                    let keep_on_panic = keep_on_panic.clone();
+                    let teardown_errors = teardown_errors.clone();
+                    let panic_on_drop_error = panic_on_drop_error.clone();
+                    let runtime_handle = runtime_handle.clone();
+                    let separate_reader_pool = separate_reader_pool.clone();
                     Box::pin(async {
-                        ReusableConnectionPoolInner::new(backend)
-                            .await
-                            .expect("connection pool creation must succeed")
+                        ReusableConnectionPoolInner::new(
+                            backend,
+                            keep_on_panic,
+                            teardown_errors,
+                            panic_on_drop_error,
+                            runtime_handle,
+                            separate_reader_pool,
+                        )
+                        .await
+                        .expect("connection pool creation must succeed")
                     })
                 },
                 |mut conn_pool| {
@@ -194,7 +1504,13 @@ pub trait DatabasePoolBuilder: Backend {
         };
         Ok(DatabasePool {
             backend,
-            object_pool,
+            object_pool: object_pool.track_leases(ReusableConnectionPoolInner::database_id),
+            keep_on_panic,
+            teardown_errors,
+            panic_on_drop_error,
+            runtime_handle,
+            separate_reader_pool,
+            leased: Arc::new(Mutex::new(Vec::new())),
         })
     }
 }
@@ -0,0 +1,279 @@
+use std::{any::Any, fmt::Debug};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{
+    backend::{r#trait::Backend, Error},
+    conn_pool::SingleUseConnectionPool,
+    db_pool::{DatabasePool, ReusableConnectionPool},
+};
+
+type DynError = Error<
+    Box<dyn Debug + Send>,
+    Box<dyn Debug + Send>,
+    Box<dyn Debug + Send>,
+    Box<dyn Debug + Send>,
+>;
+
+fn erase_error<
+    B: Debug + Send + 'static,
+    P: Debug + Send + 'static,
+    C: Debug + Send + 'static,
+    Q: Debug + Send + 'static,
+>(
+    err: Error<B, P, C, Q>,
+) -> DynError {
+    match err {
+        Error::Build(err) => Error::Build(Box::new(err)),
+        Error::Pool(err) => Error::Pool(Box::new(err)),
+        Error::Connection(err) => Error::Connection(Box::new(err)),
+        Error::Query(err) => Error::Query(Box::new(err)),
+        Error::MissingDatabaseConnection(db_id) => Error::MissingDatabaseConnection(db_id),
+        Error::InvalidDatabaseName(db_name) => Error::InvalidDatabaseName(db_name),
+        Error::FailedEntityCreation(db_id) => Error::FailedEntityCreation(db_id),
+        Error::UncleanDatabase(db_id, table_names) => Error::UncleanDatabase(db_id, table_names),
+        Error::LeasedDatabases(db_ids) => Error::LeasedDatabases(db_ids),
+        Error::UnrestrictedDatabase(db_id) => Error::UnrestrictedDatabase(db_id),
+        Error::ConnectTimeout(timeout) => Error::ConnectTimeout(timeout),
+        Error::MaxDatabasesExceeded(n, max) => Error::MaxDatabasesExceeded(n, max),
+        Error::RoleExists(name) => Error::RoleExists(name),
+    }
+}
+
+trait ErasedConnectionPool: Send {
+    fn database_id(&self) -> Uuid;
+    fn database_name(&self) -> &str;
+    fn database_url(&self) -> Option<String>;
+    fn pool(&self) -> &dyn Any;
+}
+
+impl<B: Backend> ErasedConnectionPool for ReusableConnectionPool<'_, B>
+where
+    B::Pool: 'static,
+{
+    fn database_id(&self) -> Uuid {
+        (**self).database_id()
+    }
+
+    fn database_name(&self) -> &str {
+        (**self).database_name()
+    }
+
+    fn database_url(&self) -> Option<String> {
+        (**self).database_url()
+    }
+
+    fn pool(&self) -> &dyn Any {
+        &***self
+    }
+}
+
+impl<B: Backend> ErasedConnectionPool for SingleUseConnectionPool<B>
+where
+    B::Pool: 'static,
+{
+    fn database_id(&self) -> Uuid {
+        self.database_id()
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name()
+    }
+
+    fn database_url(&self) -> Option<String> {
+        self.database_url()
+    }
+
+    fn pool(&self) -> &dyn Any {
+        &**self
+    }
+}
+
+/// Connection pool with its backend type erased, handed out by a [`DynDatabasePool`]
+pub struct DynConnectionPool<'a>(Box<dyn ErasedConnectionPool + 'a>);
+
+impl DynConnectionPool<'_> {
+    /// Returns the ID of the database, from which its name is derived
+    #[must_use]
+    pub fn database_id(&self) -> Uuid {
+        self.0.database_id()
+    }
+
+    /// Returns the name of the database
+    #[must_use]
+    pub fn database_name(&self) -> &str {
+        self.0.database_name()
+    }
+
+    /// Returns the connection URL of the database for its attached user
+    ///
+    /// [`None`] if the backend does not expose connection URLs.
+    #[must_use]
+    pub fn database_url(&self) -> Option<String> {
+        self.0.database_url()
+    }
+
+    /// Returns the underlying connection pool if it is of type ``P``
+    #[must_use]
+    pub fn pool<P: 'static>(&self) -> Option<&P> {
+        self.0.pool().downcast_ref()
+    }
+}
+
+#[async_trait]
+trait ErasedDatabasePool: Send + Sync {
+    async fn pull_immutable(&self) -> DynConnectionPool<'_>;
+    async fn create_mutable(&self) -> Result<DynConnectionPool<'static>, DynError>;
+    async fn create_mutable_empty(&self) -> Result<DynConnectionPool<'static>, DynError>;
+    fn leased_databases(&self) -> Vec<Uuid>;
+    fn take_teardown_errors(&self) -> Vec<DynError>;
+    async fn refresh_schema(&self) -> Result<(), DynError>;
+    async fn assert_restricted(&self) -> Result<(), DynError>;
+}
+
+#[async_trait]
+impl<B: Backend> ErasedDatabasePool for DatabasePool<B>
+where
+    B::Pool: 'static,
+    B::BuildError: 'static,
+    B::PoolError: 'static,
+    B::ConnectionError: Send + 'static,
+    B::QueryError: Send + 'static,
+{
+    async fn pull_immutable(&self) -> DynConnectionPool<'_> {
+        DynConnectionPool(Box::new(self.pull_immutable().await))
+    }
+
+    async fn create_mutable(&self) -> Result<DynConnectionPool<'static>, DynError> {
+        self.create_mutable()
+            .await
+            .map(|conn_pool| DynConnectionPool(Box::new(conn_pool)))
+            .map_err(erase_error)
+    }
+
+    async fn create_mutable_empty(&self) -> Result<DynConnectionPool<'static>, DynError> {
+        self.create_mutable_empty()
+            .await
+            .map(|conn_pool| DynConnectionPool(Box::new(conn_pool)))
+            .map_err(erase_error)
+    }
+
+    fn leased_databases(&self) -> Vec<Uuid> {
+        self.leased_databases()
+    }
+
+    fn take_teardown_errors(&self) -> Vec<DynError> {
+        self.take_teardown_errors()
+            .into_iter()
+            .map(erase_error)
+            .collect()
+    }
+
+    async fn refresh_schema(&self) -> Result<(), DynError> {
+        self.refresh_schema().await.map_err(erase_error)
+    }
+
+    async fn assert_restricted(&self) -> Result<(), DynError> {
+        self.assert_restricted().await.map_err(erase_error)
+    }
+}
+
+/// Database pool with its backend type erased, for choosing a backend at runtime
+/// # Example
+/// ```
+/// use bb8::Pool;
+/// use db_pool::{
+///     r#async::{
+///         DatabasePoolBuilderTrait, DieselAsyncPostgresBackend, DieselBb8, DynDatabasePool,
+///     },
+///     PrivilegedPostgresConfig,
+/// };
+/// use diesel_async::{pooled_connection::AsyncDieselConnectionManager, AsyncPgConnection};
+/// use dotenvy::dotenv;
+///
+/// async fn f() {
+///     dotenv().ok();
+///
+///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+///
+///     let backend = DieselAsyncPostgresBackend::<DieselBb8>::new(
+///         config,
+///         || Pool::builder().max_size(10),
+///         || Pool::builder().max_size(2),
+///         None,
+///         move |conn| Box::pin(async { conn }),
+///     )
+///     .await
+///     .unwrap();
+///
+///     let db_pool = DynDatabasePool::from(backend.create_database_pool().await.unwrap());
+///     let conn_pool = db_pool.pull_immutable().await;
+///     let pool = conn_pool
+///         .pool::<Pool<AsyncDieselConnectionManager<AsyncPgConnection>>>()
+///         .unwrap();
+/// }
+///
+/// tokio_test::block_on(f());
+/// ```
+pub struct DynDatabasePool(Box<dyn ErasedDatabasePool>);
+
+impl DynDatabasePool {
+    /// Pulls a reusable connection pool
+    ///
+    /// Privileges are granted only for ``SELECT``, ``INSERT``, ``UPDATE``, and ``DELETE`` operations.
+    pub async fn pull_immutable(&self) -> DynConnectionPool<'_> {
+        self.0.pull_immutable().await
+    }
+
+    /// Creates a single-use connection pool
+    ///
+    /// All privileges are granted.
+    pub async fn create_mutable(&self) -> Result<DynConnectionPool<'static>, DynError> {
+        self.0.create_mutable().await
+    }
+
+    /// Creates a single-use connection pool to an empty database
+    ///
+    /// All privileges are granted and entity creation is skipped.
+    pub async fn create_mutable_empty(&self) -> Result<DynConnectionPool<'static>, DynError> {
+        self.0.create_mutable_empty().await
+    }
+
+    /// Returns the IDs of databases currently leased through pulled and created connection pools
+    #[must_use]
+    pub fn leased_databases(&self) -> Vec<Uuid> {
+        self.0.leased_databases()
+    }
+
+    /// Takes the errors of databases that failed to drop while dropping their connection pools
+    #[must_use]
+    pub fn take_teardown_errors(&self) -> Vec<DynError> {
+        self.0.take_teardown_errors()
+    }
+
+    /// Drops the tables of idle pooled databases and re-runs entity creation on them
+    ///
+    /// Fails without refreshing any database if a pulled database is still leased.
+    pub async fn refresh_schema(&self) -> Result<(), DynError> {
+        self.0.refresh_schema().await
+    }
+
+    /// Pulls a reusable connection pool and verifies that its attached role is unable to create tables
+    pub async fn assert_restricted(&self) -> Result<(), DynError> {
+        self.0.assert_restricted().await
+    }
+}
+
+impl<B: Backend> From<DatabasePool<B>> for DynDatabasePool
+where
+    B::Pool: 'static,
+    B::BuildError: 'static,
+    B::PoolError: 'static,
+    B::ConnectionError: Send + 'static,
+    B::QueryError: Send + 'static,
+{
+    fn from(value: DatabasePool<B>) -> Self {
+        Self(Box::new(value))
+    }
+}
@@ -1,12 +1,23 @@
 mod backend;
+mod bridge;
 mod conn_pool;
 mod db_pool;
+mod dyn_db_pool;
 mod object_pool;
+#[cfg(any(feature = "_sqlx", feature = "_diesel-async"))]
+mod rollback;
+mod schema_provider;
 mod wrapper;
 
 pub use backend::*;
+pub use bridge::block_on;
 pub use conn_pool::SingleUseConnectionPool;
 pub use db_pool::{
     DatabasePool, DatabasePoolBuilder as DatabasePoolBuilderTrait, ReusableConnectionPool,
+    SharedConnectionPool,
 };
+pub use dyn_db_pool::{DynConnectionPool, DynDatabasePool};
+#[cfg(any(feature = "_sqlx", feature = "_diesel-async"))]
+pub use rollback::RollbackPool as RollbackPoolTrait;
+pub use schema_provider::SchemaProvider;
 pub use wrapper::PoolWrapper;
@@ -1,20 +1,43 @@
 // adapted from https://github.com/CJP10/object-pool and https://github.com/EVaillant/lockfree-object-pool
 
+use futures::future::join_all;
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::{OnceCell, Semaphore, SemaphorePermit};
+use uuid::Uuid;
 
 type Stack<T> = Vec<T>;
 type Init<T> =
     Box<dyn Fn() -> Pin<Box<dyn Future<Output = T> + Send + 'static>> + Send + Sync + 'static>;
 type Reset<T> =
     Box<dyn Fn(T) -> Pin<Box<dyn Future<Output = T> + Send + 'static>> + Send + Sync + 'static>;
+type LeaseKey<T> = fn(&T) -> Uuid;
 
 pub(crate) struct ObjectPool<T> {
     objects: Mutex<Stack<T>>,
+    ready: Mutex<Stack<T>>,
     init: Init<T>,
     reset: Reset<T>,
+    acquire: Option<Reset<T>>,
+    first_acquire: Option<(LeaseKey<T>, Reset<T>)>,
+    first_acquired: Mutex<HashSet<Uuid>>,
+    capacity: Option<Semaphore>,
+    max_capacity: Option<usize>,
+    detach_on_panic: bool,
+    detach_on_return: bool,
+    lease_key: Option<LeaseKey<T>>,
+    leased: Mutex<Vec<Uuid>>,
+    keyed: Mutex<HashMap<String, Keyed<T>>>,
+}
+
+struct Keyed<T> {
+    object: Arc<OnceCell<T>>,
+    handles: usize,
 }
 
 impl<T> ObjectPool<T> {
@@ -24,39 +47,335 @@ impl<T> ObjectPool<T> {
     ) -> ObjectPool<T> {
         ObjectPool {
             objects: Mutex::new(Vec::new()),
+            ready: Mutex::new(Vec::new()),
             init: Box::new(init),
             reset: Box::new(reset),
+            acquire: None,
+            first_acquire: None,
+            first_acquired: Mutex::new(HashSet::new()),
+            capacity: None,
+            max_capacity: None,
+            detach_on_panic: false,
+            detach_on_return: false,
+            lease_key: None,
+            leased: Mutex::new(Vec::new()),
+            keyed: Mutex::new(HashMap::new()),
         }
     }
 
-    pub(crate) async fn pull(&self) -> Reusable<T> {
-        let object = self.objects.lock().pop();
-        let object = if let Some(object) = object {
-            (self.reset)(object).await
+    pub(crate) fn on_acquire(
+        self,
+        acquire: impl Fn(T) -> Pin<Box<dyn Future<Output = T> + Send + 'static>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            acquire: Some(Box::new(acquire)),
+            ..self
+        }
+    }
+
+    pub(crate) fn on_first_acquire(
+        self,
+        key: LeaseKey<T>,
+        acquire: impl Fn(T) -> Pin<Box<dyn Future<Output = T> + Send + 'static>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            first_acquire: Some((key, Box::new(acquire))),
+            ..self
+        }
+    }
+
+    pub(crate) fn capacity(self, value: usize) -> Self {
+        Self {
+            capacity: Some(Semaphore::new(value)),
+            max_capacity: Some(value),
+            ..self
+        }
+    }
+
+    pub(crate) fn detach_on_panic(self, value: bool) -> Self {
+        Self {
+            detach_on_panic: value,
+            ..self
+        }
+    }
+
+    pub(crate) fn detach_on_return(self, value: bool) -> Self {
+        Self {
+            detach_on_return: value,
+            ..self
+        }
+    }
+
+    pub(crate) fn track_leases(self, lease_key: LeaseKey<T>) -> Self {
+        Self {
+            lease_key: Some(lease_key),
+            ..self
+        }
+    }
+
+    pub(crate) fn leased(&self) -> Vec<Uuid> {
+        self.leased.lock().clone()
+    }
+
+    pub(crate) fn max_capacity(&self) -> Option<usize> {
+        self.max_capacity
+    }
+
+    pub(crate) fn remaining_capacity(&self) -> Option<usize> {
+        // Count objects in the pool against the capacity not taken by leased objects
+        self.capacity.as_ref().map(|capacity| {
+            capacity
+                .available_permits()
+                .saturating_sub(self.available())
+        })
+    }
+
+    async fn acquire_capacity(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        // Wait for objects to be returned if capacity is reached
+        if let Some(capacity) = &self.capacity {
+            Some(
+                capacity
+                    .acquire_many(u32::try_from(n).expect("objects pulled at once must fit in u32"))
+                    .await
+                    .expect("capacity semaphore must never be closed"),
+            )
+        } else {
+            None
+        }
+    }
+
+    async fn acquire(&self) -> (T, bool) {
+        // Prefer objects already reset ahead of time
+        let ready = self.ready.lock().pop();
+        let (object, reused) = if let Some(object) = ready {
+            (object, true)
+        } else {
+            let object = self.objects.lock().pop();
+            if let Some(object) = object {
+                ((self.reset)(object).await, true)
+            } else {
+                ((self.init)().await, false)
+            }
+        };
+        (self.run_acquire(object).await, reused)
+    }
+
+    async fn run_acquire(&self, object: T) -> T {
+        // Run closure only the first time an object is acquired, skipping it on reuse
+        let object = match &self.first_acquire {
+            Some((key, first_acquire)) if self.first_acquired.lock().insert(key(&object)) => {
+                first_acquire(object).await
+            }
+            _ => object,
+        };
+        if let Some(acquire) = &self.acquire {
+            acquire(object).await
         } else {
-            (self.init)().await
+            object
+        }
+    }
+
+    fn reserve(&self, n: usize) -> Vec<(T, bool)> {
+        // Take up to n objects out of the pool, preferring those already reset ahead of time,
+        // along with whether each still needs to be reset
+        let mut reserved = Vec::with_capacity(n);
+        {
+            let mut ready = self.ready.lock();
+            while reserved.len() < n {
+                let Some(object) = ready.pop() else { break };
+                reserved.push((object, false));
+            }
+        }
+        {
+            let mut objects = self.objects.lock();
+            while reserved.len() < n {
+                let Some(object) = objects.pop() else { break };
+                reserved.push((object, true));
+            }
+        }
+        reserved
+    }
+
+    pub(crate) async fn pull(&self) -> Reusable<T> {
+        let permit = self.acquire_capacity(1).await;
+        let (object, reused) = self.acquire().await;
+        Reusable::new(self, object, permit, reused)
+    }
+
+    pub(crate) async fn pull_many<E, F: Future<Output = Result<Vec<T>, E>>>(
+        &self,
+        n: usize,
+        prepare: impl FnOnce(usize) -> F,
+    ) -> Result<Vec<Reusable<T>>, E> {
+        // Take capacity for all objects at once so that partially pulled objects cannot wait on each other
+        let permit = self.acquire_capacity(n).await;
+
+        // Reserve objects in the pool so that concurrent pulls cannot take them,
+        // then prepare exactly the objects missing from the pool
+        let reserved = self.reserve(n);
+        let prepared = match prepare(n - reserved.len()).await {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                // Return reserved objects to the pool
+                for (object, resets) in reserved {
+                    if resets {
+                        self.attach(object);
+                    } else {
+                        self.ready.lock().push(object);
+                    }
+                }
+                return Err(err);
+            }
+        };
+
+        let objects = join_all(
+            reserved
+                .into_iter()
+                .chain(prepared.into_iter().map(|object| (object, false)))
+                .map(|(object, resets)| async move {
+                    let object = if resets {
+                        (self.reset)(object).await
+                    } else {
+                        object
+                    };
+                    self.run_acquire(object).await
+                }),
+        )
+        .await;
+
+        // Hand capacity over to each object
+        let holds_capacity = permit.is_some();
+        if let Some(permit) = permit {
+            permit.forget();
+        }
+        Ok(objects
+            .into_iter()
+            .map(|object| Reusable::with_capacity(self, object, holds_capacity, true))
+            .collect())
+    }
+
+    pub(crate) async fn pull_keyed(&self, key: &str) -> SharedReusable<T> {
+        // Register key before taking capacity so that concurrent pulls under the same key
+        // wait on the same object instead of on capacity held by each other
+        let object = {
+            let mut keyed = self.keyed.lock();
+            let entry = keyed.entry(key.to_owned()).or_insert_with(|| Keyed {
+                object: Arc::new(OnceCell::new()),
+                handles: 0,
+            });
+            entry.handles += 1;
+            entry.object.clone()
         };
-        Reusable::new(self, object)
+        let shared = SharedReusable::new(self, key, object);
+
+        // Pull object once for all handles, letting another handle take over if this one is cancelled
+        shared
+            .data
+            .as_ref()
+            .expect(DATA_MUST_CONTAIN_SOME)
+            .get_or_init(|| async {
+                let permit = self.acquire_capacity(1).await;
+                let (object, _) = self.acquire().await;
+
+                // Keep capacity taken until the last handle is dropped
+                if let Some(permit) = permit {
+                    permit.forget();
+                }
+
+                self.lease(&object);
+                object
+            })
+            .await;
+
+        shared
+    }
+
+    fn restore_capacity(&self) {
+        if let Some(capacity) = &self.capacity {
+            capacity.add_permits(1);
+        }
+    }
+
+    pub(crate) fn available(&self) -> usize {
+        self.objects.lock().len() + self.ready.lock().len()
+    }
+
+    pub(crate) fn extend(&self, objects: impl IntoIterator<Item = T>) {
+        self.objects.lock().extend(objects);
+    }
+
+    pub(crate) fn extend_ready(&self, objects: impl IntoIterator<Item = T>) {
+        self.ready.lock().extend(objects);
+    }
+
+    pub(crate) fn drain(&self) -> Vec<T> {
+        let mut objects = std::mem::take(&mut *self.objects.lock());
+        objects.append(&mut self.ready.lock());
+        objects
+    }
+
+    pub(crate) fn drain_unready(&self) -> Vec<T> {
+        std::mem::take(&mut *self.objects.lock())
     }
 
     fn attach(&self, t: T) {
         self.objects.lock().push(t);
     }
+
+    fn lease(&self, t: &T) {
+        if let Some(lease_key) = self.lease_key {
+            self.leased.lock().push(lease_key(t));
+        }
+    }
+
+    fn release(&self, t: &T) {
+        if let Some(lease_key) = self.lease_key {
+            let key = lease_key(t);
+            self.leased.lock().retain(|leased_key| *leased_key != key);
+        }
+    }
 }
 
 /// Reusable object wrapper
 pub struct Reusable<'a, T> {
     pool: &'a ObjectPool<T>,
     data: Option<T>,
+    holds_capacity: bool,
+    reused: bool,
 }
 
 impl<'a, T> Reusable<'a, T> {
-    fn new(pool: &'a ObjectPool<T>, t: T) -> Self {
+    fn new(
+        pool: &'a ObjectPool<T>,
+        t: T,
+        permit: Option<SemaphorePermit<'a>>,
+        reused: bool,
+    ) -> Self {
+        let holds_capacity = permit.is_some();
+        if let Some(permit) = permit {
+            permit.forget();
+        }
+        Self::with_capacity(pool, t, holds_capacity, reused)
+    }
+
+    fn with_capacity(pool: &'a ObjectPool<T>, t: T, holds_capacity: bool, reused: bool) -> Self {
+        pool.lease(&t);
         Self {
             pool,
             data: Some(t),
+            holds_capacity,
+            reused,
         }
     }
+
+    /// Returns whether the object was taken from the pool rather than newly created when pulled
+    ///
+    /// Objects created ahead of time, such as databases created by
+    /// [`DatabasePool::prewarm`](super::DatabasePool::prewarm), count as reused.
+    #[must_use]
+    pub fn was_reused(&self) -> bool {
+        self.reused
+    }
 }
 
 const DATA_MUST_CONTAIN_SOME: &str = "data must always contain a [Some] value";
@@ -80,15 +399,99 @@ impl<'a, T> DerefMut for Reusable<'a, T> {
 impl<'a, T> Drop for Reusable<'a, T> {
     #[inline]
     fn drop(&mut self) {
-        self.pool
-            .attach(self.data.take().expect(DATA_MUST_CONTAIN_SOME));
+        let data = self.data.take().expect(DATA_MUST_CONTAIN_SOME);
+        self.pool.release(&data);
+
+        // Drop object instead of returning it to the pool if always detached
+        // or if dropped while unwinding from a panic
+        if !(self.pool.detach_on_return || self.pool.detach_on_panic && thread::panicking()) {
+            self.pool.attach(data);
+        }
+
+        if self.holds_capacity {
+            self.pool.restore_capacity();
+        }
+    }
+}
+
+/// Reusable object wrapper shared by all handles pulled under the same key
+pub struct SharedReusable<'a, T> {
+    pool: &'a ObjectPool<T>,
+    key: String,
+    data: Option<Arc<OnceCell<T>>>,
+}
+
+impl<'a, T> SharedReusable<'a, T> {
+    fn new(pool: &'a ObjectPool<T>, key: &str, data: Arc<OnceCell<T>>) -> Self {
+        Self {
+            pool,
+            key: key.to_owned(),
+            data: Some(data),
+        }
+    }
+}
+
+impl<'a, T> Deref for SharedReusable<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get())
+            .expect(DATA_MUST_CONTAIN_SOME)
+    }
+}
+
+impl<'a, T> Drop for SharedReusable<'a, T> {
+    fn drop(&mut self) {
+        let data = self.data.take().expect(DATA_MUST_CONTAIN_SOME);
+
+        {
+            let mut keyed = self.pool.keyed.lock();
+            let entry = keyed
+                .get_mut(&self.key)
+                .expect("keyed object must exist while handles remain");
+            entry.handles -= 1;
+
+            // Keep object until the last handle is dropped,
+            // releasing this handle's reference before unlocking so that the last handle owns the object
+            if entry.handles > 0 {
+                drop(data);
+                return;
+            }
+
+            keyed.remove(&self.key);
+        }
+
+        // Nothing was pulled if every handle was dropped before the object was ready
+        let Some(data) = Arc::into_inner(data)
+            .expect("last handle must own the keyed object")
+            .into_inner()
+        else {
+            return;
+        };
+        self.pool.release(&data);
+
+        // Drop object instead of returning it to the pool if always detached
+        // or if dropped while unwinding from a panic
+        if !(self.pool.detach_on_return || self.pool.detach_on_panic && thread::panicking()) {
+            self.pool.attach(data);
+        }
+
+        self.pool.restore_capacity();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ObjectPool;
-    use std::mem::drop;
+    use futures::{
+        future::{join, join_all},
+        FutureExt,
+    };
+    use std::{mem::drop, panic::AssertUnwindSafe, sync::Barrier, thread, time::Duration};
+    use uuid::Uuid;
 
     impl<T> ObjectPool<T> {
         fn len(&self) -> usize {
@@ -149,6 +552,218 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn extend() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Vec::<u8>::new() }),
+            |obj| Box::pin(async { obj }),
+        );
+
+        pool.extend([vec![1], vec![2]]);
+        assert_eq!(pool.len(), 2);
+
+        let object = pool.pull().await;
+        assert_eq!(*object, vec![2]);
+        drop(object);
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn was_reused() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Vec::<u8>::new() }),
+            |obj| Box::pin(async { obj }),
+        );
+
+        let object = pool.pull().await;
+        assert!(!object.was_reused());
+        drop(object);
+
+        let object = pool.pull().await;
+        assert!(object.was_reused());
+
+        // object created while the other is held must be new
+        assert!(!pool.pull().await.was_reused());
+    }
+
+    #[tokio::test]
+    async fn extend_ready() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Vec::<u8>::new() }),
+            |mut obj| {
+                Box::pin(async {
+                    obj.clear();
+                    obj
+                })
+            },
+        );
+
+        pool.extend([vec![1]]);
+        pool.extend_ready([vec![2]]);
+        assert_eq!(pool.drain_unready(), vec![vec![1]]);
+        pool.extend([vec![1]]);
+
+        // ready objects must be pulled first without being reset
+        let object1 = pool.pull().await;
+        assert_eq!(*object1, vec![2]);
+        let object2 = pool.pull().await;
+        assert_eq!(*object2, Vec::<u8>::new());
+    }
+
+    #[tokio::test]
+    async fn pull_many() {
+        let pool = &ObjectPool::new(
+            || Box::pin(async { vec![0] }),
+            |obj| Box::pin(async { obj }),
+        );
+
+        pool.extend([vec![1]]);
+        pool.extend_ready([vec![2]]);
+
+        // objects in the pool must be reserved before preparing the missing ones,
+        // so that a concurrent pull cannot take them
+        let objects = pool
+            .pull_many(3, |missing| async move {
+                assert_eq!(missing, 1);
+                assert_eq!(*pool.pull().await, vec![0]);
+                Ok::<_, ()>(vec![vec![3]])
+            })
+            .await
+            .unwrap();
+        let mut values = objects.iter().map(|object| object[0]).collect::<Vec<_>>();
+        values.sort_unstable();
+        assert_eq!(values, vec![1, 2, 3]);
+        drop(objects);
+
+        // reserved objects must be returned to the pool if preparing fails
+        drop(pool.drain());
+        pool.extend([vec![1]]);
+        pool.extend_ready([vec![2]]);
+        assert!(pool
+            .pull_many(3, |_| async { Err::<Vec<Vec<u8>>, _>(()) })
+            .await
+            .is_err());
+        assert_eq!(pool.drain_unready(), vec![vec![1]]);
+        assert_eq!(pool.drain(), vec![vec![2]]);
+    }
+
+    #[tokio::test]
+    async fn capacity() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Vec::<u8>::new() }),
+            |obj| Box::pin(async { obj }),
+        )
+        .capacity(1);
+
+        let object1 = pool.pull().await;
+        assert!(pool.pull().now_or_never().is_none());
+
+        drop(object1);
+        let object2 = pool.pull().now_or_never();
+        assert!(object2.is_some());
+        drop(object2);
+
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn detach_on_panic() {
+        for (detach_on_panic, len) in [(false, 1), (true, 0)] {
+            let pool = ObjectPool::new(
+                || Box::pin(async { Vec::<u8>::new() }),
+                |obj| Box::pin(async { obj }),
+            )
+            .detach_on_panic(detach_on_panic);
+
+            let result = AssertUnwindSafe(async {
+                let _object = pool.pull().await;
+                panic!("object must be dropped while panicking");
+            })
+            .catch_unwind()
+            .await;
+
+            assert!(result.is_err());
+
+            assert_eq!(pool.len(), len);
+        }
+    }
+
+    #[tokio::test]
+    async fn detach_on_return() {
+        for (detach_on_return, len) in [(false, 1), (true, 0)] {
+            let pool = ObjectPool::new(
+                || Box::pin(async { Vec::<u8>::new() }),
+                |obj| Box::pin(async { obj }),
+            )
+            .detach_on_return(detach_on_return);
+
+            let object = pool.pull().await;
+            drop(object);
+
+            assert_eq!(pool.len(), len);
+        }
+    }
+
+    #[tokio::test]
+    async fn on_acquire() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Vec::new() }),
+            |mut v| {
+                Box::pin(async {
+                    v.clear();
+                    v
+                })
+            },
+        )
+        .on_acquire(|mut v| {
+            Box::pin(async {
+                v.push(1);
+                v
+            })
+        });
+
+        let mut object = pool.pull().await;
+        assert_eq!(*object, vec![1]);
+        object.push(2);
+        drop(object);
+        let object = pool.pull().await;
+        assert_eq!(*object, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn on_first_acquire() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { (Uuid::new_v4(), Vec::new()) }),
+            |(id, mut v)| {
+                Box::pin(async move {
+                    v.clear();
+                    (id, v)
+                })
+            },
+        )
+        .on_first_acquire(
+            |(id, _)| *id,
+            |(id, mut v)| {
+                Box::pin(async move {
+                    v.push(1);
+                    (id, v)
+                })
+            },
+        );
+
+        let object1 = pool.pull().await;
+        let object2 = pool.pull().await;
+
+        // closure must run for every new object
+        assert_eq!(object1.1, vec![1]);
+        assert_eq!(object2.1, vec![1]);
+
+        // closure must be skipped when an object is reused
+        drop(object2);
+        let object2 = pool.pull().await;
+        assert_eq!(object2.1, Vec::<u8>::new());
+    }
+
     #[tokio::test]
     async fn reset() {
         let pool = ObjectPool::new(
@@ -181,4 +796,115 @@ mod tests {
         let object = pool.pull().await;
         assert_eq!(object.len(), 1);
     }
+
+    #[tokio::test]
+    async fn pull_keyed() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Uuid::new_v4() }),
+            |obj| Box::pin(async move { obj }),
+        )
+        .capacity(2)
+        .track_leases(|id| *id);
+
+        let object1 = pool.pull_keyed("a").await;
+        let object2 = pool.pull_keyed("a").await;
+        let object3 = pool.pull_keyed("b").await;
+
+        // same key must share the same object
+        assert_eq!(*object1, *object2);
+        assert_ne!(*object1, *object3);
+        assert_eq!(pool.leased(), vec![*object1, *object3]);
+
+        // capacity must be taken once per key
+        assert!(pool.pull().now_or_never().is_none());
+
+        // object must be kept while a handle remains
+        let id = *object1;
+        drop(object1);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(*pool.pull_keyed("a").await, id);
+
+        // object must be returned once all handles are dropped
+        drop(object2);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.leased(), vec![*object3]);
+        assert!(pool.pull().now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn pull_keyed_concurrently() {
+        let pool = ObjectPool::new(
+            || {
+                Box::pin(async {
+                    tokio::task::yield_now().await;
+                    Uuid::new_v4()
+                })
+            },
+            |obj| Box::pin(async move { obj }),
+        )
+        .capacity(1);
+
+        // concurrent pulls under the same new key must share the object instead of waiting on capacity
+        let (object1, object2) = tokio::time::timeout(
+            Duration::from_secs(1),
+            join(pool.pull_keyed("a"), pool.pull_keyed("a")),
+        )
+        .await
+        .expect("concurrent pulls under the same key must not deadlock");
+        assert_eq!(*object1, *object2);
+        drop((object1, object2));
+
+        // pull cancelled before the object is ready must not keep capacity taken
+        drop(pool.drain());
+        assert!(pool.pull_keyed("b").now_or_never().is_none());
+        assert!(tokio::time::timeout(Duration::from_secs(1), pool.pull())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drop_keyed_concurrently() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Uuid::new_v4() }),
+            |obj| Box::pin(async move { obj }),
+        );
+
+        for _ in 0..1000 {
+            let objects = join_all((0..8).map(|_| pool.pull_keyed("a"))).await;
+            let barrier = Barrier::new(objects.len());
+
+            // dropping handles concurrently must return the object exactly once
+            thread::scope(|scope| {
+                for object in objects {
+                    let barrier = &barrier;
+                    scope.spawn(move || {
+                        barrier.wait();
+                        drop(object);
+                    });
+                }
+            });
+            assert_eq!(pool.len(), 1);
+            drop(pool.pull().await);
+        }
+    }
+
+    #[tokio::test]
+    async fn track_leases() {
+        let pool = ObjectPool::new(
+            || Box::pin(async { Uuid::new_v4() }),
+            |obj| Box::pin(async move { obj }),
+        )
+        .track_leases(|id| *id);
+
+        let object1 = pool.pull().await;
+        let object2 = pool.pull().await;
+        assert_eq!(pool.leased(), vec![*object1, *object2]);
+
+        let id2 = *object2;
+        drop(object1);
+        assert_eq!(pool.leased(), vec![id2]);
+
+        drop(object2);
+        assert!(pool.leased().is_empty());
+    }
 }
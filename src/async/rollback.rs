@@ -0,0 +1,87 @@
+use std::{future::Future, pin::Pin};
+
+use async_trait::async_trait;
+
+/// Connection pool trait for running code within a transaction that is always rolled back
+///
+/// Implemented for the pools of sqlx and diesel-async backends, and usable on connection pool wrappers through dereferencing.
+/// This complements the database-per-test model for tests that share a database and need finer isolation.
+#[async_trait]
+pub trait RollbackPool {
+    /// Connection type passed to code run within a transaction
+    type Connection: Send;
+    /// Error type of acquiring a connection and beginning or rolling back a transaction
+    type Error;
+
+    /// Acquires a connection, runs a closure on it within a transaction, and rolls the transaction back
+    ///
+    /// The transaction is rolled back regardless of what the closure does, so that its changes are never committed.
+    /// # Example
+    /// ```
+    /// use db_pool::{
+    ///     r#async::{DatabasePoolBuilderTrait, RollbackPoolTrait, SqlxPostgresBackend},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use dotenvy::dotenv;
+    /// use sqlx::{postgres::PgPoolOptions, Executor};
+    ///
+    /// async fn f() {
+    ///     dotenv().ok();
+    ///
+    ///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    ///     let backend = SqlxPostgresBackend::new(
+    ///         config.into(),
+    ///         || PgPoolOptions::new().max_connections(10),
+    ///         |_| PgPoolOptions::new().max_connections(2),
+    ///         move |mut conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///                     .await
+    ///                     .unwrap();
+    ///                 conn
+    ///             })
+    ///         },
+    ///     );
+    ///
+    ///     let db_pool = backend.create_database_pool().await.unwrap();
+    ///     let conn_pool = db_pool.pull_immutable().await;
+    ///     conn_pool
+    ///         .with_rollback(|conn| {
+    ///             Box::pin(async move {
+    ///                 conn.execute("INSERT INTO book (title) VALUES ('Title')")
+    ///                     .await
+    ///                     .unwrap();
+    ///             })
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    /// }
+    ///
+    /// tokio_test::block_on(f());
+    /// ```
+    async fn with_rollback<T, F>(&self, f: F) -> Result<T, Self::Error>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut Self::Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send;
+}
+
+#[cfg(feature = "_sqlx")]
+#[async_trait]
+impl<DB: sqlx::Database> RollbackPool for sqlx::Pool<DB> {
+    type Connection = DB::Connection;
+    type Error = sqlx::Error;
+
+    async fn with_rollback<T, F>(&self, f: F) -> Result<T, Self::Error>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut Self::Connection) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send,
+    {
+        let mut tx = self.begin().await?;
+        let value = f(&mut tx).await;
+        tx.rollback().await?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::Debug;
+#[cfg(any(feature = "_diesel-async", feature = "_sqlx"))]
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+
+/// Schema setup shared between application code and database creation
+///
+/// Implementing it once allows the same migrations to be applied at application startup
+/// and by backends created via their `new_with_schema_provider` constructors.
+/// # Example
+/// ```
+/// use async_trait::async_trait;
+/// use db_pool::{
+///     r#async::{SchemaProvider, SqlxPostgresBackend},
+///     PrivilegedPostgresConfig,
+/// };
+/// use dotenvy::dotenv;
+/// use sqlx::{postgres::PgPoolOptions, Executor, PgConnection};
+///
+/// struct Migrations;
+///
+/// #[async_trait]
+/// impl SchemaProvider<PgConnection> for Migrations {
+///     type Error = sqlx::Error;
+///
+///     async fn apply(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+///         conn.execute("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+///             .await?;
+///         Ok(())
+///     }
+/// }
+///
+/// async fn f() {
+///     dotenv().ok();
+///
+///     let config = PrivilegedPostgresConfig::from_env().unwrap();
+///
+///     let backend = SqlxPostgresBackend::new_with_schema_provider(
+///         config.into(),
+///         || PgPoolOptions::new().max_connections(10),
+///         |_| PgPoolOptions::new().max_connections(2),
+///         Migrations,
+///     );
+/// }
+///
+/// tokio_test::block_on(f());
+/// ```
+#[async_trait]
+pub trait SchemaProvider<C: Send>: Send + Sync + 'static {
+    /// Error returned when the schema fails to be applied
+    type Error: Debug;
+
+    /// Applies the schema through a connection
+    async fn apply(&self, conn: &mut C) -> Result<(), Self::Error>;
+}
+
+/// Converts a schema provider into an entity creation closure passing the connection back
+#[cfg(any(feature = "_diesel-async", feature = "_sqlx"))]
+pub(crate) fn create_entities_with<C: Send + 'static>(
+    schema_provider: impl SchemaProvider<C>,
+) -> impl Fn(C) -> Pin<Box<dyn Future<Output = C> + Send + 'static>> + Send + Sync + 'static {
+    let schema_provider = Arc::new(schema_provider);
+    move |mut conn| {
+        let schema_provider = schema_provider.clone();
+        Box::pin(async move {
+            schema_provider
+                .apply(&mut conn)
+                .await
+                .expect("schema must be applied");
+            conn
+        })
+    }
+}
@@ -5,6 +5,8 @@ use super::{
 };
 
 /// Connection pool wrapper to facilitate the use of pools in code under test and reusable pools in tests
+///
+/// Any of the wrapped pools can be converted into a wrapper using [`From`].
 pub enum PoolWrapper<B: Backend> {
     /// Connection pool used in code under test
     Pool(B::Pool),
@@ -14,6 +16,13 @@ pub enum PoolWrapper<B: Backend> {
     SingleUsePool(SingleUseConnectionPool<B>),
 }
 
+impl<B: Backend> PoolWrapper<B> {
+    /// Wraps a connection pool used in code under test
+    pub fn from_pool(pool: B::Pool) -> Self {
+        Self::Pool(pool)
+    }
+}
+
 impl<B: Backend> Deref for PoolWrapper<B> {
     type Target = B::Pool;
 
@@ -37,3 +46,73 @@ impl<B: Backend> From<SingleUseConnectionPool<B>> for PoolWrapper<B> {
         Self::SingleUsePool(value)
     }
 }
+
+impl<B, M> From<bb8::Pool<M>> for PoolWrapper<B>
+where
+    B: Backend<Pool = bb8::Pool<M>>,
+    M: bb8::ManageConnection,
+{
+    fn from(value: bb8::Pool<M>) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "mock")]
+impl From<super::backend::MockPool> for PoolWrapper<super::backend::MockBackend> {
+    fn from(value: super::backend::MockPool) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(any(feature = "diesel-async-deadpool", feature = "tokio-postgres-deadpool"))]
+impl<B, M> From<deadpool::managed::Pool<M>> for PoolWrapper<B>
+where
+    B: Backend<Pool = deadpool::managed::Pool<M>>,
+    M: deadpool::managed::Manager,
+{
+    fn from(value: deadpool::managed::Pool<M>) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(any(feature = "diesel-async-mobc", feature = "tokio-postgres-mobc"))]
+impl<B, M> From<mobc::Pool<M>> for PoolWrapper<B>
+where
+    B: Backend<Pool = mobc::Pool<M>>,
+    M: mobc::Manager,
+{
+    fn from(value: mobc::Pool<M>) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "mysql-async")]
+impl<B> From<mysql_async::Pool> for PoolWrapper<B>
+where
+    B: Backend<Pool = mysql_async::Pool>,
+{
+    fn from(value: mysql_async::Pool) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "_sea-orm")]
+impl<B> From<sea_orm::DatabaseConnection> for PoolWrapper<B>
+where
+    B: Backend<Pool = sea_orm::DatabaseConnection>,
+{
+    fn from(value: sea_orm::DatabaseConnection) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "_sqlx")]
+impl<B, DB> From<sqlx::Pool<DB>> for PoolWrapper<B>
+where
+    B: Backend<Pool = sqlx::Pool<DB>>,
+    DB: sqlx::Database,
+{
+    fn from(value: sqlx::Pool<DB>) -> Self {
+        Self::Pool(value)
+    }
+}
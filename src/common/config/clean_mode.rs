@@ -0,0 +1,12 @@
+/// What happens to the database of a reusable connection pool once the connection pool is returned
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CleanMode {
+    /// The database is cleaned and reused by the next pulled connection pool
+    #[default]
+    Reuse,
+    /// The database is dropped, so that every pulled connection pool gets a freshly created database
+    ///
+    /// This trades database creation cost for skipping cleaning,
+    /// which suits tests that never rely on data left by other tests.
+    Drop,
+}
@@ -0,0 +1,14 @@
+/// How a database is cleaned once its reusable connection pool is returned
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CleanStrategy {
+    /// Tables are emptied using ``TRUNCATE``, or ``DELETE`` for [`CockroachDB`](super::PgDialect::CockroachDB)
+    #[default]
+    Truncate,
+    /// Tables are emptied using ``DELETE``
+    Delete,
+    /// Tables and sequences are dropped and entities are created again as the role they were first created as
+    ///
+    /// Suits tests that alter or drop entities, such as when entities are created as the restricted role,
+    /// at the cost of creating entities on every clean. Sequences are dropped using a ``DO`` block.
+    Recreate,
+}
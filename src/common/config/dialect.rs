@@ -0,0 +1,26 @@
+/// Postgres-compatible database targeted by a Postgres backend
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PgDialect {
+    /// [`PostgreSQL`](https://www.postgresql.org/)
+    #[default]
+    Postgres,
+    /// [`CockroachDB`](https://www.cockroachlabs.com/)
+    ///
+    /// Tables are cleaned using ``DELETE`` instead of ``TRUNCATE``, connections are not terminated
+    /// before dropping databases, and CockroachDB-specific schemas are excluded from cleaning.
+    CockroachDB,
+}
+
+impl PgDialect {
+    pub(crate) fn system_schemas(self) -> &'static [&'static str] {
+        match self {
+            Self::Postgres => &["pg_catalog", "information_schema"],
+            Self::CockroachDB => &[
+                "pg_catalog",
+                "information_schema",
+                "crdb_internal",
+                "pg_extension",
+            ],
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/// MySQL-compatible server targeted by a MySQL backend
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MySqlFlavor {
+    /// [`MySQL`](https://www.mysql.com/)
+    #[default]
+    MySQL,
+    /// [`MariaDB`](https://mariadb.org/)
+    ///
+    /// Sequences are excluded from cleaning since they cannot be truncated,
+    /// and users are not labeled since user comments are not supported.
+    MariaDB,
+}
+
+impl MySqlFlavor {
+    pub(crate) fn excluded_table_types(self) -> &'static [&'static str] {
+        match self {
+            Self::MySQL => &["VIEW"],
+            Self::MariaDB => &["VIEW", "SEQUENCE"],
+        }
+    }
+
+    #[cfg(feature = "_async-mysql")]
+    pub(crate) fn supports_user_comments(self) -> bool {
+        self == Self::MySQL
+    }
+}
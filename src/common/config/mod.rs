@@ -1,26 +1,56 @@
+mod clean_mode;
+#[cfg(feature = "_postgres")]
+mod clean_strategy;
+#[cfg(feature = "_postgres")]
+mod dialect;
+#[cfg(feature = "_mysql")]
+mod flavor;
 #[cfg(any(
     test,
     feature = "diesel-mysql",
     feature = "diesel-async-mysql",
-    feature = "sea-orm-mysql"
+    feature = "mysql-async",
+    feature = "sea-orm-mysql",
+    feature = "sqlx-mysql"
 ))]
 pub(crate) mod mysql;
+mod pool_sizes;
 #[cfg(any(
     feature = "diesel-postgres",
     feature = "diesel-async-postgres",
-    feature = "sea-orm-postgres"
+    feature = "sea-orm-postgres",
+    feature = "tokio-postgres"
 ))]
 pub(crate) mod postgres;
+#[cfg(feature = "_postgres")]
+mod privilege_profile;
+#[cfg(feature = "_postgres")]
+mod role;
 
+pub use clean_mode::CleanMode;
+#[cfg(feature = "_postgres")]
+pub use clean_strategy::CleanStrategy;
+#[cfg(feature = "_postgres")]
+pub use dialect::PgDialect;
+#[cfg(feature = "_mysql")]
+pub use flavor::MySqlFlavor;
 #[cfg(any(
     feature = "diesel-mysql",
     feature = "diesel-async-mysql",
-    feature = "sea-orm-mysql"
+    feature = "mysql-async",
+    feature = "sea-orm-mysql",
+    feature = "sqlx-mysql"
 ))]
 pub use mysql::PrivilegedMySQLConfig;
+pub use pool_sizes::{pool_sizes_from_env, PoolSizes, PoolSizesError};
 #[cfg(any(
     feature = "diesel-postgres",
     feature = "diesel-async-postgres",
-    feature = "sea-orm-postgres"
+    feature = "sea-orm-postgres",
+    feature = "tokio-postgres"
 ))]
 pub use postgres::PrivilegedPostgresConfig;
+#[cfg(feature = "_postgres")]
+pub use privilege_profile::PrivilegeProfile;
+#[cfg(feature = "_postgres")]
+pub use role::Role;
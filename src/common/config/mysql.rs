@@ -1,3 +1,5 @@
+use crate::util::{percent_encode, url_host, url_userinfo};
+
 /// Privileged MySQL configuration
 #[derive(Clone)]
 pub struct PrivilegedMySQLConfig {
@@ -5,6 +7,7 @@ pub struct PrivilegedMySQLConfig {
     pub(crate) password: Option<String>,
     pub(crate) host: String,
     pub(crate) port: u16,
+    pub(crate) maintenance_database: Option<String>,
 }
 
 impl PrivilegedMySQLConfig {
@@ -12,6 +15,7 @@ impl PrivilegedMySQLConfig {
     const DEFAULT_PASSWORD: Option<String> = None;
     const DEFAULT_HOST: &'static str = "localhost";
     const DEFAULT_PORT: u16 = 3306;
+    const DEFAULT_MAINTENANCE_DATABASE: Option<String> = None;
 
     /// Creates a new privileged MySQL configuration
     /// # Example
@@ -25,6 +29,7 @@ impl PrivilegedMySQLConfig {
     /// - Password: {blank}
     /// - Host: localhost
     /// - Port: 3306
+    /// - Maintenance database: {none}
     #[must_use]
     pub fn new() -> Self {
         Self {
@@ -32,6 +37,7 @@ impl PrivilegedMySQLConfig {
             password: Self::DEFAULT_PASSWORD,
             host: Self::DEFAULT_HOST.to_owned(),
             port: Self::DEFAULT_PORT,
+            maintenance_database: Self::DEFAULT_MAINTENANCE_DATABASE,
         }
     }
 
@@ -40,27 +46,34 @@ impl PrivilegedMySQLConfig {
     /// - `MYSQL_USERNAME`
     /// - `MYSQL_PASSWORD`
     /// - `MYSQL_HOST`
+    /// - `MYSQL_SOCKET`, taking precedence over `MYSQL_HOST`
     /// - `MYSQL_PORT`
+    /// - `MYSQL_MAINTENANCE_DATABASE`
     /// # Defaults
     /// - Username: root
     /// - Password: {blank}
     /// - Host: localhost
     /// - Port: 3306
+    /// - Maintenance database: {none}
     pub fn from_env() -> Result<Self, Error> {
         use std::env;
 
         let username = env::var("MYSQL_USERNAME").unwrap_or(Self::DEFAULT_USERNAME.to_owned());
         let password = env::var("MYSQL_PASSWORD").ok();
-        let host = env::var("MYSQL_HOST").unwrap_or(Self::DEFAULT_HOST.to_owned());
+        let host = env::var("MYSQL_SOCKET")
+            .or_else(|_| env::var("MYSQL_HOST"))
+            .unwrap_or(Self::DEFAULT_HOST.to_owned());
         let port = env::var("MYSQL_PORT")
             .map_or(Ok(Self::DEFAULT_PORT), |port| port.parse())
             .map_err(Error::InvalidPort)?;
+        let maintenance_database = env::var("MYSQL_MAINTENANCE_DATABASE").ok();
 
         Ok(Self {
             username,
             password,
             host,
             port,
+            maintenance_database,
         })
     }
 
@@ -96,11 +109,15 @@ impl PrivilegedMySQLConfig {
     }
 
     /// Sets a new host
+    ///
+    /// A host starting with ``/`` is the path of a Unix-domain socket, through which all privileged
+    /// and restricted connections are made. Database users are then created for ``localhost``.
     /// # Example
     /// ```
     /// # use db_pool::PrivilegedMySQLConfig;
     /// #
     /// let config = PrivilegedMySQLConfig::new().host("localhost".to_owned());
+    /// let config = PrivilegedMySQLConfig::new().host("/var/run/mysqld/mysqld.sock".to_owned());
     /// ```
     #[must_use]
     pub fn host(self, value: String) -> Self {
@@ -125,46 +142,96 @@ impl PrivilegedMySQLConfig {
         }
     }
 
-    pub(crate) fn default_connection_url(&self) -> String {
-        let Self {
-            username,
-            password,
-            host,
-            port,
-        } = self;
-        if let Some(password) = password {
-            format!("mysql://{username}:{password}@{host}:{port}")
-        } else {
-            format!("mysql://{username}@{host}:{port}")
+    /// Sets a new maintenance database, which the privileged user connects to
+    /// in order to create and drop databases
+    ///
+    /// Privileged connections are otherwise made without selecting a database.
+    /// # Example
+    /// ```
+    /// # use db_pool::PrivilegedMySQLConfig;
+    /// #
+    /// let config = PrivilegedMySQLConfig::new().maintenance_database("maintenance");
+    /// ```
+    #[must_use]
+    pub fn maintenance_database(self, value: impl Into<String>) -> Self {
+        Self {
+            maintenance_database: Some(value.into()),
+            ..self
         }
     }
 
+    /// Returns the connection URL of the maintenance database, or of the database server if none is set,
+    /// for the privileged user,
+    /// for building a privileged pool to be passed to a backend
+    /// # Example
+    /// ```
+    /// # use db_pool::PrivilegedMySQLConfig;
+    /// #
+    /// let url = PrivilegedMySQLConfig::new().default_connection_url();
+    /// ```
+    #[must_use]
+    pub fn default_connection_url(&self) -> String {
+        self.connection_url(
+            self.username.as_str(),
+            self.password.as_deref(),
+            self.maintenance_database.as_deref(),
+        )
+    }
+
+    #[cfg(any(feature = "diesel-async-mysql", feature = "sea-orm-mysql"))]
     pub(crate) fn privileged_database_connection_url(&self, db_name: &str) -> String {
-        let Self {
-            username,
-            password,
-            host,
-            port,
-            ..
-        } = self;
-        if let Some(password) = password {
-            format!("mysql://{username}:{password}@{host}:{port}/{db_name}")
-        } else {
-            format!("mysql://{username}@{host}:{port}/{db_name}")
-        }
+        self.connection_url(
+            self.username.as_str(),
+            self.password.as_deref(),
+            Some(db_name),
+        )
     }
 
+    #[cfg(any(
+        test,
+        feature = "diesel-mysql",
+        feature = "diesel-async-mysql",
+        feature = "sea-orm-mysql"
+    ))]
     pub(crate) fn restricted_database_connection_url(
         &self,
         username: &str,
         password: Option<&str>,
         db_name: &str,
+    ) -> String {
+        self.connection_url(username, password, Some(db_name))
+    }
+
+    #[cfg(any(
+        feature = "diesel-mysql",
+        feature = "diesel-async-mysql",
+        feature = "sea-orm-mysql"
+    ))]
+    pub(crate) fn user_host(&self) -> &str {
+        // Socket connections are made from localhost
+        if self.host.starts_with('/') {
+            Self::DEFAULT_HOST
+        } else {
+            self.host.as_str()
+        }
+    }
+
+    fn connection_url(
+        &self,
+        username: &str,
+        password: Option<&str>,
+        db_name: Option<&str>,
     ) -> String {
         let Self { host, port, .. } = self;
-        if let Some(password) = password {
-            format!("mysql://{username}:{password}@{host}:{port}/{db_name}")
+        let userinfo = url_userinfo(username, password);
+        let path = db_name.map_or_else(String::new, |db_name| format!("/{db_name}"));
+        if host.starts_with('/') {
+            // Socket paths are passed as a query parameter since they cannot be held in the host part of a URL
+            let socket = percent_encode(host);
+            format!("mysql://{userinfo}@localhost:{port}{path}?socket={socket}")
         } else {
-            format!("mysql://{username}@{host}:{port}/{db_name}")
+            let host = url_host(host);
+            format!("mysql://{userinfo}@{host}:{port}{path}")
         }
     }
 }
@@ -183,11 +250,18 @@ impl Default for PrivilegedMySQLConfig {
 #[cfg(feature = "mysql")]
 impl From<PrivilegedMySQLConfig> for r2d2_mysql::mysql::OptsBuilder {
     fn from(value: PrivilegedMySQLConfig) -> Self {
-        Self::new()
+        let opts = Self::new()
             .user(Some(value.username.clone()))
             .pass(value.password.clone())
-            .ip_or_hostname(Some(value.host.clone()))
             .tcp_port(value.port)
+            .db_name(value.maintenance_database.clone());
+
+        if value.host.starts_with('/') {
+            opts.ip_or_hostname(Some(PrivilegedMySQLConfig::DEFAULT_HOST))
+                .socket(Some(value.host))
+        } else {
+            opts.ip_or_hostname(Some(value.host))
+        }
     }
 }
 
@@ -198,6 +272,30 @@ impl From<PrivilegedMySQLConfig> for r2d2_mysql::mysql::Opts {
     }
 }
 
+#[cfg(feature = "mysql-async")]
+impl From<PrivilegedMySQLConfig> for mysql_async::OptsBuilder {
+    fn from(value: PrivilegedMySQLConfig) -> Self {
+        let opts = Self::default()
+            .user(Some(value.username))
+            .pass(value.password)
+            .tcp_port(value.port)
+            .db_name(value.maintenance_database);
+
+        if value.host.starts_with('/') {
+            opts.socket(Some(value.host))
+        } else {
+            opts.ip_or_hostname(value.host)
+        }
+    }
+}
+
+#[cfg(feature = "mysql-async")]
+impl From<PrivilegedMySQLConfig> for mysql_async::Opts {
+    fn from(value: PrivilegedMySQLConfig) -> Self {
+        mysql_async::OptsBuilder::from(value).into()
+    }
+}
+
 #[cfg(feature = "sqlx-mysql")]
 impl From<PrivilegedMySQLConfig> for sqlx::mysql::MySqlConnectOptions {
     fn from(value: PrivilegedMySQLConfig) -> Self {
@@ -206,12 +304,22 @@ impl From<PrivilegedMySQLConfig> for sqlx::mysql::MySqlConnectOptions {
             password,
             host,
             port,
+            maintenance_database,
         } = value;
 
-        let opts = Self::new()
-            .username(username.as_str())
-            .host(host.as_str())
-            .port(port);
+        let opts = Self::new().username(username.as_str()).port(port);
+
+        let opts = if let Some(maintenance_database) = maintenance_database {
+            opts.database(maintenance_database.as_str())
+        } else {
+            opts
+        };
+
+        let opts = if host.starts_with('/') {
+            opts.socket(host.as_str())
+        } else {
+            opts.host(host.as_str())
+        };
 
         if let Some(password) = password {
             opts.password(password.as_str())
@@ -0,0 +1,83 @@
+use std::{env, num::ParseIntError};
+
+/// Maximum connection pool sizes read from environment variables
+///
+/// Sizes are meant to be plugged into the pool builder closures passed to backends,
+/// so that pool sizes can be tuned without recompiling.
+/// # Example
+/// ```
+/// # use db_pool::PoolSizes;
+/// #
+/// let pool_sizes = PoolSizes {
+///     privileged: Some(20),
+///     restricted: None,
+/// };
+/// assert_eq!(pool_sizes.privileged_or(10), 20);
+/// assert_eq!(pool_sizes.restricted_or(2), 2);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolSizes {
+    /// Maximum size of privileged connection pools, read from `DB_POOL_PRIVILEGED_MAX`
+    pub privileged: Option<u32>,
+    /// Maximum size of restricted connection pools, read from `DB_POOL_RESTRICTED_MAX`
+    pub restricted: Option<u32>,
+}
+
+impl PoolSizes {
+    /// Maximum size of privileged connection pools, falling back to the given size if not set
+    #[must_use]
+    pub fn privileged_or(&self, default: u32) -> u32 {
+        self.privileged.unwrap_or(default)
+    }
+
+    /// Maximum size of restricted connection pools, falling back to the given size if not set
+    #[must_use]
+    pub fn restricted_or(&self, default: u32) -> u32 {
+        self.restricted.unwrap_or(default)
+    }
+}
+
+/// Error reading maximum connection pool sizes from environment variables
+#[derive(Debug)]
+pub enum PoolSizesError {
+    /// `DB_POOL_PRIVILEGED_MAX` is not a valid size
+    InvalidPrivilegedMax(ParseIntError),
+    /// `DB_POOL_RESTRICTED_MAX` is not a valid size
+    InvalidRestrictedMax(ParseIntError),
+}
+
+/// Reads maximum connection pool sizes from environment variables
+/// # Environment variables
+/// - `DB_POOL_PRIVILEGED_MAX`
+/// - `DB_POOL_RESTRICTED_MAX`
+/// # Precedence
+/// A size set in the environment takes precedence over the fallback size passed to
+/// [`PoolSizes::privileged_or`] or [`PoolSizes::restricted_or`].
+/// Sizes are never applied implicitly, so pool builders that do not use them keep their own sizes.
+/// # Example
+/// ```
+/// use db_pool::pool_sizes_from_env;
+///
+/// std::env::set_var("DB_POOL_PRIVILEGED_MAX", "20");
+///
+/// let pool_sizes = pool_sizes_from_env().unwrap();
+/// assert_eq!(pool_sizes.privileged_or(10), 20);
+/// assert_eq!(pool_sizes.restricted_or(2), 2);
+/// ```
+pub fn pool_sizes_from_env() -> Result<PoolSizes, PoolSizesError> {
+    let privileged = env::var("DB_POOL_PRIVILEGED_MAX")
+        .ok()
+        .map(|size| size.parse())
+        .transpose()
+        .map_err(PoolSizesError::InvalidPrivilegedMax)?;
+    let restricted = env::var("DB_POOL_RESTRICTED_MAX")
+        .ok()
+        .map(|size| size.parse())
+        .transpose()
+        .map_err(PoolSizesError::InvalidRestrictedMax)?;
+
+    Ok(PoolSizes {
+        privileged,
+        restricted,
+    })
+}
@@ -1,9 +1,14 @@
+use crate::util::{percent_encode, url_host, url_userinfo};
+
 /// Privileged Postgres configuration
+#[derive(Clone)]
 pub struct PrivilegedPostgresConfig {
     pub(crate) username: String,
     pub(crate) password: Option<String>,
     pub(crate) host: String,
     pub(crate) port: u16,
+    pub(crate) maintenance_database: String,
+    pub(crate) application_name: String,
 }
 
 impl PrivilegedPostgresConfig {
@@ -11,6 +16,8 @@ impl PrivilegedPostgresConfig {
     const DEFAULT_PASSWORD: Option<String> = None;
     const DEFAULT_HOST: &'static str = "localhost";
     const DEFAULT_PORT: u16 = 5432;
+    const DEFAULT_MAINTENANCE_DATABASE: &'static str = "postgres";
+    const DEFAULT_APPLICATION_NAME: &'static str = "db_pool";
 
     /// Creates a new privileged Postgres configuration with defaults
     /// # Example
@@ -24,6 +31,8 @@ impl PrivilegedPostgresConfig {
     /// - Password: {blank}
     /// - Host: localhost
     /// - Port: 5432
+    /// - Maintenance database: postgres
+    /// - Application name: `db_pool`
     #[must_use]
     pub fn new() -> Self {
         Self {
@@ -31,6 +40,8 @@ impl PrivilegedPostgresConfig {
             password: Self::DEFAULT_PASSWORD,
             host: Self::DEFAULT_HOST.to_owned(),
             port: Self::DEFAULT_PORT,
+            maintenance_database: Self::DEFAULT_MAINTENANCE_DATABASE.to_owned(),
+            application_name: Self::DEFAULT_APPLICATION_NAME.to_owned(),
         }
     }
 
@@ -39,27 +50,40 @@ impl PrivilegedPostgresConfig {
     /// - `POSTGRES_USERNAME`
     /// - `POSTGRES_PASSWORD`
     /// - `POSTGRES_HOST`
+    /// - `POSTGRES_SOCKET`, taking precedence over `POSTGRES_HOST`
     /// - `POSTGRES_PORT`
+    /// - `POSTGRES_MAINTENANCE_DATABASE`
+    /// - `POSTGRES_APPLICATION_NAME`
     /// # Defaults
     /// - Username: postgres
     /// - Password: {blank}
     /// - Host: localhost
     /// - Port: 5432
+    /// - Maintenance database: postgres
+    /// - Application name: `db_pool`
     pub fn from_env() -> Result<Self, Error> {
         use std::env;
 
         let username = env::var("POSTGRES_USERNAME").unwrap_or(Self::DEFAULT_USERNAME.to_owned());
         let password = env::var("POSTGRES_PASSWORD").ok();
-        let host = env::var("POSTGRES_HOST").unwrap_or(Self::DEFAULT_HOST.to_owned());
+        let host = env::var("POSTGRES_SOCKET")
+            .or_else(|_| env::var("POSTGRES_HOST"))
+            .unwrap_or(Self::DEFAULT_HOST.to_owned());
         let port = env::var("POSTGRES_PORT")
             .map_or(Ok(Self::DEFAULT_PORT), |port| port.parse())
             .map_err(Error::InvalidPort)?;
+        let maintenance_database = env::var("POSTGRES_MAINTENANCE_DATABASE")
+            .unwrap_or(Self::DEFAULT_MAINTENANCE_DATABASE.to_owned());
+        let application_name = env::var("POSTGRES_APPLICATION_NAME")
+            .unwrap_or(Self::DEFAULT_APPLICATION_NAME.to_owned());
 
         Ok(Self {
             username,
             password,
             host,
             port,
+            maintenance_database,
+            application_name,
         })
     }
 
@@ -96,11 +120,15 @@ impl PrivilegedPostgresConfig {
     }
 
     /// Sets a new host
+    ///
+    /// A host starting with ``/`` is the directory of a Unix-domain socket,
+    /// through which all privileged and restricted connections are made.
     /// # Example
     /// ```
     /// # use db_pool::PrivilegedPostgresConfig;
     /// #
     /// let config = PrivilegedPostgresConfig::new().host("localhost".to_owned());
+    /// let config = PrivilegedPostgresConfig::new().host("/var/run/postgresql".to_owned());
     /// ```
     #[must_use]
     pub fn host(self, value: String) -> Self {
@@ -125,46 +153,84 @@ impl PrivilegedPostgresConfig {
         }
     }
 
-    pub(crate) fn default_connection_url(&self) -> String {
-        let Self {
-            username,
-            password,
-            host,
-            port,
-        } = self;
-        if let Some(password) = password {
-            format!("postgres://{username}:{password}@{host}:{port}")
-        } else {
-            format!("postgres://{username}@{host}:{port}")
+    /// Sets a new maintenance database, which the privileged user connects to
+    /// in order to create and drop databases
+    /// # Example
+    /// ```
+    /// # use db_pool::PrivilegedPostgresConfig;
+    /// #
+    /// let config = PrivilegedPostgresConfig::new().maintenance_database("defaultdb");
+    /// ```
+    #[must_use]
+    pub fn maintenance_database(self, value: impl Into<String>) -> Self {
+        Self {
+            maintenance_database: value.into(),
+            ..self
+        }
+    }
+
+    /// Sets a new application name, which privileged connections report in ``pg_stat_activity``
+    ///
+    /// Backends deriving restricted connections from the privileged configuration report it as well
+    /// unless an application name is set through restricted connection parameters.
+    /// # Example
+    /// ```
+    /// # use db_pool::PrivilegedPostgresConfig;
+    /// #
+    /// let config = PrivilegedPostgresConfig::new().application_name("my_tests");
+    /// ```
+    #[must_use]
+    pub fn application_name(self, value: impl Into<String>) -> Self {
+        Self {
+            application_name: value.into(),
+            ..self
         }
     }
 
+    /// Returns the connection URL of the maintenance database for the privileged user,
+    /// for building a privileged pool to be passed to a backend
+    /// # Example
+    /// ```
+    /// # use db_pool::PrivilegedPostgresConfig;
+    /// #
+    /// let url = PrivilegedPostgresConfig::new().default_connection_url();
+    /// ```
+    #[must_use]
+    pub fn default_connection_url(&self) -> String {
+        self.privileged_database_connection_url(self.maintenance_database.as_str())
+    }
+
     pub(crate) fn privileged_database_connection_url(&self, db_name: &str) -> String {
         let Self {
             username,
             password,
             host,
             port,
+            application_name,
+            ..
         } = self;
-        if let Some(password) = password {
-            format!("postgres://{username}:{password}@{host}:{port}/{db_name}")
-        } else {
-            format!("postgres://{username}@{host}:{port}/{db_name}")
-        }
+        let userinfo = url_userinfo(username, password.as_deref());
+        let host = url_host(host);
+        let application_name = percent_encode(application_name);
+        format!("postgres://{userinfo}@{host}:{port}/{db_name}?application_name={application_name}")
     }
 
+    #[cfg(any(
+        test,
+        feature = "diesel-postgres",
+        feature = "diesel-async-postgres",
+        feature = "sea-orm-postgres"
+    ))]
     pub(crate) fn restricted_database_connection_url(
         &self,
         username: &str,
         password: Option<&str>,
         db_name: &str,
     ) -> String {
-        let Self { host, port, .. } = self;
-        if let Some(password) = password {
-            format!("postgres://{username}:{password}@{host}:{port}/{db_name}")
-        } else {
-            format!("postgres://{username}@{host}:{port}/{db_name}")
-        }
+        let userinfo = url_userinfo(username, password);
+        let host = url_host(self.host.as_str());
+        let port = self.port;
+        format!("postgres://{userinfo}@{host}:{port}/{db_name}")
     }
 }
 
@@ -187,6 +253,8 @@ impl From<PrivilegedPostgresConfig> for r2d2_postgres::postgres::Config {
             password,
             host,
             port,
+            maintenance_database,
+            application_name,
         } = value;
 
         let mut config = Self::new();
@@ -194,7 +262,9 @@ impl From<PrivilegedPostgresConfig> for r2d2_postgres::postgres::Config {
         config
             .user(username.as_str())
             .host(host.as_str())
-            .port(port);
+            .port(port)
+            .dbname(maintenance_database.as_str())
+            .application_name(application_name.as_str());
 
         if let Some(password) = password {
             config.password(password.as_str());
@@ -212,12 +282,16 @@ impl From<PrivilegedPostgresConfig> for sqlx::postgres::PgConnectOptions {
             password,
             host,
             port,
+            maintenance_database,
+            application_name,
         } = value;
 
         let opts = Self::new()
             .username(username.as_str())
             .host(host.as_str())
-            .port(port);
+            .port(port)
+            .database(maintenance_database.as_str())
+            .application_name(application_name.as_str());
 
         if let Some(password) = password {
             opts.password(password.as_str())
@@ -235,6 +309,8 @@ impl From<PrivilegedPostgresConfig> for tokio_postgres::Config {
             password,
             host,
             port,
+            maintenance_database,
+            application_name,
         } = value;
 
         let mut config = Self::new();
@@ -242,7 +318,9 @@ impl From<PrivilegedPostgresConfig> for tokio_postgres::Config {
         config
             .user(username.as_str())
             .host(host.as_str())
-            .port(port);
+            .port(port)
+            .dbname(maintenance_database.as_str())
+            .application_name(application_name.as_str());
 
         if let Some(password) = password {
             config.password(password.as_str());
@@ -0,0 +1,19 @@
+/// Set of statements a Postgres backend uses to set up databases and their roles, depending on the attributes of the privileged role
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrivilegeProfile {
+    /// Statements available to a superuser, such as transferring ownership of unrestricted databases to their roles
+    #[default]
+    Superuser,
+    /// Statements available to a privileged role with only the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances
+    ///
+    /// Databases remain owned by the privileged role. Roles of unrestricted databases are granted all privileges
+    /// on their databases and on the ``public`` schema instead of ownership, and databases are never created with their roles as owners.
+    /// Restricted roles rely on default privileges as usual, so entities should be created as the privileged role for cleaning to succeed.
+    CreateDb,
+}
+
+impl PrivilegeProfile {
+    pub(crate) fn transfers_ownership(self) -> bool {
+        self == Self::Superuser
+    }
+}
@@ -0,0 +1,11 @@
+/// Role creating entities in databases with restricted privileges
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Role {
+    /// The privileged role, with privileges on created entities granted to the restricted role afterwards
+    #[default]
+    Privileged,
+    /// The restricted role, which then owns created entities and can alter and drop them
+    ///
+    /// The restricted role is allowed to create entities in schemas only while entities are being created.
+    Restricted,
+}
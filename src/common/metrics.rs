@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+/// Callback receiving metric events
+#[cfg(any(feature = "_sync", feature = "_async"))]
+pub(crate) type MetricsCallback = dyn Fn(MetricEvent) + Send + Sync + 'static;
+
+/// Database operation measured by a metric event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricOperation {
+    /// A database was created along with its attached role or user
+    Create,
+    /// A database was cleaned for reuse
+    Clean,
+    /// A database was dropped along with its attached role or user
+    Drop,
+}
+
+/// Timing of a database operation that completed successfully
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetricEvent {
+    /// Operation that completed
+    pub operation: MetricOperation,
+    /// ID of the database the operation was performed on
+    pub db_id: Uuid,
+    /// Time taken by the operation
+    pub elapsed: Duration,
+    /// Number of tables emptied by a cleaning,
+    /// or `None` for other operations and for cleanings run by a custom routine
+    pub cleaned_tables: Option<usize>,
+}
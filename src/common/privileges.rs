@@ -0,0 +1,30 @@
+mod private {
+    pub trait Sealed {}
+}
+
+/// Privileges granted on a database, tracked in the type of its connection pool
+pub trait Privileges: private::Sealed {
+    /// Whether privileges are restricted
+    const IS_RESTRICTED: bool;
+}
+
+/// Privileges restricted to ``SELECT``, ``INSERT``, ``UPDATE``, and ``DELETE`` operations,
+/// granted on databases of reusable connection pools
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Restricted;
+
+impl private::Sealed for Restricted {}
+
+impl Privileges for Restricted {
+    const IS_RESTRICTED: bool = true;
+}
+
+/// All privileges, including running DDL statements, granted on databases of single-use connection pools
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unrestricted;
+
+impl private::Sealed for Unrestricted {}
+
+impl Privileges for Unrestricted {
+    const IS_RESTRICTED: bool = false;
+}
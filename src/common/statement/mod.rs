@@ -1,4 +1,14 @@
-#[cfg(any(feature = "_sync-mysql", feature = "_async-mysql"))]
+/// MySQL statements
+#[cfg(any(
+    feature = "_sync-mysql",
+    feature = "_async-mysql",
+    feature = "statements"
+))]
 pub mod mysql;
-#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+/// Postgres statements
+#[cfg(any(
+    feature = "_sync-postgres",
+    feature = "_async-postgres",
+    feature = "statements"
+))]
 pub mod postgres;
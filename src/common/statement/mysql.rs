@@ -1,47 +1,230 @@
+/// Maximum length of an identifier
+pub const MAX_IDENTIFIER_LENGTH: usize = 64;
+
+/// Query selecting the names of all databases created by `db-pool`
 #[allow(dead_code)]
 pub const GET_DATABASE_NAMES: &str =
     "SELECT schema_name FROM information_schema.schemata WHERE schema_name LIKE 'db_pool_%';";
 
+/// Query selecting the names of all users created by `db-pool` for a host
+#[allow(dead_code)]
+#[must_use]
+pub fn get_user_names(host: &str) -> String {
+    format!("SELECT user FROM mysql.user WHERE user LIKE 'db_pool_%' AND host = '{host}'")
+}
+
+/// Query selecting the names of all databases whose names start with a prefix
+#[allow(dead_code)]
+#[must_use]
+pub fn get_database_names_with_prefix(prefix: &str) -> String {
+    format!(
+        "SELECT schema_name FROM information_schema.schemata WHERE schema_name LIKE '{prefix}%'"
+    )
+}
+
+/// Query selecting the names and hosts of all users whose names start with a prefix
+#[allow(dead_code)]
+#[must_use]
+pub fn get_user_names_and_hosts_with_prefix(prefix: &str) -> String {
+    format!("SELECT user, host FROM mysql.user WHERE user LIKE '{prefix}%'")
+}
+
+/// Query selecting names and labels of labeled users whose names start with a prefix
+#[cfg(any(feature = "sqlx-mysql", feature = "statements"))]
+#[must_use]
+pub fn get_user_labels_with_prefix(prefix: &str) -> String {
+    format!("SELECT user, attribute->>'$.comment' FROM information_schema.user_attributes WHERE user LIKE '{prefix}%' AND attribute->>'$.comment' IS NOT NULL")
+}
+
+/// Statement checking that a connection is alive
+pub const PING: &str = "SELECT 1";
+
+/// Name of the table created to check for restricted privileges
+#[cfg(any(feature = "_async-mysql", feature = "statements"))]
+pub const PRIVILEGE_CHECK_TABLE_NAME: &str = "db_pool_privilege_check";
+/// Statement creating the table used to check for restricted privileges
+#[cfg(any(feature = "_async-mysql", feature = "statements"))]
+pub const CREATE_PRIVILEGE_CHECK_TABLE: &str = "CREATE TABLE db_pool_privilege_check(id INT)";
+
+/// Statement disabling foreign key checks for the session
 pub const TURN_OFF_FOREIGN_KEY_CHECKS: &str = "SET FOREIGN_KEY_CHECKS = 0";
+/// Statement enabling foreign key checks for the session
 pub const TURN_ON_FOREIGN_KEY_CHECKS: &str = "SET FOREIGN_KEY_CHECKS = 1";
 
+/// Statement switching to a database that always exists
 pub const USE_DEFAULT_DATABASE: &str = "USE information_schema";
 
-pub fn create_database(db_name: &str) -> String {
-    format!("CREATE DATABASE {db_name}")
+/// Error number raised when creating a user that already exists
+#[cfg(any(
+    feature = "mysql",
+    feature = "mysql-async",
+    feature = "sea-orm-mysql",
+    feature = "sqlx-mysql",
+    feature = "statements"
+))]
+pub const DUPLICATE_USER_ERROR_NUMBER: u16 = 1396;
+
+/// Error number raised when a deadlock is detected
+#[cfg(any(
+    feature = "mysql",
+    feature = "mysql-async",
+    feature = "sea-orm-mysql",
+    feature = "sqlx-mysql",
+    feature = "statements"
+))]
+pub const DEADLOCK_ERROR_NUMBER: u16 = 1213;
+
+/// Error number raised when a lock wait times out
+#[cfg(any(
+    feature = "mysql",
+    feature = "mysql-async",
+    feature = "sea-orm-mysql",
+    feature = "sqlx-mysql",
+    feature = "statements"
+))]
+pub const LOCK_WAIT_TIMEOUT_ERROR_NUMBER: u16 = 1205;
+
+/// Quotes an identifier so that reserved words and special characters are accepted
+#[must_use]
+pub fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Statement setting the SQL mode of the session
+#[must_use]
+pub fn set_session_sql_mode(sql_mode: &str) -> String {
+    format!("SET SESSION sql_mode = '{sql_mode}'")
 }
 
+/// Statement creating a database with an optional character set and collation
+#[must_use]
+pub fn create_database(db_name: &str, charset: Option<&str>, collation: Option<&str>) -> String {
+    let mut stmt = format!("CREATE DATABASE {}", quote_identifier(db_name));
+    if let Some(charset) = charset {
+        stmt.push_str(format!(" CHARACTER SET {charset}").as_str());
+    }
+    if let Some(collation) = collation {
+        stmt.push_str(format!(" COLLATE {collation}").as_str());
+    }
+    stmt
+}
+
+/// Statement creating a user for a host, using its name as its password
+#[must_use]
 pub fn create_user(name: &str, host: &str) -> String {
-    format!("CREATE USER {name}@{host} IDENTIFIED BY '{name}'")
+    format!(
+        "CREATE USER {}@'{host}' IDENTIFIED BY '{name}'",
+        quote_identifier(name)
+    )
 }
 
+/// Statement switching to a database
+#[must_use]
 pub fn use_database(db_name: &str) -> String {
-    format!("USE {db_name}")
+    format!("USE {}", quote_identifier(db_name))
+}
+
+/// Statement granting all privileges on a database to a user
+#[must_use]
+pub fn grant_all_privileges(db_name: &str, user_name: &str, host: &str) -> String {
+    format!(
+        "GRANT ALL PRIVILEGES ON {}.* TO {}@'{host}'",
+        quote_identifier(db_name),
+        quote_identifier(user_name)
+    )
 }
 
-pub fn grant_all_privileges(db_name: &str, host: &str) -> String {
-    format!("GRANT ALL PRIVILEGES ON {db_name}.* TO {db_name}@{host}")
+/// Statement granting data manipulation privileges on a database to a user
+#[must_use]
+pub fn grant_restricted_privileges(db_name: &str, user_name: &str, host: &str) -> String {
+    format!(
+        "GRANT SELECT, INSERT, UPDATE, DELETE ON {}.* TO {}@'{host}'",
+        quote_identifier(db_name),
+        quote_identifier(user_name)
+    )
 }
 
-pub fn grant_restricted_privileges(db_name: &str, host: &str) -> String {
-    format!("GRANT SELECT, INSERT, UPDATE, DELETE ON {db_name}.* TO {db_name}@{host}")
+/// Statement revoking all privileges on a database from a user
+#[must_use]
+pub fn revoke_all_privileges(db_name: &str, user_name: &str, host: &str) -> String {
+    format!(
+        "REVOKE ALL PRIVILEGES ON {}.* FROM {}@'{host}'",
+        quote_identifier(db_name),
+        quote_identifier(user_name)
+    )
 }
 
+/// Query selecting the names of tables in a database excluding the given table types
 #[allow(dead_code)]
-pub fn get_table_names(db_name: &str) -> String {
-    format!("SELECT table_name FROM information_schema.tables WHERE table_schema = '{db_name}'")
+#[must_use]
+pub fn get_table_names(db_name: &str, excluded_table_types: &[&str]) -> String {
+    let excluded_table_types = excluded_table_types
+        .iter()
+        .map(|table_type| format!("'{table_type}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("SELECT table_name FROM information_schema.tables WHERE table_schema = '{db_name}' AND table_type NOT IN ({excluded_table_types})")
 }
 
+/// Statement truncating a table
+#[must_use]
 pub fn truncate_table(table_name: &str, db_name: &str) -> String {
-    format!("TRUNCATE TABLE {db_name}.{table_name}")
+    format!(
+        "TRUNCATE TABLE {}.{}",
+        quote_identifier(db_name),
+        quote_identifier(table_name)
+    )
+}
+
+/// Statement dropping a table if it exists
+#[cfg(any(feature = "_async-mysql", feature = "statements"))]
+#[must_use]
+pub fn drop_table(table_name: &str, db_name: &str) -> String {
+    format!(
+        "DROP TABLE IF EXISTS {}.{}",
+        quote_identifier(db_name),
+        quote_identifier(table_name)
+    )
 }
 
+/// Query selecting the names of the given tables that contain rows
+#[must_use]
+pub fn get_nonempty_table_names(table_names: &[String], db_name: &str) -> String {
+    table_names
+        .iter()
+        .map(|table_name| {
+            format!(
+                "SELECT '{}' AS table_name FROM DUAL WHERE EXISTS (SELECT 1 FROM {}.{})",
+                table_name.replace('\\', "\\\\").replace('\'', "''"),
+                quote_identifier(db_name),
+                quote_identifier(table_name)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+/// Statement dropping a database
+#[must_use]
 pub fn drop_database(db_name: &str) -> String {
-    format!("DROP DATABASE {db_name}")
+    format!("DROP DATABASE {}", quote_identifier(db_name))
 }
 
+/// Statement dropping a user
+#[must_use]
 pub fn drop_user(name: &str, host: &str) -> String {
-    format!("DROP USER {name}@{host}")
+    format!("DROP USER {}@'{host}'", quote_identifier(name))
+}
+
+/// Statement labeling a user, such as with the name of the test that pulled its database
+#[cfg(any(feature = "_async-mysql", feature = "statements"))]
+#[must_use]
+pub fn comment_user(name: &str, host: &str, label: &str) -> String {
+    format!(
+        "ALTER USER {}@'{host}' COMMENT '{}'",
+        quote_identifier(name),
+        label.replace('\\', "\\\\").replace('\'', "''")
+    )
 }
 
 #[cfg(test)]
@@ -1,40 +1,496 @@
+/// Maximum length of an identifier
+pub const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Statement starting a transaction
+pub const START_TRANSACTION: &str = "BEGIN";
+/// Statement committing a transaction
+pub const COMMIT_TRANSACTION: &str = "COMMIT";
+/// Statement rolling back a transaction
+#[allow(dead_code)]
+pub const ROLLBACK_TRANSACTION: &str = "ROLLBACK";
+/// Statement failing if the current transaction was aborted by a failed statement
+pub const CHECK_TRANSACTION: &str = "SELECT 1";
+
+/// Statement checking that a connection is alive
+pub const PING: &str = "SELECT 1";
+
+/// Name of the default schema
+pub const PUBLIC_SCHEMA_NAME: &str = "public";
+
+/// Name of the table created to check for restricted privileges
+#[cfg(any(feature = "_async-postgres", feature = "statements"))]
+pub const PRIVILEGE_CHECK_TABLE_NAME: &str = "db_pool_privilege_check";
+/// Statement creating the table used to check for restricted privileges
+#[cfg(any(feature = "_async-postgres", feature = "statements"))]
+pub const CREATE_PRIVILEGE_CHECK_TABLE: &str = "CREATE TABLE db_pool_privilege_check(id INT)";
+
+/// Statement acquiring the advisory lock held during initialization
+pub const LOCK_INITIALIZATION: &str = "SELECT pg_advisory_lock(hashtext('db_pool'))";
+/// Statement releasing the advisory lock held during initialization
+pub const UNLOCK_INITIALIZATION: &str = "SELECT pg_advisory_unlock(hashtext('db_pool'))";
+
+/// Query selecting the names of all databases created by `db-pool`
 #[allow(dead_code)]
 pub const GET_DATABASE_NAMES: &str =
     "SELECT datname FROM pg_catalog.pg_database WHERE datname LIKE 'db_pool_%'";
 
+/// Query selecting the names of databases created by `db-pool` and marked as owned
+#[allow(dead_code)]
+pub const GET_OWNED_DATABASE_NAMES: &str =
+    "SELECT datname FROM pg_catalog.pg_database WHERE datname LIKE 'db_pool_%' AND (pg_catalog.shobj_description(oid, 'pg_database') = 'db_pool' OR pg_catalog.shobj_description(oid, 'pg_database') LIKE 'db_pool:%')";
+
+/// Query selecting the names of databases created by `db-pool` and marked with a schema version
+#[allow(dead_code)]
+#[must_use]
+pub fn get_database_names_with_schema_version(schema_version: &str) -> String {
+    format!(
+        "SELECT datname FROM pg_catalog.pg_database WHERE datname LIKE 'db_pool_%' AND pg_catalog.shobj_description(oid, 'pg_database') = {}",
+        quote_literal(database_comment(Some(schema_version)).as_str())
+    )
+}
+
+/// Query selecting the names of all roles created by `db-pool`
+#[allow(dead_code)]
+pub const GET_ROLE_NAMES: &str =
+    "SELECT rolname FROM pg_catalog.pg_roles WHERE rolname LIKE 'db_pool_%'";
+
+/// Query selecting the names of all databases whose names start with a prefix
+#[allow(dead_code)]
+#[must_use]
+pub fn get_database_names_with_prefix(prefix: &str) -> String {
+    format!("SELECT datname FROM pg_catalog.pg_database WHERE datname LIKE '{prefix}%'")
+}
+
+/// Query selecting the names of all roles whose names start with a prefix
+#[allow(dead_code)]
+#[must_use]
+pub fn get_role_names_with_prefix(prefix: &str) -> String {
+    format!("SELECT rolname FROM pg_catalog.pg_roles WHERE rolname LIKE '{prefix}%'")
+}
+
+/// Query selecting names and labels of labeled roles whose names start with a prefix
+#[cfg(any(feature = "tokio-postgres", feature = "statements"))]
+#[must_use]
+pub fn get_role_labels_with_prefix(prefix: &str) -> String {
+    format!("SELECT rolname, description FROM pg_catalog.pg_roles JOIN pg_catalog.pg_shdescription ON objoid = oid AND classoid = 'pg_catalog.pg_authid'::regclass WHERE rolname LIKE '{prefix}%'")
+}
+
+/// Query selecting schema and table names outside of the given system schemas
 #[allow(dead_code)]
-pub const GET_TABLE_NAMES: &str = "SELECT tablename FROM pg_catalog.pg_tables WHERE schemaname != 'pg_catalog' AND schemaname != 'information_schema'";
+#[must_use]
+pub fn get_table_names(system_schemas: &[&str]) -> String {
+    let system_schemas = system_schemas
+        .iter()
+        .map(|schema_name| format!("'{schema_name}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("SELECT schemaname, tablename FROM pg_catalog.pg_tables WHERE schemaname NOT IN ({system_schemas})")
+}
+
+/// Quotes an identifier so that its case is preserved and reserved words are accepted
+#[must_use]
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes a string literal
+#[must_use]
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quotes and qualifies a table name with its schema name
+#[must_use]
+pub fn qualify_table_name(schema_name: &str, table_name: &str) -> String {
+    format!(
+        "{}.{}",
+        quote_identifier(schema_name),
+        quote_identifier(table_name)
+    )
+}
+
+/// Generates statements converting tables to ``UNLOGGED`` tables, for use when creating entities
+///
+/// Unlogged tables skip the write-ahead log, which speeds up writing ephemeral test data.
+/// # Example
+/// ```
+/// use db_pool::convert_tables_unlogged;
+///
+/// let stmts = convert_tables_unlogged(["book", "author"]);
+/// assert_eq!(
+///     stmts,
+///     [
+///         "ALTER TABLE book SET UNLOGGED",
+///         "ALTER TABLE author SET UNLOGGED",
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn convert_tables_unlogged<'a>(table_names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    table_names
+        .into_iter()
+        .map(|table_name| format!("ALTER TABLE {table_name} SET UNLOGGED"))
+        .collect()
+}
+
+/// Statement creating a database with optional owner, encoding, collation, character type, and template
+#[must_use]
+pub fn create_database(
+    db_name: &str,
+    owner: Option<&str>,
+    encoding: Option<&str>,
+    lc_collate: Option<&str>,
+    lc_ctype: Option<&str>,
+    template: Option<&str>,
+) -> String {
+    let mut stmt = format!("CREATE DATABASE {}", quote_identifier(db_name));
+    if owner.is_some()
+        || encoding.is_some()
+        || lc_collate.is_some()
+        || lc_ctype.is_some()
+        || template.is_some()
+    {
+        stmt.push_str(" WITH");
+    }
+    if let Some(owner) = owner {
+        stmt.push_str(format!(" OWNER {}", quote_identifier(owner)).as_str());
+    }
+    if let Some(encoding) = encoding {
+        stmt.push_str(format!(" ENCODING '{encoding}'").as_str());
+    }
+    if let Some(lc_collate) = lc_collate {
+        stmt.push_str(format!(" LC_COLLATE '{lc_collate}'").as_str());
+    }
+    if let Some(lc_ctype) = lc_ctype {
+        stmt.push_str(format!(" LC_CTYPE '{lc_ctype}'").as_str());
+    }
+    if let Some(template) = template {
+        stmt.push_str(format!(" TEMPLATE {template}").as_str());
+    }
+    stmt
+}
+
+fn database_comment(schema_version: Option<&str>) -> String {
+    schema_version.map_or_else(
+        || "db_pool".to_owned(),
+        |schema_version| format!("db_pool:{schema_version}"),
+    )
+}
+
+/// Statement marking a database as owned by `db-pool`, along with the schema version of its entities if any
+#[must_use]
+pub fn comment_database(db_name: &str, schema_version: Option<&str>) -> String {
+    format!(
+        "COMMENT ON DATABASE {} IS {}",
+        quote_identifier(db_name),
+        quote_literal(database_comment(schema_version).as_str())
+    )
+}
+
+/// Statement labeling a role, such as with the name of the test that pulled its database
+#[cfg(any(feature = "_async-postgres", feature = "statements"))]
+#[must_use]
+pub fn comment_role(role_name: &str, label: &str) -> String {
+    format!(
+        "COMMENT ON ROLE {} IS {}",
+        quote_identifier(role_name),
+        quote_literal(label)
+    )
+}
+
+/// Statement creating an extension if it does not exist
+#[must_use]
+pub fn create_extension(name: &str) -> String {
+    format!("CREATE EXTENSION IF NOT EXISTS \"{name}\"")
+}
+
+/// Name of the group role shared by restricted roles
+pub const GROUP_ROLE_NAME: &str = "db_pool_group";
+
+/// SQLSTATE code of a duplicate database error
+#[cfg(any(
+    feature = "sea-orm-postgres",
+    feature = "sqlx-postgres",
+    feature = "statements"
+))]
+pub const DUPLICATE_DATABASE_CODE: &str = "42P04";
 
-pub fn create_database(db_name: &str) -> String {
-    format!("CREATE DATABASE {db_name}")
+/// SQLSTATE code of a duplicate role error
+#[cfg(any(
+    feature = "sea-orm-postgres",
+    feature = "sqlx-postgres",
+    feature = "statements"
+))]
+pub const DUPLICATE_ROLE_CODE: &str = "42710";
+
+/// SQLSTATE code of a deadlock error
+#[cfg(any(
+    feature = "sea-orm-postgres",
+    feature = "sqlx-postgres",
+    feature = "statements"
+))]
+pub const DEADLOCK_DETECTED_CODE: &str = "40P01";
+
+/// Statement creating a group role if it does not exist
+#[must_use]
+pub fn create_group_role(name: &str) -> String {
+    format!("DO $$ BEGIN IF NOT EXISTS (SELECT FROM pg_catalog.pg_roles WHERE rolname = '{name}') THEN CREATE ROLE {} WITH NOLOGIN; END IF; END $$", quote_identifier(name))
+}
+
+/// Statement creating a login role using its name as its password with additional attributes, optionally as a member of a group role
+#[must_use]
+pub fn create_role(name: &str, group_name: Option<&str>, attributes: &[String]) -> String {
+    let mut stmt = format!(
+        "CREATE ROLE {} WITH LOGIN PASSWORD '{name}'",
+        quote_identifier(name)
+    );
+    for attribute in attributes {
+        stmt.push_str(format!(" {attribute}").as_str());
+    }
+    if let Some(group_name) = group_name {
+        stmt.push_str(format!(" IN ROLE {}", quote_identifier(group_name)).as_str());
+    }
+    stmt
+}
+
+/// Statement revoking the privilege to connect to a database from all roles
+#[must_use]
+pub fn revoke_public_database_connection(db_name: &str) -> String {
+    format!(
+        "REVOKE CONNECT ON DATABASE {} FROM PUBLIC",
+        quote_identifier(db_name)
+    )
 }
 
-pub fn create_role(name: &str) -> String {
-    format!("CREATE ROLE {name} WITH LOGIN PASSWORD '{name}'")
+/// Statement granting the privilege to connect to a database to a role
+#[must_use]
+pub fn grant_database_connection(db_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT CONNECT ON DATABASE {} TO {}",
+        quote_identifier(db_name),
+        quote_identifier(role_name)
+    )
 }
 
+/// Statement setting the search path of a role in a database
+#[must_use]
+pub fn set_role_search_path(role_name: &str, db_name: &str, schemas: &[String]) -> String {
+    format!(
+        "ALTER ROLE {} IN DATABASE {} SET search_path TO {}",
+        quote_identifier(role_name),
+        quote_identifier(db_name),
+        schemas.join(", ")
+    )
+}
+
+/// Statement transferring ownership of a database to a role
+#[must_use]
 pub fn grant_database_ownership(db_name: &str, role_name: &str) -> String {
-    format!("ALTER DATABASE {db_name} OWNER to {role_name}")
+    format!(
+        "ALTER DATABASE {} OWNER to {}",
+        quote_identifier(db_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting all privileges on a database to a role without transferring ownership
+#[must_use]
+pub fn grant_database_privileges(db_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT ALL PRIVILEGES ON DATABASE {} TO {}",
+        quote_identifier(db_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting all privileges on a schema to a role without transferring ownership
+#[must_use]
+pub fn grant_schema_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT ALL PRIVILEGES ON SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting usage privileges on a schema to a role
+#[must_use]
+pub fn grant_schema_usage(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT USAGE ON SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting privileges to create entities in a schema to a role
+#[must_use]
+pub fn grant_schema_creation(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT CREATE ON SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement revoking privileges to create entities in a schema from a role
+#[must_use]
+pub fn revoke_schema_creation(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "REVOKE CREATE ON SCHEMA {} FROM {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting data manipulation privileges on all tables in a schema to a role
+#[must_use]
+pub fn grant_restricted_table_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting read privileges on all tables in a schema to a role
+#[must_use]
+pub fn grant_read_only_table_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT SELECT ON ALL TABLES IN SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
 }
 
-pub fn grant_restricted_table_privileges(role_name: &str) -> String {
-    format!("GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public TO {role_name}")
+/// Statement granting usage privileges on all sequences in a schema to a role
+#[must_use]
+pub fn grant_restricted_sequence_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "GRANT USAGE, SELECT ON ALL SEQUENCES IN SCHEMA {} TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
 }
 
-pub fn grant_restricted_sequence_privileges(role_name: &str) -> String {
-    format!("GRANT USAGE, SELECT ON ALL SEQUENCES IN SCHEMA public TO {role_name}")
+/// Statement granting data manipulation privileges on tables created later in a schema to a role
+#[must_use]
+pub fn grant_restricted_default_table_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "ALTER DEFAULT PRIVILEGES IN SCHEMA {} GRANT SELECT, INSERT, UPDATE, DELETE ON TABLES TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
 }
 
+/// Statement granting read privileges on tables created later in a schema to a role
+#[must_use]
+pub fn grant_read_only_default_table_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "ALTER DEFAULT PRIVILEGES IN SCHEMA {} GRANT SELECT ON TABLES TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement granting usage privileges on sequences created later in a schema to a role
+#[must_use]
+pub fn grant_restricted_default_sequence_privileges(schema_name: &str, role_name: &str) -> String {
+    format!(
+        "ALTER DEFAULT PRIVILEGES IN SCHEMA {} GRANT USAGE, SELECT ON SEQUENCES TO {}",
+        quote_identifier(schema_name),
+        quote_identifier(role_name)
+    )
+}
+
+/// Statement truncating a table and restarting its identity columns
+///
+/// The table name is interpolated as is, so it must be quoted and qualified by [`qualify_table_name`].
+#[must_use]
 pub fn truncate_table(table_name: &str) -> String {
     format!("TRUNCATE TABLE {table_name} RESTART IDENTITY CASCADE")
 }
 
+/// Query selecting the names of the given tables that contain rows
+///
+/// The table names are interpolated as is, so they must be quoted and qualified by [`qualify_table_name`].
+#[must_use]
+pub fn get_nonempty_table_names(table_names: &[String]) -> String {
+    table_names
+        .iter()
+        .map(|table_name| {
+            format!(
+                "SELECT '{}' AS table_name WHERE EXISTS (SELECT 1 FROM {table_name})",
+                table_name.replace('\'', "''")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+/// Statement terminating all other connections to a database
+#[must_use]
+pub fn terminate_database_connections(db_name: &str) -> String {
+    format!("SELECT pg_terminate_backend(pid) FROM pg_catalog.pg_stat_activity WHERE datname = '{db_name}' AND pid <> pg_backend_pid()")
+}
+
+/// Statement deleting all rows from the given tables at once, checking foreign keys only once all are emptied
+///
+/// The table names are interpolated as is, so they must be quoted and qualified by [`qualify_table_name`].
+#[must_use]
+pub fn delete_from_tables(table_names: &[String]) -> String {
+    let deletes = table_names
+        .iter()
+        .enumerate()
+        .map(|(index, table_name)| format!("deleted_{index} AS (DELETE FROM {table_name})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("WITH {deletes} SELECT 1")
+}
+
+/// Statement dropping all sequences outside of the given system schemas
+#[allow(dead_code)]
+#[must_use]
+pub fn drop_sequences(system_schemas: &[&str]) -> String {
+    let system_schemas = system_schemas
+        .iter()
+        .map(|schema_name| format!("'{schema_name}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("DO $$ DECLARE r record; BEGIN FOR r IN SELECT sequence_schema, sequence_name FROM information_schema.sequences WHERE sequence_schema NOT IN ({system_schemas}) LOOP EXECUTE format('DROP SEQUENCE IF EXISTS %I.%I CASCADE', r.sequence_schema, r.sequence_name); END LOOP; END $$")
+}
+
+/// Statement dropping a table if it exists
+///
+/// The table name is interpolated as is, so it must be quoted and qualified by [`qualify_table_name`].
+#[cfg(any(feature = "_async-postgres", feature = "statements"))]
+#[must_use]
+pub fn drop_table(table_name: &str) -> String {
+    format!("DROP TABLE IF EXISTS {table_name} CASCADE")
+}
+
+/// Statement dropping a database
+#[must_use]
 pub fn drop_database(db_name: &str) -> String {
-    format!("DROP DATABASE {db_name}")
+    format!("DROP DATABASE {}", quote_identifier(db_name))
+}
+
+/// Statement dropping a database if it exists
+#[must_use]
+pub fn drop_database_if_exists(db_name: &str) -> String {
+    format!("DROP DATABASE IF EXISTS {}", quote_identifier(db_name))
 }
 
+/// Statement dropping a role
+#[must_use]
 pub fn drop_role(name: &str) -> String {
-    format!("DROP ROLE {name}")
+    format!("DROP ROLE {}", quote_identifier(name))
+}
+
+/// Statement dropping a role if it exists
+#[must_use]
+pub fn drop_role_if_exists(name: &str) -> String {
+    format!("DROP ROLE IF EXISTS {}", quote_identifier(name))
 }
 
 #[cfg(test)]
@@ -44,6 +500,11 @@ pub(crate) mod tests {
         "CREATE TABLE dummy(id SERIAL PRIMARY KEY)",
     ];
 
+    pub const CREATE_ENTITIES_WITH_FOREIGN_KEYS_STATEMENTS: [&str; 2] = [
+        "CREATE TABLE author(id SERIAL PRIMARY KEY)",
+        "CREATE TABLE book(id SERIAL PRIMARY KEY, author_id INT NOT NULL REFERENCES author(id))",
+    ];
+
     pub const DDL_STATEMENTS: [&str; 9] = [
         "CREATE TABLE author()",
         "ALTER TABLE book RENAME TO new_book",
@@ -52,18 +52,41 @@
 //!
 //! ### Async
 //!
-//! | Backend                                                           | Pool                                                                                      | Features                                    |
-//! | ----------------------------------------------------------------- | ----------------------------------------------------------------------------------------- | ------------------------------------------- |
-//! | [diesel-async/mysql](struct@async::DieselAsyncMySQLBackend)       | [bb8](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/bb8/index.html)   | `diesel-async-mysql`, `diesel-async-bb8`    |
-//! | [diesel-async/mysql](struct@async::DieselAsyncMySQLBackend)       | [mobc](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/mobc/index.html) | `diesel-async-mysql`, `diesel-async-mobc`   |
-//! | [diesel-async/postgres](struct@async::DieselAsyncPostgresBackend) | [bb8](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/bb8/index.html)   | `diesel-async-postgres`, `diesel-async-bb8` |
-//! | [diesel-async/postgres](struct@async::DieselAsyncPostgresBackend) | [mobc](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/mobc/index.html) | `diesel-async-postgres`, `diesel-async-bb8` |
-//! | [sea-orm/sqlx-mysql](struct@async::SeaORMMySQLBackend)            | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                  | `sea-orm-mysql`                             |
-//! | [sea-orm/sqlx-postgres](struct@async::SeaORMPostgresBackend)      | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                  | `sea-orm-postgres`                          |
-//! | [sqlx/mysql](struct@async::SqlxMySQLBackend)                      | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                  | `sqlx-mysql`                                |
-//! | [sqlx/postgres](struct@async::SqlxPostgresBackend)                | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                  | `sqlx-postgres`                             |
-//! | [tokio-postgres](struct@async::TokioPostgresBackend)              | [bb8](https://docs.rs/bb8-postgres/0.8.1/bb8_postgres/)                                   | `tokio-postgres`, `tokio-postgres-bb8`      |
-//! | [tokio-postgres](struct@async::TokioPostgresBackend)              | [mobc](https://docs.rs/mobc-postgres/0.8.0/mobc_postgres/)                                | `tokio-postgres`, `tokio-postgres-mobc`     |
+//! | Backend                                                           | Pool                                                                                              | Features                                         |
+//! | ----------------------------------------------------------------- | ------------------------------------------------------------------------------------------------- | ------------------------------------------------ |
+//! | [diesel-async/mysql](struct@async::DieselAsyncMySQLBackend)       | [bb8](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/bb8/index.html)           | `diesel-async-mysql`, `diesel-async-bb8`         |
+//! | [diesel-async/mysql](struct@async::DieselAsyncMySQLBackend)       | [mobc](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/mobc/index.html)         | `diesel-async-mysql`, `diesel-async-mobc`        |
+//! | [diesel-async/mysql](struct@async::DieselAsyncMySQLBackend)       | [deadpool](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/deadpool/index.html) | `diesel-async-mysql`, `diesel-async-deadpool`    |
+//! | [diesel-async/postgres](struct@async::DieselAsyncPostgresBackend) | [bb8](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/bb8/index.html)           | `diesel-async-postgres`, `diesel-async-bb8`      |
+//! | [diesel-async/postgres](struct@async::DieselAsyncPostgresBackend) | [mobc](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/mobc/index.html)         | `diesel-async-postgres`, `diesel-async-bb8`      |
+//! | [diesel-async/postgres](struct@async::DieselAsyncPostgresBackend) | [deadpool](https://docs.rs/diesel-async/0.5.0/diesel_async/pooled_connection/deadpool/index.html) | `diesel-async-postgres`, `diesel-async-deadpool` |
+//! | [`mysql_async`](struct@async::MySQLAsyncBackend)                  | [`mysql_async`](https://docs.rs/mysql_async/0.34.2/mysql_async/struct.Pool.html)                  | `mysql-async`                                    |
+//! | [sea-orm/sqlx-mysql](struct@async::SeaORMMySQLBackend)            | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                          | `sea-orm-mysql`                                  |
+//! | [sea-orm/sqlx-postgres](struct@async::SeaORMPostgresBackend)      | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                          | `sea-orm-postgres`                               |
+//! | [sqlx/mysql](struct@async::SqlxMySQLBackend)                      | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                          | `sqlx-mysql`                                     |
+//! | [sqlx/postgres](struct@async::SqlxPostgresBackend)                | [sqlx](https://docs.rs/sqlx/0.8.2/sqlx/struct.Pool.html)                                          | `sqlx-postgres`                                  |
+//! | [tokio-postgres](struct@async::TokioPostgresBackend)              | [bb8](https://docs.rs/bb8-postgres/0.8.1/bb8_postgres/)                                           | `tokio-postgres`, `tokio-postgres-bb8`           |
+//! | [tokio-postgres](struct@async::TokioPostgresBackend)              | [mobc](https://docs.rs/mobc-postgres/0.8.0/mobc_postgres/)                                        | `tokio-postgres`, `tokio-postgres-mobc`          |
+//!
+//! ### Managed Postgres
+//!
+//! Postgres backends use statements requiring a superuser by default. When the privileged role only has the `CREATEDB` and `CREATEROLE` attributes, such as on managed instances, set `privilege_profile(PrivilegeProfile::CreateDb)` on the backend so that databases stay owned by the privileged role and database-unrestricted roles are granted privileges instead of ownership.
+//!
+//! ### Privileged Pool Sizing
+//!
+//! Creating a database takes a connection from the privileged pool only while creating the database and its role, with extensions and entities being set up over separate connections to the new database. Concurrent pulls from a cold pool still wait on one another when the privileged pool is smaller than the number of concurrent pulls, so size it according to the expected concurrency, such as the number of test threads, through the privileged pool closure passed to the backend.
+//!
+//! ### TLS
+//!
+//! The `native-tls` and `rustls` features enable TLS for the sqlx and sea-orm backends using the corresponding TLS implementation. The `tokio-postgres-native-tls` and `tokio-postgres-rustls` features do the same for the tokio-postgres backend, with `tokio-postgres-native-tls` taking precedence if both are enabled. TLS is then used according to the SSL mode of the privileged connection configuration.
+//!
+//! ### Statements
+//!
+//! The `statements` feature exposes the SQL statements used by the backends under `db_pool::statement`, for building compatible tooling.
+//!
+//! ### Mock
+//!
+//! The `mock` feature adds a `MockBackend` to each of the sync and async modules enabled by other features, which records lifecycle operations instead of running them, for unit-testing fixture wiring offline.
 
 #![doc(
     html_favicon_url = "https://raw.githubusercontent.com/yasamoka/db-pool/main/logo.svg",
@@ -98,8 +121,28 @@ pub mod r#async;
 pub mod sync;
 mod util;
 
+/// SQL statements used by the backends, for reuse in external tooling
+///
+/// # Example
+/// ```
+/// use db_pool::statement::postgres::{qualify_table_name, truncate_table};
+///
+/// assert_eq!(
+///     truncate_table(&qualify_table_name("public", "book")),
+///     r#"TRUNCATE TABLE "public"."book" RESTART IDENTITY CASCADE"#
+/// );
+/// ```
+#[cfg(feature = "statements")]
+pub mod statement {
+    pub use crate::common::statement::{mysql, postgres};
+}
+
 #[allow(unused_imports)]
 pub use common::config::*;
+pub use common::metrics::{MetricEvent, MetricOperation};
+pub use common::privileges::{Privileges, Restricted, Unrestricted};
+#[cfg(feature = "_postgres")]
+pub use common::statement::postgres::convert_tables_unlogged;
 
 #[cfg(test)]
 mod tests {
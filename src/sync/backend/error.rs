@@ -1,10 +1,19 @@
 use std::fmt::Debug;
 
+use uuid::Uuid;
+
 #[derive(Debug)]
 pub enum Error<C: Debug, Q: Debug> {
+    Build(r2d2::Error),
     Pool(r2d2::Error),
     Connection(C),
     Query(Q),
+    Statement(String, Q),
+    MissingDatabaseConnection(Uuid),
+    InvalidDatabaseName(String),
+    RoleExists(String),
+    FailedEntityCreation(Uuid),
+    UncleanDatabase(Uuid, Vec<String>),
 }
 
 impl<C: Debug, Q: Debug> From<r2d2::Error> for Error<C, Q> {
@@ -0,0 +1,247 @@
+use std::{convert::Infallible, sync::Arc};
+
+use parking_lot::Mutex;
+use r2d2::{ManageConnection, Pool};
+use uuid::Uuid;
+
+use crate::util::get_db_name;
+
+use super::{error::Error as BackendError, r#trait::Backend};
+
+/// Lifecycle operation recorded by [`MockBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockCall {
+    /// Backend initialized
+    Init,
+    /// Database server pinged
+    Ping,
+    /// Database created
+    Create {
+        /// Database ID
+        db_id: Uuid,
+        /// Whether privileges are restricted
+        restrict_privileges: bool,
+    },
+    /// Reader pool created
+    CreateReaderPool {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Database cleaned
+    Clean {
+        /// Database ID
+        db_id: Uuid,
+    },
+    /// Database dropped
+    Drop {
+        /// Database ID
+        db_id: Uuid,
+        /// Whether privileges were restricted
+        is_restricted: bool,
+    },
+}
+
+/// Dummy connection handed out by pools of [`MockBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockConnection {
+    database_id: Option<Uuid>,
+}
+
+impl MockConnection {
+    /// Returns the ID of the database the connection was established to, or [`None`] for privileged connections
+    #[must_use]
+    pub fn database_id(&self) -> Option<Uuid> {
+        self.database_id
+    }
+}
+
+/// Connection manager establishing dummy connections for [`MockBackend`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MockConnectionManager {
+    database_id: Option<Uuid>,
+}
+
+impl ManageConnection for MockConnectionManager {
+    type Connection = MockConnection;
+    type Error = Infallible;
+
+    fn connect(&self) -> Result<MockConnection, Infallible> {
+        Ok(MockConnection {
+            database_id: self.database_id,
+        })
+    }
+
+    fn is_valid(&self, _: &mut MockConnection) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn has_broken(&self, _: &mut MockConnection) -> bool {
+        false
+    }
+}
+
+/// In-process backend that records lifecycle operations instead of running them against a database server
+///
+/// Intended for unit-testing fixture wiring and [`PoolWrapper`](crate::sync::PoolWrapper) plumbing offline.
+/// Clones share their recorded operations, so a clone kept before creating a database pool can be inspected afterwards.
+/// # Example
+/// ```
+/// use db_pool::sync::{DatabasePoolBuilderTrait, MockBackend, MockCall};
+///
+/// let backend = MockBackend::new();
+///
+/// let db_pool = backend.clone().create_database_pool().unwrap();
+/// let db_id = db_pool.pull_immutable().database_id();
+///
+/// assert_eq!(
+///     backend.calls()[..2],
+///     [
+///         MockCall::Init,
+///         MockCall::Create {
+///             db_id,
+///             restrict_privileges: true
+///         }
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockBackend {
+    calls: Arc<Mutex<Vec<MockCall>>>,
+    privileged_pool: Pool<MockConnectionManager>,
+}
+
+impl MockBackend {
+    /// Creates a new mock backend
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            calls: Arc::new(Mutex::new(Vec::new())),
+            privileged_pool: create_pool(None),
+        }
+    }
+
+    /// Returns the lifecycle operations recorded so far, in order
+    #[must_use]
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().clone()
+    }
+
+    fn record(&self, call: MockCall) {
+        self.calls.lock().push(call);
+    }
+}
+
+impl Default for MockBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn create_pool(database_id: Option<Uuid>) -> Pool<MockConnectionManager> {
+    Pool::builder().build_unchecked(MockConnectionManager { database_id })
+}
+
+type BError = BackendError<Infallible, Infallible>;
+
+impl Backend for MockBackend {
+    type ConnectionManager = MockConnectionManager;
+    type ConnectionError = Infallible;
+    type QueryError = Infallible;
+
+    fn init(&self) -> Result<(), BError> {
+        self.record(MockCall::Init);
+        Ok(())
+    }
+
+    fn ping(&self) -> Result<(), BError> {
+        self.record(MockCall::Ping);
+        Ok(())
+    }
+
+    fn create(
+        &self,
+        db_id: Uuid,
+        restrict_privileges: bool,
+    ) -> Result<Pool<MockConnectionManager>, BError> {
+        self.record(MockCall::Create {
+            db_id,
+            restrict_privileges,
+        });
+        Ok(create_pool(Some(db_id)))
+    }
+
+    fn create_reader_pool(&self, db_id: Uuid) -> Result<Pool<MockConnectionManager>, BError> {
+        self.record(MockCall::CreateReaderPool { db_id });
+        Ok(create_pool(Some(db_id)))
+    }
+
+    fn clean(&self, db_id: Uuid) -> Result<(), BError> {
+        self.record(MockCall::Clean { db_id });
+        Ok(())
+    }
+
+    fn drop(&self, db_id: Uuid, is_restricted: bool) -> Result<(), BError> {
+        self.record(MockCall::Drop {
+            db_id,
+            is_restricted,
+        });
+        Ok(())
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        Some(format!("mock:///{}", get_db_name(db_id)))
+    }
+
+    fn privileged_pool(&self) -> &Pool<MockConnectionManager> {
+        &self.privileged_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use crate::sync::{backend::r#trait::Backend, db_pool::DatabasePoolBuilder, PoolWrapper};
+
+    use super::{MockBackend, MockCall};
+
+    #[test]
+    fn backend_records_lifecycle_operations() {
+        let backend = MockBackend::new();
+        let db_pool = backend.clone().create_database_pool().unwrap();
+
+        // pulling twice must reuse and clean the database
+        let db_id = db_pool.pull_immutable().database_id();
+        assert_eq!(db_pool.pull_immutable().database_id(), db_id);
+        drop(db_pool);
+
+        assert_eq!(
+            backend.calls(),
+            [
+                MockCall::Init,
+                MockCall::Create {
+                    db_id,
+                    restrict_privileges: true
+                },
+                MockCall::Clean { db_id },
+                MockCall::Drop {
+                    db_id,
+                    is_restricted: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn wrapper_derefs_to_mock_pool() {
+        let backend = MockBackend::new();
+
+        // connections of wrapped privileged pool must not belong to any database
+        let pool = PoolWrapper::<MockBackend>::Pool(backend.privileged_pool().clone());
+        assert_eq!(pool.get().unwrap().database_id(), None);
+    }
+}
@@ -1,5 +1,7 @@
 mod common;
 mod error;
+#[cfg(feature = "mock")]
+mod mock;
 #[cfg(feature = "_sync-mysql")]
 mod mysql;
 #[cfg(feature = "_sync-postgres")]
@@ -7,6 +9,8 @@ mod postgres;
 pub(crate) mod r#trait;
 
 pub(crate) use error::Error;
+#[cfg(feature = "mock")]
+pub use mock::{MockBackend, MockCall, MockConnection, MockConnectionManager};
 #[cfg(feature = "diesel-mysql")]
 pub use mysql::DieselMySQLBackend;
 #[cfg(feature = "mysql")]
@@ -1,19 +1,23 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use diesel::{
     connection::SimpleConnection,
     mysql::MysqlConnection,
     prelude::*,
     r2d2::ConnectionManager,
-    result::{ConnectionError, Error, QueryResult},
+    result::{ConnectionError, DatabaseErrorKind, Error, QueryResult},
     sql_query,
 };
-use r2d2::{Builder, Pool, PooledConnection};
+use r2d2::{Builder, CustomizeConnection, Pool, PooledConnection};
 use uuid::Uuid;
 
 use crate::{
-    common::{config::mysql::PrivilegedMySQLConfig, statement::mysql},
-    util::get_db_name,
+    common::{
+        config::{mysql::PrivilegedMySQLConfig, MySqlFlavor},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    util::{get_credentials, get_db_name, PreviousDatabaseQuery, ReusedLogin, TableNamesCache},
 };
 
 use super::{
@@ -22,14 +26,48 @@ use super::{
 };
 
 type Manager = ConnectionManager<MysqlConnection>;
+type CreateEntities = dyn Fn(&mut MysqlConnection, &str) + Send + Sync + 'static;
+
+#[derive(Debug)]
+struct SessionSqlMode(String);
+
+impl CustomizeConnection<MysqlConnection, diesel::r2d2::Error> for SessionSqlMode {
+    fn on_acquire(&self, conn: &mut MysqlConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(self.0.as_str())
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+// mysqlclient takes socket paths under a different query parameter than other drivers
+fn mysqlclient_url(url: &str) -> String {
+    url.replacen("?socket=", "?unix_socket=", 1)
+}
 
 /// [`Diesel MySQL`](https://docs.rs/diesel/2.2.4/diesel/mysql/struct.MysqlConnection.html) backend
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct DieselMySQLBackend {
     privileged_config: PrivilegedMySQLConfig,
     default_pool: Pool<Manager>,
-    create_restricted_pool: Box<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
-    create_entities: Box<dyn Fn(&mut MysqlConnection) + Send + Sync + 'static>,
+    table_names_cache: Arc<TableNamesCache>,
+    create_restricted_pool: Arc<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
+    create_entities: Arc<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Arc<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    name_generator: Arc<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Arc<MetricsCallback>>,
 }
 
 impl DieselMySQLBackend {
@@ -65,16 +103,128 @@ impl DieselMySQLBackend {
         create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
         create_entities: impl Fn(&mut MysqlConnection) + Send + Sync + 'static,
     ) -> Result<Self, r2d2::Error> {
-        let manager = Manager::new(privileged_config.default_connection_url());
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`Diesel MySQL`](https://docs.rs/diesel/2.2.4/diesel/mysql/struct.MysqlConnection.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::DieselMySQLBackend, PrivilegedMySQLConfig};
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselMySQLBackend::new_with_context(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn, db_name| {
+    ///         let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///         sql_query(query.as_str())
+    ///         .execute(conn)
+    ///         .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new_with_context(
+        privileged_config: PrivilegedMySQLConfig,
+        create_privileged_pool: impl Fn() -> Builder<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut MysqlConnection, &str) + Send + Sync + 'static,
+    ) -> Result<Self, r2d2::Error> {
+        let manager = Manager::new(mysqlclient_url(&privileged_config.default_connection_url()));
         let default_pool = (create_privileged_pool()).build(manager)?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            privileged_config,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`Diesel MySQL`](https://docs.rs/diesel/2.2.4/diesel/mysql/struct.MysqlConnection.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::DieselMySQLBackend, PrivilegedMySQLConfig};
+    /// use diesel::{mysql::MysqlConnection, r2d2::ConnectionManager, sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedMySQLConfig::from_env().unwrap();
+    /// let manager = ConnectionManager::<MysqlConnection>::new(config.default_connection_url());
+    /// let privileged_pool = Pool::builder().max_size(10).build(manager).unwrap();
+    ///
+    /// let backend = DieselMySQLBackend::new_with_privileged_pool(
+    ///     config,
+    ///     privileged_pool,
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        privileged_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut MysqlConnection) + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedMySQLConfig,
+        default_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut MysqlConnection, &str) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
             privileged_config,
             default_pool,
-            create_entities: Box::new(create_entities),
-            create_restricted_pool: Box::new(create_restricted_pool),
+            table_names_cache: Arc::default(),
+            create_entities: Arc::new(create_entities),
+            create_restricted_pool: Arc::new(create_restricted_pool),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            name_generator: Arc::new(get_db_name),
+            metrics_callback: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -85,6 +235,152 @@ impl DieselMySQLBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    /// Replaces any connection customizer set on the restricted pool builder.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Arc::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Arc::new(value)),
+            ..self
+        }
+    }
 }
 
 impl MySQLBackend for DieselMySQLBackend {
@@ -115,7 +411,44 @@ impl MySQLBackend for DieselMySQLBackend {
     }
 
     fn get_host(&self) -> Cow<str> {
-        self.privileged_config.host.as_str().into()
+        self.user_host
+            .as_deref()
+            .unwrap_or(self.privileged_config.user_host())
+            .into()
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &Error) -> bool {
+        // Diesel does not expose MySQL error numbers, so the error message is matched instead
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("Operation CREATE USER failed")
+        )
+    }
+
+    fn is_transient_lock_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("Deadlock found")
+                    || info.message().starts_with("Lock wait timeout exceeded")
+        )
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
     }
 
     fn get_previous_database_names(
@@ -134,23 +467,74 @@ impl MySQLBackend for DieselMySQLBackend {
             .load::<String>(conn)
     }
 
-    fn create_entities(&self, conn: &mut MysqlConnection) {
-        (self.create_entities)(conn);
+    fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut <Self::ConnectionManager as r2d2::ManageConnection>::Connection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            schema_name: String,
+        }
+
+        sql_query(query)
+            .load::<DatabaseName>(conn)
+            .map(|rows| rows.into_iter().map(|row| row.schema_name).collect())
+    }
+
+    fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut <Self::ConnectionManager as r2d2::ManageConnection>::Connection,
+    ) -> QueryResult<Vec<String>> {
+        table! {
+            mysql.user (user_name, host) {
+                #[sql_name = "user"]
+                user_name -> Text,
+                host -> Text
+            }
+        }
+
+        user::table
+            .select(user::user_name)
+            .filter(user::user_name.like("db_pool_%"))
+            .filter(user::host.eq(host))
+            .load::<String>(conn)
+    }
+
+    fn create_entities(&self, conn: &mut MysqlConnection, db_name: &str) {
+        (self.create_entities)(conn, db_name);
     }
 
     fn create_connection_pool(
         &self,
         db_id: Uuid,
     ) -> Result<Pool<Self::ConnectionManager>, r2d2::Error> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
-        );
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        let database_url =
+            mysqlclient_url(&self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ));
         let manager = ConnectionManager::<MysqlConnection>::new(database_url.as_str());
-        (self.create_restricted_pool)().build(manager)
+        let mut builder = (self.create_restricted_pool)();
+        if let Some(sql_mode) = &self.session_sql_mode {
+            builder = builder.connection_customizer(Box::new(SessionSqlMode(
+                mysql::set_session_sql_mode(sql_mode),
+            )));
+        }
+
+        // Build pool without connecting in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            return Ok(builder.build_unchecked(manager));
+        }
+
+        builder.build(manager)
     }
 
     fn get_table_names(
@@ -161,7 +545,8 @@ impl MySQLBackend for DieselMySQLBackend {
         table! {
             tables (table_name) {
                 table_name -> Text,
-                table_schema -> Text
+                table_schema -> Text,
+                table_type -> Text
             }
         }
 
@@ -169,13 +554,64 @@ impl MySQLBackend for DieselMySQLBackend {
 
         tables::table
             .filter(tables::table_schema.eq(db_name))
+            .filter(
+                tables::table_type
+                    .ne_all(self.server_flavor.excluded_table_types().iter().copied()),
+            )
             .select(tables::table_name)
             .load::<String>(conn)
     }
 
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut MysqlConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct TableName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            table_name: String,
+        }
+
+        sql_query(mysql::get_nonempty_table_names(table_names, db_name))
+            .load::<TableName>(conn)
+            .map(|rows| rows.into_iter().map(|row| row.table_name).collect())
+    }
+
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
 }
 
 impl Backend for DieselMySQLBackend {
@@ -187,6 +623,10 @@ impl Backend for DieselMySQLBackend {
         MySQLBackendWrapper::new(self).init()
     }
 
+    fn ping(&self) -> Result<(), BackendError<ConnectionError, Error>> {
+        MySQLBackendWrapper::new(self).ping()
+    }
+
     fn create(
         &self,
         db_id: Uuid,
@@ -195,6 +635,13 @@ impl Backend for DieselMySQLBackend {
         MySQLBackendWrapper::new(self).create(db_id, restrict_privileges)
     }
 
+    fn create_reader_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Pool<Manager>, BackendError<ConnectionError, Error>> {
+        MySQLBackendWrapper::new(self).create_reader_pool(db_id)
+    }
+
     fn clean(&self, db_id: Uuid) -> Result<(), BackendError<ConnectionError, Error>> {
         MySQLBackendWrapper::new(self).clean(db_id)
     }
@@ -206,6 +653,27 @@ impl Backend for DieselMySQLBackend {
     ) -> Result<(), BackendError<ConnectionError, Error>> {
         MySQLBackendWrapper::new(self).drop(db_id)
     }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        Some(mysqlclient_url(
+            &self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ),
+        ))
+    }
+
+    fn privileged_pool(&self) -> &Pool<Manager> {
+        &self.default_pool
+    }
 }
 
 #[cfg(test)]
@@ -226,12 +694,14 @@ mod tests {
         },
         sync::{
             backend::mysql::r#trait::tests::{
+                test_backend_creates_database_with_charset_and_collation,
                 test_backend_creates_database_with_unrestricted_privileges,
                 test_pool_drops_created_unrestricted_database,
             },
             db_pool::DatabasePoolBuilder,
         },
         tests::get_privileged_mysql_config,
+        MySqlFlavor,
     };
 
     use super::{
@@ -286,6 +756,26 @@ mod tests {
         test_backend_creates_database_with_restricted_privileges(&backend);
     }
 
+    #[test]
+    fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(&backend);
+    }
+
+    #[test]
+    fn backend_creates_database_with_charset_and_collation() {
+        const CHARSET: &str = "utf8mb4";
+        const COLLATION: &str = "utf8mb4_bin";
+
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .charset(CHARSET)
+            .collation(COLLATION);
+        test_backend_creates_database_with_charset_and_collation(&backend, CHARSET, COLLATION);
+    }
+
     #[test]
     fn backend_creates_database_with_unrestricted_privileges() {
         let backend = create_backend(true).drop_previous_databases(false);
@@ -298,6 +788,14 @@ mod tests {
         test_backend_cleans_database_with_tables(&backend);
     }
 
+    #[test]
+    fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
     #[test]
     fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).drop_previous_databases(false);
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use r2d2::{Builder, Pool, PooledConnection};
 use r2d2_mysql::{
@@ -7,7 +7,17 @@ use r2d2_mysql::{
 };
 use uuid::Uuid;
 
-use crate::{common::statement::mysql, util::get_db_name};
+use crate::{
+    common::{
+        config::MySqlFlavor,
+        metrics::{MetricEvent, MetricsCallback},
+        statement::mysql,
+    },
+    util::{
+        get_credentials, get_db_name, url_host, url_userinfo, PreviousDatabaseQuery, ReusedLogin,
+        TableNamesCache,
+    },
+};
 
 use super::{
     super::{error::Error as BackendError, r#trait::Backend},
@@ -15,14 +25,33 @@ use super::{
 };
 
 type Manager = MySqlConnectionManager;
+type CreateEntities = dyn Fn(&mut Conn, &str) + Send + Sync + 'static;
 
 /// MySQL backend
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct MySQLBackend {
     opts: Opts,
     default_pool: Pool<Manager>,
-    create_restricted_pool: Box<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
-    create_entities: Box<dyn Fn(&mut Conn) + Send + Sync + 'static>,
+    table_names_cache: Arc<TableNamesCache>,
+    create_restricted_pool: Arc<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
+    create_entities: Arc<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Arc<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    user_host: Option<String>,
+    reused_user: Option<ReusedLogin>,
+    server_flavor: MySqlFlavor,
+    charset: Option<String>,
+    collation: Option<String>,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    session_sql_mode: Option<String>,
+    name_generator: Arc<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Arc<MetricsCallback>>,
 }
 
 impl MySQLBackend {
@@ -56,17 +85,132 @@ impl MySQLBackend {
         create_privileged_pool: impl Fn() -> Builder<Manager>,
         create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
         create_entities: impl Fn(&mut Conn) + Send + Sync + 'static,
+    ) -> Result<Self, r2d2::Error> {
+        Self::new_with_context(
+            opts,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new MySQL backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::MySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    /// use r2d2_mysql::mysql::{prelude::Queryable, OptsBuilder};
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedMySQLConfig::from_env().unwrap();
+    ///
+    /// let backend = MySQLBackend::new_with_context(
+    ///     config.into(),
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn, db_name| {
+    ///         let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///         conn.query_drop(query.as_str())
+    ///         .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new_with_context(
+        opts: Opts,
+        create_privileged_pool: impl Fn() -> Builder<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Conn, &str) + Send + Sync + 'static,
     ) -> Result<Self, r2d2::Error> {
         let manager = Manager::new(OptsBuilder::from_opts(opts.clone()));
         let default_pool = (create_privileged_pool()).build(manager)?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
             opts,
             default_pool,
-            create_entities: Box::new(create_entities),
-            create_restricted_pool: Box::new(create_restricted_pool),
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new MySQL backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::MySQLBackend, PrivilegedMySQLConfig};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    /// use r2d2_mysql::{
+    ///     mysql::{prelude::Queryable, Opts, OptsBuilder},
+    ///     MySqlConnectionManager,
+    /// };
+    ///
+    /// dotenv().ok();
+    ///
+    /// let opts: Opts = PrivilegedMySQLConfig::from_env().unwrap().into();
+    /// let manager = MySqlConnectionManager::new(OptsBuilder::from_opts(opts.clone()));
+    /// let privileged_pool = Pool::builder().max_size(10).build(manager).unwrap();
+    ///
+    /// let backend = MySQLBackend::new_with_privileged_pool(
+    ///     opts,
+    ///     privileged_pool,
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         conn.query_drop(
+    ///             "CREATE TABLE book(id INTEGER PRIMARY KEY AUTO_INCREMENT, title TEXT NOT NULL)",
+    ///         )
+    ///         .unwrap();
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        opts: Opts,
+        privileged_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Conn) + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            opts,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        opts: Opts,
+        default_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Conn, &str) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            opts,
+            default_pool,
+            table_names_cache: Arc::default(),
+            create_entities: Arc::new(create_entities),
+            create_restricted_pool: Arc::new(create_restricted_pool),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            user_host: None,
+            reused_user: None,
+            server_flavor: MySqlFlavor::default(),
+            charset: None,
+            collation: None,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            session_sql_mode: None,
+            name_generator: Arc::new(get_db_name),
+            metrics_callback: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -77,6 +221,151 @@ impl MySQLBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``schema_name``.
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and users instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Host of created users, overriding the host of the privileged connection
+    /// (e.g. `%` to allow connecting from any host)
+    #[must_use]
+    pub fn user_host(self, value: impl Into<String>) -> Self {
+        Self {
+            user_host: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Reuse an existing user for created databases instead of creating a user per database
+    #[must_use]
+    pub fn reuse_user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_user: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// MySQL-compatible server targeted by the backend
+    #[must_use]
+    pub fn server_flavor(self, value: MySqlFlavor) -> Self {
+        Self {
+            server_flavor: value,
+            ..self
+        }
+    }
+
+    /// Character set of created databases, overriding the server default
+    #[must_use]
+    pub fn charset(self, value: impl Into<String>) -> Self {
+        Self {
+            charset: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation of created databases, overriding the server default
+    #[must_use]
+    pub fn collation(self, value: impl Into<String>) -> Self {
+        Self {
+            collation: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock or lock wait timeout
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// SQL mode set on every connection of restricted pools, overriding the server default
+    ///
+    /// Pins behavior such as strict mode regardless of the server configuration.
+    #[must_use]
+    pub fn session_sql_mode(self, value: impl Into<String>) -> Self {
+        Self {
+            session_sql_mode: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Arc::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Arc::new(value)),
+            ..self
+        }
+    }
 }
 
 impl MySQLBackendTrait for MySQLBackend {
@@ -107,7 +396,38 @@ impl MySQLBackendTrait for MySQLBackend {
     }
 
     fn get_host(&self) -> Cow<str> {
-        self.opts.get_ip_or_hostname()
+        self.user_host
+            .as_deref()
+            .map_or_else(|| self.opts.get_ip_or_hostname(), Into::into)
+    }
+
+    fn get_reused_user(&self) -> Option<&str> {
+        self.reused_user.as_ref().map(|user| user.name.as_str())
+    }
+
+    fn is_duplicate_user_error(&self, err: &Error) -> bool {
+        matches!(err, Error::MySqlError(err) if err.code == mysql::DUPLICATE_USER_ERROR_NUMBER)
+    }
+
+    fn is_transient_lock_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::MySqlError(err)
+                if err.code == mysql::DEADLOCK_ERROR_NUMBER
+                    || err.code == mysql::LOCK_WAIT_TIMEOUT_ERROR_NUMBER
+        )
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    fn get_collation(&self) -> Option<&str> {
+        self.collation.as_deref()
     }
 
     fn get_previous_database_names(
@@ -117,28 +437,100 @@ impl MySQLBackendTrait for MySQLBackend {
         conn.query(mysql::GET_DATABASE_NAMES)
     }
 
-    fn create_entities(&self, conn: &mut Conn) {
-        (self.create_entities)(conn);
+    fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut <Self::ConnectionManager as r2d2::ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Error> {
+        conn.query(query)
+    }
+
+    fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut <Self::ConnectionManager as r2d2::ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Error> {
+        conn.query(mysql::get_user_names(host))
+    }
+
+    fn create_entities(&self, conn: &mut Conn, db_name: &str) {
+        (self.create_entities)(conn, db_name);
     }
 
     fn create_connection_pool(&self, db_id: Uuid) -> Result<Pool<Manager>, r2d2::Error> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name);
+        let mut init = self.opts.get_init();
+        if let Some(sql_mode) = &self.session_sql_mode {
+            init.push(mysql::set_session_sql_mode(sql_mode));
+        }
         let opts = OptsBuilder::from_opts(self.opts.clone())
             .db_name(Some(db_name))
-            .user(Some(db_name))
-            .pass(Some(db_name));
+            .user(Some(username))
+            .pass(Some(password))
+            .init(init);
         let manager = MySqlConnectionManager::new(opts);
-        (self.create_restricted_pool)().build(manager)
+        let builder = (self.create_restricted_pool)();
+
+        // Build pool without connecting in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            return Ok(builder.build_unchecked(manager));
+        }
+
+        builder.build(manager)
     }
 
     fn get_table_names(&self, db_name: &str, conn: &mut Conn) -> Result<Vec<String>, Error> {
-        conn.query(mysql::get_table_names(db_name))
+        conn.query(mysql::get_table_names(
+            db_name,
+            self.server_flavor.excluded_table_types(),
+        ))
+    }
+
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut Conn,
+    ) -> Result<Vec<String>, Error> {
+        conn.query(mysql::get_nonempty_table_names(table_names, db_name))
     }
 
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
 }
 
 impl From<Error> for BackendError<Error, Error> {
@@ -156,6 +548,10 @@ impl Backend for MySQLBackend {
         MySQLBackendWrapper::new(self).init()
     }
 
+    fn ping(&self) -> Result<(), BackendError<Error, Error>> {
+        MySQLBackendWrapper::new(self).ping()
+    }
+
     fn create(
         &self,
         db_id: Uuid,
@@ -164,6 +560,10 @@ impl Backend for MySQLBackend {
         MySQLBackendWrapper::new(self).create(db_id, restrict_privileges)
     }
 
+    fn create_reader_pool(&self, db_id: Uuid) -> Result<Pool<Manager>, BackendError<Error, Error>> {
+        MySQLBackendWrapper::new(self).create_reader_pool(db_id)
+    }
+
     fn clean(&self, db_id: Uuid) -> Result<(), BackendError<Error, Error>> {
         MySQLBackendWrapper::new(self).clean(db_id)
     }
@@ -171,6 +571,24 @@ impl Backend for MySQLBackend {
     fn drop(&self, db_id: Uuid, _is_restricted: bool) -> Result<(), BackendError<Error, Error>> {
         MySQLBackendWrapper::new(self).drop(db_id)
     }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_user.as_ref(), db_name.as_str());
+        let userinfo = url_userinfo(username, Some(password));
+        let host = self.opts.get_ip_or_hostname();
+        let host = url_host(host.as_ref());
+        let port = self.opts.get_tcp_port();
+        Some(format!("mysql://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &Pool<Manager> {
+        &self.default_pool
+    }
 }
 
 #[cfg(test)]
@@ -178,11 +596,13 @@ mod tests {
     #![allow(unused_variables, clippy::unwrap_used)]
 
     use r2d2::Pool;
-    use r2d2_mysql::mysql::{params, prelude::Queryable};
+    use r2d2_mysql::mysql::{params, prelude::Queryable, Conn, Opts};
+    use uuid::Uuid;
 
     use crate::{
-        common::statement::mysql::tests::{
-            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
+        common::statement::mysql::{
+            self,
+            tests::{CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS},
         },
         sync::{
             backend::mysql::r#trait::tests::{
@@ -192,6 +612,7 @@ mod tests {
             DatabasePoolBuilderTrait,
         },
         tests::get_privileged_mysql_config,
+        MySqlFlavor,
     };
 
     use super::{
@@ -233,6 +654,14 @@ mod tests {
         test_backend_creates_database_with_restricted_privileges(&backend);
     }
 
+    #[test]
+    fn backend_creates_database_with_any_user_host() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .user_host("%");
+        test_backend_creates_database_with_restricted_privileges(&backend);
+    }
+
     #[test]
     fn backend_creates_database_with_unrestricted_privileges() {
         let backend = create_backend(true).drop_previous_databases(false);
@@ -245,6 +674,14 @@ mod tests {
         test_backend_cleans_database_with_tables(&backend);
     }
 
+    #[test]
+    fn backend_cleans_database_with_tables_in_mariadb_flavor() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .server_flavor(MySqlFlavor::MariaDB);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
     #[test]
     fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).drop_previous_databases(false);
@@ -412,6 +849,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pool_applies_session_sql_mode() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .session_sql_mode("STRICT_ALL_TABLES");
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // session must use configured SQL mode
+        assert_eq!(
+            conn.query_first::<String, _>("SELECT @@SESSION.sql_mode")
+                .unwrap()
+                .unwrap(),
+            "STRICT_ALL_TABLES"
+        );
+
+        // invalid value must be rejected in strict mode
+        assert!(conn
+            .query_drop("INSERT INTO book (id, title) VALUES ('invalid', 'Title')")
+            .is_err());
+    }
+
+    #[test]
+    fn backend_connects_to_maintenance_database() {
+        let maintenance_database = format!("maintenance_{}", Uuid::new_v4().simple());
+
+        let guard = lock_read();
+
+        // Create maintenance database
+        let config = get_privileged_mysql_config().clone();
+        let mut conn = Conn::new(Opts::from(config.clone())).unwrap();
+        conn.query_drop(mysql::create_database(
+            maintenance_database.as_str(),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        {
+            let config = config.maintenance_database(maintenance_database.as_str());
+
+            let backend = MySQLBackend::new(config.into(), Pool::builder, Pool::builder, {
+                move |conn| {
+                    conn.query_drop(CREATE_ENTITIES_STATEMENTS.join(";"))
+                        .unwrap();
+                }
+            })
+            .unwrap()
+            .drop_previous_databases(false);
+
+            // privileged user must connect to maintenance database
+            assert_eq!(
+                backend
+                    .default_pool
+                    .get()
+                    .unwrap()
+                    .query_first::<String, _>("SELECT DATABASE()")
+                    .unwrap()
+                    .unwrap(),
+                maintenance_database
+            );
+
+            let db_pool = backend.create_database_pool().unwrap();
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+
+            // created database must be usable
+            assert!(conn
+                .query_drop("INSERT INTO book (title) VALUES ('Title')")
+                .is_ok());
+        }
+
+        // Drop maintenance database
+        conn.query_drop(mysql::drop_database(maintenance_database.as_str()))
+            .unwrap();
+    }
+
+    #[test]
+    fn pool_cleans_tables_with_quoted_names() {
+        let config = get_privileged_mysql_config().clone();
+        let backend = MySQLBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.query_drop(
+                    "CREATE TABLE `User`(id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT NOT NULL); \
+                     CREATE TABLE `order`(id INTEGER PRIMARY KEY AUTO_INCREMENT)",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        {
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+            conn.query_drop(
+                "INSERT INTO `User` (name) VALUES ('Name'); \
+                 INSERT INTO `order` VALUES ()",
+            )
+            .unwrap();
+        }
+
+        // tables with mixed-case and reserved names must be cleaned
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+        assert_eq!(
+            conn.query_first::<i64, _>(
+                "SELECT (SELECT COUNT(*) FROM `User`) + (SELECT COUNT(*) FROM `order`)"
+            )
+            .unwrap()
+            .unwrap(),
+            0
+        );
+    }
+
     #[test]
     fn pool_drops_created_restricted_databases() {
         let backend = create_backend(false);
@@ -1,9 +1,18 @@
-use std::{borrow::Cow, fmt::Debug, ops::Deref};
+use std::{borrow::Cow, fmt::Debug, ops::Deref, thread, time::Instant};
 
 use r2d2::{ManageConnection, Pool, PooledConnection};
 use uuid::Uuid;
 
-use crate::common::statement::mysql;
+use crate::{
+    common::{
+        metrics::{MetricEvent, MetricOperation, MetricsCallback},
+        statement::mysql,
+    },
+    util::{
+        get_clean_retry_backoff, is_valid_db_name, PreviousDatabaseQuery, TableNamesCache,
+        DB_NAME_PREFIX,
+    },
+};
 
 use super::super::error::Error as BackendError;
 
@@ -26,12 +35,32 @@ pub(super) trait MySQLBackend {
     ) -> Result<(), Self::QueryError>;
 
     fn get_host(&self) -> Cow<str>;
+    fn get_reused_user(&self) -> Option<&str>;
+    fn is_duplicate_user_error(&self, err: &Self::QueryError) -> bool;
+    fn is_transient_lock_error(&self, err: &Self::QueryError) -> bool;
+    fn get_db_name(&self, db_id: Uuid) -> String;
+    fn get_charset(&self) -> Option<&str>;
+    fn get_collation(&self) -> Option<&str>;
 
     fn get_previous_database_names(
         &self,
         conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
-    fn create_entities(&self, conn: &mut <Self::ConnectionManager as ManageConnection>::Connection);
+    fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_previous_user_names(
+        &self,
+        host: &str,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn create_entities(
+        &self,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+        db_name: &str,
+    );
     fn create_connection_pool(
         &self,
         db_id: Uuid,
@@ -42,8 +71,23 @@ pub(super) trait MySQLBackend {
         db_name: &str,
         conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        db_name: &str,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
 
     fn get_drop_previous_databases(&self) -> bool;
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery>;
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool;
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool;
+    fn get_verify_clean(&self) -> bool;
+    fn get_clean_retries(&self) -> usize;
+    fn get_table_names_cache(&self) -> &TableNamesCache;
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback>;
 }
 
 pub(super) struct MySQLBackendWrapper<'a, B: MySQLBackend>(&'a B);
@@ -63,24 +107,134 @@ impl<'a, B: MySQLBackend> Deref for MySQLBackendWrapper<'a, B> {
 }
 
 impl<'a, B: MySQLBackend> MySQLBackendWrapper<'a, B> {
+    #[cfg_attr(not(feature = "logging"), allow(clippy::unused_self))]
+    fn is_dry_run(&self) -> bool {
+        #[cfg(feature = "logging")]
+        return self.get_dry_run();
+        #[cfg(not(feature = "logging"))]
+        false
+    }
+
+    fn execute(
+        &self,
+        query: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        #[cfg(feature = "logging")]
+        if self.get_log_statements() || self.get_dry_run() {
+            log::info!("{query}");
+        }
+
+        // Skip executing statement in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.0
+            .execute(query, conn)
+            .map_err(|err| BackendError::Statement(query.to_owned(), err))
+    }
+
+    fn batch_execute<'b>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'b, str>>,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        let query = {
+            let query = query.into_iter().collect::<Vec<_>>();
+            if self.get_log_statements() || self.get_dry_run() {
+                for stmt in &query {
+                    log::info!("{stmt}");
+                }
+            }
+            query
+        };
+
+        // Skip executing statements in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.0.batch_execute(query, conn)
+    }
+
+    fn get_cached_table_names(
+        &self,
+        db_id: Uuid,
+        db_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, B::QueryError> {
+        if let Some(table_names) = self.get_table_names_cache().lock().get(&db_id) {
+            return Ok(table_names.clone());
+        }
+
+        let table_names = self.get_table_names(db_name, conn)?;
+        self.get_table_names_cache()
+            .lock()
+            .insert(db_id, table_names.clone());
+        Ok(table_names)
+    }
+
+    fn report_metric(
+        &self,
+        operation: MetricOperation,
+        db_id: Uuid,
+        start: Instant,
+        cleaned_tables: Option<usize>,
+    ) {
+        if let Some(metrics_callback) = self.get_metrics_callback() {
+            metrics_callback(MetricEvent {
+                operation,
+                db_id,
+                elapsed: start.elapsed(),
+                cleaned_tables,
+            });
+        }
+    }
+
+    pub(super) fn ping(&self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let conn = &mut self.get_connection()?;
+        self.execute(mysql::PING, conn)
+    }
+
     pub(super) fn init(&self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Ensure generated database names are valid identifiers before any database is created
+        let db_name = self.get_db_name(Uuid::new_v4());
+        if !is_valid_db_name(&db_name, mysql::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name));
+        }
+
         // Drop previous databases if needed
         if self.get_drop_previous_databases() {
             // Get privileged connection
             let conn = &mut self.get_connection()?;
 
-            // Get previous database names
-            self.execute(mysql::USE_DEFAULT_DATABASE, conn)
-                .map_err(Into::into)?;
-            let db_names = self.get_previous_database_names(conn).map_err(Into::into)?;
+            // Get previous database names using custom query if set
+            self.execute(mysql::USE_DEFAULT_DATABASE, conn)?;
+            let db_names = if let Some(query) = self.get_previous_database_query() {
+                self.get_database_names(query(DB_NAME_PREFIX).as_str(), conn)
+            } else {
+                self.get_previous_database_names(conn)
+            }
+            .map_err(Into::into)?;
 
             // Drop databases
             for db_name in &db_names {
-                self.execute(
-                    crate::common::statement::mysql::drop_database(db_name.as_str()).as_str(),
-                    conn,
-                )
+                self.execute(mysql::drop_database(db_name.as_str()).as_str(), conn)?;
+            }
+
+            // Get previous user names
+            let host = &self.get_host();
+            let user_names = self
+                .get_previous_user_names(host, conn)
                 .map_err(Into::into)?;
+
+            // Drop users left behind by previous databases
+            for user_name in &user_names {
+                if Some(user_name.as_str()) != self.get_reused_user() {
+                    self.execute(mysql::drop_user(user_name.as_str(), host).as_str(), conn)?;
+                }
             }
         }
 
@@ -93,78 +247,155 @@ impl<'a, B: MySQLBackend> MySQLBackendWrapper<'a, B> {
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<Pool<B::ConnectionManager>, BackendError<B::ConnectionError, B::QueryError>> {
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = crate::util::get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
+        // Ensure database name is a valid identifier
+        if !is_valid_db_name(db_name, mysql::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name.to_owned()));
+        }
+
         let host = &self.get_host();
 
+        // Get name of user attached to database
+        let reused_user = self.get_reused_user();
+        let user_name = reused_user.unwrap_or(db_name);
+
         // Get privileged connection
         let conn = &mut self.get_connection()?;
 
         // Create database
-        self.execute(mysql::create_database(db_name).as_str(), conn)
-            .map_err(Into::into)?;
-
-        // Create user
-        self.execute(mysql::create_user(db_name, host).as_str(), conn)
-            .map_err(Into::into)?;
+        self.execute(
+            mysql::create_database(db_name, self.get_charset(), self.get_collation()).as_str(),
+            conn,
+        )?;
+
+        // Create user if not reusing an existing one
+        if reused_user.is_none() {
+            if let Err(err) = self.execute(mysql::create_user(user_name, host).as_str(), conn) {
+                return Err(match err {
+                    BackendError::Statement(_, ref query_err)
+                        if self.is_duplicate_user_error(query_err) =>
+                    {
+                        BackendError::RoleExists(user_name.to_owned())
+                    }
+                    err => err,
+                });
+            }
+        }
 
-        // Create entities
-        self.execute(mysql::use_database(db_name).as_str(), conn)
-            .map_err(Into::into)?;
-        self.create_entities(conn);
-        self.execute(mysql::USE_DEFAULT_DATABASE, conn)
-            .map_err(Into::into)?;
+        // Create entities, except in dry run as the database was never created
+        if !self.is_dry_run() {
+            self.execute(mysql::use_database(db_name).as_str(), conn)?;
+            self.create_entities(conn, db_name);
+            self.execute(mysql::USE_DEFAULT_DATABASE, conn)?;
+        }
 
         if restrict_privileges {
             // Grant privileges to restricted user
             self.execute(
-                mysql::grant_restricted_privileges(db_name, host).as_str(),
+                mysql::grant_restricted_privileges(db_name, user_name, host).as_str(),
                 conn,
-            )
-            .map_err(Into::into)?;
+            )?;
         } else {
             // Grant all privileges to database-unrestricted user
-            self.execute(mysql::grant_all_privileges(db_name, host).as_str(), conn)
-                .map_err(Into::into)?;
+            self.execute(
+                mysql::grant_all_privileges(db_name, user_name, host).as_str(),
+                conn,
+            )?;
         }
 
         // Create connection pool with attached user
-        let pool = self.create_connection_pool(db_id)?;
+        let pool = self
+            .create_connection_pool(db_id)
+            .map_err(BackendError::Build)?;
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Create, db_id, start, None);
 
         Ok(pool)
     }
 
+    #[allow(clippy::complexity)]
+    pub(super) fn create_reader_pool(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<Pool<B::ConnectionManager>, BackendError<B::ConnectionError, B::QueryError>> {
+        // Create connection pool with attached user
+        self.create_connection_pool(db_id)
+            .map_err(BackendError::Build)
+    }
+
     pub(super) fn clean(
         &self,
         db_id: uuid::Uuid,
     ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Skip cleaning in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = crate::util::get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
         // Get privileged connection
         let conn = &mut self.get_connection()?;
 
-        // Get table names
-        let mut table_names = self.get_table_names(db_name, conn).map_err(Into::into)?;
+        // Get table names, cached since the schema cannot change
+        let table_names = self
+            .get_cached_table_names(db_id, db_name, conn)
+            .map_err(Into::into)?;
 
         // Generate truncate statements
         let stmts = table_names
-            .drain(..)
+            .iter()
             .map(|table_name| mysql::truncate_table(table_name.as_str(), db_name).into());
 
         // Turn off foreign key checks
-        self.execute(mysql::TURN_OFF_FOREIGN_KEY_CHECKS, conn)
-            .map_err(Into::into)?;
+        self.execute(mysql::TURN_OFF_FOREIGN_KEY_CHECKS, conn)?;
+
+        // Truncate tables, which cannot be done in a transaction since TRUNCATE implicitly commits
+        // Retry after a backoff on deadlocks and lock wait timeouts if needed
+        let mut attempt = 0;
+        let truncated = loop {
+            match self.batch_execute(stmts.clone(), conn) {
+                Err(err)
+                    if attempt < self.get_clean_retries() && self.is_transient_lock_error(&err) =>
+                {
+                    attempt += 1;
+                    thread::sleep(get_clean_retry_backoff(attempt));
+                }
+                truncated => break truncated.map_err(Into::into),
+            }
+        };
 
-        // Truncate tables
-        self.batch_execute(stmts, conn).map_err(Into::into)?;
+        // Turn on foreign key checks even if truncating failed, since the connection is returned to the pool
+        self.execute(mysql::TURN_ON_FOREIGN_KEY_CHECKS, conn)?;
+        truncated?;
 
-        // Turn on foreign key checks
-        self.execute(mysql::TURN_ON_FOREIGN_KEY_CHECKS, conn)
-            .map_err(Into::into)?;
+        // Verify that no table was left with rows if needed
+        if self.get_verify_clean() && !table_names.is_empty() {
+            let nonempty_table_names = self
+                .get_nonempty_table_names(&table_names, db_name, conn)
+                .map_err(Into::into)?;
+            if !nonempty_table_names.is_empty() {
+                return Err(BackendError::UncleanDatabase(db_id, nonempty_table_names));
+            }
+        }
+
+        // Report time taken and number of tables cleaned if needed
+        self.report_metric(
+            MetricOperation::Clean,
+            db_id,
+            start,
+            Some(table_names.len()),
+        );
 
         Ok(())
     }
@@ -173,8 +404,13 @@ impl<'a, B: MySQLBackend> MySQLBackendWrapper<'a, B> {
         &self,
         db_id: uuid::Uuid,
     ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let start = Instant::now();
+
+        // Forget table names
+        self.get_table_names_cache().lock().remove(&db_id);
+
         // Get database name based on UUID
-        let db_name = crate::util::get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
         let host = &self.get_host();
@@ -183,12 +419,21 @@ impl<'a, B: MySQLBackend> MySQLBackendWrapper<'a, B> {
         let conn = &mut self.get_connection()?;
 
         // Drop database
-        self.execute(mysql::drop_database(db_name).as_str(), conn)
-            .map_err(Into::into)?;
+        self.execute(mysql::drop_database(db_name).as_str(), conn)?;
 
-        // Drop CRUD user
-        self.execute(mysql::drop_user(db_name, host).as_str(), conn)
-            .map_err(Into::into)?;
+        if let Some(user_name) = self.get_reused_user() {
+            // Revoke privileges from reused user
+            self.execute(
+                mysql::revoke_all_privileges(db_name, user_name, host).as_str(),
+                conn,
+            )?;
+        } else {
+            // Drop CRUD user
+            self.execute(mysql::drop_user(db_name, host).as_str(), conn)?;
+        }
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Drop, db_id, start, None);
 
         Ok(())
     }
@@ -220,7 +465,9 @@ pub(super) mod tests {
 
     table! {
         schemata (schema_name) {
-            schema_name -> Text
+            schema_name -> Text,
+            default_character_set_name -> Text,
+            default_collation_name -> Text,
         }
     }
 
@@ -358,6 +605,39 @@ pub(super) mod tests {
         }
     }
 
+    pub fn test_backend_creates_database_with_charset_and_collation(
+        backend: &impl Backend,
+        charset: &str,
+        collation: &str,
+    ) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let guard = lock_read();
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        backend.init().unwrap();
+        backend.create(db_id, true).unwrap();
+
+        use_information_schema(conn);
+
+        // database must have character set and collation
+        assert_eq!(
+            schemata::table
+                .filter(schemata::schema_name.eq(db_name))
+                .select((
+                    schemata::default_character_set_name,
+                    schemata::default_collation_name
+                ))
+                .get_result::<(String, String)>(conn)
+                .unwrap(),
+            (charset.to_owned(), collation.to_owned())
+        );
+    }
+
     pub fn test_backend_creates_database_with_unrestricted_privileges(backend: &impl Backend) {
         let guard = lock_read();
 
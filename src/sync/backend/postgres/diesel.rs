@@ -1,14 +1,28 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, sync::Arc, time::Duration};
 
 use diesel::{
-    connection::SimpleConnection, pg::PgConnection, prelude::*, r2d2::ConnectionManager,
-    result::Error, sql_query, QueryResult, RunQueryDsl,
+    connection::SimpleConnection,
+    pg::PgConnection,
+    prelude::*,
+    r2d2::ConnectionManager,
+    result::{DatabaseErrorKind, Error},
+    sql_query, QueryResult, RunQueryDsl,
 };
 use parking_lot::Mutex;
 use r2d2::{Builder, Pool, PooledConnection};
 use uuid::Uuid;
 
-use crate::{common::config::postgres::PrivilegedPostgresConfig, util::get_db_name};
+use crate::{
+    common::{
+        config::{postgres::PrivilegedPostgresConfig, PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    util::{
+        append_pg_connect_timeout, append_pg_connection_params, get_credentials, get_db_name,
+        PreviousDatabaseQuery, ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{error::Error as BackendError, r#trait::Backend},
@@ -16,15 +30,51 @@ use super::{
 };
 
 type Manager = ConnectionManager<PgConnection>;
+type CreateEntities = dyn Fn(&mut PgConnection, &str) + Send + Sync + 'static;
 
 /// [`Diesel Postgres`](https://docs.rs/diesel/2.2.4/diesel/pg/struct.PgConnection.html) backend
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct DieselPostgresBackend {
     privileged_config: PrivilegedPostgresConfig,
     default_pool: Pool<Manager>,
-    db_conns: Mutex<HashMap<Uuid, PgConnection>>,
-    create_restricted_pool: Box<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
-    create_entities: Box<dyn Fn(&mut PgConnection) + Send + Sync + 'static>,
+    table_names_cache: Arc<TableNamesCache>,
+    db_conns: Arc<Mutex<HashMap<Uuid, PgConnection>>>,
+    create_restricted_pool: Arc<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
+    create_entities: Arc<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Arc<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    name_generator: Arc<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Arc<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
 }
 
 impl DieselPostgresBackend {
@@ -57,18 +107,147 @@ impl DieselPostgresBackend {
         create_privileged_pool: impl Fn() -> Builder<Manager>,
         create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
         create_entities: impl Fn(&mut PgConnection) + Send + Sync + 'static,
+    ) -> Result<Self, r2d2::Error> {
+        Self::new_with_context(
+            privileged_config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new [`Diesel Postgres`](https://docs.rs/diesel/2.2.4/diesel/pg/struct.PgConnection.html) backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::DieselPostgresBackend, PrivilegedPostgresConfig};
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new_with_context(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn, db_name| {
+    ///         let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///         sql_query(query.as_str())
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new_with_context(
+        privileged_config: PrivilegedPostgresConfig,
+        create_privileged_pool: impl Fn() -> Builder<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut PgConnection, &str) + Send + Sync + 'static,
     ) -> Result<Self, r2d2::Error> {
         let manager = Manager::new(privileged_config.default_connection_url());
         let default_pool = (create_privileged_pool()).build(manager)?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
             privileged_config,
             default_pool,
-            db_conns: Mutex::new(HashMap::new()),
-            create_entities: Box::new(create_entities),
-            create_restricted_pool: Box::new(create_restricted_pool),
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new [`Diesel Postgres`](https://docs.rs/diesel/2.2.4/diesel/pg/struct.PgConnection.html) backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::DieselPostgresBackend, PrivilegedPostgresConfig};
+    /// use diesel::{pg::PgConnection, r2d2::ConnectionManager, sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    /// let manager = ConnectionManager::<PgConnection>::new(config.default_connection_url());
+    /// let privileged_pool = Pool::builder().max_size(10).build(manager).unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new_with_privileged_pool(
+    ///     config,
+    ///     privileged_pool,
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        privileged_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut PgConnection) + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            privileged_config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        privileged_config: PrivilegedPostgresConfig,
+        default_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut PgConnection, &str) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            privileged_config,
+            default_pool,
+            table_names_cache: Arc::default(),
+            db_conns: Arc::new(Mutex::new(HashMap::new())),
+            create_entities: Arc::new(create_entities),
+            create_restricted_pool: Arc::new(create_restricted_pool),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            name_generator: Arc::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -79,6 +258,347 @@ impl DieselPostgresBackend {
             ..self
         }
     }
+
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
+    }
+
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
+    }
+
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
+    }
+
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
+    }
+
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
+    }
+
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
+    }
+
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
+    }
+
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
+    }
+
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
+    }
+
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
+    }
+
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
+
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
+    }
+
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Arc::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities and to clean databases when not storing connections.
+    /// Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
 }
 
 impl PostgresBackend for DieselPostgresBackend {
@@ -112,10 +632,12 @@ impl PostgresBackend for DieselPostgresBackend {
         &self,
         db_id: Uuid,
     ) -> ConnectionResult<PgConnection> {
-        let db_name = get_db_name(db_id);
-        let database_url = self
-            .privileged_config
-            .privileged_database_connection_url(db_name.as_str());
+        let db_name = self.get_db_name(db_id);
+        let database_url = append_pg_connect_timeout(
+            self.privileged_config
+                .privileged_database_connection_url(db_name.as_str()),
+            self.connect_timeout,
+        );
         PgConnection::establish(database_url.as_str())
     }
 
@@ -123,13 +645,18 @@ impl PostgresBackend for DieselPostgresBackend {
         &self,
         db_id: Uuid,
     ) -> ConnectionResult<PgConnection> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let database_url = append_pg_connection_params(
+            self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ),
+            &self.restricted_connection_params,
         );
+        let database_url = append_pg_connect_timeout(database_url, self.connect_timeout);
         PgConnection::establish(database_url.as_str())
     }
 
@@ -137,11 +664,8 @@ impl PostgresBackend for DieselPostgresBackend {
         self.db_conns.lock().insert(db_id, conn);
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> PgConnection {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    fn get_database_connection(&self, db_id: Uuid) -> Option<PgConnection> {
+        self.db_conns.lock().remove(&db_id)
     }
 
     fn get_previous_database_names(&self, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
@@ -158,23 +682,93 @@ impl PostgresBackend for DieselPostgresBackend {
             .load::<String>(conn)
     }
 
-    fn create_entities(&self, conn: &mut PgConnection) {
-        (self.create_entities)(conn);
+    fn get_database_names(&self, query: &str, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(query)
+            .load::<DatabaseName>(conn)
+            .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    fn get_previous_owned_database_names(
+        &self,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(postgres::GET_OWNED_DATABASE_NAMES)
+            .load::<DatabaseName>(conn)
+            .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct DatabaseName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            datname: String,
+        }
+
+        sql_query(postgres::get_database_names_with_schema_version(
+            schema_version,
+        ))
+        .load::<DatabaseName>(conn)
+        .map(|rows| rows.into_iter().map(|row| row.datname).collect())
+    }
+
+    fn get_previous_role_names(&self, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        table! {
+            pg_roles (rolname) {
+                rolname -> Text
+            }
+        }
+
+        pg_roles::table
+            .select(pg_roles::rolname)
+            .filter(pg_roles::rolname.like("db_pool_%"))
+            .load::<String>(conn)
+    }
+
+    fn create_entities(&self, conn: &mut PgConnection, db_name: &str) {
+        (self.create_entities)(conn, db_name);
     }
 
     fn create_connection_pool(
         &self,
         db_id: Uuid,
     ) -> Result<Pool<Self::ConnectionManager>, r2d2::Error> {
-        let db_name = get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
-        let database_url = self.privileged_config.restricted_database_connection_url(
-            db_name,
-            Some(db_name),
-            db_name,
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        let database_url = append_pg_connection_params(
+            self.privileged_config.restricted_database_connection_url(
+                username,
+                Some(password),
+                db_name,
+            ),
+            &self.restricted_connection_params,
         );
         let manager = ConnectionManager::<PgConnection>::new(database_url.as_str());
-        (self.create_restricted_pool)().build(manager)
+        let builder = (self.create_restricted_pool)();
+
+        // Build pool without connecting in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            return Ok(builder.build_unchecked(manager));
+        }
+
+        builder.build(manager)
     }
 
     fn get_table_names(&self, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
@@ -187,14 +781,181 @@ impl PostgresBackend for DieselPostgresBackend {
         }
 
         pg_tables::table
-            .filter(pg_tables::schema_name.ne_all(["pg_catalog", "information_schema"]))
-            .select(pg_tables::tablename)
-            .load(conn)
+            .filter(pg_tables::schema_name.ne_all(self.dialect.system_schemas().iter().copied()))
+            .select((pg_tables::schema_name, pg_tables::tablename))
+            .load::<(String, String)>(conn)
+            .map(|table_names| {
+                table_names
+                    .iter()
+                    .map(|(schema_name, table_name)| {
+                        postgres::qualify_table_name(schema_name, table_name)
+                    })
+                    .collect()
+            })
+    }
+
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct TableName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            table_name: String,
+        }
+
+        sql_query(postgres::get_nonempty_table_names(table_names))
+            .load::<TableName>(conn)
+            .map(|rows| rows.into_iter().map(|row| row.table_name).collect())
+    }
+
+    fn is_duplicate_database_error(&self, err: &Error) -> bool {
+        // Diesel does not expose SQLSTATE codes, so the error message is matched instead
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().ends_with("already exists")
+        )
+    }
+
+    fn is_duplicate_role_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("role") && info.message().ends_with("already exists")
+        )
+    }
+
+    fn is_transient_lock_error(&self, err: &Error) -> bool {
+        matches!(
+            err,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().starts_with("deadlock detected")
+        )
     }
 
     fn get_drop_previous_databases(&self) -> bool {
         self.drop_previous_databases_flag
     }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
+    }
+
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
+    }
+
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
+    }
+
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
+    }
+
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
+    }
+
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
+    }
+
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
+    }
+
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
+    }
+
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
+    }
+
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
+    }
 }
 
 impl Backend for DieselPostgresBackend {
@@ -206,6 +967,10 @@ impl Backend for DieselPostgresBackend {
         PostgresBackendWrapper::new(self).init()
     }
 
+    fn ping(&self) -> Result<(), BackendError<ConnectionError, Error>> {
+        PostgresBackendWrapper::new(self).ping()
+    }
+
     fn create(
         &self,
         db_id: Uuid,
@@ -214,6 +979,13 @@ impl Backend for DieselPostgresBackend {
         PostgresBackendWrapper::new(self).create(db_id, restrict_privileges)
     }
 
+    fn create_reader_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Pool<Manager>, BackendError<ConnectionError, Error>> {
+        PostgresBackendWrapper::new(self).create_reader_pool(db_id)
+    }
+
     fn clean(&self, db_id: Uuid) -> Result<(), BackendError<ConnectionError, Error>> {
         PostgresBackendWrapper::new(self).clean(db_id)
     }
@@ -225,6 +997,25 @@ impl Backend for DieselPostgresBackend {
     ) -> Result<(), BackendError<ConnectionError, Error>> {
         PostgresBackendWrapper::new(self).drop(db_id, is_restricted)
     }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        Some(self.privileged_config.restricted_database_connection_url(
+            username,
+            Some(password),
+            db_name,
+        ))
+    }
+
+    fn privileged_pool(&self) -> &Pool<Manager> {
+        &self.default_pool
+    }
 }
 
 #[cfg(test)]
@@ -234,22 +1025,22 @@ mod tests {
     use std::borrow::Cow;
 
     use diesel::{
-        connection::SimpleConnection, insert_into, sql_query, table, Insertable, QueryDsl,
-        RunQueryDsl,
+        connection::SimpleConnection, dsl::sql, insert_into, select, sql_query, sql_types::Text,
+        table, Insertable, QueryDsl, RunQueryDsl,
     };
     use dotenvy::dotenv;
     use r2d2::Pool;
 
     use crate::{
         common::{
-            config::PrivilegedPostgresConfig,
+            config::{PgDialect, PrivilegedPostgresConfig, Role},
             statement::postgres::tests::{
                 CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
             },
         },
         sync::{
             backend::postgres::r#trait::tests::test_backend_creates_database_with_unrestricted_privileges,
-            db_pool::DatabasePoolBuilder,
+            backend::r#trait::Backend, db_pool::DatabasePoolBuilder,
         },
     };
 
@@ -257,8 +1048,14 @@ mod tests {
         super::r#trait::tests::{
             lock_read, test_backend_cleans_database_with_tables,
             test_backend_cleans_database_without_tables,
-            test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
-            test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
+            test_backend_creates_database_owned_by_role,
+            test_backend_creates_database_with_restricted_privileges,
+            test_backend_creates_entities_as_restricted_role, test_backend_drops_database,
+            test_backend_drops_previous_databases,
+            test_backend_fails_to_clean_unrestricted_database,
+            test_backend_force_drops_database_with_lingering_connection,
+            test_backend_recreates_database_on_conflict, test_backend_recreates_role_on_conflict,
+            test_pool_drops_created_restricted_databases,
             test_pool_drops_created_unrestricted_database, test_pool_drops_previous_databases,
         },
         DieselPostgresBackend,
@@ -314,12 +1111,55 @@ mod tests {
         test_backend_creates_database_with_unrestricted_privileges(&backend);
     }
 
+    #[test]
+    fn backend_recreates_database_on_conflict() {
+        let default = create_backend(true).drop_previous_databases(false);
+        let enabled = create_backend(true)
+            .drop_previous_databases(false)
+            .recreate_on_conflict(true);
+        test_backend_recreates_database_on_conflict(&default, &enabled);
+    }
+
+    #[test]
+    fn backend_creates_entities_as_restricted_role() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .create_entities_as(Role::Restricted);
+        test_backend_creates_entities_as_restricted_role(&backend);
+    }
+
+    #[test]
+    fn backend_creates_database_owned_by_role() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .owner_at_creation(true)
+            .recreate_on_conflict(true);
+        test_backend_creates_database_owned_by_role(&backend);
+    }
+
+    #[test]
+    fn backend_recreates_role_on_conflict() {
+        let default = create_backend(true).drop_previous_databases(false);
+        let enabled = create_backend(true)
+            .drop_previous_databases(false)
+            .recreate_on_conflict(true);
+        test_backend_recreates_role_on_conflict(&default, &enabled);
+    }
+
     #[test]
     fn backend_cleans_database_with_tables() {
         let backend = create_backend(true).drop_previous_databases(false);
         test_backend_cleans_database_with_tables(&backend);
     }
 
+    #[test]
+    fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
     #[test]
     fn backend_cleans_database_without_tables() {
         let backend = create_backend(false).drop_previous_databases(false);
@@ -338,6 +1178,20 @@ mod tests {
         test_backend_drops_database(&backend, false);
     }
 
+    #[test]
+    fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(&backend);
+    }
+
+    #[test]
+    fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(&backend);
+    }
+
     #[test]
     fn pool_drops_previous_databases() {
         test_pool_drops_previous_databases(
@@ -489,4 +1343,33 @@ mod tests {
         let backend = create_backend(false);
         test_pool_drops_created_unrestricted_database(backend);
     }
+
+    #[test]
+    fn privileged_connections_report_application_name() {
+        dotenv().ok();
+
+        for (config, application_name) in [
+            (PrivilegedPostgresConfig::from_env().unwrap(), "db_pool"),
+            (
+                PrivilegedPostgresConfig::from_env()
+                    .unwrap()
+                    .application_name("db_pool test suite"),
+                "db_pool test suite",
+            ),
+        ] {
+            let backend =
+                DieselPostgresBackend::new(config, Pool::builder, Pool::builder, |_| {}).unwrap();
+            let conn = &mut backend.privileged_pool().get().unwrap();
+
+            // privileged connection must report application name
+            assert_eq!(
+                select(sql::<Text>(
+                    "(SELECT application_name FROM pg_catalog.pg_stat_activity WHERE pid = pg_backend_pid())"
+                ))
+                .get_result::<String>(conn)
+                .unwrap(),
+                application_name
+            );
+        }
+    }
 }
@@ -1,14 +1,24 @@
-use std::{borrow::Cow, collections::HashMap, ops::Deref};
+use std::{borrow::Cow, collections::HashMap, ops::Deref, sync::Arc, time::Duration};
 
 use parking_lot::Mutex;
 use r2d2::{Builder, Pool, PooledConnection};
 use r2d2_postgres::{
-    postgres::{Client, Config, Error, NoTls},
+    postgres::{config::Host, error::SqlState, Client, Config, Error, NoTls},
     PostgresConnectionManager,
 };
 use uuid::Uuid;
 
-use crate::{common::statement::postgres, util::get_db_name};
+use crate::{
+    common::{
+        config::{PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricsCallback},
+        statement::postgres,
+    },
+    util::{
+        format_pg_options, get_credentials, get_db_name, get_pg_application_name, url_host,
+        url_userinfo, PreviousDatabaseQuery, ReusedLogin, TableNamesCache,
+    },
+};
 
 use super::{
     super::{error::Error as BackendError, r#trait::Backend},
@@ -16,15 +26,51 @@ use super::{
 };
 
 type Manager = PostgresConnectionManager<NoTls>;
+type CreateEntities = dyn Fn(&mut Client, &str) + Send + Sync + 'static;
 
 /// Postgres backend
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct PostgresBackend {
     config: Config,
     default_pool: Pool<Manager>,
-    db_conns: Mutex<HashMap<Uuid, Client>>,
-    create_restricted_pool: Box<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
-    create_entities: Box<dyn Fn(&mut Client) + Send + Sync + 'static>,
+    table_names_cache: Arc<TableNamesCache>,
+    db_conns: Arc<Mutex<HashMap<Uuid, Client>>>,
+    create_restricted_pool: Arc<dyn Fn() -> Builder<Manager> + Send + Sync + 'static>,
+    create_entities: Arc<CreateEntities>,
     drop_previous_databases_flag: bool,
+    previous_database_query: Option<Arc<PreviousDatabaseQuery>>,
+    #[cfg(feature = "logging")]
+    log_statements_flag: bool,
+    #[cfg(feature = "logging")]
+    dry_run_flag: bool,
+    force_drop_flag: bool,
+    reused_role: Option<ReusedLogin>,
+    dialect: PgDialect,
+    create_entities_in_transaction_flag: bool,
+    clean_in_transaction_flag: bool,
+    verify_clean_flag: bool,
+    clean_retries: usize,
+    restricted_connection_params: HashMap<String, String>,
+    only_drop_owned_flag: bool,
+    schema_version: Option<String>,
+    encoding: Option<String>,
+    lc_collate: Option<String>,
+    lc_ctype: Option<String>,
+    template: Option<String>,
+    extensions: Vec<String>,
+    group_role_flag: bool,
+    search_path: Vec<String>,
+    role_attributes: Vec<String>,
+    schemas: Vec<String>,
+    recreate_on_conflict_flag: bool,
+    owner_at_creation_flag: bool,
+    create_entities_as: Role,
+    privilege_profile: PrivilegeProfile,
+    read_only_flag: bool,
+    name_generator: Arc<dyn Fn(Uuid) -> String + Send + Sync + 'static>,
+    metrics_callback: Option<Arc<MetricsCallback>>,
+    connect_timeout: Option<Duration>,
 }
 
 impl PostgresBackend {
@@ -58,18 +104,153 @@ impl PostgresBackend {
         create_privileged_pool: impl Fn() -> Builder<Manager>,
         create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
         create_entities: impl Fn(&mut Client) + Send + Sync + 'static,
+    ) -> Result<Self, r2d2::Error> {
+        Self::new_with_context(
+            config,
+            create_privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    /// Creates a new Postgres backend whose entity creation also receives the name of the database
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::PostgresBackend, PrivilegedPostgresConfig};
+    /// use r2d2::Pool;
+    /// use dotenvy::dotenv;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = PostgresBackend::new_with_context(
+    ///     config.into(),
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn, db_name| {
+    ///         let query = format!("CREATE TABLE tenant AS SELECT '{db_name}' AS name");
+    ///         conn.query(
+    ///             query.as_str(),
+    ///             &[],
+    ///         )
+    ///         .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new_with_context(
+        config: Config,
+        create_privileged_pool: impl Fn() -> Builder<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Client, &str) + Send + Sync + 'static,
     ) -> Result<Self, r2d2::Error> {
         let manager = Manager::new(config.clone(), NoTls);
         let default_pool = (create_privileged_pool()).build(manager)?;
 
-        Ok(Self {
+        Ok(Self::from_privileged_pool(
+            config,
+            default_pool,
+            create_restricted_pool,
+            create_entities,
+        ))
+    }
+
+    /// Creates a new Postgres backend from an already built privileged pool
+    ///
+    /// The privileged pool can be shared across backends or preconfigured with custom TLS or proxy settings.
+    /// Restricted pools are still built by the backend.
+    /// # Example
+    /// ```
+    /// use db_pool::{sync::PostgresBackend, PrivilegedPostgresConfig};
+    /// use r2d2::Pool;
+    /// use r2d2_postgres::{
+    ///     postgres::{Config, NoTls},
+    ///     PostgresConnectionManager,
+    /// };
+    /// use dotenvy::dotenv;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config: Config = PrivilegedPostgresConfig::from_env().unwrap().into();
+    /// let manager = PostgresConnectionManager::new(config.clone(), NoTls);
+    /// let privileged_pool = Pool::builder().max_size(10).build(manager).unwrap();
+    ///
+    /// let backend = PostgresBackend::new_with_privileged_pool(
+    ///     config,
+    ///     privileged_pool,
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         conn.query(
+    ///             "CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)",
+    ///             &[],
+    ///         )
+    ///         .unwrap();
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new_with_privileged_pool(
+        config: Config,
+        privileged_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Client) + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_privileged_pool(
+            config,
+            privileged_pool,
+            create_restricted_pool,
+            move |conn, _| create_entities(conn),
+        )
+    }
+
+    fn from_privileged_pool(
+        config: Config,
+        default_pool: Pool<Manager>,
+        create_restricted_pool: impl Fn() -> Builder<Manager> + Send + Sync + 'static,
+        create_entities: impl Fn(&mut Client, &str) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
             config,
             default_pool,
-            db_conns: Mutex::new(HashMap::new()),
-            create_restricted_pool: Box::new(create_restricted_pool),
-            create_entities: Box::new(create_entities),
+            table_names_cache: Arc::default(),
+            db_conns: Arc::new(Mutex::new(HashMap::new())),
+            create_restricted_pool: Arc::new(create_restricted_pool),
+            create_entities: Arc::new(create_entities),
             drop_previous_databases_flag: true,
-        })
+            previous_database_query: None,
+            #[cfg(feature = "logging")]
+            log_statements_flag: false,
+            #[cfg(feature = "logging")]
+            dry_run_flag: false,
+            force_drop_flag: false,
+            reused_role: None,
+            dialect: PgDialect::default(),
+            create_entities_in_transaction_flag: false,
+            clean_in_transaction_flag: false,
+            verify_clean_flag: false,
+            clean_retries: 0,
+            restricted_connection_params: HashMap::new(),
+            only_drop_owned_flag: false,
+            schema_version: None,
+            encoding: None,
+            lc_collate: None,
+            lc_ctype: None,
+            template: None,
+            extensions: Vec::new(),
+            group_role_flag: false,
+            search_path: Vec::new(),
+            role_attributes: Vec::new(),
+            schemas: vec![postgres::PUBLIC_SCHEMA_NAME.to_owned()],
+            recreate_on_conflict_flag: false,
+            owner_at_creation_flag: false,
+            create_entities_as: Role::default(),
+            privilege_profile: PrivilegeProfile::default(),
+            read_only_flag: false,
+            name_generator: Arc::new(get_db_name),
+            metrics_callback: None,
+            connect_timeout: None,
+        }
     }
 
     /// Drop databases created in previous runs upon initialization
@@ -80,421 +261,2572 @@ impl PostgresBackend {
             ..self
         }
     }
-}
 
-impl PostgresBackendTrait for PostgresBackend {
-    type ConnectionManager = Manager;
-    type ConnectionError = ConnectionError;
-    type QueryError = QueryError;
+    /// Query selecting the names of databases created in previous runs, given the database name prefix,
+    /// replacing the built-in catalog query when dropping them upon initialization
+    ///
+    /// Intended as an escape hatch for servers restricting catalog visibility or for custom naming.
+    /// Database names must be selected as the only column, named ``datname``.
+    /// Takes precedence over [`only_drop_owned`](Self::only_drop_owned).
+    #[must_use]
+    pub fn previous_database_query(
+        self,
+        value: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            previous_database_query: Some(Arc::new(value)),
+            ..self
+        }
+    }
 
-    fn execute_query(&self, query: &str, conn: &mut Client) -> Result<(), QueryError> {
-        conn.execute(query, &[])?;
-        Ok(())
+    /// Drop only databases tagged as created by a backend, along with their roles,
+    /// when dropping databases created in previous runs upon initialization
+    #[must_use]
+    pub fn only_drop_owned(self, value: bool) -> Self {
+        Self {
+            only_drop_owned_flag: value,
+            ..self
+        }
     }
 
-    fn batch_execute_query<'a>(
-        &self,
-        query: impl IntoIterator<Item = Cow<'a, str>>,
-        conn: &mut Client,
-    ) -> Result<(), QueryError> {
-        let query = query.into_iter().collect::<Vec<_>>().join(";");
-        conn.batch_execute(query.as_str())?;
-        Ok(())
+    /// Version of the schema created by entity creation, kept as a comment on created databases
+    ///
+    /// Databases from previous runs marked with the same version are kept instead of being dropped upon initialization,
+    /// while databases marked with other versions are dropped.
+    /// The version should change whenever entity creation does, such as by deriving it from migration names.
+    #[must_use]
+    pub fn schema_version(self, value: impl Into<String>) -> Self {
+        Self {
+            schema_version: Some(value.into()),
+            ..self
+        }
     }
 
-    fn get_default_connection(&self) -> Result<PooledConnection<Manager>, r2d2::Error> {
-        self.default_pool.get()
+    /// Log statements executed against the database server
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn log_statements(self, value: bool) -> Self {
+        Self {
+            log_statements_flag: value,
+            ..self
+        }
     }
 
-    fn establish_privileged_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<Client, ConnectionError> {
-        let mut config = self.config.clone();
-        let db_name = get_db_name(db_id);
-        config.dbname(db_name.as_str());
-        config.connect(NoTls).map_err(Into::into)
+    /// Log lifecycle statements creating, cleaning, and dropping databases and roles instead of executing them
+    ///
+    /// Since databases are never created, statements run on them, including entity creation, are skipped,
+    /// and their connection pools are built without connecting, so that connecting through them fails.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn dry_run(self, value: bool) -> Self {
+        Self {
+            dry_run_flag: value,
+            ..self
+        }
     }
 
-    fn establish_restricted_database_connection(
-        &self,
-        db_id: Uuid,
-    ) -> Result<Client, ConnectionError> {
-        let mut config = self.config.clone();
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        config.user(db_name).password(db_name).dbname(db_name);
-        config.connect(NoTls).map_err(Into::into)
+    /// Terminate lingering connections to databases before dropping them
+    #[must_use]
+    pub fn force_drop(self, value: bool) -> Self {
+        Self {
+            force_drop_flag: value,
+            ..self
+        }
     }
 
-    fn put_database_connection(&self, db_id: Uuid, conn: Client) {
-        self.db_conns.lock().insert(db_id, conn);
+    /// Reuse an existing role for created databases instead of creating a role per database
+    #[must_use]
+    pub fn reuse_role(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            reused_role: Some(ReusedLogin {
+                name: name.into(),
+                password: password.into(),
+            }),
+            ..self
+        }
     }
 
-    fn get_database_connection(&self, db_id: Uuid) -> Client {
-        self.db_conns
-            .lock()
-            .remove(&db_id)
-            .unwrap_or_else(|| panic!("connection map must have a connection for {db_id}"))
+    /// Postgres-compatible database targeted by the backend
+    #[must_use]
+    pub fn dialect(self, value: PgDialect) -> Self {
+        Self {
+            dialect: value,
+            ..self
+        }
     }
 
-    fn get_previous_database_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
-        conn.query(postgres::GET_DATABASE_NAMES, &[])
-            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
-            .map_err(Into::into)
+    /// Create entities within a transaction that is rolled back if entity creation panics or a statement fails,
+    /// in which case the partially created database is dropped and database creation fails
+    #[must_use]
+    pub fn create_entities_in_transaction(self, value: bool) -> Self {
+        Self {
+            create_entities_in_transaction_flag: value,
+            ..self
+        }
     }
 
-    fn create_entities(&self, conn: &mut Client) {
-        (self.create_entities)(conn);
+    /// Clean databases within a transaction, so that a database is either fully cleaned or left untouched
+    /// if cleaning fails midway
+    ///
+    /// Unlike Postgres, MySQL implicitly commits on ``TRUNCATE``, so MySQL backends offer no equivalent.
+    #[must_use]
+    pub fn clean_in_transaction(self, value: bool) -> Self {
+        Self {
+            clean_in_transaction_flag: value,
+            ..self
+        }
     }
 
-    fn create_connection_pool(&self, db_id: Uuid) -> Result<Pool<Manager>, r2d2::Error> {
-        let mut config = self.config.clone();
-        let db_name = get_db_name(db_id);
-        let db_name = db_name.as_str();
-        config.dbname(db_name);
-        config.user(db_name);
-        config.password(db_name);
-        let manager = PostgresConnectionManager::new(config, NoTls);
-        (self.create_restricted_pool)().build(manager)
+    /// Verify after cleaning that no table was left with rows, failing the cleaning otherwise
+    ///
+    /// This is a debugging aid for catching cleaning bugs and slows down cleaning.
+    #[must_use]
+    pub fn verify_clean(self, value: bool) -> Self {
+        Self {
+            verify_clean_flag: value,
+            ..self
+        }
     }
 
-    fn get_table_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
-        conn.query(postgres::GET_TABLE_NAMES, &[])
-            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
-            .map_err(Into::into)
+    /// Retry cleaning a database up to the given number of times if it fails on a deadlock
+    ///
+    /// Retries back off for longer after each failed attempt.
+    #[must_use]
+    pub fn clean_retries(self, value: usize) -> Self {
+        Self {
+            clean_retries: value,
+            ..self
+        }
     }
 
-    fn get_drop_previous_databases(&self) -> bool {
-        self.drop_previous_databases_flag
+    /// Connection parameters applied to connections made as the restricted role,
+    /// where ``application_name`` sets the application name and any other parameter
+    /// is set as a server runtime parameter such as ``timezone``
+    #[must_use]
+    pub fn restricted_connection_params(self, value: HashMap<String, String>) -> Self {
+        Self {
+            restricted_connection_params: value,
+            ..self
+        }
     }
-}
 
-#[derive(Debug)]
-pub struct ConnectionError(Error);
+    /// Encoding of created databases, overriding the encoding of the template database
+    #[must_use]
+    pub fn encoding(self, value: impl Into<String>) -> Self {
+        Self {
+            encoding: Some(value.into()),
+            ..self
+        }
+    }
 
-impl Deref for ConnectionError {
-    type Target = Error;
+    /// Collation order of created databases, overriding the collation order of the template database
+    #[must_use]
+    pub fn lc_collate(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_collate: Some(value.into()),
+            ..self
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// Character classification of created databases, overriding that of the template database
+    #[must_use]
+    pub fn lc_ctype(self, value: impl Into<String>) -> Self {
+        Self {
+            lc_ctype: Some(value.into()),
+            ..self
+        }
     }
-}
 
-impl From<Error> for ConnectionError {
-    fn from(value: Error) -> Self {
-        Self(value)
+    /// Template database to create databases from instead of `template1`
+    ///
+    /// `template0` is needed for an encoding or locale different from that of `template1`,
+    /// and avoids inheriting extensions and objects installed in `template1`
+    #[must_use]
+    pub fn template(self, value: impl Into<String>) -> Self {
+        Self {
+            template: Some(value.into()),
+            ..self
+        }
     }
-}
 
-#[derive(Debug)]
-pub struct QueryError(Error);
+    /// Install extensions in created databases as the privileged user before creating entities
+    #[must_use]
+    pub fn extensions(self, value: &[&str]) -> Self {
+        Self {
+            extensions: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
 
-impl Deref for QueryError {
-    type Target = Error;
+    /// Attach database roles to a shared ``NOLOGIN`` group role holding restricted privileges
+    ///
+    /// The group role is created upon initialization and each database role inherits from it,
+    /// so restricted privileges are granted to a single role instead of one role per database.
+    /// Connecting to each database is then restricted to its own role,
+    /// so database roles cannot use these privileges in other databases.
+    /// Ignored when reusing a role.
+    #[must_use]
+    pub fn group_role(self, value: bool) -> Self {
+        Self {
+            group_role_flag: value,
+            ..self
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// Schemas set as the search path of database roles in created databases
+    ///
+    /// The search path is stored as a role setting for the database, so unqualified names
+    /// resolve to these schemas on every restricted connection.
+    #[must_use]
+    pub fn search_path(self, value: &[&str]) -> Self {
+        Self {
+            search_path: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
     }
-}
 
-impl From<Error> for QueryError {
-    fn from(value: Error) -> Self {
-        Self(value)
+    /// Attributes of roles attached to created databases, such as `NOBYPASSRLS` or `CONNECTION LIMIT 10`
+    ///
+    /// Attributes are appended to role creation statements, which keeps restricted roles subject to row-level security policies.
+    /// Session settings read by policies, such as `app.current_user`, can be set using restricted connection parameters.
+    #[must_use]
+    pub fn role_attributes(self, value: &[&str]) -> Self {
+        Self {
+            role_attributes: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
     }
-}
 
-impl From<ConnectionError> for BackendError<ConnectionError, QueryError> {
-    fn from(value: ConnectionError) -> Self {
-        Self::Connection(value)
+    /// Schemas in which restricted roles are granted privileges on tables and sequences
+    ///
+    /// Defaults to the public schema only. Entities created in other schemas are only accessible
+    /// to restricted connections if their schemas are listed here.
+    #[must_use]
+    pub fn schemas(self, value: &[&str]) -> Self {
+        Self {
+            schemas: value.iter().map(ToString::to_string).collect(),
+            ..self
+        }
     }
-}
 
-impl From<QueryError> for BackendError<ConnectionError, QueryError> {
-    fn from(value: QueryError) -> Self {
-        Self::Query(value)
+    /// Drop and recreate a database or role left over by a previous run under the same name
+    ///
+    /// Without this, creating a database fails if one with the same name already exists,
+    /// and creating a role fails with `RoleExists`.
+    #[must_use]
+    pub fn recreate_on_conflict(self, value: bool) -> Self {
+        Self {
+            recreate_on_conflict_flag: value,
+            ..self
+        }
     }
-}
 
-impl Backend for PostgresBackend {
+    /// Create databases with unrestricted privileges owned by their role directly
+    ///
+    /// Creates the role before the database and passes it as the owner at creation time
+    /// instead of transferring ownership afterwards.
+    #[must_use]
+    pub fn owner_at_creation(self, value: bool) -> Self {
+        Self {
+            owner_at_creation_flag: value,
+            ..self
+        }
+    }
+
+    /// Role creating entities in databases with restricted privileges
+    ///
+    /// Defaults to the privileged role. Entities created by the restricted role are owned by it,
+    /// so that it can alter and drop them in tests.
+    #[must_use]
+    pub fn create_entities_as(self, value: Role) -> Self {
+        Self {
+            create_entities_as: value,
+            ..self
+        }
+    }
+
+    /// Set of statements used to set up databases and their roles
+    ///
+    /// Defaults to statements requiring a superuser. Use [`PrivilegeProfile::CreateDb`] when the privileged role
+    /// only has the ``CREATEDB`` and ``CREATEROLE`` attributes, such as on managed instances.
+    #[must_use]
+    pub fn privilege_profile(self, value: PrivilegeProfile) -> Self {
+        Self {
+            privilege_profile: value,
+            ..self
+        }
+    }
+
+    /// Grant restricted roles only ``SELECT`` on tables, along with usage of sequences,
+    /// for testing read paths against a locked-down role such as one on a read replica
+    #[must_use]
+    pub fn read_only(self, value: bool) -> Self {
+        Self {
+            read_only_flag: value,
+            ..self
+        }
+    }
+
+    /// Generate database names from database IDs using a custom function
+    ///
+    /// Generated names must be valid unquoted identifiers within the server's length limit,
+    /// and must start with `db_pool_` to be dropped as previous databases upon initialization.
+    /// A sample name is checked when the database pool is created.
+    #[must_use]
+    pub fn name_generator(self, value: impl Fn(Uuid) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            name_generator: Arc::new(value),
+            ..self
+        }
+    }
+
+    /// Report the time taken by every successful database creation, cleaning and drop to a callback
+    ///
+    /// Useful for shipping timings to a metrics system without enabling full logging.
+    #[must_use]
+    pub fn metrics_callback(self, value: impl Fn(MetricEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            metrics_callback: Some(Arc::new(value)),
+            ..self
+        }
+    }
+
+    /// Time after which establishing a direct connection to a created database is given up
+    ///
+    /// Applies to connections established to create entities and to clean databases when not storing connections.
+    /// Connections taken from pools time out according to their pool builders.
+    #[must_use]
+    pub fn connect_timeout(self, value: Duration) -> Self {
+        Self {
+            connect_timeout: Some(value),
+            ..self
+        }
+    }
+}
+
+impl PostgresBackendTrait for PostgresBackend {
     type ConnectionManager = Manager;
     type ConnectionError = ConnectionError;
     type QueryError = QueryError;
 
-    fn init(&self) -> Result<(), BackendError<ConnectionError, QueryError>> {
-        PostgresBackendWrapper::new(self).init()
+    fn execute_query(&self, query: &str, conn: &mut Client) -> Result<(), QueryError> {
+        conn.execute(query, &[])?;
+        Ok(())
     }
 
-    fn create(
+    fn batch_execute_query<'a>(
         &self,
-        db_id: Uuid,
-        restrict_privileges: bool,
-    ) -> Result<Pool<Manager>, BackendError<ConnectionError, QueryError>> {
-        PostgresBackendWrapper::new(self).create(db_id, restrict_privileges)
+        query: impl IntoIterator<Item = Cow<'a, str>>,
+        conn: &mut Client,
+    ) -> Result<(), QueryError> {
+        let query = query.into_iter().collect::<Vec<_>>().join(";");
+        conn.batch_execute(query.as_str())?;
+        Ok(())
     }
 
-    fn clean(&self, db_id: Uuid) -> Result<(), BackendError<ConnectionError, QueryError>> {
-        PostgresBackendWrapper::new(self).clean(db_id)
+    fn get_default_connection(&self) -> Result<PooledConnection<Manager>, r2d2::Error> {
+        self.default_pool.get()
     }
 
-    fn drop(
+    fn establish_privileged_database_connection(
         &self,
         db_id: Uuid,
-        is_restricted: bool,
-    ) -> Result<(), BackendError<ConnectionError, QueryError>> {
-        PostgresBackendWrapper::new(self).drop(db_id, is_restricted)
+    ) -> Result<Client, ConnectionError> {
+        let mut config = self.config.clone();
+        let db_name = self.get_db_name(db_id);
+        config.dbname(db_name.as_str());
+        if let Some(connect_timeout) = self.connect_timeout {
+            config.connect_timeout(connect_timeout);
+        }
+        config.connect(NoTls).map_err(Into::into)
+    }
+
+    fn establish_restricted_database_connection(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Client, ConnectionError> {
+        let mut config = self.config.clone();
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        config.user(username).password(password).dbname(db_name);
+        if let Some(application_name) = get_pg_application_name(&self.restricted_connection_params)
+        {
+            config.application_name(application_name);
+        }
+        if let Some(options) = format_pg_options(&self.restricted_connection_params) {
+            config.options(options.as_str());
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            config.connect_timeout(connect_timeout);
+        }
+        config.connect(NoTls).map_err(Into::into)
+    }
+
+    fn put_database_connection(&self, db_id: Uuid, conn: Client) {
+        self.db_conns.lock().insert(db_id, conn);
+    }
+
+    fn get_database_connection(&self, db_id: Uuid) -> Option<Client> {
+        self.db_conns.lock().remove(&db_id)
+    }
+
+    fn get_previous_database_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_DATABASE_NAMES, &[])
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(query, &[])
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    fn get_previous_owned_database_names(
+        &self,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_OWNED_DATABASE_NAMES, &[])
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(
+            postgres::get_database_names_with_schema_version(schema_version).as_str(),
+            &[],
+        )
+        .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+        .map_err(Into::into)
+    }
+
+    fn get_previous_role_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
+        conn.query(postgres::GET_ROLE_NAMES, &[])
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(Into::into)
+    }
+
+    fn create_entities(&self, conn: &mut Client, db_name: &str) {
+        (self.create_entities)(conn, db_name);
+    }
+
+    fn create_connection_pool(&self, db_id: Uuid) -> Result<Pool<Manager>, r2d2::Error> {
+        let mut config = self.config.clone();
+        let db_name = self.get_db_name(db_id);
+        let db_name = db_name.as_str();
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name);
+        config.dbname(db_name);
+        config.user(username);
+        config.password(password);
+        if let Some(application_name) = get_pg_application_name(&self.restricted_connection_params)
+        {
+            config.application_name(application_name);
+        }
+        if let Some(options) = format_pg_options(&self.restricted_connection_params) {
+            config.options(options.as_str());
+        }
+        let manager = PostgresConnectionManager::new(config, NoTls);
+        let builder = (self.create_restricted_pool)();
+
+        // Build pool without connecting in dry run, as the database was never created
+        #[cfg(feature = "logging")]
+        if self.dry_run_flag {
+            return Ok(builder.build_unchecked(manager));
+        }
+
+        builder.build(manager)
+    }
+
+    fn get_table_names(&self, conn: &mut Client) -> Result<Vec<String>, QueryError> {
+        conn.query(
+            postgres::get_table_names(self.dialect.system_schemas()).as_str(),
+            &[],
+        )
+        .map(|rows| {
+            rows.iter()
+                .map(|row| postgres::qualify_table_name(row.get(0), row.get(1)))
+                .collect()
+        })
+        .map_err(Into::into)
+    }
+
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        conn: &mut Client,
+    ) -> Result<Vec<String>, QueryError> {
+        conn.query(
+            postgres::get_nonempty_table_names(table_names).as_str(),
+            &[],
+        )
+        .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+        .map_err(Into::into)
+    }
+
+    fn is_duplicate_database_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::DUPLICATE_DATABASE)
+    }
+
+    fn is_duplicate_role_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::DUPLICATE_OBJECT)
+    }
+
+    fn is_transient_lock_error(&self, err: &QueryError) -> bool {
+        err.code() == Some(&SqlState::T_R_DEADLOCK_DETECTED)
+    }
+
+    fn get_drop_previous_databases(&self) -> bool {
+        self.drop_previous_databases_flag
+    }
+
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery> {
+        self.previous_database_query.as_deref()
+    }
+
+    fn get_only_drop_owned(&self) -> bool {
+        self.only_drop_owned_flag
+    }
+
+    fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool {
+        self.log_statements_flag
+    }
+
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool {
+        self.dry_run_flag
+    }
+
+    fn get_force_drop(&self) -> bool {
+        self.force_drop_flag
+    }
+
+    fn get_reused_role(&self) -> Option<&str> {
+        self.reused_role.as_ref().map(|role| role.name.as_str())
+    }
+
+    fn get_dialect(&self) -> PgDialect {
+        self.dialect
+    }
+
+    fn get_create_entities_in_transaction(&self) -> bool {
+        self.create_entities_in_transaction_flag
+    }
+
+    fn get_clean_in_transaction(&self) -> bool {
+        self.clean_in_transaction_flag
+    }
+
+    fn get_verify_clean(&self) -> bool {
+        self.verify_clean_flag
+    }
+
+    fn get_clean_retries(&self) -> usize {
+        self.clean_retries
+    }
+
+    fn get_table_names_cache(&self) -> &TableNamesCache {
+        &self.table_names_cache
+    }
+
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_deref()
+    }
+
+    fn get_db_name(&self, db_id: Uuid) -> String {
+        (self.name_generator)(db_id)
+    }
+
+    fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn get_lc_collate(&self) -> Option<&str> {
+        self.lc_collate.as_deref()
+    }
+
+    fn get_lc_ctype(&self) -> Option<&str> {
+        self.lc_ctype.as_deref()
+    }
+
+    fn get_template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    fn get_extensions(&self) -> &[String] {
+        self.extensions.as_slice()
+    }
+
+    fn get_group_role(&self) -> bool {
+        self.group_role_flag
+    }
+
+    fn get_search_path(&self) -> &[String] {
+        self.search_path.as_slice()
+    }
+
+    fn get_role_attributes(&self) -> &[String] {
+        self.role_attributes.as_slice()
+    }
+
+    fn get_schemas(&self) -> &[String] {
+        self.schemas.as_slice()
+    }
+
+    fn get_recreate_on_conflict(&self) -> bool {
+        self.recreate_on_conflict_flag
+    }
+
+    fn get_owner_at_creation(&self) -> bool {
+        self.owner_at_creation_flag
+    }
+
+    fn get_create_entities_as(&self) -> Role {
+        self.create_entities_as
+    }
+
+    fn get_privilege_profile(&self) -> PrivilegeProfile {
+        self.privilege_profile
+    }
+
+    fn get_read_only(&self) -> bool {
+        self.read_only_flag
     }
 }
 
-#[cfg(test)]
-mod tests {
-    #![allow(unused_variables, clippy::unwrap_used)]
+#[derive(Debug)]
+pub struct ConnectionError(Error);
 
-    use dotenvy::dotenv;
-    use r2d2::Pool;
+impl Deref for ConnectionError {
+    type Target = Error;
 
-    use crate::{
-        common::statement::postgres::tests::{
-            CREATE_ENTITIES_STATEMENTS, DDL_STATEMENTS, DML_STATEMENTS,
-        },
-        sync::{
-            backend::postgres::r#trait::tests::{
-                test_backend_creates_database_with_unrestricted_privileges,
-                test_pool_drops_created_unrestricted_database,
-            },
-            db_pool::DatabasePoolBuilder,
-        },
-        PrivilegedPostgresConfig,
-    };
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
-    use super::{
-        super::r#trait::tests::{
-            lock_read, test_backend_cleans_database_with_tables,
-            test_backend_cleans_database_without_tables,
-            test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
-            test_backend_drops_previous_databases, test_pool_drops_created_restricted_databases,
-            test_pool_drops_previous_databases,
-        },
-        PostgresBackend,
-    };
+impl From<Error> for ConnectionError {
+    fn from(value: Error) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct QueryError(Error);
+
+impl Deref for QueryError {
+    type Target = Error;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Error> for QueryError {
+    fn from(value: Error) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ConnectionError> for BackendError<ConnectionError, QueryError> {
+    fn from(value: ConnectionError) -> Self {
+        Self::Connection(value)
+    }
+}
+
+impl From<QueryError> for BackendError<ConnectionError, QueryError> {
+    fn from(value: QueryError) -> Self {
+        Self::Query(value)
+    }
+}
+
+impl Backend for PostgresBackend {
+    type ConnectionManager = Manager;
+    type ConnectionError = ConnectionError;
+    type QueryError = QueryError;
+
+    fn init(&self) -> Result<(), BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).init()
+    }
+
+    fn ping(&self) -> Result<(), BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).ping()
+    }
+
+    fn create(
+        &self,
+        db_id: Uuid,
+        restrict_privileges: bool,
+    ) -> Result<Pool<Manager>, BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).create(db_id, restrict_privileges)
+    }
+
+    fn create_reader_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Pool<Manager>, BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).create_reader_pool(db_id)
+    }
+
+    fn clean(&self, db_id: Uuid) -> Result<(), BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).clean(db_id)
+    }
+
+    fn drop(
+        &self,
+        db_id: Uuid,
+        is_restricted: bool,
+    ) -> Result<(), BackendError<ConnectionError, QueryError>> {
+        PostgresBackendWrapper::new(self).drop(db_id, is_restricted)
+    }
+
+    fn get_database_name(&self, db_id: Uuid) -> String {
+        self.get_db_name(db_id)
+    }
+
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let db_name = self.get_db_name(db_id);
+        let (username, password) = get_credentials(self.reused_role.as_ref(), db_name.as_str());
+        let userinfo = url_userinfo(username, Some(password));
+        let host = match self.config.get_hosts().first() {
+            Some(Host::Tcp(host)) => host.clone(),
+            Some(Host::Unix(path)) => path.to_string_lossy().into_owned(),
+            None => "localhost".to_owned(),
+        };
+        let host = url_host(host.as_str());
+        let port = self.config.get_ports().first().copied().unwrap_or(5432);
+        Some(format!("postgres://{userinfo}@{host}:{port}/{db_name}"))
+    }
+
+    fn privileged_pool(&self) -> &Pool<Manager> {
+        &self.default_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused_variables, clippy::unwrap_used)]
+
+    use std::{collections::HashMap, panic, sync::Arc, thread, time::Duration};
+
+    use dotenvy::dotenv;
+    use parking_lot::Mutex;
+    use r2d2::Pool;
+    use r2d2_postgres::postgres::{Client, Config, NoTls};
+    use uuid::Uuid;
+
+    use crate::{
+        common::statement::postgres::{
+            self,
+            tests::{
+                CREATE_ENTITIES_STATEMENTS, CREATE_ENTITIES_WITH_FOREIGN_KEYS_STATEMENTS,
+                DDL_STATEMENTS, DML_STATEMENTS,
+            },
+        },
+        sync::{
+            backend::postgres::r#trait::tests::{
+                test_backend_creates_database_owned_by_role,
+                test_backend_creates_database_with_unrestricted_privileges,
+                test_backend_creates_entities_as_restricted_role,
+                test_backend_recreates_database_on_conflict,
+                test_backend_recreates_role_on_conflict,
+                test_pool_drops_created_unrestricted_database,
+            },
+            backend::r#trait::Backend,
+            backend::Error as BackendError,
+            db_pool::DatabasePoolBuilder,
+        },
+        util::get_db_name,
+        MetricOperation, PgDialect, PrivilegeProfile, PrivilegedPostgresConfig, Role,
+    };
+
+    use super::{
+        super::r#trait::tests::{
+            lock_read, test_backend_cleans_database_with_tables,
+            test_backend_cleans_database_without_tables,
+            test_backend_creates_database_with_restricted_privileges, test_backend_drops_database,
+            test_backend_drops_previous_databases,
+            test_backend_drops_previous_databases_with_custom_query,
+            test_backend_fails_to_clean_unrestricted_database,
+            test_backend_force_drops_database_with_lingering_connection,
+            test_backend_keeps_group_role_of_kept_databases,
+            test_backend_keeps_previous_databases_with_schema_version,
+            test_backend_only_drops_owned_previous_databases,
+            test_backend_waits_for_initialization_lock,
+            test_pool_drops_created_restricted_databases, test_pool_drops_previous_databases,
+        },
+        Manager, PostgresBackend,
+    };
+
+    fn create_backend(with_table: bool) -> PostgresBackend {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                if with_table {
+                    conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                        .unwrap();
+                }
+            }
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn backend_drops_previous_databases() {
+        test_backend_drops_previous_databases(
+            create_backend(false),
+            create_backend(false).drop_previous_databases(true),
+            create_backend(false).drop_previous_databases(false),
+        );
+    }
+
+    #[test]
+    fn backend_waits_for_initialization_lock() {
+        test_backend_waits_for_initialization_lock(create_backend(false));
+    }
+
+    #[test]
+    fn backend_only_drops_owned_previous_databases() {
+        test_backend_only_drops_owned_previous_databases(
+            &create_backend(false),
+            &create_backend(false).only_drop_owned(true).force_drop(true),
+        );
+    }
+
+    #[test]
+    fn backend_drops_previous_databases_with_custom_query() {
+        test_backend_drops_previous_databases_with_custom_query(
+            &create_backend(false),
+            &create_backend(false).previous_database_query(|prefix| {
+                format!("SELECT datname FROM pg_catalog.pg_database WHERE datname LIKE '{prefix}%' AND pg_catalog.shobj_description(oid, 'pg_database') = 'db_pool:custom'")
+            }),
+        );
+    }
+
+    #[test]
+    fn backend_keeps_previous_databases_with_schema_version() {
+        test_backend_keeps_previous_databases_with_schema_version(
+            &create_backend(true).schema_version("1"),
+            &create_backend(true).schema_version("2").force_drop(true),
+        );
+    }
+
+    #[test]
+    fn backend_keeps_group_role_of_kept_databases() {
+        test_backend_keeps_group_role_of_kept_databases(
+            &create_backend(true)
+                .schema_version("group")
+                .group_role(true),
+        );
+    }
+
+    #[test]
+    fn backend_creates_database_with_restricted_privileges() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_creates_database_with_restricted_privileges(&backend);
+    }
+
+    #[test]
+    fn backend_creates_database_with_unrestricted_privileges() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_creates_database_with_unrestricted_privileges(&backend);
+    }
+
+    #[test]
+    fn backend_recreates_database_on_conflict() {
+        let default = create_backend(true).drop_previous_databases(false);
+        let enabled = create_backend(true)
+            .drop_previous_databases(false)
+            .recreate_on_conflict(true);
+        test_backend_recreates_database_on_conflict(&default, &enabled);
+    }
+
+    #[test]
+    fn backend_creates_entities_as_restricted_role() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .create_entities_as(Role::Restricted);
+        test_backend_creates_entities_as_restricted_role(&backend);
+    }
+
+    #[test]
+    fn backend_creates_database_owned_by_role() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .owner_at_creation(true)
+            .recreate_on_conflict(true);
+        test_backend_creates_database_owned_by_role(&backend);
+    }
+
+    #[test]
+    fn backend_recreates_role_on_conflict() {
+        let default = create_backend(true).drop_previous_databases(false);
+        let enabled = create_backend(true)
+            .drop_previous_databases(false)
+            .recreate_on_conflict(true);
+        test_backend_recreates_role_on_conflict(&default, &enabled);
+    }
+
+    #[test]
+    fn backend_cleans_database_with_tables() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
+    #[test]
+    fn backend_cleans_database_with_tables_in_transaction() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .clean_in_transaction(true);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
+    #[test]
+    fn backend_cleans_database_with_tables_in_cockroachdb_dialect() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .dialect(PgDialect::CockroachDB);
+        test_backend_cleans_database_with_tables(&backend);
+    }
+
+    #[test]
+    fn backend_cleans_database_with_tables_in_custom_schema() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(
+                    "CREATE SCHEMA custom; CREATE TABLE custom.book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false);
+
+        let db_id = Uuid::new_v4();
+
+        let guard = lock_read();
+
+        backend.init().unwrap();
+        backend.create(db_id, true).unwrap();
+
+        let mut config = Config::from(PrivilegedPostgresConfig::from_env().unwrap());
+        config.dbname(get_db_name(db_id).as_str());
+        let mut client = config.connect(NoTls).unwrap();
+        client
+            .execute("INSERT INTO custom.book (title) VALUES ($1)", &[&"Title"])
+            .unwrap();
+
+        backend.clean(db_id).unwrap();
+
+        // there must be no books in custom schema
+        assert_eq!(
+            client
+                .query_one("SELECT COUNT(*) FROM custom.book", &[])
+                .unwrap()
+                .get::<_, i64>(0),
+            0
+        );
+
+        drop(client);
+        backend.drop(db_id, true).unwrap();
+    }
+
+    #[test]
+    fn backend_cleans_database_with_foreign_keys_in_cockroachdb_dialect() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(&CREATE_ENTITIES_WITH_FOREIGN_KEYS_STATEMENTS.join(";"))
+                    .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .dialect(PgDialect::CockroachDB);
+
+        let db_id = Uuid::new_v4();
+
+        let guard = lock_read();
+
+        backend.init().unwrap();
+        backend.create(db_id, true).unwrap();
+
+        let mut config = Config::from(PrivilegedPostgresConfig::from_env().unwrap());
+        config.dbname(get_db_name(db_id).as_str());
+        let mut client = config.connect(NoTls).unwrap();
+        client
+            .batch_execute(
+                "INSERT INTO author DEFAULT VALUES; INSERT INTO book (author_id) VALUES (1)",
+            )
+            .unwrap();
+
+        // referenced tables must be cleaned along with referencing tables
+        backend.clean(db_id).unwrap();
+        for table_name in ["author", "book"] {
+            assert_eq!(
+                client
+                    .query_one(format!("SELECT COUNT(*) FROM {table_name}").as_str(), &[])
+                    .unwrap()
+                    .get::<_, i64>(0),
+                0
+            );
+        }
+
+        drop(client);
+        backend.drop(db_id, true).unwrap();
+    }
+
+    #[test]
+    fn backend_cleans_database_without_tables() {
+        let backend = create_backend(false).drop_previous_databases(false);
+        test_backend_cleans_database_without_tables(&backend);
+    }
+
+    #[test]
+    fn backend_provides_privileged_pool() {
+        let backend = create_backend(false);
+
+        // privileged pool must connect as privileged user
+        assert!(backend
+            .privileged_pool()
+            .get()
+            .unwrap()
+            .query_one(
+                "SELECT rolsuper FROM pg_catalog.pg_roles WHERE rolname = current_user",
+                &[],
+            )
+            .unwrap()
+            .get::<_, bool>(0));
+    }
+
+    #[test]
+    fn backend_drops_restricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_drops_database(&backend, true);
+    }
+
+    #[test]
+    fn backend_drops_unrestricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_drops_database(&backend, false);
+    }
+
+    #[test]
+    fn backend_fails_to_clean_unrestricted_database() {
+        let backend = create_backend(true).drop_previous_databases(false);
+        test_backend_fails_to_clean_unrestricted_database(&backend);
+    }
+
+    #[test]
+    fn backend_force_drops_database_with_lingering_connection() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .force_drop(true);
+        test_backend_force_drops_database_with_lingering_connection(&backend);
+    }
+
+    #[test]
+    fn pool_drops_previous_databases() {
+        test_pool_drops_previous_databases(
+            create_backend(false),
+            create_backend(false).drop_previous_databases(true),
+            create_backend(false).drop_previous_databases(false),
+        );
+    }
+
+    #[test]
+    fn pool_provides_isolated_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pools = (0..NUM_DBS)
+            .map(|_| db_pool.pull_immutable())
+            .collect::<Vec<_>>();
+
+        // insert single row into each database
+        conn_pools.iter().enumerate().for_each(|(i, conn_pool)| {
+            let conn = &mut conn_pool.get().unwrap();
+            conn.execute(
+                "INSERT INTO book (title) VALUES ($1)",
+                &[&format!("Title {i}").as_str()],
+            )
+            .unwrap();
+        });
+
+        // rows fetched must be as inserted
+        conn_pools.iter().enumerate().for_each(|(i, conn_pool)| {
+            let conn = &mut conn_pool.get().unwrap();
+            assert_eq!(
+                conn.query("SELECT title FROM book", &[])
+                    .unwrap()
+                    .iter()
+                    .map(|row| row.get::<_, String>(0))
+                    .collect::<Vec<_>>(),
+                vec![format!("Title {i}")]
+            );
+        });
+    }
+
+    #[test]
+    fn pool_provides_restricted_databases() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // DDL statements must fail
+        for stmt in DDL_STATEMENTS {
+            assert!(conn.execute(stmt, &[]).is_err());
+        }
+
+        // DML statements must succeed
+        for stmt in DML_STATEMENTS {
+            assert!(conn.execute(stmt, &[]).is_ok());
+        }
+    }
+
+    #[test]
+    fn pool_provides_unrestricted_databases() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        // DML statements must succeed
+        {
+            let conn_pool = db_pool.create_mutable().unwrap();
+            let conn = &mut conn_pool.get().unwrap();
+            for stmt in DML_STATEMENTS {
+                assert!(conn.execute(stmt, &[]).is_ok());
+            }
+        }
+
+        // DDL statements must succeed
+        for stmt in DDL_STATEMENTS {
+            let conn_pool = db_pool.create_mutable().unwrap();
+            let conn = &mut conn_pool.get().unwrap();
+            assert!(conn.execute(stmt, &[]).is_ok());
+        }
+    }
+
+    #[test]
+    fn pool_provides_clean_databases() {
+        const NUM_DBS: i64 = 3;
+
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        // fetch connection pools the first time
+        {
+            let conn_pools = (0..NUM_DBS)
+                .map(|_| db_pool.pull_immutable())
+                .collect::<Vec<_>>();
+
+            // databases must be empty
+            for conn_pool in &conn_pools {
+                let conn = &mut conn_pool.get().unwrap();
+                assert_eq!(
+                    conn.query_one("SELECT COUNT(*) FROM book", &[])
+                        .unwrap()
+                        .get::<_, i64>(0),
+                    0
+                );
+            }
+
+            // insert data into each database
+            for conn_pool in &conn_pools {
+                let conn = &mut conn_pool.get().unwrap();
+                conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                    .unwrap();
+            }
+        }
+
+        // fetch same connection pools a second time
+        {
+            let conn_pools = (0..NUM_DBS)
+                .map(|_| db_pool.pull_immutable())
+                .collect::<Vec<_>>();
+
+            // databases must be empty
+            for conn_pool in &conn_pools {
+                let conn = &mut conn_pool.get().unwrap();
+                assert_eq!(
+                    conn.query_one("SELECT COUNT(*) FROM book", &[])
+                        .unwrap()
+                        .get::<_, i64>(0),
+                    0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pool_provides_database_url() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+
+        // URL must connect to the pulled database as its attached role
+        let mut client =
+            Client::connect(conn_pool.database_url().unwrap().as_str(), NoTls).unwrap();
+        assert_eq!(
+            client
+                .query_one("SELECT current_database()", &[])
+                .unwrap()
+                .get::<_, String>(0),
+            conn_pool.database_name()
+        );
+        assert!(client
+            .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+            .is_ok());
+    }
+
+    #[test]
+    fn pool_grants_privileges_on_tables_created_later() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+
+        // Create table as privileged user after privileges are granted
+        {
+            dotenv().ok();
+
+            let mut config = Config::from(PrivilegedPostgresConfig::from_env().unwrap());
+            config.dbname(conn_pool.database_name());
+            let mut client = config.connect(NoTls).unwrap();
+            client
+                .batch_execute("CREATE TABLE author(id SERIAL PRIMARY KEY, name TEXT NOT NULL)")
+                .unwrap();
+        }
+
+        // restricted role must be able to access table
+        let conn = &mut conn_pool.get().unwrap();
+        assert!(conn
+            .execute("INSERT INTO author (name) VALUES ($1)", &[&"Name"])
+            .is_ok());
+        assert!(conn.query("SELECT * FROM author", &[]).is_ok());
+    }
+
+    #[test]
+    fn pool_provides_database_id() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let immutable_conn_pool = db_pool.pull_immutable();
+        let mutable_conn_pool = db_pool.create_mutable().unwrap();
+
+        // database name must be derived from database ID
+        assert_eq!(
+            get_db_name(immutable_conn_pool.database_id()),
+            immutable_conn_pool.database_name()
+        );
+        assert_eq!(
+            get_db_name(mutable_conn_pool.database_id()),
+            mutable_conn_pool.database_name()
+        );
+
+        // databases must have distinct IDs
+        assert_ne!(
+            immutable_conn_pool.database_id(),
+            mutable_conn_pool.database_id()
+        );
+    }
+
+    #[test]
+    fn pool_keeps_database_on_panic() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap().keep_on_panic(true);
+
+        let mut db_name = None;
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let conn_pool = db_pool.pull_immutable();
+            db_name = Some(conn_pool.database_name().to_owned());
+            panic!("test must fail");
+        }));
+        assert!(result.is_err());
+        let db_name = db_name.unwrap();
+
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+        let mut client = Config::from(config).connect(NoTls).unwrap();
+
+        // database must survive panic
+        assert!(client
+            .query_opt(
+                "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                &[&db_name],
+            )
+            .unwrap()
+            .is_some());
+
+        client
+            .execute(
+                format!("DROP DATABASE {db_name} WITH (FORCE)").as_str(),
+                &[],
+            )
+            .unwrap();
+        client
+            .execute(format!("DROP ROLE {db_name}").as_str(), &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn pool_reuses_existing_role() {
+        const ROLE: &str = "reused";
+
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        // Create role if needed
+        let mut client = Config::from(config).connect(NoTls).unwrap();
+        let role_exists = |client: &mut Client| {
+            client
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_roles WHERE rolname = $1",
+                    &[&ROLE],
+                )
+                .unwrap()
+                .is_some()
+        };
+        if !role_exists(&mut client) {
+            client
+                .execute(
+                    format!("CREATE ROLE {ROLE} LOGIN PASSWORD '{ROLE}'").as_str(),
+                    &[],
+                )
+                .unwrap();
+        }
+
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .reuse_role(ROLE, ROLE);
+
+        let guard = lock_read();
+
+        {
+            let db_pool = backend.create_database_pool().unwrap();
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+
+            // database must be accessed through reused role
+            assert_eq!(
+                conn.query_one("SELECT current_user", &[])
+                    .unwrap()
+                    .get::<_, String>(0),
+                ROLE
+            );
+            assert!(conn
+                .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .is_ok());
+        }
+
+        // reused role must outlive dropped databases
+        assert!(role_exists(&mut client));
+    }
+
+    #[test]
+    fn pool_provides_database_name_to_entity_creation() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend =
+            PostgresBackend::new_with_context(config.into(), Pool::builder, Pool::builder, {
+                move |conn, db_name| {
+                    conn.batch_execute(&format!(
+                        "CREATE TABLE tenant AS SELECT '{db_name}'::TEXT AS name"
+                    ))
+                    .unwrap();
+                }
+            })
+            .unwrap()
+            .drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // entities must be created with name of database
+        assert_eq!(
+            conn.query_one("SELECT name FROM tenant", &[])
+                .unwrap()
+                .get::<_, String>(0),
+            conn_pool.database_name()
+        );
+    }
+
+    #[test]
+    fn pool_uses_generated_database_names() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .name_generator(|db_id| format!("db_pool_test_{}", &db_id.simple().to_string()[..8]));
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // database must be created with generated name
+        assert!(conn_pool.database_name().starts_with("db_pool_test_"));
+        assert_eq!(
+            conn.query_one("SELECT current_database()", &[])
+                .unwrap()
+                .get::<_, String>(0),
+            conn_pool.database_name()
+        );
+    }
+
+    #[test]
+    fn backend_rejects_invalid_generated_database_names() {
+        let guard = lock_read();
+
+        // names must be unquoted identifiers
+        let backend = create_backend(false)
+            .drop_previous_databases(false)
+            .name_generator(|db_id| format!("db-pool-{db_id}"));
+        assert!(matches!(
+            backend.create(Uuid::new_v4(), true),
+            Err(BackendError::InvalidDatabaseName(_))
+        ));
+
+        // names must fit within identifier length limit
+        let backend = create_backend(false)
+            .drop_previous_databases(false)
+            .name_generator(|db_id| format!("db_pool_{}", db_id.simple().to_string().repeat(2)));
+        assert!(matches!(
+            backend.create(Uuid::new_v4(), true),
+            Err(BackendError::InvalidDatabaseName(_))
+        ));
+
+        // names must be checked when creating the pool
+        assert!(matches!(
+            backend.create_database_pool(),
+            Err(BackendError::InvalidDatabaseName(_))
+        ));
+    }
+
+    #[test]
+    fn pool_cleans_unlogged_tables() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                    .unwrap();
+                conn.batch_execute(&postgres::convert_tables_unlogged(["book", "dummy"]).join(";"))
+                    .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        {
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+
+            // tables must be unlogged
+            assert_eq!(
+                conn.query_one(
+                    "SELECT COUNT(*) FROM pg_catalog.pg_class WHERE relname IN ('book', 'dummy') AND relpersistence = 'u'",
+                    &[],
+                )
+                .unwrap()
+                .get::<_, i64>(0),
+                2
+            );
+
+            conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .unwrap();
+        }
+
+        // unlogged tables must be cleaned when database is reused
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+        assert_eq!(
+            conn.query_one("SELECT COUNT(*) FROM book", &[])
+                .unwrap()
+                .get::<_, i64>(0),
+            0
+        );
+    }
+
+    #[test]
+    fn pool_lists_leased_databases() {
+        let backend = create_backend(true).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        {
+            let immutable_conn_pool = db_pool.pull_immutable();
+            let mutable_conn_pool = db_pool.create_mutable().unwrap();
+
+            // databases held through pulled and created connection pools must be listed
+            let mut db_ids = db_pool.leased_databases();
+            db_ids.sort();
+            let mut expected_db_ids = vec![
+                immutable_conn_pool.database_id(),
+                mutable_conn_pool.database_id(),
+            ];
+            expected_db_ids.sort();
+            assert_eq!(db_ids, expected_db_ids);
+        }
+
+        // returned databases must no longer be listed
+        assert!(db_pool.leased_databases().is_empty());
+    }
+
+    #[test]
+    fn backend_creates_database_with_encoding_and_locale() {
+        let backend = create_backend(false)
+            .drop_previous_databases(false)
+            .encoding("UTF8")
+            .lc_collate("C")
+            .lc_ctype("C")
+            .template("template0");
+
+        let guard = lock_read();
+
+        let db_id = Uuid::new_v4();
+        backend.create(db_id, true).unwrap();
+
+        // database must have encoding and locale
+        let row = backend
+            .default_pool
+            .get()
+            .unwrap()
+            .query_one(
+                "SELECT pg_encoding_to_char(encoding), datcollate, datctype FROM pg_catalog.pg_database WHERE datname = $1",
+                &[&get_db_name(db_id)],
+            )
+            .unwrap();
+        assert_eq!(row.get::<_, String>(0), "UTF8");
+        assert_eq!(row.get::<_, String>(1), "C");
+        assert_eq!(row.get::<_, String>(2), "C");
+
+        backend.drop(db_id, true).unwrap();
+    }
+
+    #[test]
+    fn pool_provides_databases_with_extensions() {
+        let backend = create_backend(false)
+            .drop_previous_databases(false)
+            .extensions(&["pgcrypto"]);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // extension must be installed
+        assert!(conn
+            .query_one(
+                "SELECT 1 FROM pg_catalog.pg_extension WHERE extname = 'pgcrypto'",
+                &[]
+            )
+            .is_ok());
+
+        // extension functions must be usable by restricted user
+        assert!(conn.query_one("SELECT gen_random_uuid()", &[]).is_ok());
+        assert!(conn.query_one("SELECT gen_salt('bf')", &[]).is_ok());
+    }
+
+    #[test]
+    fn pool_provides_databases_with_group_role() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .group_role(true);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // database role must be a member of group role
+        assert!(conn
+            .query_one(
+                "SELECT pg_has_role($1, 'MEMBER')",
+                &[&postgres::GROUP_ROLE_NAME]
+            )
+            .unwrap()
+            .get::<_, bool>(0));
+
+        // restricted privileges must be granted to group role only
+        let count_grants = |conn: &mut Client, grantee: &str| {
+            conn.query_one(
+                "SELECT COUNT(*) FROM information_schema.table_privileges WHERE grantee = $1",
+                &[&grantee],
+            )
+            .unwrap()
+            .get::<_, i64>(0)
+        };
+        assert_eq!(count_grants(conn, conn_pool.database_name()), 0);
+        assert!(count_grants(conn, postgres::GROUP_ROLE_NAME) > 0);
+
+        // inherited privileges must be usable by restricted user
+        assert!(conn
+            .execute("INSERT INTO book (title) VALUES ('Title')", &[])
+            .is_ok());
+
+        // database role must not be able to connect to other database
+        let other_conn_pool = db_pool.pull_immutable();
+        let mut config: Config = PrivilegedPostgresConfig::from_env().unwrap().into();
+        config
+            .user(other_conn_pool.database_name())
+            .password(other_conn_pool.database_name())
+            .dbname(conn_pool.database_name());
+        assert!(config.connect(NoTls).is_err());
+    }
+
+    #[test]
+    fn pool_resolves_unqualified_names_with_search_path() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(
+                    "CREATE SCHEMA app; \
+                     CREATE TABLE app.book(id SERIAL PRIMARY KEY, title TEXT NOT NULL); \
+                     GRANT USAGE ON SCHEMA app TO PUBLIC; \
+                     GRANT SELECT, INSERT ON ALL TABLES IN SCHEMA app TO PUBLIC; \
+                     GRANT USAGE ON ALL SEQUENCES IN SCHEMA app TO PUBLIC",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .search_path(&["app", "public"]);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // search path must be set for restricted user
+        assert_eq!(
+            conn.query_one("SHOW search_path", &[])
+                .unwrap()
+                .get::<_, String>(0),
+            "app, public"
+        );
+
+        // unqualified table names must resolve to custom schema
+        conn.execute("INSERT INTO book (title) VALUES ('Title')", &[])
+            .unwrap();
+        assert_eq!(
+            conn.query_one("SELECT COUNT(*) FROM app.book", &[])
+                .unwrap()
+                .get::<_, i64>(0),
+            1
+        );
+    }
+
+    #[test]
+    fn pool_grants_privileges_in_given_schemas() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(
+                    "CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL); \
+                     CREATE SCHEMA billing; \
+                     CREATE TABLE billing.invoice(id SERIAL PRIMARY KEY, amount INT NOT NULL); \
+                     CREATE SCHEMA audit; \
+                     CREATE TABLE audit.entry(id SERIAL PRIMARY KEY, message TEXT NOT NULL)",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .schemas(&["public", "billing"]);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        {
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+
+            // restricted user must have privileges on tables in given schemas
+            conn.execute("INSERT INTO book (title) VALUES ('Title')", &[])
+                .unwrap();
+            conn.execute("INSERT INTO billing.invoice (amount) VALUES (1)", &[])
+                .unwrap();
+
+            // restricted user must not have privileges on tables in other schemas
+            assert!(conn
+                .execute("INSERT INTO audit.entry (message) VALUES ('Message')", &[])
+                .is_err());
+        }
+
+        // tables in all given schemas must be cleaned
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+        assert_eq!(
+            conn.query_one("SELECT COUNT(*) FROM billing.invoice", &[])
+                .unwrap()
+                .get::<_, i64>(0),
+            0
+        );
+    }
+
+    #[test]
+    fn pool_subjects_restricted_roles_to_row_level_security() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(
+                    "CREATE TABLE document(id SERIAL PRIMARY KEY, owner TEXT NOT NULL); \
+                     INSERT INTO document (owner) VALUES ('alice'), ('bob'); \
+                     ALTER TABLE document ENABLE ROW LEVEL SECURITY; \
+                     ALTER TABLE document FORCE ROW LEVEL SECURITY; \
+                     CREATE POLICY document_owner ON document \
+                     USING (owner = current_setting('app.current_user'))",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .role_attributes(&["NOBYPASSRLS", "NOCREATEDB", "CONNECTION LIMIT 10"])
+        .restricted_connection_params(HashMap::from([(
+            "app.current_user".to_owned(),
+            "alice".to_owned(),
+        )]));
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // restricted role must carry attributes
+        let row = conn
+            .query_one(
+                "SELECT rolbypassrls, rolcreatedb, rolconnlimit FROM pg_catalog.pg_roles WHERE rolname = current_user",
+                &[],
+            )
+            .unwrap();
+        assert!(!row.get::<_, bool>(0));
+        assert!(!row.get::<_, bool>(1));
+        assert_eq!(row.get::<_, i32>(2), 10);
+
+        // rows must be filtered by policy
+        assert_eq!(
+            conn.query("SELECT owner FROM document", &[])
+                .unwrap()
+                .iter()
+                .map(|row| row.get::<_, String>(0))
+                .collect::<Vec<_>>(),
+            vec!["alice".to_owned()]
+        );
+    }
+
+    #[test]
+    fn pool_cleans_tables_with_quoted_names() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(
+                    "CREATE TABLE \"User\"(id SERIAL PRIMARY KEY, name TEXT NOT NULL); \
+                     CREATE TABLE \"order\"(id SERIAL PRIMARY KEY)",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+
+        {
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+            conn.batch_execute(
+                "INSERT INTO \"User\" (name) VALUES ('Name'); \
+                 INSERT INTO \"order\" DEFAULT VALUES",
+            )
+            .unwrap();
+        }
+
+        // tables with mixed-case and reserved names must be cleaned
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+        assert_eq!(
+            conn.query_one(
+                "SELECT (SELECT COUNT(*) FROM \"User\") + (SELECT COUNT(*) FROM \"order\")",
+                &[]
+            )
+            .unwrap()
+            .get::<_, i64>(0),
+            0
+        );
+    }
+
+    #[test]
+    fn backend_reports_failed_statement() {
+        let backend = create_backend(false).drop_previous_databases(false);
+
+        let guard = lock_read();
+
+        let db_id = Uuid::new_v4();
+        backend.create(db_id, true).unwrap();
+
+        // failed statement must be reported along with its error
+        let stmt =
+            postgres::create_database(get_db_name(db_id).as_str(), None, None, None, None, None);
+        assert!(matches!(
+            backend.create(db_id, true),
+            Err(BackendError::Statement(failed_stmt, _)) if failed_stmt == stmt
+        ));
+
+        backend.drop(db_id, true).unwrap();
+    }
+
+    #[test]
+    fn backend_reports_operation_timings() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .metrics_callback({
+                let events = events.clone();
+                move |event| events.lock().push(event)
+            });
+
+        let guard = lock_read();
+
+        let db_id = Uuid::new_v4();
+        backend.create(db_id, true).unwrap();
+        backend.clean(db_id).unwrap();
+        backend.drop(db_id, true).unwrap();
+
+        // every operation must be reported for its database, along with every table cleaned
+        let events = events.lock();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.operation, event.db_id, event.cleaned_tables))
+                .collect::<Vec<_>>(),
+            vec![
+                (MetricOperation::Create, db_id, None),
+                (
+                    MetricOperation::Clean,
+                    db_id,
+                    Some(CREATE_ENTITIES_STATEMENTS.len())
+                ),
+                (MetricOperation::Drop, db_id, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn pool_applies_restricted_connection_params() {
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .restricted_connection_params(HashMap::from([
+                ("application_name".to_owned(), "db_pool_test".to_owned()),
+                ("search_path".to_owned(), "public, pg_catalog".to_owned()),
+            ]));
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let conn = &mut conn_pool.get().unwrap();
+
+        // restricted connections must carry connection parameters
+        let row = conn
+            .query_one(
+                "SELECT current_setting('application_name'), current_setting('search_path')",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(row.get::<_, &str>(0), "db_pool_test");
+        assert_eq!(row.get::<_, &str>(1), "public, pg_catalog");
+    }
+
+    #[test]
+    fn backend_reports_tables_left_unclean() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                    .unwrap();
+                conn.batch_execute(
+                    "CREATE FUNCTION refill_book() RETURNS trigger AS $$ BEGIN INSERT INTO book (title) VALUES ('Title'); RETURN NULL; END $$ LANGUAGE plpgsql; \
+                    CREATE TRIGGER refill_book AFTER TRUNCATE ON book EXECUTE FUNCTION refill_book()",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .verify_clean(true);
+
+        let guard = lock_read();
+
+        let db_id = Uuid::new_v4();
+        backend.create(db_id, true).unwrap();
+
+        // tables left with rows after cleaning must be reported
+        assert!(matches!(
+            backend.clean(db_id),
+            Err(BackendError::UncleanDatabase(id, table_names))
+                if id == db_id && table_names == ["\"public\".\"book\""]
+        ));
+
+        backend.drop(db_id, true).unwrap();
+    }
+
+    #[test]
+    fn backend_leaves_database_untouched_on_failed_clean_in_transaction() {
+        dotenv().ok();
+
+        let config = PrivilegedPostgresConfig::from_env().unwrap();
+
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+            move |conn| {
+                conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                    .unwrap();
+                conn.batch_execute(
+                    "CREATE FUNCTION reject_truncate() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'rejected'; END $$ LANGUAGE plpgsql; \
+                    CREATE TRIGGER reject_truncate BEFORE TRUNCATE ON dummy EXECUTE FUNCTION reject_truncate()",
+                )
+                .unwrap();
+            }
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .clean_in_transaction(true);
+
+        let guard = lock_read();
+
+        let db_id = Uuid::new_v4();
+
+        {
+            let conn_pool = backend.create(db_id, true).unwrap();
+            let conn = &mut conn_pool.get().unwrap();
+            conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .unwrap();
+
+            // cleaning must fail
+            assert!(backend.clean(db_id).is_err());
+
+            // rows must be left untouched
+            assert_eq!(
+                conn.query_one("SELECT COUNT(*) FROM book", &[])
+                    .unwrap()
+                    .get::<_, i64>(0),
+                1
+            );
+
+            // stored connection must be kept outside of a transaction after failing to clean
+            let mut config = Config::from(PrivilegedPostgresConfig::from_env().unwrap());
+            config.dbname(get_db_name(db_id).as_str());
+            config
+                .connect(NoTls)
+                .unwrap()
+                .batch_execute("DROP TRIGGER reject_truncate ON dummy")
+                .unwrap();
+            backend.clean(db_id).unwrap();
+            assert_eq!(
+                conn.query_one("SELECT COUNT(*) FROM book", &[])
+                    .unwrap()
+                    .get::<_, i64>(0),
+                0
+            );
+        }
+
+        backend.drop(db_id, true).unwrap();
+    }
 
-    fn create_backend(with_table: bool) -> PostgresBackend {
+    #[test]
+    fn backend_discards_database_on_failed_entity_creation_in_transaction() {
         dotenv().ok();
 
         let config = PrivilegedPostgresConfig::from_env().unwrap();
 
-        PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
-            move |conn| {
-                if with_table {
-                    conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
-                        .unwrap();
-                }
-            }
-        })
-        .unwrap()
+        // entity creation either panics or swallows the failed statement
+        for swallows_error in [false, true] {
+            let backend =
+                PostgresBackend::new(config.clone().into(), Pool::builder, Pool::builder, {
+                    move |conn| {
+                        conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                            .unwrap();
+                        let inserted = conn.batch_execute("INSERT INTO missing (id) VALUES (1)");
+                        if !swallows_error {
+                            inserted.unwrap();
+                        }
+                    }
+                })
+                .unwrap()
+                .drop_previous_databases(false)
+                .create_entities_in_transaction(true);
+
+            let guard = lock_read();
+
+            let db_id = Uuid::new_v4();
+            let db_name = get_db_name(db_id);
+
+            // database creation must fail
+            assert!(matches!(
+                backend.create(db_id, true),
+                Err(BackendError::FailedEntityCreation(id)) if id == db_id
+            ));
+
+            // partially created database and role must be dropped
+            let conn = &mut backend.default_pool.get().unwrap();
+            assert!(conn
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                    &[&db_name],
+                )
+                .unwrap()
+                .is_none());
+            assert!(conn
+                .query_opt(
+                    "SELECT 1 FROM pg_catalog.pg_roles WHERE rolname = $1",
+                    &[&db_name],
+                )
+                .unwrap()
+                .is_none());
+
+            drop(guard);
+        }
     }
 
-    #[test]
-    fn backend_drops_previous_databases() {
-        test_backend_drops_previous_databases(
-            create_backend(false),
-            create_backend(false).drop_previous_databases(true),
-            create_backend(false).drop_previous_databases(false),
-        );
-    }
+    #[cfg(feature = "logging")]
+    struct Logger(parking_lot::Mutex<Vec<String>>);
 
-    #[test]
-    fn backend_creates_database_with_restricted_privileges() {
-        let backend = create_backend(true).drop_previous_databases(false);
-        test_backend_creates_database_with_restricted_privileges(&backend);
-    }
+    #[cfg(feature = "logging")]
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target().starts_with("db_pool")
+        }
 
-    #[test]
-    fn backend_creates_database_with_unrestricted_privileges() {
-        let backend = create_backend(true).drop_previous_databases(false);
-        test_backend_creates_database_with_unrestricted_privileges(&backend);
-    }
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().push(record.args().to_string());
+            }
+        }
 
-    #[test]
-    fn backend_cleans_database_with_tables() {
-        let backend = create_backend(true).drop_previous_databases(false);
-        test_backend_cleans_database_with_tables(&backend);
+        fn flush(&self) {}
     }
 
-    #[test]
-    fn backend_cleans_database_without_tables() {
-        let backend = create_backend(false).drop_previous_databases(false);
-        test_backend_cleans_database_without_tables(&backend);
+    #[cfg(feature = "logging")]
+    static LOGGER: Logger = Logger(parking_lot::Mutex::new(Vec::new()));
+
+    #[cfg(feature = "logging")]
+    fn init_logger() {
+        static INIT: std::sync::Once = std::sync::Once::new();
+
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Info);
+        });
     }
 
+    #[cfg(feature = "logging")]
     #[test]
-    fn backend_drops_restricted_database() {
-        let backend = create_backend(true).drop_previous_databases(false);
-        test_backend_drops_database(&backend, true);
+    fn backend_logs_statements() {
+        init_logger();
+
+        let backend = create_backend(true)
+            .drop_previous_databases(false)
+            .log_statements(true);
+
+        let guard = lock_read();
+
+        let db_pool = backend.create_database_pool().unwrap();
+        let conn_pool = db_pool.pull_immutable();
+        let db_name = conn_pool.database_name();
+
+        // statements creating database and role must be logged
+        let stmts = LOGGER.0.lock();
+        assert!(stmts.contains(&postgres::create_database(
+            db_name, None, None, None, None, None
+        )));
+        assert!(stmts.contains(&postgres::create_role(db_name, None, &[])));
     }
 
+    #[cfg(feature = "logging")]
     #[test]
-    fn backend_drops_unrestricted_database() {
+    fn backend_logs_lifecycle_statements_without_executing_them_in_dry_run() {
+        init_logger();
+
         let backend = create_backend(true).drop_previous_databases(false);
-        test_backend_drops_database(&backend, false);
+        let dry_run_backend = create_backend(true)
+            .drop_previous_databases(true)
+            .dry_run(true);
+
+        let guard = lock_read();
+
+        let conn = &mut backend.default_pool.get().unwrap();
+        let mut database_exists = |db_name: &str| {
+            conn.query_opt(
+                "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                &[&db_name],
+            )
+            .unwrap()
+            .is_some()
+        };
+
+        // database left over by a previous run must be kept, with statements dropping it logged
+        let previous_db_id = Uuid::new_v4();
+        let previous_db_name = get_db_name(previous_db_id);
+        let previous_db_name = previous_db_name.as_str();
+        backend.create(previous_db_id, true).unwrap();
+        dry_run_backend.init().unwrap();
+        {
+            let stmts = LOGGER.0.lock();
+            assert!(stmts.contains(&postgres::drop_database(previous_db_name)));
+            assert!(stmts.contains(&postgres::drop_role(previous_db_name)));
+        }
+        assert!(database_exists(previous_db_name));
+
+        // database must never be created, with statements creating and dropping it logged
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+        dry_run_backend.create(db_id, true).unwrap();
+        dry_run_backend.clean(db_id).unwrap();
+        {
+            let stmts = LOGGER.0.lock();
+            assert!(stmts.contains(&postgres::create_database(
+                db_name, None, None, None, None, None
+            )));
+            assert!(stmts.contains(&postgres::create_role(db_name, None, &[])));
+        }
+        assert!(!database_exists(db_name));
+        dry_run_backend.drop(db_id, true).unwrap();
+        {
+            let stmts = LOGGER.0.lock();
+            assert!(stmts.contains(&postgres::drop_database(db_name)));
+            assert!(stmts.contains(&postgres::drop_role(db_name)));
+        }
+
+        backend.drop(previous_db_id, true).unwrap();
     }
 
+    #[cfg(feature = "logging")]
     #[test]
-    fn pool_drops_previous_databases() {
-        test_pool_drops_previous_databases(
-            create_backend(false),
-            create_backend(false).drop_previous_databases(true),
-            create_backend(false).drop_previous_databases(false),
-        );
+    fn backend_keeps_conflicting_database_when_recreating_on_conflict_in_dry_run() {
+        init_logger();
+
+        let backend = create_backend(true);
+        let dry_run_backend = create_backend(true)
+            .recreate_on_conflict(true)
+            .dry_run(true);
+
+        let guard = lock_read();
+
+        // database left over by a previous run
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+        backend.create(db_id, true).unwrap();
+
+        // creating conflicting database must succeed without dropping it
+        dry_run_backend.create(db_id, true).unwrap();
+        assert!(LOGGER.0.lock().contains(&postgres::create_database(
+            db_name, None, None, None, None, None
+        )));
+        assert!(backend
+            .default_pool
+            .get()
+            .unwrap()
+            .query_opt(
+                "SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1",
+                &[&db_name],
+            )
+            .unwrap()
+            .is_some());
+
+        backend.drop(db_id, true).unwrap();
     }
 
     #[test]
-    fn pool_provides_isolated_databases() {
-        const NUM_DBS: i64 = 3;
+    fn backend_connects_to_maintenance_database() {
+        dotenv().ok();
 
-        let backend = create_backend(true).drop_previous_databases(false);
+        let maintenance_database = format!("maintenance_{}", Uuid::new_v4().simple());
 
         let guard = lock_read();
 
-        let db_pool = backend.create_database_pool().unwrap();
-        let conn_pools = (0..NUM_DBS)
-            .map(|_| db_pool.pull_immutable())
-            .collect::<Vec<_>>();
-
-        // insert single row into each database
-        conn_pools.iter().enumerate().for_each(|(i, conn_pool)| {
-            let conn = &mut conn_pool.get().unwrap();
-            conn.execute(
-                "INSERT INTO book (title) VALUES ($1)",
-                &[&format!("Title {i}").as_str()],
+        // Create maintenance database
+        let mut client = Config::from(PrivilegedPostgresConfig::from_env().unwrap())
+            .connect(NoTls)
+            .unwrap();
+        client
+            .execute(
+                postgres::create_database(
+                    maintenance_database.as_str(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .as_str(),
+                &[],
             )
             .unwrap();
-        });
 
-        // rows fetched must be as inserted
-        conn_pools.iter().enumerate().for_each(|(i, conn_pool)| {
-            let conn = &mut conn_pool.get().unwrap();
+        {
+            let config = PrivilegedPostgresConfig::from_env()
+                .unwrap()
+                .maintenance_database(maintenance_database.as_str());
+
+            let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, {
+                move |conn| {
+                    conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                        .unwrap();
+                }
+            })
+            .unwrap()
+            .drop_previous_databases(false);
+
+            // privileged user must connect to maintenance database
             assert_eq!(
-                conn.query("SELECT title FROM book", &[])
+                backend
+                    .default_pool
+                    .get()
                     .unwrap()
-                    .iter()
-                    .map(|row| row.get::<_, String>(0))
-                    .collect::<Vec<_>>(),
-                vec![format!("Title {i}")]
+                    .query_one("SELECT current_database()", &[])
+                    .unwrap()
+                    .get::<_, String>(0),
+                maintenance_database
             );
-        });
+
+            let db_pool = backend.create_database_pool().unwrap();
+            let conn_pool = db_pool.pull_immutable();
+            let conn = &mut conn_pool.get().unwrap();
+
+            // created database must be usable
+            assert!(conn
+                .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+                .is_ok());
+        }
+
+        // Drop maintenance database
+        client
+            .execute(
+                postgres::terminate_database_connections(maintenance_database.as_str()).as_str(),
+                &[],
+            )
+            .unwrap();
+        client
+            .execute(
+                postgres::drop_database(maintenance_database.as_str()).as_str(),
+                &[],
+            )
+            .unwrap();
     }
 
     #[test]
-    fn pool_provides_restricted_databases() {
-        let backend = create_backend(true).drop_previous_databases(false);
+    fn backend_uses_provided_privileged_pool() {
+        const APPLICATION_NAME: &str = "shared_privileged_pool";
+
+        dotenv().ok();
+
+        let config: Config = PrivilegedPostgresConfig::from_env().unwrap().into();
+        let privileged_pool = {
+            let mut config = config.clone();
+            config.application_name(APPLICATION_NAME);
+            Pool::builder().build(Manager::new(config, NoTls)).unwrap()
+        };
+
+        let backend = PostgresBackend::new_with_privileged_pool(
+            config,
+            privileged_pool,
+            Pool::builder,
+            move |conn| {
+                conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                    .unwrap();
+            },
+        )
+        .drop_previous_databases(false);
+
+        // backend must use provided privileged pool
+        assert_eq!(
+            backend
+                .default_pool
+                .get()
+                .unwrap()
+                .query_one("SHOW application_name", &[])
+                .unwrap()
+                .get::<_, String>(0),
+            APPLICATION_NAME
+        );
 
         let guard = lock_read();
 
         let db_pool = backend.create_database_pool().unwrap();
-
         let conn_pool = db_pool.pull_immutable();
         let conn = &mut conn_pool.get().unwrap();
 
-        // DDL statements must fail
-        for stmt in DDL_STATEMENTS {
-            assert!(conn.execute(stmt, &[]).is_err());
-        }
+        // created database must be usable
+        assert!(conn
+            .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+            .is_ok());
+    }
 
-        // DML statements must succeed
-        for stmt in DML_STATEMENTS {
-            assert!(conn.execute(stmt, &[]).is_ok());
-        }
+    #[test]
+    fn pool_drops_created_restricted_databases() {
+        let backend = create_backend(false);
+        test_pool_drops_created_restricted_databases(backend);
     }
 
     #[test]
-    fn pool_provides_unrestricted_databases() {
+    fn pool_drops_created_unrestricted_database() {
+        let backend = create_backend(false);
+        test_pool_drops_created_unrestricted_database(backend);
+    }
+
+    #[test]
+    fn backend_clones_share_state() {
         let backend = create_backend(true).drop_previous_databases(false);
 
         let guard = lock_read();
 
-        let db_pool = backend.create_database_pool().unwrap();
+        backend.init().unwrap();
 
-        // DML statements must succeed
-        {
-            let conn_pool = db_pool.create_mutable().unwrap();
-            let conn = &mut conn_pool.get().unwrap();
-            for stmt in DML_STATEMENTS {
-                assert!(conn.execute(stmt, &[]).is_ok());
-            }
-        }
+        // database created through a clone on another thread
+        let db_id = Uuid::new_v4();
+        let cloned = backend.clone();
+        assert!(thread::spawn(move || cloned.create(db_id, true).is_ok())
+            .join()
+            .unwrap());
 
-        // DDL statements must succeed
-        for stmt in DDL_STATEMENTS {
-            let conn_pool = db_pool.create_mutable().unwrap();
-            let conn = &mut conn_pool.get().unwrap();
-            assert!(conn.execute(stmt, &[]).is_ok());
-        }
+        // must be cleanable and droppable through the original backend
+        backend.clean(db_id).unwrap();
+        backend.drop(db_id, true).unwrap();
     }
 
     #[test]
-    fn pool_provides_clean_databases() {
-        const NUM_DBS: i64 = 3;
+    fn backend_pings_server() {
+        // reachable server must respond
+        assert!(create_backend(false).ping().is_ok());
+
+        // unreachable server must fail with a pool error
+        let mut config: Config = PrivilegedPostgresConfig::from_env().unwrap().into();
+        config.port(1);
+        let privileged_pool = Pool::builder()
+            .connection_timeout(Duration::from_secs(1))
+            .build_unchecked(Manager::new(config.clone(), NoTls));
+        let backend = PostgresBackend::new_with_privileged_pool(
+            config,
+            privileged_pool,
+            Pool::builder,
+            |_| {},
+        );
+        assert!(matches!(backend.ping(), Err(BackendError::Pool(_))));
+    }
 
-        let backend = create_backend(true).drop_previous_databases(false);
+    #[test]
+    fn privileged_connections_report_application_name() {
+        dotenv().ok();
+
+        for (config, application_name) in [
+            (PrivilegedPostgresConfig::from_env().unwrap(), "db_pool"),
+            (
+                PrivilegedPostgresConfig::from_env()
+                    .unwrap()
+                    .application_name("db_pool_test_suite"),
+                "db_pool_test_suite",
+            ),
+        ] {
+            let backend =
+                PostgresBackend::new(config.into(), Pool::builder, Pool::builder, |_| {}).unwrap();
+            let conn = &mut backend.privileged_pool().get().unwrap();
+
+            // privileged connection must report application name
+            assert_eq!(
+                conn.query_one(
+                    "SELECT application_name FROM pg_catalog.pg_stat_activity WHERE pid = pg_backend_pid()",
+                    &[]
+                )
+                .unwrap()
+                .get::<_, String>(0),
+                application_name
+            );
+        }
+    }
+
+    #[test]
+    fn pool_provides_separate_reader_pool() {
+        let default = create_backend(true).drop_previous_databases(false);
+        let enabled = create_backend(true).drop_previous_databases(false);
 
         let guard = lock_read();
 
-        let db_pool = backend.create_database_pool().unwrap();
+        // reader must be the writer by default
+        {
+            let db_pool = default.create_database_pool().unwrap();
+            let conn_pool = db_pool.pull_immutable();
+            assert!(std::ptr::eq(conn_pool.reader(), conn_pool.writer()));
+        }
+
+        let db_pool = enabled
+            .create_database_pool()
+            .unwrap()
+            .separate_reader_pool(true);
+        let conn_pool = db_pool.pull_immutable();
+        assert!(!std::ptr::eq(conn_pool.reader(), conn_pool.writer()));
+
+        let writer = &mut conn_pool.writer().get().unwrap();
+        let reader = &mut conn_pool.reader().get().unwrap();
+
+        // reader must read what writer wrote over a distinct connection to the same database
+        writer
+            .execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
+            .unwrap();
+        let title = reader
+            .query_one("SELECT title FROM book", &[])
+            .unwrap()
+            .get::<_, String>(0);
+        assert_eq!(title, "Title");
+
+        let get_pid_and_database = "SELECT pg_backend_pid(), current_database()";
+        let writer_row = writer.query_one(get_pid_and_database, &[]).unwrap();
+        let reader_row = reader.query_one(get_pid_and_database, &[]).unwrap();
+        assert_ne!(writer_row.get::<_, i32>(0), reader_row.get::<_, i32>(0));
+        assert_eq!(writer_row.get::<_, &str>(1), conn_pool.database_name());
+        assert_eq!(reader_row.get::<_, &str>(1), conn_pool.database_name());
+    }
+
+    #[test]
+    fn pool_provides_databases_with_createdb_only_profile() {
+        dotenv().ok();
+
+        let login_name = format!("createdb_{}", Uuid::new_v4().simple());
+        let admin = create_backend(false);
+        let admin_conn = &mut admin.privileged_pool().get().unwrap();
+        admin_conn
+            .batch_execute(
+                format!("CREATE ROLE {login_name} WITH LOGIN PASSWORD '{login_name}' CREATEDB CREATEROLE")
+                    .as_str(),
+            )
+            .unwrap();
+
+        let config = PrivilegedPostgresConfig::from_env()
+            .unwrap()
+            .username(login_name.clone())
+            .password(Some(login_name.clone()));
+        let backend = PostgresBackend::new(config.into(), Pool::builder, Pool::builder, |conn| {
+            conn.batch_execute(&CREATE_ENTITIES_STATEMENTS.join(";"))
+                .unwrap();
+        })
+        .unwrap()
+        .drop_previous_databases(false)
+        .privilege_profile(PrivilegeProfile::CreateDb);
 
-        // fetch connection pools the first time
         {
-            let conn_pools = (0..NUM_DBS)
-                .map(|_| db_pool.pull_immutable())
-                .collect::<Vec<_>>();
+            let guard = lock_read();
 
-            // databases must be empty
-            for conn_pool in &conn_pools {
-                let conn = &mut conn_pool.get().unwrap();
-                assert_eq!(
-                    conn.query_one("SELECT COUNT(*) FROM book", &[])
-                        .unwrap()
-                        .get::<_, i64>(0),
-                    0
-                );
-            }
+            let db_pool = backend.create_database_pool().unwrap();
 
-            // insert data into each database
-            for conn_pool in &conn_pools {
+            // DML statements must succeed on restricted database created without superuser
+            {
+                let conn_pool = db_pool.pull_immutable();
                 let conn = &mut conn_pool.get().unwrap();
-                conn.execute("INSERT INTO book (title) VALUES ($1)", &[&"Title"])
-                    .unwrap();
+                for stmt in DML_STATEMENTS {
+                    assert!(conn.execute(stmt, &[]).is_ok());
+                }
+                for stmt in DDL_STATEMENTS {
+                    assert!(conn.execute(stmt, &[]).is_err());
+                }
             }
-        }
-
-        // fetch same connection pools a second time
-        {
-            let conn_pools = (0..NUM_DBS)
-                .map(|_| db_pool.pull_immutable())
-                .collect::<Vec<_>>();
 
-            // databases must be empty
-            for conn_pool in &conn_pools {
+            // DDL statements must succeed on unrestricted database created without superuser
+            for stmt in DDL_STATEMENTS {
+                let conn_pool = db_pool.create_mutable().unwrap();
                 let conn = &mut conn_pool.get().unwrap();
-                assert_eq!(
-                    conn.query_one("SELECT COUNT(*) FROM book", &[])
-                        .unwrap()
-                        .get::<_, i64>(0),
-                    0
-                );
+                assert!(conn.execute(stmt, &[]).is_ok());
             }
         }
-    }
-
-    #[test]
-    fn pool_drops_created_restricted_databases() {
-        let backend = create_backend(false);
-        test_pool_drops_created_restricted_databases(backend);
-    }
 
-    #[test]
-    fn pool_drops_created_unrestricted_database() {
-        let backend = create_backend(false);
-        test_pool_drops_created_unrestricted_database(backend);
+        admin_conn
+            .batch_execute(format!("DROP ROLE {login_name}").as_str())
+            .unwrap();
     }
 }
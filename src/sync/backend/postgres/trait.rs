@@ -1,9 +1,26 @@
-use std::{borrow::Cow, fmt::Debug, ops::Deref};
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::Instant,
+};
 
 use r2d2::{ManageConnection, Pool, PooledConnection};
 use uuid::Uuid;
 
-use crate::common::statement::postgres;
+use crate::{
+    common::{
+        config::{PgDialect, PrivilegeProfile, Role},
+        metrics::{MetricEvent, MetricOperation, MetricsCallback},
+        statement::postgres,
+    },
+    util::{
+        get_clean_retry_backoff, is_valid_db_name, PreviousDatabaseQuery, TableNamesCache,
+        DB_NAME_PREFIX,
+    },
+};
 
 use super::super::error::Error as BackendError;
 
@@ -42,13 +59,35 @@ pub(super) trait PostgresBackend {
     fn get_database_connection(
         &self,
         db_id: Uuid,
-    ) -> <Self::ConnectionManager as ManageConnection>::Connection;
+    ) -> Option<<Self::ConnectionManager as ManageConnection>::Connection>;
 
     fn get_previous_database_names(
         &self,
         conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
-    fn create_entities(&self, conn: &mut <Self::ConnectionManager as ManageConnection>::Connection);
+    fn get_database_names(
+        &self,
+        query: &str,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_previous_owned_database_names(
+        &self,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_database_names_with_schema_version(
+        &self,
+        schema_version: &str,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_previous_role_names(
+        &self,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+    fn create_entities(
+        &self,
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+        db_name: &str,
+    );
     fn create_connection_pool(
         &self,
         db_id: Uuid,
@@ -58,8 +97,48 @@ pub(super) trait PostgresBackend {
         &self,
         conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
     ) -> Result<Vec<String>, Self::QueryError>;
+    fn get_nonempty_table_names(
+        &self,
+        table_names: &[String],
+        conn: &mut <Self::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, Self::QueryError>;
+
+    fn is_duplicate_database_error(&self, err: &Self::QueryError) -> bool;
+    fn is_duplicate_role_error(&self, err: &Self::QueryError) -> bool;
+    fn is_transient_lock_error(&self, err: &Self::QueryError) -> bool;
 
     fn get_drop_previous_databases(&self) -> bool;
+    fn get_previous_database_query(&self) -> Option<&PreviousDatabaseQuery>;
+    fn get_only_drop_owned(&self) -> bool;
+    fn get_schema_version(&self) -> Option<&str>;
+    #[cfg(feature = "logging")]
+    fn get_log_statements(&self) -> bool;
+    #[cfg(feature = "logging")]
+    fn get_dry_run(&self) -> bool;
+    fn get_force_drop(&self) -> bool;
+    fn get_reused_role(&self) -> Option<&str>;
+    fn get_dialect(&self) -> PgDialect;
+    fn get_create_entities_in_transaction(&self) -> bool;
+    fn get_clean_in_transaction(&self) -> bool;
+    fn get_verify_clean(&self) -> bool;
+    fn get_clean_retries(&self) -> usize;
+    fn get_table_names_cache(&self) -> &TableNamesCache;
+    fn get_metrics_callback(&self) -> Option<&MetricsCallback>;
+    fn get_db_name(&self, db_id: Uuid) -> String;
+    fn get_encoding(&self) -> Option<&str>;
+    fn get_lc_collate(&self) -> Option<&str>;
+    fn get_lc_ctype(&self) -> Option<&str>;
+    fn get_template(&self) -> Option<&str>;
+    fn get_extensions(&self) -> &[String];
+    fn get_group_role(&self) -> bool;
+    fn get_search_path(&self) -> &[String];
+    fn get_role_attributes(&self) -> &[String];
+    fn get_schemas(&self) -> &[String];
+    fn get_recreate_on_conflict(&self) -> bool;
+    fn get_owner_at_creation(&self) -> bool;
+    fn get_create_entities_as(&self) -> Role;
+    fn get_privilege_profile(&self) -> PrivilegeProfile;
+    fn get_read_only(&self) -> bool;
 }
 
 pub(super) struct PostgresBackendWrapper<'a, B: PostgresBackend>(&'a B);
@@ -78,21 +157,527 @@ impl<'a, B: PostgresBackend> Deref for PostgresBackendWrapper<'a, B> {
     }
 }
 
+/// Privileged connection to a database, stored back once dropped so that it is kept for reuse
+/// whether the operation using it succeeds or fails
+struct DatabaseConnection<'a, B: PostgresBackend> {
+    backend: &'a B,
+    db_id: Uuid,
+    conn: Option<<B::ConnectionManager as ManageConnection>::Connection>,
+}
+
+impl<B: PostgresBackend> DatabaseConnection<'_, B> {
+    fn discard(mut self) {
+        self.conn = None;
+    }
+}
+
+impl<B: PostgresBackend> Deref for DatabaseConnection<'_, B> {
+    type Target = <B::ConnectionManager as ManageConnection>::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn
+            .as_ref()
+            .expect("conn must always contain a [Some] value")
+    }
+}
+
+impl<B: PostgresBackend> DerefMut for DatabaseConnection<'_, B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.conn
+            .as_mut()
+            .expect("conn must always contain a [Some] value")
+    }
+}
+
+impl<B: PostgresBackend> Drop for DatabaseConnection<'_, B> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.backend.put_database_connection(self.db_id, conn);
+        }
+    }
+}
+
 impl<'a, B: PostgresBackend> PostgresBackendWrapper<'a, B> {
-    pub(super) fn init(&self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+    #[cfg_attr(not(feature = "logging"), allow(clippy::unused_self))]
+    fn is_dry_run(&self) -> bool {
+        #[cfg(feature = "logging")]
+        return self.get_dry_run();
+        #[cfg(not(feature = "logging"))]
+        false
+    }
+
+    fn execute_query(
+        &self,
+        query: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        #[cfg(feature = "logging")]
+        if self.get_log_statements() || self.get_dry_run() {
+            log::info!("{query}");
+        }
+
+        // Skip executing statement in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.0
+            .execute_query(query, conn)
+            .map_err(|err| BackendError::Statement(query.to_owned(), err))
+    }
+
+    fn batch_execute_query<'b>(
+        &self,
+        query: impl IntoIterator<Item = Cow<'b, str>>,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), B::QueryError> {
+        #[cfg(feature = "logging")]
+        let query = {
+            let query = query.into_iter().collect::<Vec<_>>();
+            if self.get_log_statements() || self.get_dry_run() {
+                for stmt in &query {
+                    log::info!("{stmt}");
+                }
+            }
+            query
+        };
+
+        // Skip executing statements in dry run
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        self.0.batch_execute_query(query, conn)
+    }
+
+    fn get_cached_table_names(
+        &self,
+        db_id: Uuid,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<Vec<String>, B::QueryError> {
+        if let Some(table_names) = self.get_table_names_cache().lock().get(&db_id) {
+            return Ok(table_names.clone());
+        }
+
+        let table_names = self.get_table_names(conn)?;
+        self.get_table_names_cache()
+            .lock()
+            .insert(db_id, table_names.clone());
+        Ok(table_names)
+    }
+
+    fn report_metric(
+        &self,
+        operation: MetricOperation,
+        db_id: Uuid,
+        start: Instant,
+        cleaned_tables: Option<usize>,
+    ) {
+        if let Some(metrics_callback) = self.get_metrics_callback() {
+            metrics_callback(MetricEvent {
+                operation,
+                db_id,
+                elapsed: start.elapsed(),
+                cleaned_tables,
+            });
+        }
+    }
+
+    fn create_entities(
+        &self,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+        db_name: &str,
+        db_id: Uuid,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        if !self.get_create_entities_in_transaction() {
+            self.0.create_entities(conn, db_name);
+            return Ok(());
+        }
+
+        // Start transaction
+        self.execute_query(postgres::START_TRANSACTION, conn)?;
+
+        // Create entities, rolling back if entity creation panics
+        if panic::catch_unwind(AssertUnwindSafe(|| self.0.create_entities(conn, db_name))).is_err()
+        {
+            self.execute_query(postgres::ROLLBACK_TRANSACTION, conn)?;
+            return Err(BackendError::FailedEntityCreation(db_id));
+        }
+
+        // Roll back if a statement failed, since committing an aborted transaction silently rolls it back
+        if self
+            .execute_query(postgres::CHECK_TRANSACTION, conn)
+            .is_err()
+        {
+            self.execute_query(postgres::ROLLBACK_TRANSACTION, conn)?;
+            return Err(BackendError::FailedEntityCreation(db_id));
+        }
+
+        // Commit transaction
+        self.execute_query(postgres::COMMIT_TRANSACTION, conn)
+    }
+
+    fn grant_restricted_privileges(
+        &self,
+        role_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let read_only = self.get_read_only();
+        for schema_name in self.get_schemas() {
+            // Grant schema usage to restricted role
+            self.execute_query(
+                postgres::grant_schema_usage(schema_name, role_name).as_str(),
+                conn,
+            )?;
+
+            // Grant table privileges to restricted role, limited to reading if needed
+            self.execute_query(
+                if read_only {
+                    postgres::grant_read_only_table_privileges(schema_name, role_name)
+                } else {
+                    postgres::grant_restricted_table_privileges(schema_name, role_name)
+                }
+                .as_str(),
+                conn,
+            )?;
+
+            // Grant sequence privileges to restricted role
+            self.execute_query(
+                postgres::grant_restricted_sequence_privileges(schema_name, role_name).as_str(),
+                conn,
+            )?;
+
+            // Grant table privileges on future tables to restricted role, limited to reading if needed
+            self.execute_query(
+                if read_only {
+                    postgres::grant_read_only_default_table_privileges(schema_name, role_name)
+                } else {
+                    postgres::grant_restricted_default_table_privileges(schema_name, role_name)
+                }
+                .as_str(),
+                conn,
+            )?;
+
+            // Grant sequence privileges on future sequences to restricted role
+            self.execute_query(
+                postgres::grant_restricted_default_sequence_privileges(schema_name, role_name)
+                    .as_str(),
+                conn,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn grant_unrestricted_privileges(
+        &self,
+        db_name: &str,
+        role_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        if self.get_privilege_profile().transfers_ownership() {
+            // Grant database ownership
+            self.execute_query(
+                postgres::grant_database_ownership(db_name, role_name).as_str(),
+                conn,
+            )
+        } else {
+            // Grant all privileges on database and public schema in place of ownership
+            self.execute_query(
+                postgres::grant_database_privileges(db_name, role_name).as_str(),
+                conn,
+            )?;
+            self.execute_query(
+                postgres::grant_schema_privileges(postgres::PUBLIC_SCHEMA_NAME, role_name).as_str(),
+                conn,
+            )
+        }
+    }
+
+    fn grant_schema_creation(
+        &self,
+        role_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        for schema_name in self.get_schemas() {
+            self.execute_query(
+                postgres::grant_schema_creation(schema_name, role_name).as_str(),
+                conn,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn revoke_schema_creation(
+        &self,
+        role_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        for schema_name in self.get_schemas() {
+            self.execute_query(
+                postgres::revoke_schema_creation(schema_name, role_name).as_str(),
+                conn,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn create_extensions(
+        &self,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        for extension in self.get_extensions() {
+            self.execute_query(postgres::create_extension(extension).as_str(), conn)?;
+        }
+
+        Ok(())
+    }
+
+    fn discard_database(
+        &self,
+        db_name: &str,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Get connection to default database as privileged user
+        let conn = &mut self.get_default_connection()?;
+
+        // Terminate connections left open while creating database
+        self.terminate_database_connections(db_name, conn)?;
+
+        // Drop database
+        self.execute_query(postgres::drop_database(db_name).as_str(), conn)?;
+
+        // Drop attached role if not reusing an existing one
+        if self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role(db_name).as_str(), conn)?;
+        }
+
+        Ok(())
+    }
+
+    fn create_database(
+        &self,
+        db_name: &str,
+        owner: Option<&str>,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let create_database = postgres::create_database(
+            db_name,
+            owner,
+            self.get_encoding(),
+            self.get_lc_collate(),
+            self.get_lc_ctype(),
+            self.get_template(),
+        );
+        if let Err(err) = self.execute_query(create_database.as_str(), conn) {
+            let conflicts = matches!(
+                &err,
+                BackendError::Statement(_, err) if self.is_duplicate_database_error(err)
+            );
+            if !(conflicts && self.get_recreate_on_conflict()) {
+                return Err(err);
+            }
+            // Keep owner role created beforehand
+            self.drop_conflicting_database(db_name, owner.is_none(), conn)?;
+            self.execute_query(create_database.as_str(), conn)?;
+        }
+        Ok(())
+    }
+
+    fn create_role(
+        &self,
+        role_name: &str,
+        group_role: Option<&str>,
+        owned_db_name: Option<&str>,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let create_role = postgres::create_role(role_name, group_role, self.get_role_attributes());
+        if let Err(err) = self.execute_query(create_role.as_str(), conn) {
+            let conflicts = matches!(
+                &err,
+                BackendError::Statement(_, err) if self.is_duplicate_role_error(err)
+            );
+            if !conflicts {
+                return Err(err);
+            }
+            if !self.get_recreate_on_conflict() {
+                return Err(BackendError::RoleExists(role_name.to_owned()));
+            }
+            // Drop database owned by role left over by a previous run if needed
+            if let Some(db_name) = owned_db_name {
+                self.terminate_database_connections(db_name, conn)?;
+                self.execute_query(postgres::drop_database_if_exists(db_name).as_str(), conn)?;
+            }
+            // Drop role left over by a previous run
+            self.execute_query(postgres::drop_role(role_name).as_str(), conn)?;
+            self.execute_query(create_role.as_str(), conn)?;
+        }
+        Ok(())
+    }
+
+    fn restrict_database_connection(
+        &self,
+        db_name: &str,
+        role_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        self.execute_query(
+            postgres::revoke_public_database_connection(db_name).as_str(),
+            conn,
+        )?;
+        self.execute_query(
+            postgres::grant_database_connection(db_name, role_name).as_str(),
+            conn,
+        )
+    }
+
+    fn terminate_database_connections(
+        &self,
+        db_name: &str,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Terminate connections on Postgres only, as CockroachDB lacks pg_terminate_backend
+        // and drops databases regardless of open connections
+        if self.get_dialect() == PgDialect::Postgres {
+            self.execute_query(
+                postgres::terminate_database_connections(db_name).as_str(),
+                conn,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn drop_conflicting_database(
+        &self,
+        db_name: &str,
+        drop_role: bool,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Terminate connections to conflicting database
+        self.terminate_database_connections(db_name, conn)?;
+
+        // Drop conflicting database
+        self.execute_query(postgres::drop_database(db_name).as_str(), conn)?;
+
+        // Drop attached role if needed and not reusing an existing one
+        if drop_role && self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role_if_exists(db_name).as_str(), conn)?;
+        }
+
+        Ok(())
+    }
+
+    fn drop_previous_databases(
+        &self,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Get names of databases to keep since marked with the current schema version
+        let kept_db_names = if let Some(schema_version) = self.get_schema_version() {
+            self.get_database_names_with_schema_version(schema_version, conn)
+                .map_err(Into::into)?
+        } else {
+            Vec::new()
+        };
+
+        // Get previous database names using custom query if set,
+        // restricted to databases tagged as created by a backend otherwise if needed
+        let db_names = if let Some(query) = self.get_previous_database_query() {
+            self.get_database_names(query(DB_NAME_PREFIX).as_str(), conn)
+        } else if self.get_only_drop_owned() {
+            self.get_previous_owned_database_names(conn)
+        } else {
+            self.get_previous_database_names(conn)
+        }
+        .map_err(Into::into)?
+        .into_iter()
+        .filter(|db_name| !kept_db_names.contains(db_name))
+        .collect::<Vec<_>>();
+
+        // Drop databases
+        for db_name in &db_names {
+            // Terminate lingering connections if needed
+            if self.get_force_drop() {
+                self.terminate_database_connections(db_name.as_str(), conn)?;
+            }
+
+            self.execute_query(postgres::drop_database(db_name.as_str()).as_str(), conn)?;
+        }
+
+        // Get previous role names
+        let role_names = self.get_previous_role_names(conn).map_err(Into::into)?;
+
+        // Drop roles left behind by previous databases, restricted to roles of dropped databases if needed,
+        // keeping the group role shared by databases that may still exist
+        for role_name in &role_names {
+            if Some(role_name.as_str()) != self.get_reused_role()
+                && role_name != postgres::GROUP_ROLE_NAME
+                && (!self.get_only_drop_owned() || db_names.contains(role_name))
+                && !kept_db_names.contains(role_name)
+            {
+                self.execute_query(postgres::drop_role(role_name.as_str()).as_str(), conn)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn ping(&self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        let conn = &mut self.get_default_connection()?;
+        self.execute_query(postgres::PING, conn)
+    }
+
+    fn initialize(
+        &self,
+        conn: &mut <B::ConnectionManager as ManageConnection>::Connection,
+        creates_group_role: bool,
+    ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
         // Drop previous databases if needed
         if self.get_drop_previous_databases() {
+            self.drop_previous_databases(conn)?;
+        }
+
+        // Create group role if needed
+        if creates_group_role {
+            self.execute_query(
+                postgres::create_group_role(postgres::GROUP_ROLE_NAME).as_str(),
+                conn,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn init(&self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Ensure generated database names are valid identifiers before any database is created
+        let db_name = self.get_db_name(Uuid::new_v4());
+        if !is_valid_db_name(&db_name, postgres::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name));
+        }
+
+        let creates_group_role = self.get_reused_role().is_none() && self.get_group_role();
+
+        if self.get_drop_previous_databases() || creates_group_role {
             // Get default connection
             let conn = &mut self.get_default_connection()?;
 
-            // Get previous database names
-            let db_names = self.get_previous_database_names(conn).map_err(Into::into)?;
-
-            // Drop databases
-            for db_name in &db_names {
-                self.execute_query(postgres::drop_database(db_name.as_str()).as_str(), conn)
-                    .map_err(Into::into)?;
+            // Lock initialization so that only one initializer sharing the server runs at a time
+            let locks = self.get_dialect() == PgDialect::Postgres;
+            if locks {
+                self.execute_query(postgres::LOCK_INITIALIZATION, conn)?;
             }
+
+            let initialized = self.initialize(conn, creates_group_role);
+
+            // Unlock initialization even if it failed, since the connection is returned to the pool,
+            // reporting the initialization error first
+            let unlocked = if locks {
+                self.execute_query(postgres::UNLOCK_INITIALIZATION, conn)
+            } else {
+                Ok(())
+            };
+            initialized?;
+            unlocked?;
         }
 
         Ok(())
@@ -104,56 +689,132 @@ impl<'a, B: PostgresBackend> PostgresBackendWrapper<'a, B> {
         db_id: uuid::Uuid,
         restrict_privileges: bool,
     ) -> Result<Pool<B::ConnectionManager>, BackendError<B::ConnectionError, B::QueryError>> {
+        let start = Instant::now();
+
         // Get database name based on UUID
-        let db_name = crate::util::get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
+        // Ensure database name is a valid identifier
+        if !is_valid_db_name(db_name, postgres::MAX_IDENTIFIER_LENGTH) {
+            return Err(BackendError::InvalidDatabaseName(db_name.to_owned()));
+        }
+
+        // Get name of role attached to database
+        let reused_role = self.get_reused_role();
+        let role_name = reused_role.unwrap_or(db_name);
+
+        // Get name of role holding restricted privileges
+        let group_role =
+            (reused_role.is_none() && self.get_group_role()).then_some(postgres::GROUP_ROLE_NAME);
+        let grantee_name = group_role.unwrap_or(role_name);
+
+        // Get whether database is owned by its role since creation
+        let owner_at_creation = !restrict_privileges
+            && self.get_owner_at_creation()
+            && self.get_privilege_profile().transfers_ownership();
+
         {
             // Get connection to default database as privileged user
             let conn = &mut self.get_default_connection()?;
 
-            // Create database
-            self.execute_query(postgres::create_database(db_name).as_str(), conn)
-                .map_err(Into::into)?;
+            // Create owner role first if not reusing an existing one
+            if owner_at_creation && reused_role.is_none() {
+                self.create_role(role_name, group_role, Some(db_name), conn)?;
+            }
 
-            // Create role
-            self.execute_query(postgres::create_role(db_name).as_str(), conn)
-                .map_err(Into::into)?;
-        }
+            // Create database, recreating one left over by a previous run if needed
+            self.create_database(db_name, owner_at_creation.then_some(role_name), conn)?;
 
-        {
-            // Connect to database as privileged user
-            let mut conn = self
-                .establish_privileged_database_connection(db_id)
-                .map_err(Into::into)?;
+            // Tag database as created by a backend
+            self.execute_query(
+                postgres::comment_database(db_name, self.get_schema_version()).as_str(),
+                conn,
+            )?;
 
-            if restrict_privileges {
-                // Create entities as privileged user
-                self.create_entities(&mut conn);
+            // Create role if not reusing an existing one
+            if !owner_at_creation && reused_role.is_none() {
+                self.create_role(role_name, group_role, None, conn)?;
+            }
 
-                // Grant table privileges to restricted role
-                self.execute_query(
-                    postgres::grant_restricted_table_privileges(db_name).as_str(),
-                    &mut conn,
-                )
-                .map_err(Into::into)?;
+            // Restrict connecting to database to its role if attached to group role,
+            // so that privileges granted to group role in database cannot be used by other database roles
+            if group_role.is_some() {
+                self.restrict_database_connection(db_name, role_name, conn)?;
+            }
 
-                // Grant sequence privileges to restricted role
+            // Set search path of role in database if needed
+            let search_path = self.get_search_path();
+            if !search_path.is_empty() {
                 self.execute_query(
-                    postgres::grant_restricted_sequence_privileges(db_name).as_str(),
-                    &mut conn,
-                )
-                .map_err(Into::into)?;
+                    postgres::set_role_search_path(role_name, db_name, search_path).as_str(),
+                    conn,
+                )?;
+            }
+        }
+
+        // Skip statements run on database in dry run, as it was never created
+        if !self.is_dry_run() {
+            if restrict_privileges {
+                // Connect to database as privileged user
+                let mut conn = self
+                    .establish_privileged_database_connection(db_id)
+                    .map_err(Into::into)?;
+
+                // Install extensions as privileged user
+                self.create_extensions(&mut conn)?;
+
+                match self.get_create_entities_as() {
+                    Role::Privileged => {
+                        // Create entities as privileged user
+                        if let Err(err) = self.create_entities(&mut conn, db_name, db_id) {
+                            self.discard_database(db_name)?;
+                            return Err(err);
+                        }
+
+                        // Grant privileges to restricted role
+                        self.grant_restricted_privileges(grantee_name, &mut conn)?;
+                    }
+                    Role::Restricted => {
+                        // Grant privileges to restricted role, temporarily including entity creation
+                        self.grant_restricted_privileges(grantee_name, &mut conn)?;
+                        self.grant_schema_creation(grantee_name, &mut conn)?;
+
+                        // Connect to database as restricted user
+                        let mut restricted_conn = self
+                            .establish_restricted_database_connection(db_id)
+                            .map_err(Into::into)?;
+
+                        // Create entities as restricted user
+                        if let Err(err) = self.create_entities(&mut restricted_conn, db_name, db_id)
+                        {
+                            self.discard_database(db_name)?;
+                            return Err(err);
+                        }
+
+                        // Revoke entity creation from restricted role
+                        self.revoke_schema_creation(grantee_name, &mut conn)?;
+                    }
+                }
 
                 // Store database connection for reuse when cleaning
                 self.put_database_connection(db_id, conn);
             } else {
-                // Grant database ownership to database-unrestricted role
-                self.execute_query(
-                    postgres::grant_database_ownership(db_name, db_name).as_str(),
-                    &mut conn,
-                )
-                .map_err(Into::into)?;
+                // Skip privileged connection if database is owned since creation and has no extensions
+                if !(owner_at_creation && self.get_extensions().is_empty()) {
+                    // Connect to database as privileged user
+                    let mut conn = self
+                        .establish_privileged_database_connection(db_id)
+                        .map_err(Into::into)?;
+
+                    // Install extensions as privileged user
+                    self.create_extensions(&mut conn)?;
+
+                    // Grant database ownership or equivalent privileges to database-unrestricted role if not owning it already
+                    if !owner_at_creation {
+                        self.grant_unrestricted_privileges(db_name, role_name, &mut conn)?;
+                    }
+                }
 
                 // Connect to database as database-unrestricted user
                 let mut conn = self
@@ -161,37 +822,140 @@ impl<'a, B: PostgresBackend> PostgresBackendWrapper<'a, B> {
                     .map_err(Into::into)?;
 
                 // Create entities as database-unrestricted user
-                self.create_entities(&mut conn);
+                if let Err(err) = self.create_entities(&mut conn, db_name, db_id) {
+                    self.discard_database(db_name)?;
+                    return Err(err);
+                }
             }
         }
 
         // Create connection pool with attached role
-        let pool = self.create_connection_pool(db_id)?;
+        let pool = self
+            .create_connection_pool(db_id)
+            .map_err(BackendError::Build)?;
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Create, db_id, start, None);
 
         Ok(pool)
     }
 
+    #[allow(clippy::complexity)]
+    pub(super) fn create_reader_pool(
+        &self,
+        db_id: uuid::Uuid,
+    ) -> Result<Pool<B::ConnectionManager>, BackendError<B::ConnectionError, B::QueryError>> {
+        // Create connection pool with attached role
+        self.create_connection_pool(db_id)
+            .map_err(BackendError::Build)
+    }
+
     pub(super) fn clean(
         &self,
         db_id: uuid::Uuid,
     ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
+        // Skip cleaning in dry run, as the database was never created
+        if self.is_dry_run() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
         // Get privileged connection to database
-        let mut conn = self.get_database_connection(db_id);
+        let mut conn = DatabaseConnection {
+            backend: self.0,
+            db_id,
+            conn: Some(
+                self.get_database_connection(db_id)
+                    .ok_or(BackendError::MissingDatabaseConnection(db_id))?,
+            ),
+        };
+
+        // Get table names, cached since the schema cannot change
+        let table_names = self
+            .get_cached_table_names(db_id, &mut conn)
+            .map_err(Into::into)?;
 
-        // Get table names
-        let table_names = self.get_table_names(&mut conn).map_err(Into::into)?;
+        // Generate cleaning statements
+        let dialect = self.get_dialect();
+        let stmts = match dialect {
+            PgDialect::Postgres => table_names
+                .iter()
+                .map(|table_name| postgres::truncate_table(table_name.as_str()))
+                .collect::<Vec<_>>(),
+            // Delete from all tables in a single statement, so that foreign keys are only checked once all are emptied
+            PgDialect::CockroachDB => (!table_names.is_empty())
+                .then(|| postgres::delete_from_tables(&table_names))
+                .into_iter()
+                .collect(),
+        };
+        let stmts = stmts.iter().map(|stmt| stmt.as_str().into());
+
+        let in_transaction = self.get_clean_in_transaction();
+        let mut attempt = 0;
+        loop {
+            // Start transaction if needed
+            if in_transaction {
+                self.execute_query(postgres::START_TRANSACTION, &mut conn)?;
+            }
 
-        // Generate truncate statements
-        let stmts = table_names
-            .iter()
-            .map(|table_name| postgres::truncate_table(table_name.as_str()).into());
+            // Clean tables
+            let cleaned = self.batch_execute_query(stmts.clone(), &mut conn);
+
+            // Retry after a backoff on deadlocks if needed
+            match cleaned {
+                Err(err)
+                    if attempt < self.get_clean_retries() && self.is_transient_lock_error(&err) =>
+                {
+                    if in_transaction {
+                        self.execute_query(postgres::ROLLBACK_TRANSACTION, &mut conn)?;
+                    }
+                    attempt += 1;
+                    thread::sleep(get_clean_retry_backoff(attempt));
+                }
+                Err(err) => {
+                    // Roll back so that the connection is stored back outside of a transaction,
+                    // discarding it if rolling back fails
+                    if in_transaction
+                        && self
+                            .execute_query(postgres::ROLLBACK_TRANSACTION, &mut conn)
+                            .is_err()
+                    {
+                        conn.discard();
+                    }
+                    return Err(err.into());
+                }
+                Ok(()) => break,
+            }
+        }
 
-        // Truncate tables
-        self.batch_execute_query(stmts, &mut conn)
-            .map_err(Into::into)?;
+        // Commit transaction if needed
+        if in_transaction {
+            self.execute_query(postgres::COMMIT_TRANSACTION, &mut conn)?;
+        }
+
+        // Find tables left with rows if verifying cleaning
+        let nonempty_table_names = if self.get_verify_clean() && !table_names.is_empty() {
+            self.get_nonempty_table_names(&table_names, &mut conn)
+                .map_err(Into::into)?
+        } else {
+            Vec::new()
+        };
 
         // Store database connection back for reuse
-        self.put_database_connection(db_id, conn);
+        drop(conn);
+
+        if !nonempty_table_names.is_empty() {
+            return Err(BackendError::UncleanDatabase(db_id, nonempty_table_names));
+        }
+
+        // Report time taken and number of tables cleaned if needed
+        self.report_metric(
+            MetricOperation::Clean,
+            db_id,
+            start,
+            Some(table_names.len()),
+        );
 
         Ok(())
     }
@@ -201,25 +965,36 @@ impl<'a, B: PostgresBackend> PostgresBackendWrapper<'a, B> {
         db_id: uuid::Uuid,
         is_restricted: bool,
     ) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
-        // Drop privileged connection to database
+        let start = Instant::now();
+
+        // Drop privileged connection to database and forget its table names
         if is_restricted {
             self.get_database_connection(db_id);
+            self.get_table_names_cache().lock().remove(&db_id);
         }
 
         // Get database name based on UUID
-        let db_name = crate::util::get_db_name(db_id);
+        let db_name = self.get_db_name(db_id);
         let db_name = db_name.as_str();
 
         // Get connection to default database as privileged user
         let conn = &mut self.get_default_connection()?;
 
+        // Terminate lingering connections if needed
+        if self.get_force_drop() {
+            self.terminate_database_connections(db_name, conn)?;
+        }
+
         // Drop database
-        self.execute_query(postgres::drop_database(db_name).as_str(), conn)
-            .map_err(Into::into)?;
+        self.execute_query(postgres::drop_database(db_name).as_str(), conn)?;
 
-        // Drop attached role
-        self.execute_query(postgres::drop_role(db_name).as_str(), conn)
-            .map_err(Into::into)?;
+        // Drop attached role if not reusing an existing one
+        if self.get_reused_role().is_none() {
+            self.execute_query(postgres::drop_role(db_name).as_str(), conn)?;
+        }
+
+        // Report time taken if needed
+        self.report_metric(MetricOperation::Drop, db_id, start, None);
 
         Ok(())
     }
@@ -229,7 +1004,7 @@ impl<'a, B: PostgresBackend> PostgresBackendWrapper<'a, B> {
 pub(super) mod tests {
     #![allow(unused_variables, clippy::unwrap_used)]
 
-    use std::sync::OnceLock;
+    use std::{sync::OnceLock, thread, time::Duration};
 
     use diesel::{
         dsl::exists, insert_into, prelude::*, r2d2::ConnectionManager, select, sql_query, table,
@@ -240,8 +1015,14 @@ pub(super) mod tests {
     use uuid::Uuid;
 
     use crate::{
-        common::statement::postgres::tests::{DDL_STATEMENTS, DML_STATEMENTS},
-        r#sync::{backend::r#trait::Backend, db_pool::DatabasePoolBuilder},
+        common::statement::postgres::{
+            self,
+            tests::{DDL_STATEMENTS, DML_STATEMENTS},
+        },
+        r#sync::{
+            backend::{r#trait::Backend, Error as BackendError},
+            db_pool::DatabasePoolBuilder,
+        },
         tests::{get_privileged_postgres_config, PG_DROP_LOCK},
         util::get_db_name,
     };
@@ -255,6 +1036,12 @@ pub(super) mod tests {
         }
     }
 
+    table! {
+        pg_roles (rolname) {
+            rolname -> Text
+        }
+    }
+
     fn lock_drop<'a>() -> RwLockWriteGuard<'a, ()> {
         PG_DROP_LOCK.blocking_write()
     }
@@ -302,6 +1089,26 @@ pub(super) mod tests {
             .unwrap()
     }
 
+    fn create_role(conn: &mut PgConnection) -> String {
+        let role_name = get_db_name(Uuid::new_v4());
+        sql_query(format!("CREATE ROLE {role_name}"))
+            .execute(conn)
+            .unwrap();
+        role_name
+    }
+
+    fn create_roles(count: i64, conn: &mut PgConnection) -> Vec<String> {
+        (0..count).map(|_| create_role(conn)).collect()
+    }
+
+    fn count_roles(role_names: &Vec<String>, conn: &mut PgConnection) -> i64 {
+        pg_roles::table
+            .filter(pg_roles::rolname.eq_any(role_names))
+            .count()
+            .get_result(conn)
+            .unwrap()
+    }
+
     fn count_all_databases(conn: &mut PgConnection) -> i64 {
         pg_database::table
             .filter(pg_database::datname.like("db_pool_%"))
@@ -320,6 +1127,7 @@ pub(super) mod tests {
 
     pub fn test_backend_drops_previous_databases<B: Backend>(default: B, enabled: B, disabled: B) {
         const NUM_DBS: i64 = 3;
+        const NUM_ROLES: i64 = 3;
 
         let conn_pool = get_privileged_connection_pool();
         let conn = &mut conn_pool.get().unwrap();
@@ -328,15 +1136,159 @@ pub(super) mod tests {
 
         for (backend, cleans) in [(default, true), (enabled, true), (disabled, false)] {
             let db_names = create_databases(NUM_DBS, conn);
+            let role_names = create_roles(NUM_ROLES, conn);
             assert_eq!(count_databases(&db_names, conn), NUM_DBS);
+            assert_eq!(count_roles(&role_names, conn), NUM_ROLES);
             backend.init().unwrap();
             assert_eq!(
                 count_databases(&db_names, conn),
                 if cleans { 0 } else { NUM_DBS }
             );
+            // orphaned roles must be dropped along with databases
+            assert_eq!(
+                count_roles(&role_names, conn),
+                if cleans { 0 } else { NUM_ROLES }
+            );
         }
     }
 
+    pub fn test_backend_waits_for_initialization_lock<B: Backend>(backend: B) {
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_drop();
+
+        // hold the initialization lock as another initializer would
+        sql_query(postgres::LOCK_INITIALIZATION)
+            .execute(conn)
+            .unwrap();
+
+        let handle = thread::spawn(move || backend.init().is_ok());
+
+        // initialization must wait for the lock to be released
+        thread::sleep(Duration::from_millis(500));
+        assert!(!handle.is_finished());
+
+        sql_query(postgres::UNLOCK_INITIALIZATION)
+            .execute(conn)
+            .unwrap();
+
+        assert!(handle.join().unwrap());
+    }
+
+    pub fn test_backend_only_drops_owned_previous_databases(
+        default: &impl Backend,
+        enabled: &impl Backend,
+    ) {
+        const NUM_DBS: i64 = 3;
+        const NUM_ROLES: i64 = 3;
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_drop();
+
+        // databases and roles not created by a backend
+        let db_names = create_databases(NUM_DBS, conn);
+        let role_names = create_roles(NUM_ROLES, conn);
+
+        // database and role created by a backend
+        let db_id = Uuid::new_v4();
+        let owned_db_names = vec![get_db_name(db_id)];
+        drop(default.create(db_id, false).unwrap());
+
+        enabled.init().unwrap();
+
+        // databases and roles not created by a backend must be kept
+        assert_eq!(count_databases(&db_names, conn), NUM_DBS);
+        assert_eq!(count_roles(&role_names, conn), NUM_ROLES);
+
+        // database created by a backend must be dropped along with its role
+        assert_eq!(count_databases(&owned_db_names, conn), 0);
+        assert_eq!(count_roles(&owned_db_names, conn), 0);
+
+        // remaining databases and roles must be dropped by default
+        default.init().unwrap();
+        assert_eq!(count_databases(&db_names, conn), 0);
+        assert_eq!(count_roles(&role_names, conn), 0);
+    }
+
+    pub fn test_backend_drops_previous_databases_with_custom_query(
+        default: &impl Backend,
+        custom: &impl Backend,
+    ) {
+        const NUM_DBS: i64 = 3;
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_drop();
+
+        // databases selected by custom query, marked with schema version "custom"
+        let selected_db_names = create_databases(NUM_DBS, conn);
+        for db_name in &selected_db_names {
+            sql_query(postgres::comment_database(db_name.as_str(), Some("custom")))
+                .execute(conn)
+                .unwrap();
+        }
+
+        // databases not selected by custom query
+        let db_names = create_databases(NUM_DBS, conn);
+
+        // only databases selected by custom query must be dropped
+        custom.init().unwrap();
+        assert_eq!(count_databases(&selected_db_names, conn), 0);
+        assert_eq!(count_databases(&db_names, conn), NUM_DBS);
+
+        // remaining databases must be dropped by default
+        default.init().unwrap();
+        assert_eq!(count_databases(&db_names, conn), 0);
+    }
+
+    pub fn test_backend_keeps_previous_databases_with_schema_version(
+        current: &impl Backend,
+        changed: &impl Backend,
+    ) {
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_drop();
+
+        // database created by a backend with the current schema version
+        let db_id = Uuid::new_v4();
+        let db_names = vec![get_db_name(db_id)];
+        drop(current.create(db_id, true).unwrap());
+
+        // database marked with the current schema version must be kept along with its role
+        current.init().unwrap();
+        assert_eq!(count_databases(&db_names, conn), 1);
+        assert_eq!(count_roles(&db_names, conn), 1);
+
+        // database must be dropped along with its role once the schema version changes
+        changed.init().unwrap();
+        assert_eq!(count_databases(&db_names, conn), 0);
+        assert_eq!(count_roles(&db_names, conn), 0);
+    }
+
+    pub fn test_backend_keeps_group_role_of_kept_databases(backend: &impl Backend) {
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_drop();
+
+        // database kept by its schema version, with privileges granted to the group role
+        backend.init().unwrap();
+        let db_id = Uuid::new_v4();
+        let db_names = vec![get_db_name(db_id)];
+        drop(backend.create(db_id, true).unwrap());
+
+        // group role must be kept while dropping previous databases
+        backend.init().unwrap();
+        assert_eq!(count_databases(&db_names, conn), 1);
+
+        backend.drop(db_id, true).unwrap();
+    }
+
     pub fn test_backend_creates_database_with_restricted_privileges(backend: &impl Backend) {
         let db_id = Uuid::new_v4();
         let db_name = get_db_name(db_id);
@@ -421,6 +1373,153 @@ pub(super) mod tests {
         }
     }
 
+    pub fn test_backend_recreates_database_on_conflict(
+        default: &impl Backend,
+        enabled: &impl Backend,
+    ) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_read();
+
+        // leave database and role behind as if from a previous run
+        sql_query(format!("CREATE DATABASE {db_name}"))
+            .execute(conn)
+            .unwrap();
+        sql_query(format!("CREATE ROLE {db_name}"))
+            .execute(conn)
+            .unwrap();
+
+        // creating conflicting database must fail by default
+        default.init().unwrap();
+        assert!(default.create(db_id, true).is_err());
+        assert!(database_exists(db_name, conn));
+
+        // conflicting database must be recreated if enabled
+        enabled.init().unwrap();
+        enabled.create(db_id, true).unwrap();
+        assert!(database_exists(db_name, conn));
+
+        // recreated database must be usable by recreated role
+        {
+            let conn_pool = &mut create_restricted_connection_pool(db_name);
+            let conn = &mut conn_pool.get().unwrap();
+
+            for stmt in DML_STATEMENTS {
+                assert!(sql_query(stmt).execute(conn).is_ok());
+            }
+        }
+
+        enabled.drop(db_id, true).unwrap();
+    }
+
+    pub fn test_backend_creates_entities_as_restricted_role(backend: &impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let guard = lock_read();
+
+        backend.init().unwrap();
+        backend.create(db_id, true).unwrap();
+
+        {
+            let conn_pool = &mut create_restricted_connection_pool(db_name);
+            let conn = &mut conn_pool.get().unwrap();
+
+            // restricted role must not be able to create further entities
+            assert!(sql_query("CREATE TABLE author(id SERIAL PRIMARY KEY)")
+                .execute(conn)
+                .is_err());
+
+            // restricted role must be able to drop entities it owns
+            assert!(sql_query("DROP TABLE book").execute(conn).is_ok());
+        }
+
+        backend.drop(db_id, true).unwrap();
+    }
+
+    pub fn test_backend_creates_database_owned_by_role(backend: &impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_read();
+
+        // leave database owned by role behind as if from a previous run
+        sql_query(format!("CREATE ROLE {db_name}"))
+            .execute(conn)
+            .unwrap();
+        sql_query(format!("CREATE DATABASE {db_name} OWNER {db_name}"))
+            .execute(conn)
+            .unwrap();
+
+        // conflicting database and role must be recreated
+        backend.init().unwrap();
+        backend.create(db_id, false).unwrap();
+
+        // database must be owned by recreated role
+        {
+            let conn_pool = &mut create_restricted_connection_pool(db_name);
+            let conn = &mut conn_pool.get().unwrap();
+
+            assert!(sql_query(postgres::comment_database(db_name, None))
+                .execute(conn)
+                .is_ok());
+        }
+
+        backend.drop(db_id, false).unwrap();
+    }
+
+    pub fn test_backend_recreates_role_on_conflict(default: &impl Backend, enabled: &impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_read();
+
+        // leave role behind as if from a previous run
+        sql_query(format!("CREATE ROLE {db_name}"))
+            .execute(conn)
+            .unwrap();
+
+        // creating conflicting role must fail by default
+        default.init().unwrap();
+        assert!(matches!(
+            default.create(db_id, true),
+            Err(BackendError::RoleExists(role_name)) if role_name == db_name
+        ));
+        sql_query(postgres::drop_database(db_name))
+            .execute(conn)
+            .unwrap();
+
+        // conflicting role must be recreated if enabled
+        enabled.init().unwrap();
+        enabled.create(db_id, true).unwrap();
+
+        // database must be usable by recreated role
+        {
+            let conn_pool = &mut create_restricted_connection_pool(db_name);
+            let conn = &mut conn_pool.get().unwrap();
+
+            for stmt in DML_STATEMENTS {
+                assert!(sql_query(stmt).execute(conn).is_ok());
+            }
+        }
+
+        enabled.drop(db_id, true).unwrap();
+    }
+
     pub fn test_backend_cleans_database_with_tables(backend: &impl Backend) {
         const NUM_BOOKS: i64 = 3;
 
@@ -501,6 +1600,47 @@ pub(super) mod tests {
         assert!(!database_exists(db_name, conn));
     }
 
+    pub fn test_backend_fails_to_clean_unrestricted_database(backend: &impl Backend) {
+        let db_id = Uuid::new_v4();
+
+        let guard = lock_read();
+
+        backend.init().unwrap();
+        backend.create(db_id, false).unwrap();
+
+        // cleaning must fail without a stored privileged connection
+        assert!(matches!(
+            backend.clean(db_id),
+            Err(BackendError::MissingDatabaseConnection(id)) if id == db_id
+        ));
+
+        backend.drop(db_id, false).unwrap();
+    }
+
+    pub fn test_backend_force_drops_database_with_lingering_connection(backend: &impl Backend) {
+        let db_id = Uuid::new_v4();
+        let db_name = get_db_name(db_id);
+        let db_name = db_name.as_str();
+
+        let conn_pool = get_privileged_connection_pool();
+        let conn = &mut conn_pool.get().unwrap();
+
+        let guard = lock_read();
+
+        // database must exist
+        backend.init().unwrap();
+        backend.create(db_id, true).unwrap();
+        assert!(database_exists(db_name, conn));
+
+        // open connection that is never closed
+        let restricted_conn_pool = create_restricted_connection_pool(db_name);
+        let restricted_conn = restricted_conn_pool.get().unwrap();
+
+        // database must not exist
+        backend.drop(db_id, true).unwrap();
+        assert!(!database_exists(db_name, conn));
+    }
+
     pub fn test_pool_drops_previous_databases<B: Backend>(default: B, enabled: B, disabled: B) {
         const NUM_DBS: i64 = 3;
 
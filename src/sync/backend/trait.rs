@@ -17,6 +17,9 @@ pub trait Backend: Sized + Send + Sync + 'static {
     /// Initializes the backend
     fn init(&self) -> Result<(), Error<Self::ConnectionError, Self::QueryError>>;
 
+    /// Checks that the database server is reachable by running a trivial query as the privileged user
+    fn ping(&self) -> Result<(), Error<Self::ConnectionError, Self::QueryError>>;
+
     /// Creates a database
     #[allow(clippy::complexity)]
     fn create(
@@ -25,6 +28,13 @@ pub trait Backend: Sized + Send + Sync + 'static {
         restrict_privileges: bool,
     ) -> Result<Pool<Self::ConnectionManager>, Error<Self::ConnectionError, Self::QueryError>>;
 
+    /// Creates an additional connection pool to an existing restricted database for its attached user
+    #[allow(clippy::complexity)]
+    fn create_reader_pool(
+        &self,
+        db_id: Uuid,
+    ) -> Result<Pool<Self::ConnectionManager>, Error<Self::ConnectionError, Self::QueryError>>;
+
     /// Cleans a database
     fn clean(&self, db_id: Uuid) -> Result<(), Error<Self::ConnectionError, Self::QueryError>>;
 
@@ -34,4 +44,18 @@ pub trait Backend: Sized + Send + Sync + 'static {
         db_id: Uuid,
         is_restricted: bool,
     ) -> Result<(), Error<Self::ConnectionError, Self::QueryError>>;
+
+    /// Returns the name of a database
+    fn get_database_name(&self, db_id: Uuid) -> String;
+
+    /// Returns the connection URL of a database for its attached user
+    ///
+    /// Defaults to [`None`] for backends not exposing connection URLs.
+    fn get_database_url(&self, db_id: Uuid) -> Option<String> {
+        let _ = db_id;
+        None
+    }
+
+    /// Returns the privileged connection pool, for running maintenance queries as the privileged user
+    fn privileged_pool(&self) -> &Pool<Self::ConnectionManager>;
 }
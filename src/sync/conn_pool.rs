@@ -1,15 +1,43 @@
-use std::{ops::Deref, sync::Arc};
-
+use std::{
+    marker::PhantomData,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use parking_lot::Mutex;
 use r2d2::Pool;
 use uuid::Uuid;
 
+use crate::common::privileges::{Privileges, Restricted, Unrestricted};
+
 use super::backend::{r#trait::Backend, Error as BackendError};
 
 struct ConnectionPool<B: Backend> {
     backend: Arc<B>,
     db_id: Uuid,
+    db_name: String,
     conn_pool: Option<Pool<B::ConnectionManager>>,
+    reader_pool: Option<Pool<B::ConnectionManager>>,
     is_restricted: bool,
+    keep_on_panic: Arc<AtomicBool>,
+}
+
+impl<B: Backend> ConnectionPool<B> {
+    fn database_id(&self) -> Uuid {
+        self.db_id
+    }
+
+    fn database_name(&self) -> &str {
+        self.db_name.as_str()
+    }
+
+    fn database_url(&self) -> Option<String> {
+        self.backend.get_database_url(self.db_id)
+    }
 }
 
 impl<B: Backend> Deref for ConnectionPool<B> {
@@ -25,34 +53,102 @@ impl<B: Backend> Deref for ConnectionPool<B> {
 impl<B: Backend> Drop for ConnectionPool<B> {
     fn drop(&mut self) {
         self.conn_pool = None;
+        self.reader_pool = None;
+
+        // Keep database for inspection if dropped while unwinding from a panic
+        if thread::panicking() && self.keep_on_panic.load(Ordering::Relaxed) {
+            return;
+        }
+
         (*self.backend).drop(self.db_id, self.is_restricted).ok();
     }
 }
 
 /// Reusable connection pool wrapper
-pub struct ReusableConnectionPool<B: Backend>(ConnectionPool<B>);
-
-impl<B: Backend> ReusableConnectionPool<B> {
+///
+/// Its type parameter tracks the privileges granted on the database.
+pub struct ReusableConnectionPool<B: Backend, P: Privileges = Restricted>(
+    ConnectionPool<B>,
+    PhantomData<P>,
+);
+
+impl<B: Backend, P: Privileges> ReusableConnectionPool<B, P> {
     pub(crate) fn new(
         backend: Arc<B>,
+        keep_on_panic: Arc<AtomicBool>,
+        separate_reader_pool: &AtomicBool,
     ) -> Result<Self, BackendError<B::ConnectionError, B::QueryError>> {
         let db_id = Uuid::new_v4();
-        let conn_pool = backend.create(db_id, true)?;
-
-        Ok(Self(ConnectionPool {
-            backend,
-            db_id,
-            conn_pool: Some(conn_pool),
-            is_restricted: true,
-        }))
+        let conn_pool = backend.create(db_id, P::IS_RESTRICTED)?;
+        let reader_pool = if separate_reader_pool.load(Ordering::Relaxed) {
+            match backend.create_reader_pool(db_id) {
+                Ok(reader_pool) => Some(reader_pool),
+                Err(err) => {
+                    drop(conn_pool);
+                    (*backend).drop(db_id, P::IS_RESTRICTED).ok();
+                    return Err(err);
+                }
+            }
+        } else {
+            None
+        };
+        let db_name = backend.get_database_name(db_id);
+
+        Ok(Self(
+            ConnectionPool {
+                backend,
+                db_id,
+                db_name,
+                conn_pool: Some(conn_pool),
+                reader_pool,
+                is_restricted: P::IS_RESTRICTED,
+                keep_on_panic,
+            },
+            PhantomData,
+        ))
     }
 
     pub(crate) fn clean(&mut self) -> Result<(), BackendError<B::ConnectionError, B::QueryError>> {
         self.0.backend.clean(self.0.db_id)
     }
+
+    /// Returns the ID of the database, from which its name is derived
+    #[must_use]
+    pub fn database_id(&self) -> Uuid {
+        self.0.database_id()
+    }
+
+    /// Returns the name of the database
+    #[must_use]
+    pub fn database_name(&self) -> &str {
+        self.0.database_name()
+    }
+
+    /// Returns the connection URL of the database for its attached user
+    ///
+    /// [`None`] if the backend does not expose connection URLs.
+    #[must_use]
+    pub fn database_url(&self) -> Option<String> {
+        self.0.database_url()
+    }
+
+    /// Returns the connection pool to write to the database through
+    #[must_use]
+    pub fn writer(&self) -> &Pool<B::ConnectionManager> {
+        &self.0
+    }
+
+    /// Returns the connection pool to read from the database through
+    ///
+    /// This is a distinct pool to the same database if separate reader pools are enabled on the database pool,
+    /// and the writer pool otherwise.
+    #[must_use]
+    pub fn reader(&self) -> &Pool<B::ConnectionManager> {
+        self.0.reader_pool.as_ref().unwrap_or_else(|| self.writer())
+    }
 }
 
-impl<B: Backend> Deref for ReusableConnectionPool<B> {
+impl<B: Backend, P: Privileges> Deref for ReusableConnectionPool<B, P> {
     type Target = Pool<B::ConnectionManager>;
 
     fn deref(&self) -> &Self::Target {
@@ -61,28 +157,84 @@ impl<B: Backend> Deref for ReusableConnectionPool<B> {
 }
 
 /// Single-use connection pool wrapper
-pub struct SingleUseConnectionPool<B: Backend>(ConnectionPool<B>);
-
-impl<B: Backend> SingleUseConnectionPool<B> {
+///
+/// Its type parameter tracks the privileges granted on the database.
+pub struct SingleUseConnectionPool<B: Backend, P: Privileges = Unrestricted>(
+    ConnectionPool<B>,
+    Arc<Mutex<Vec<Uuid>>>,
+    PhantomData<P>,
+);
+
+impl<B: Backend, P: Privileges> SingleUseConnectionPool<B, P> {
     pub(crate) fn new(
         backend: Arc<B>,
+        keep_on_panic: Arc<AtomicBool>,
+        leased: Arc<Mutex<Vec<Uuid>>>,
     ) -> Result<Self, BackendError<B::ConnectionError, B::QueryError>> {
         let db_id = Uuid::new_v4();
-        let conn_pool = backend.create(db_id, false)?;
+        let conn_pool = backend.create(db_id, P::IS_RESTRICTED)?;
+        let db_name = backend.get_database_name(db_id);
+
+        leased.lock().push(db_id);
+
+        Ok(Self(
+            ConnectionPool {
+                backend,
+                db_id,
+                db_name,
+                conn_pool: Some(conn_pool),
+                reader_pool: None,
+                is_restricted: P::IS_RESTRICTED,
+                keep_on_panic,
+            },
+            leased,
+            PhantomData,
+        ))
+    }
 
-        Ok(Self(ConnectionPool {
-            backend,
-            db_id,
-            conn_pool: Some(conn_pool),
-            is_restricted: false,
-        }))
+    /// Returns the ID of the database, from which its name is derived
+    #[must_use]
+    pub fn database_id(&self) -> Uuid {
+        self.0.database_id()
+    }
+
+    /// Returns the name of the database
+    #[must_use]
+    pub fn database_name(&self) -> &str {
+        self.0.database_name()
+    }
+
+    /// Returns the connection URL of the database for its attached user
+    ///
+    /// [`None`] if the backend does not expose connection URLs.
+    #[must_use]
+    pub fn database_url(&self) -> Option<String> {
+        self.0.database_url()
+    }
+}
+
+impl<B: Backend> SingleUseConnectionPool<B, Unrestricted> {
+    /// Returns the connection pool, asserting at compile time that DDL statements can be run through it
+    ///
+    /// Only available on unrestricted connection pools, so that helpers running DDL statements
+    /// can require one by calling it.
+    #[must_use]
+    pub fn assert_can_ddl(&self) -> &Pool<B::ConnectionManager> {
+        &self.0
     }
 }
 
-impl<B: Backend> Deref for SingleUseConnectionPool<B> {
+impl<B: Backend, P: Privileges> Deref for SingleUseConnectionPool<B, P> {
     type Target = Pool<B::ConnectionManager>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
+
+impl<B: Backend, P: Privileges> Drop for SingleUseConnectionPool<B, P> {
+    fn drop(&mut self) {
+        let db_id = self.0.db_id;
+        self.1.lock().retain(|leased_db_id| *leased_db_id != db_id);
+    }
+}
@@ -1,4 +1,15 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use crate::common::{
+    config::CleanMode,
+    privileges::{Restricted, Unrestricted},
+};
 
 use super::{
     backend::{r#trait::Backend, Error},
@@ -7,12 +18,15 @@ use super::{
 };
 
 /// Wrapper for a reusable connection pool wrapped in a reusable object wrapper
-pub type ReusableConnectionPool<'a, B> = Reusable<'a, ReusableConnectionPoolInner<B>>;
+pub type ReusableConnectionPool<'a, B> = Reusable<'a, ReusableConnectionPoolInner<B, Restricted>>;
 
 /// Database pool
 pub struct DatabasePool<B: Backend> {
     backend: Arc<B>,
     object_pool: ObjectPool<ReusableConnectionPoolInner<B>>,
+    keep_on_panic: Arc<AtomicBool>,
+    separate_reader_pool: Arc<AtomicBool>,
+    leased: Arc<Mutex<Vec<Uuid>>>,
 }
 
 impl<B: Backend> DatabasePool<B> {
@@ -53,6 +67,141 @@ impl<B: Backend> DatabasePool<B> {
         self.object_pool.pull()
     }
 
+    /// Sets what happens to the database of a reusable connection pool once the connection pool is returned
+    ///
+    /// Databases are cleaned and reused by default.
+    /// # Example
+    /// ```
+    /// use db_pool::{
+    ///     sync::{DatabasePoolBuilderTrait, DieselPostgresBackend},
+    ///     CleanMode, PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_pool = backend
+    ///     .create_database_pool()
+    ///     .unwrap()
+    ///     .clean_mode(CleanMode::Drop);
+    /// let conn_pool = db_pool.pull_immutable();
+    /// ```
+    #[must_use]
+    pub fn clean_mode(self, value: CleanMode) -> Self {
+        Self {
+            object_pool: self.object_pool.detach_on_return(value == CleanMode::Drop),
+            ..self
+        }
+    }
+
+    /// Keeps databases of connection pools dropped while panicking, such as in failed tests
+    ///
+    /// A kept database is not dropped, cleaned, or reused, and can be found for inspection by its name.
+    /// # Example
+    /// ```
+    /// use db_pool::{
+    ///     sync::{DatabasePoolBuilderTrait, DieselPostgresBackend},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_pool = backend.create_database_pool().unwrap().keep_on_panic(true);
+    /// let conn_pool = db_pool.pull_immutable();
+    /// ```
+    #[must_use]
+    pub fn keep_on_panic(self, value: bool) -> Self {
+        self.keep_on_panic.store(value, Ordering::Relaxed);
+        Self {
+            object_pool: self.object_pool.detach_on_panic(value),
+            ..self
+        }
+    }
+
+    /// Gives each reusable connection pool a distinct reader pool to the same database
+    ///
+    /// Allows tests to read through `reader` and write through `writer`
+    /// as they would against a read replica, with both pools connecting to the same database.
+    /// Without this, both accessors return the same pool.
+    /// Only applies to connection pools created afterwards.
+    /// # Example
+    /// ```
+    /// use db_pool::{
+    ///     sync::{DatabasePoolBuilderTrait, DieselPostgresBackend},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_pool = backend
+    ///     .create_database_pool()
+    ///     .unwrap()
+    ///     .separate_reader_pool(true);
+    /// let conn_pool = db_pool.pull_immutable();
+    ///
+    /// sql_query("INSERT INTO book (title) VALUES ('Title')")
+    ///     .execute(&mut conn_pool.writer().get().unwrap())
+    ///     .unwrap();
+    /// sql_query("SELECT title FROM book")
+    ///     .execute(&mut conn_pool.reader().get().unwrap())
+    ///     .unwrap();
+    /// ```
+    #[must_use]
+    pub fn separate_reader_pool(self, value: bool) -> Self {
+        self.separate_reader_pool.store(value, Ordering::Relaxed);
+        self
+    }
+
     /// Creates a single-use connection pool
     ///
     /// All privileges are granted.
@@ -87,8 +236,53 @@ impl<B: Backend> DatabasePool<B> {
     /// ```
     pub fn create_mutable(
         &self,
-    ) -> Result<SingleUseConnectionPool<B>, Error<B::ConnectionError, B::QueryError>> {
-        SingleUseConnectionPool::new(self.backend.clone())
+    ) -> Result<SingleUseConnectionPool<B, Unrestricted>, Error<B::ConnectionError, B::QueryError>>
+    {
+        SingleUseConnectionPool::new(
+            self.backend.clone(),
+            self.keep_on_panic.clone(),
+            self.leased.clone(),
+        )
+    }
+
+    /// Returns the IDs of databases currently leased through pulled and created connection pools
+    ///
+    /// The IDs are a snapshot taken at the time of the call, meant for diagnosing tests stuck on held databases.
+    /// # Example
+    /// ```
+    /// use db_pool::{
+    ///     sync::{DatabasePoolBuilderTrait, DieselPostgresBackend},
+    ///     PrivilegedPostgresConfig,
+    /// };
+    /// use diesel::{sql_query, RunQueryDsl};
+    /// use dotenvy::dotenv;
+    /// use r2d2::Pool;
+    ///
+    /// dotenv().ok();
+    ///
+    /// let config = PrivilegedPostgresConfig::from_env().unwrap();
+    ///
+    /// let backend = DieselPostgresBackend::new(
+    ///     config,
+    ///     || Pool::builder().max_size(10),
+    ///     || Pool::builder().max_size(2),
+    ///     move |conn| {
+    ///         sql_query("CREATE TABLE book(id SERIAL PRIMARY KEY, title TEXT NOT NULL)")
+    ///             .execute(conn)
+    ///             .unwrap();
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_pool = backend.create_database_pool().unwrap();
+    /// let conn_pool = db_pool.pull_immutable();
+    /// let db_ids = db_pool.leased_databases();
+    /// ```
+    #[must_use]
+    pub fn leased_databases(&self) -> Vec<Uuid> {
+        let mut db_ids = self.object_pool.leased();
+        db_ids.extend(self.leased.lock().iter().copied());
+        db_ids
     }
 }
 
@@ -128,12 +322,17 @@ pub trait DatabasePoolBuilder: Backend {
     ) -> Result<DatabasePool<Self>, Error<Self::ConnectionError, Self::QueryError>> {
         self.init()?;
         let backend = Arc::new(self);
+        let keep_on_panic = Arc::new(AtomicBool::new(false));
+        let separate_reader_pool = Arc::new(AtomicBool::new(false));
         let object_pool = {
             let backend = backend.clone();
+            let keep_on_panic = keep_on_panic.clone();
+            let separate_reader_pool = separate_reader_pool.clone();
             ObjectPool::new(
                 move || {
                     let backend = backend.clone();
-                    ReusableConnectionPoolInner::new(backend)
+                    let keep_on_panic = keep_on_panic.clone();
+                    ReusableConnectionPoolInner::new(backend, keep_on_panic, &separate_reader_pool)
                         .expect("connection pool creation must succeed")
                 },
                 |conn_pool| {
@@ -145,7 +344,10 @@ pub trait DatabasePoolBuilder: Backend {
         };
         Ok(DatabasePool {
             backend,
-            object_pool,
+            object_pool: object_pool.track_leases(ReusableConnectionPoolInner::database_id),
+            keep_on_panic,
+            separate_reader_pool,
+            leased: Arc::new(Mutex::new(Vec::new())),
         })
     }
 }
@@ -1,17 +1,26 @@
 // adapted from https://github.com/CJP10/object-pool and https://github.com/EVaillant/lockfree-object-pool
 
 use parking_lot::Mutex;
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    thread,
+};
+use uuid::Uuid;
 
 type Stack<T> = Vec<T>;
 type Init<T> = Box<dyn Fn() -> T + Send + Sync + 'static>;
 type Reset<T> = Box<dyn Fn(&mut T) + Send + Sync + 'static>;
+type LeaseKey<T> = fn(&T) -> Uuid;
 
 /// Object pool
 pub struct ObjectPool<T> {
     objects: Mutex<Stack<T>>,
     init: Init<T>,
     reset: Reset<T>,
+    detach_on_panic: bool,
+    detach_on_return: bool,
+    lease_key: Option<LeaseKey<T>>,
+    leased: Mutex<Vec<Uuid>>,
 }
 
 impl<T> ObjectPool<T> {
@@ -23,15 +32,44 @@ impl<T> ObjectPool<T> {
             objects: Mutex::new(Vec::new()),
             init: Box::new(init),
             reset: Box::new(reset),
+            detach_on_panic: false,
+            detach_on_return: false,
+            lease_key: None,
+            leased: Mutex::new(Vec::new()),
         }
     }
 
+    pub(crate) fn detach_on_panic(self, value: bool) -> Self {
+        Self {
+            detach_on_panic: value,
+            ..self
+        }
+    }
+
+    pub(crate) fn detach_on_return(self, value: bool) -> Self {
+        Self {
+            detach_on_return: value,
+            ..self
+        }
+    }
+
+    pub(crate) fn track_leases(self, lease_key: LeaseKey<T>) -> Self {
+        Self {
+            lease_key: Some(lease_key),
+            ..self
+        }
+    }
+
+    pub(crate) fn leased(&self) -> Vec<Uuid> {
+        self.leased.lock().clone()
+    }
+
     pub(crate) fn pull(&self) -> Reusable<T> {
         self.objects.lock().pop().map_or_else(
-            || Reusable::new(self, (self.init)()),
+            || Reusable::new(self, (self.init)(), false),
             |mut data| {
                 (self.reset)(&mut data);
-                Reusable::new(self, data)
+                Reusable::new(self, data, true)
             },
         )
     }
@@ -39,21 +77,43 @@ impl<T> ObjectPool<T> {
     fn attach(&self, t: T) {
         self.objects.lock().push(t);
     }
+
+    fn lease(&self, t: &T) {
+        if let Some(lease_key) = self.lease_key {
+            self.leased.lock().push(lease_key(t));
+        }
+    }
+
+    fn release(&self, t: &T) {
+        if let Some(lease_key) = self.lease_key {
+            let key = lease_key(t);
+            self.leased.lock().retain(|leased_key| *leased_key != key);
+        }
+    }
 }
 
 /// Reusable object wrapper
 pub struct Reusable<'a, T> {
     pool: &'a ObjectPool<T>,
     data: Option<T>,
+    reused: bool,
 }
 
 impl<'a, T> Reusable<'a, T> {
-    fn new(pool: &'a ObjectPool<T>, t: T) -> Self {
+    fn new(pool: &'a ObjectPool<T>, t: T, reused: bool) -> Self {
+        pool.lease(&t);
         Self {
             pool,
             data: Some(t),
+            reused,
         }
     }
+
+    /// Returns whether the object was taken from the pool rather than newly created when pulled
+    #[must_use]
+    pub fn was_reused(&self) -> bool {
+        self.reused
+    }
 }
 
 const DATA_MUST_CONTAIN_SOME: &str = "data must always contain a [Some] value";
@@ -74,15 +134,22 @@ impl<'a, T> DerefMut for Reusable<'a, T> {
 
 impl<'a, T> Drop for Reusable<'a, T> {
     fn drop(&mut self) {
-        self.pool
-            .attach(self.data.take().expect(DATA_MUST_CONTAIN_SOME));
+        let data = self.data.take().expect(DATA_MUST_CONTAIN_SOME);
+        self.pool.release(&data);
+
+        // Drop object instead of returning it to the pool if always detached
+        // or if dropped while unwinding from a panic
+        if !(self.pool.detach_on_return || self.pool.detach_on_panic && thread::panicking()) {
+            self.pool.attach(data);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ObjectPool;
-    use std::mem::drop;
+    use std::{mem::drop, panic};
+    use uuid::Uuid;
 
     impl<T> ObjectPool<T> {
         fn len(&self) -> usize {
@@ -134,6 +201,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn detach_on_panic() {
+        for (detach_on_panic, len) in [(false, 1), (true, 0)] {
+            let pool =
+                ObjectPool::<Vec<u8>>::new(Vec::new, |_| {}).detach_on_panic(detach_on_panic);
+
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                let _object = pool.pull();
+                panic!("object must be dropped while panicking");
+            }));
+
+            assert!(result.is_err());
+
+            assert_eq!(pool.len(), len);
+        }
+    }
+
+    #[test]
+    fn detach_on_return() {
+        for (detach_on_return, len) in [(false, 1), (true, 0)] {
+            let pool =
+                ObjectPool::<Vec<u8>>::new(Vec::new, |_| {}).detach_on_return(detach_on_return);
+
+            let object = pool.pull();
+            drop(object);
+
+            assert_eq!(pool.len(), len);
+        }
+    }
+
+    #[test]
+    fn was_reused() {
+        let pool = ObjectPool::<Vec<u8>>::new(Vec::new, |_| {});
+
+        let object = pool.pull();
+        assert!(!object.was_reused());
+        drop(object);
+
+        let object = pool.pull();
+        assert!(object.was_reused());
+
+        // object created while the other is held must be new
+        assert!(!pool.pull().was_reused());
+    }
+
     #[test]
     fn reset() {
         let pool = ObjectPool::new(Vec::new, Vec::clear);
@@ -155,4 +267,20 @@ mod tests {
         let object = pool.pull();
         assert_eq!(object.len(), 1);
     }
+
+    #[test]
+    fn track_leases() {
+        let pool = ObjectPool::new(Uuid::new_v4, |_| {}).track_leases(|id| *id);
+
+        let object1 = pool.pull();
+        let object2 = pool.pull();
+        assert_eq!(pool.leased(), vec![*object1, *object2]);
+
+        let id2 = *object2;
+        drop(object1);
+        assert_eq!(pool.leased(), vec![id2]);
+
+        drop(object2);
+        assert!(pool.leased().is_empty());
+    }
 }
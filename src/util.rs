@@ -1,5 +1,165 @@
+#[cfg(any(feature = "_sync", feature = "_async"))]
+use std::time::Duration;
+use std::{borrow::Cow, collections::HashMap};
+
+use parking_lot::Mutex;
 use uuid::Uuid;
 
+/// Delay before the first retry of a clean failing on a transient lock error, growing linearly with each retry
+#[cfg(any(feature = "_sync", feature = "_async"))]
+const CLEAN_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+#[cfg(any(feature = "_sync", feature = "_async"))]
+pub(crate) fn get_clean_retry_backoff(attempt: usize) -> Duration {
+    CLEAN_RETRY_BACKOFF.saturating_mul(u32::try_from(attempt).unwrap_or(u32::MAX))
+}
+
+/// Prefix of names of databases created by backends
+pub(crate) const DB_NAME_PREFIX: &str = "db_pool_";
+
+/// Query selecting the names of databases created in previous runs, given the database name prefix
+#[cfg(any(feature = "_sync", feature = "_async"))]
+pub(crate) type PreviousDatabaseQuery = dyn Fn(&str) -> String + Send + Sync + 'static;
+
+/// Table names of restricted databases, whose schemas cannot change once created
+pub(crate) type TableNamesCache = Mutex<HashMap<Uuid, Vec<String>>>;
+
 pub fn get_db_name(id: Uuid) -> String {
-    format!("db_pool_{}", id.to_string().replace('-', "_"))
+    format!("{DB_NAME_PREFIX}{}", id.to_string().replace('-', "_"))
+}
+
+pub(crate) fn is_valid_db_name(db_name: &str, max_length: usize) -> bool {
+    db_name.len() <= max_length
+        && db_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && db_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Clone)]
+pub(crate) struct ReusedLogin {
+    pub(crate) name: String,
+    pub(crate) password: String,
+}
+
+pub(crate) fn get_credentials<'a>(
+    reused_login: Option<&'a ReusedLogin>,
+    db_name: &'a str,
+) -> (&'a str, &'a str) {
+    reused_login.map_or((db_name, db_name), |login| {
+        (login.name.as_str(), login.password.as_str())
+    })
+}
+
+#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+const PG_APPLICATION_NAME_PARAM: &str = "application_name";
+
+#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+pub(crate) fn get_pg_application_name(params: &HashMap<String, String>) -> Option<&str> {
+    params.get(PG_APPLICATION_NAME_PARAM).map(String::as_str)
+}
+
+#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+pub(crate) fn get_pg_runtime_params(params: &HashMap<String, String>) -> Vec<(&str, String)> {
+    // spaces and backslashes in values must be escaped in the options string
+    let mut runtime_params = params
+        .iter()
+        .filter(|(name, _)| name.as_str() != PG_APPLICATION_NAME_PARAM)
+        .map(|(name, value)| {
+            (
+                name.as_str(),
+                value.replace('\\', "\\\\").replace(' ', "\\ "),
+            )
+        })
+        .collect::<Vec<_>>();
+    runtime_params.sort_unstable();
+    runtime_params
+}
+
+#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+#[allow(dead_code)]
+pub(crate) fn format_pg_options(params: &HashMap<String, String>) -> Option<String> {
+    let runtime_params = get_pg_runtime_params(params);
+    if runtime_params.is_empty() {
+        return None;
+    }
+
+    let options = runtime_params
+        .iter()
+        .map(|(name, value)| format!("-c {name}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(options)
+}
+
+#[allow(dead_code)]
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+/// Returns the userinfo part of a connection URL, percent-encoding the credentials
+#[allow(dead_code)]
+pub(crate) fn url_userinfo(username: &str, password: Option<&str>) -> String {
+    let username = percent_encode(username);
+    if let Some(password) = password {
+        format!("{username}:{}", percent_encode(password))
+    } else {
+        username
+    }
+}
+
+/// Returns the host part of a connection URL, bracketing addresses containing colons and percent-encoding socket paths
+#[allow(dead_code)]
+pub(crate) fn url_host(host: &str) -> Cow<'_, str> {
+    if host.starts_with('/') {
+        percent_encode(host).into()
+    } else if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]").into()
+    } else {
+        host.into()
+    }
+}
+
+#[cfg(feature = "diesel-postgres")]
+pub(crate) fn append_pg_connect_timeout(
+    mut url: String,
+    connect_timeout: Option<Duration>,
+) -> String {
+    if let Some(connect_timeout) = connect_timeout {
+        // only whole seconds are accepted, so round up
+        let secs = connect_timeout.as_secs() + u64::from(connect_timeout.subsec_nanos() > 0);
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(format!("connect_timeout={secs}").as_str());
+    }
+    url
+}
+
+#[cfg(any(feature = "_sync-postgres", feature = "_async-postgres"))]
+#[allow(dead_code)]
+pub(crate) fn append_pg_connection_params(
+    mut url: String,
+    params: &HashMap<String, String>,
+) -> String {
+    let query = get_pg_application_name(params)
+        .map(|application_name| (PG_APPLICATION_NAME_PARAM, application_name.to_owned()))
+        .into_iter()
+        .chain(format_pg_options(params).map(|options| ("options", options)))
+        .map(|(name, value)| format!("{name}={}", percent_encode(value.as_str())))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(query.as_str());
+    }
+    url
 }